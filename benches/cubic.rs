@@ -0,0 +1,79 @@
+//! `CubicBezier::solve` vs the bisection it replaced, over 10k solves.
+//!
+//! Bisection is the original solver: halve [0, 1] until it is 0.001 wide,
+//! about ten evaluations of x(t) per call.
+//!
+//! ```sh
+//! cargo bench --bench cubic
+//! ```
+
+use animation_engine::CubicBezierCurve as CubicBezier;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SOLVES: usize = 10_000;
+const ROUNDS: u32 = 200;
+
+fn time(mut run: impl FnMut()) -> Duration {
+    run();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        run();
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn bisect(curve: &CubicBezier, t: f64) -> f64 {
+    if t <= 0.0 {
+        return 0.0;
+    }
+    if t >= 1.0 {
+        return 1.0;
+    }
+    let component = |p1: f64, p2: f64, t: f64| {
+        let u = 1.0 - t;
+        3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+    };
+
+    let mut start = 0.0;
+    let mut end = 1.0;
+    while end - start > 0.001 {
+        let mid = (start + end) / 2.0;
+        if component(curve.x1, curve.x2, mid) < t {
+            start = mid;
+        } else {
+            end = mid;
+        }
+    }
+    component(curve.y1, curve.y2, (start + end) / 2.0)
+}
+
+fn main() {
+    let inputs: Vec<f64> = (0..SOLVES).map(|i| i as f64 / SOLVES as f64).collect();
+
+    println!("{} solves, {} rounds", SOLVES, ROUNDS);
+
+    for (name, curve) in [
+        ("ease", CubicBezier::default()),
+        ("ease-in-out", CubicBezier::ease_in_out()),
+        ("back", CubicBezier::new(0.68, -0.55, 0.265, 1.55)),
+    ] {
+        let bisected = time(|| {
+            for &t in &inputs {
+                black_box(bisect(black_box(&curve), t));
+            }
+        });
+        let solved = time(|| {
+            for &t in &inputs {
+                black_box(black_box(&curve).solve(t));
+            }
+        });
+        println!(
+            "{:<12} bisect {:>10.1?}  solve {:>10.1?}  {:.2}x",
+            name,
+            bisected,
+            solved,
+            bisected.as_secs_f64() / solved.as_secs_f64()
+        );
+    }
+}
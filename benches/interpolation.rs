@@ -0,0 +1,98 @@
+//! Time of `batch` interpolation over large arrays, next to a plain iterator loop.
+//!
+//! Without SIMD both columns are scalar code, so they only show what the
+//! batch functions cost over an inline loop. To see what SIMD buys, run it on
+//! a wasm runtime (e.g. with wasmtime as the runner) once with and once
+//! without the `simd` feature and compare the batch columns:
+//!
+//! ```sh
+//! RUSTFLAGS="-C target-feature=+simd128" \
+//!     cargo bench --target wasm32-wasip1 --features simd --bench interpolation
+//! RUSTFLAGS="-C target-feature=+simd128" \
+//!     cargo bench --target wasm32-wasip1 --bench interpolation
+//! ```
+
+use animation_engine::testing::batch;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const VALUES: usize = 100_000;
+const ROUNDS: u32 = 200;
+
+fn time(mut run: impl FnMut()) -> Duration {
+    run();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        run();
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn report(name: &str, looped: Duration, batched: Duration) {
+    println!(
+        "{:<14} loop {:>10.1?}  batch {:>10.1?}",
+        name, looped, batched
+    );
+}
+
+fn main() {
+    let from: Vec<f64> = (0..VALUES).map(|i| i as f64).collect();
+    let to: Vec<f64> = (0..VALUES).map(|i| (i as f64).sin() * 100.0).collect();
+    let t: Vec<f64> = (0..VALUES).map(|i| (i % 100) as f64 / 100.0).collect();
+    let mut out = vec![0.0; VALUES];
+
+    println!(
+        "{} values, {} rounds, batch {}",
+        VALUES,
+        ROUNDS,
+        if batch::SIMD {
+            "with SIMD"
+        } else {
+            "scalar (no SIMD)"
+        }
+    );
+
+    let looped = time(|| {
+        let values = out.iter_mut().zip(&from).zip(&to).zip(&t);
+        for (((out, &a), &b), &t) in values {
+            *out = a + (b - a) * t;
+        }
+        black_box(&out);
+    });
+    let batched = time(|| {
+        batch::lerp(&from, &to, &t, &mut out);
+        black_box(&out);
+    });
+    report("lerp", looped, batched);
+
+    let looped = time(|| {
+        for ((out, &a), &b) in out.iter_mut().zip(&from).zip(&to) {
+            *out = a + (b - a) * 0.5;
+        }
+        black_box(&out);
+    });
+    let batched = time(|| {
+        batch::lerp_uniform(&from, &to, 0.5, &mut out);
+        black_box(&out);
+    });
+    report("lerp_uniform", looped, batched);
+
+    let colors = &t[..VALUES / 4];
+    let looped = time(|| {
+        let channels = out
+            .chunks_exact_mut(4)
+            .zip(from.chunks_exact(4))
+            .zip(to.chunks_exact(4));
+        for (((out, a), b), &t) in channels.zip(colors) {
+            for c in 0..4 {
+                out[c] = a[c] + (b[c] - a[c]) * t;
+            }
+        }
+        black_box(&out);
+    });
+    let batched = time(|| {
+        batch::lerp_colors(&from, &to, colors, &mut out);
+        black_box(&out);
+    });
+    report("lerp_colors", looped, batched);
+}
@@ -0,0 +1,119 @@
+// ============================================================================
+// BATCH - Interpolating many values at once
+// ============================================================================
+//
+// Animations with many number properties, GPU interpolator CPU fallbacks and
+// particle color gradients all lerp runs of f64s. With the `simd` feature and
+// a wasm32 build with `-C target-feature=+simd128`, two lanes are computed
+// per instruction; everywhere else the same functions fall back to a scalar
+// loop, so results are identical either way.
+
+/// True when this build uses the wasm SIMD path
+pub const SIMD: bool = cfg!(all(
+    feature = "simd",
+    target_arch = "wasm32",
+    target_feature = "simd128"
+));
+
+/// `out[i] = from[i] + (to[i] - from[i]) * t[i]`, each value with its own
+/// progress. All slices are cut to the shortest.
+pub fn lerp(from: &[f64], to: &[f64], t: &[f64], out: &mut [f64]) {
+    let len = from.len().min(to.len()).min(t.len()).min(out.len());
+    let done = simd::lerp(&from[..len], &to[..len], &t[..len], &mut out[..len]);
+    let tail = out[done..len].iter_mut().zip(&from[done..]);
+    for ((out, &a), (&b, &t)) in tail.zip(to[done..].iter().zip(&t[done..])) {
+        *out = a + (b - a) * t;
+    }
+}
+
+/// `out[i] = from[i] + (to[i] - from[i]) * t`, every value at the same
+/// progress. All slices are cut to the shortest.
+pub fn lerp_uniform(from: &[f64], to: &[f64], t: f64, out: &mut [f64]) {
+    let len = from.len().min(to.len()).min(out.len());
+    let done = simd::lerp_uniform(&from[..len], &to[..len], t, &mut out[..len]);
+    let tail = out[done..len]
+        .iter_mut()
+        .zip(&from[done..])
+        .zip(&to[done..]);
+    for ((out, &a), &b) in tail {
+        *out = a + (b - a) * t;
+    }
+}
+
+/// One RGBA color (or any four channels) at progress `t`
+pub fn lerp4(from: [f64; 4], to: [f64; 4], t: f64) -> [f64; 4] {
+    let mut out = [0.0; 4];
+    lerp_uniform(&from, &to, t, &mut out);
+    out
+}
+
+/// Packed RGBA colors, four channels per color and one progress per color.
+/// Colors are cut to the shortest of the inputs.
+pub fn lerp_colors(from: &[f64], to: &[f64], t: &[f64], out: &mut [f64]) {
+    let count = (from.len().min(to.len()).min(out.len()) / 4).min(t.len());
+    for (i, &t) in t[..count].iter().enumerate() {
+        let channels = i * 4..i * 4 + 4;
+        lerp_uniform(
+            &from[channels.clone()],
+            &to[channels.clone()],
+            t,
+            &mut out[channels],
+        );
+    }
+}
+
+/// Two lanes at a time; each function returns how many values it wrote so
+/// the caller finishes the odd tail
+#[cfg(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128"))]
+mod simd {
+    use core::arch::wasm32::{
+        f64x2_add, f64x2_mul, f64x2_splat, f64x2_sub, v128, v128_load, v128_store,
+    };
+
+    pub(super) fn lerp(from: &[f64], to: &[f64], t: &[f64], out: &mut [f64]) -> usize {
+        let pairs = out.len() / 2;
+        let chunks = from
+            .chunks_exact(2)
+            .zip(to.chunks_exact(2))
+            .zip(t.chunks_exact(2));
+        for (((a, b), t), out) in chunks.zip(out.chunks_exact_mut(2)) {
+            // Each chunk is two f64s, one v128; wasm loads and stores don't
+            // need the address aligned to 16
+            unsafe {
+                let a = v128_load(a.as_ptr() as *const v128);
+                let b = v128_load(b.as_ptr() as *const v128);
+                let t = v128_load(t.as_ptr() as *const v128);
+                let v = f64x2_add(a, f64x2_mul(f64x2_sub(b, a), t));
+                v128_store(out.as_mut_ptr() as *mut v128, v);
+            }
+        }
+        pairs * 2
+    }
+
+    pub(super) fn lerp_uniform(from: &[f64], to: &[f64], t: f64, out: &mut [f64]) -> usize {
+        let pairs = out.len() / 2;
+        let t = f64x2_splat(t);
+        let chunks = from.chunks_exact(2).zip(to.chunks_exact(2));
+        for ((a, b), out) in chunks.zip(out.chunks_exact_mut(2)) {
+            unsafe {
+                let a = v128_load(a.as_ptr() as *const v128);
+                let b = v128_load(b.as_ptr() as *const v128);
+                let v = f64x2_add(a, f64x2_mul(f64x2_sub(b, a), t));
+                v128_store(out.as_mut_ptr() as *mut v128, v);
+            }
+        }
+        pairs * 2
+    }
+}
+
+/// Without SIMD nothing is done up front and the scalar tail does it all
+#[cfg(not(all(feature = "simd", target_arch = "wasm32", target_feature = "simd128")))]
+mod simd {
+    pub(super) fn lerp(_: &[f64], _: &[f64], _: &[f64], _: &mut [f64]) -> usize {
+        0
+    }
+
+    pub(super) fn lerp_uniform(_: &[f64], _: &[f64], _: f64, _: &mut [f64]) -> usize {
+        0
+    }
+}
@@ -1,6 +1,14 @@
 use wasm_bindgen::prelude::*;
+use web_sys::{window, Element, HtmlElement};
+use serde::Deserialize;
 use std::cell::RefCell;
 use std::rc::Rc;
+use crate::gesture::{GestureAxis, GestureController, GestureEvent};
+use crate::measure::{self, Rect};
+use crate::presence::ListTransition;
+use crate::spring::Spring;
+use crate::ticker;
+use crate::types::{parse_css_length, AnimatableValue, AnimationProperty, LengthUnit, PropertyType};
 
 
 // ============================================================================
@@ -16,131 +24,430 @@ pub enum TransitionContext {
     Pop = 3,
 }
 
+// Resting state of a view that's been presented over or pushed past
+const PRESENTER_SCALE: f64 = 0.94;
+const PRESENTER_BRIGHTNESS: f64 = 0.7;
+const COVERED_BRIGHTNESS: f64 = 0.85;
+// Fraction of the width a pushed-past view drifts
+const PARALLAX: f64 = 0.3;
+
+/// Start a smooth animation of `element` between explicit from/to values
+fn tween(element: Element, values: &[(PropertyType, f64, f64)], duration: f64) -> Result<Rc<RefCell<crate::Animation>>, JsValue> {
+    let mut animation = crate::Animation::new(element)?.smooth(duration.max(0.0));
+    for &(property_type, from, to) in values {
+        animation.properties.push(AnimationProperty {
+            property_type,
+            start: AnimatableValue::Number(from),
+            end: AnimatableValue::Number(to),
+            current: AnimatableValue::Number(from),
+        });
+    }
+    Ok(Rc::clone(&animation.start()?.animation))
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SharedElementOptions {
+    duration: Option<f64>,
+    easing: Option<String>,
+    z_index: Option<i32>,
+}
+
+/// Pixel border radius of an element, 0 when it isn't set in px
+fn border_radius(element: &Element) -> f64 {
+    window()
+        .and_then(|w| w.get_computed_style(element).ok().flatten())
+        .and_then(|style| style.get_property_value("border-top-left-radius").ok())
+        .and_then(|value| parse_css_length(&value).ok())
+        .filter(|(_, unit)| matches!(unit, LengthUnit::Px))
+        .map(|(value, _)| value)
+        .unwrap_or(0.0)
+}
+
+/// Copy of `from` floating above the page at its current box
+fn float_clone(from: &HtmlElement, rect: &Rect, z_index: i32) -> Result<HtmlElement, JsValue> {
+    let hero: HtmlElement = from.clone_node_with_deep(true)?.dyn_into()?;
+    hero.remove_attribute("id")?;
+    let style = hero.style();
+    style.set_property("position", "fixed")?;
+    style.set_property("left", "0")?;
+    style.set_property("top", "0")?;
+    style.set_property("margin", "0")?;
+    style.set_property("box-sizing", "border-box")?;
+    style.set_property("pointer-events", "none")?;
+    style.set_property("visibility", "visible")?;
+    style.set_property("z-index", &z_index.to_string())?;
+    style.set_property("width", &format!("{}px", rect.width))?;
+    style.set_property("height", &format!("{}px", rect.height))?;
+    style.set_property("transform", &format!("translate3d({}px, {}px, 0)", rect.x, rect.y))?;
+
+    window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.body())
+        .ok_or_else(|| JsValue::from_str("No document body"))?
+        .append_child(&hero)?;
+    Ok(hero)
+}
+
+// Spring that carries an interactive transition home after release; close to
+// critically damped so the fraction doesn't overshoot the end it settles on
+const SETTLE_STIFFNESS: f64 = 300.0;
+const SETTLE_DAMPING: f64 = 35.0;
+
 #[wasm_bindgen]
 pub struct Choreographer {
+    state: Rc<RefCell<ChoreographerState>>,
+}
+
+/// Shared with the settle spring and a driving gesture
+struct ChoreographerState {
     context: TransitionContext,
     fraction: f64,
     interactive: bool,
     cancelled: bool,
     animations: Vec<Rc<RefCell<crate::Animation>>>,
+    // The tweens of the latest transition(), also in `animations`
+    preset: Vec<Rc<RefCell<crate::Animation>>>,
+    // Gesture travel in px for the whole transition; measured when unset
+    distance: Option<f64>,
+    settle_ticker: Option<u32>,
+}
+
+impl ChoreographerState {
+    fn begin_interactive(&mut self) -> Result<(), JsValue> {
+        self.interactive = true;
+        self.cancelled = false;
+        match self.settle_ticker.take() {
+            Some(id) => ticker::unsubscribe(id),
+            None => self.fraction = 0.0,
+        }
+
+        // Pause all animations
+        for anim in &self.animations {
+            anim.borrow_mut().pause()?;
+        }
+
+        Ok(())
+    }
+
+    fn update_interactive(&mut self, fraction: f64) -> Result<(), JsValue> {
+        if !self.interactive { return Ok(()); }
+
+        self.fraction = fraction.clamp(0.0, 1.0);
+
+        // Scrub all animations to this fraction
+        for anim in &self.animations {
+            anim.borrow_mut().set_fraction_complete(self.fraction)?;
+        }
+
+        Ok(())
+    }
+
+    fn distance(&self) -> Result<f64, JsValue> {
+        if let Some(distance) = self.distance {
+            return Ok(distance);
+        }
+        let viewport = measure::viewport_rect()?;
+        Ok(match self.context {
+            TransitionContext::Present | TransitionContext::Dismiss => viewport.height,
+            TransitionContext::Push | TransitionContext::Pop => viewport.width,
+        })
+    }
+}
+
+/// Decide between completing and cancelling and spring there; `velocity` in
+/// px/s, positive towards completion
+fn finish_interactive(state: &Rc<RefCell<ChoreographerState>>, velocity: f64) -> Result<bool, JsValue> {
+    let (should_complete, fraction_velocity) = {
+        let mut s = state.borrow_mut();
+        s.interactive = false;
+
+        let should_complete = s.fraction > 0.5 || velocity > 300.0;
+        s.cancelled = !should_complete;
+
+        let distance = s.distance()?;
+        (should_complete, if distance > 0.0 { velocity / distance } else { 0.0 })
+    };
+
+    settle(state, if should_complete { 1.0 } else { 0.0 }, fraction_velocity)?;
+    Ok(should_complete)
+}
+
+/// Spring every animation's fraction to `target` (0 or 1) on the ticker,
+/// then stop them there
+fn settle(state: &Rc<RefCell<ChoreographerState>>, target: f64, velocity: f64) -> Result<(), JsValue> {
+    let mut s = state.borrow_mut();
+    if let Some(id) = s.settle_ticker.take() {
+        ticker::unsubscribe(id);
+    }
+
+    let mut spring = Spring::new(SETTLE_STIFFNESS, SETTLE_DAMPING);
+    spring.reset(s.fraction);
+    spring.velocity = velocity;
+
+    let tick_state = Rc::clone(state);
+    s.settle_ticker = Some(ticker::subscribe(Box::new(move |_now, dt| {
+        let value = spring.update(target, dt.min(0.032));
+        let settled = (value - target).abs() < 0.001 && spring.velocity.abs() < 0.01;
+        let value = if settled { target } else { value.clamp(0.0, 1.0) };
+
+        let mut s = tick_state.borrow_mut();
+        s.fraction = value;
+        for anim in &s.animations {
+            let mut anim = anim.borrow_mut();
+            let _ = anim.set_fraction_complete(value);
+            if settled {
+                let _ = anim.stop();
+            }
+        }
+        if settled {
+            s.settle_ticker = None;
+        }
+        !settled
+    }))?);
+    Ok(())
 }
 
 #[wasm_bindgen]
 impl Choreographer {
     #[wasm_bindgen(constructor)]
-    pub fn new(context: u8) -> Result<Choreographer, JsValue> {
-        let ctx = match context {
-            0 => TransitionContext::Present,
-            1 => TransitionContext::Dismiss,
-            2 => TransitionContext::Push,
-            3 => TransitionContext::Pop,
-            _ => return Err(JsValue::from_str("Invalid context: 0=Present, 1=Dismiss, 2=Push, 3=Pop")),
-        };
-
-        Ok(Choreographer {
-            context: ctx,
-            fraction: 0.0,
-            interactive: false,
-            cancelled: false,
-            animations: Vec::new(),
-        })
+    pub fn new(context: TransitionContext) -> Choreographer {
+        Choreographer {
+            state: Rc::new(RefCell::new(ChoreographerState {
+                context,
+                fraction: 0.0,
+                interactive: false,
+                cancelled: false,
+                animations: Vec::new(),
+                preset: Vec::new(),
+                distance: None,
+                settle_ticker: None,
+            })),
+        }
     }
 
     /// Add animation to be coordinated
     #[wasm_bindgen(js_name = addAnimation)]
     pub fn add_animation(&mut self, handle: &crate::AnimationHandle) {
-        self.animations.push(Rc::clone(&handle.animation));
+        self.state.borrow_mut().animations.push(Rc::clone(&handle.animation));
     }
 
-    /// Start interactive transition
-    #[wasm_bindgen(js_name = beginInteractive)]
-    pub fn begin_interactive(&mut self) -> Result<(), JsValue> {
-        self.interactive = true;
-        self.fraction = 0.0;
-        
-        // Pause all animations
-        for anim in &self.animations {
-            anim.borrow_mut().pause()?;
+    /// Build and start the preset transition for this context between two
+    /// views. `from` is the view on screen now and `to` the one that ends up
+    /// on top:
+    /// - Present: `to` slides up from the bottom while `from` scales down and dims
+    /// - Dismiss: `from` slides down and away while `to` scales back up and brightens
+    /// - Push: `to` slides in from the right while `from` drifts left and dims
+    /// - Pop: `from` slides out to the right while `to` drifts back in
+    ///
+    /// Call beginInteractive() straight after to drive it from a gesture instead.
+    #[wasm_bindgen]
+    pub fn transition(&mut self, from: Element, to: Element, duration: f64) -> Result<(), JsValue> {
+        let viewport = measure::viewport_rect()?;
+        let (width, height) = (viewport.width, viewport.height);
+
+        let mut state = self.state.borrow_mut();
+        let (leaving, arriving) = match state.context {
+            TransitionContext::Present => (
+                vec![(PropertyType::Scale, 1.0, PRESENTER_SCALE), (PropertyType::Brightness, 1.0, PRESENTER_BRIGHTNESS)],
+                vec![(PropertyType::Y, height, 0.0)],
+            ),
+            TransitionContext::Dismiss => (
+                vec![(PropertyType::Y, 0.0, height)],
+                vec![(PropertyType::Scale, PRESENTER_SCALE, 1.0), (PropertyType::Brightness, PRESENTER_BRIGHTNESS, 1.0)],
+            ),
+            TransitionContext::Push => (
+                vec![(PropertyType::X, 0.0, -width * PARALLAX), (PropertyType::Brightness, 1.0, COVERED_BRIGHTNESS)],
+                vec![(PropertyType::X, width, 0.0)],
+            ),
+            TransitionContext::Pop => (
+                vec![(PropertyType::X, 0.0, width)],
+                vec![(PropertyType::X, -width * PARALLAX, 0.0), (PropertyType::Brightness, COVERED_BRIGHTNESS, 1.0)],
+            ),
+        };
+
+        // The previous transition's tweens would otherwise go on being
+        // scrubbed and settled along with the new ones
+        if let Some(id) = state.settle_ticker.take() {
+            ticker::unsubscribe(id);
         }
-        
+        for old in std::mem::take(&mut state.preset) {
+            state.animations.retain(|anim| !Rc::ptr_eq(anim, &old));
+            old.borrow_mut().stop()?;
+        }
+
+        state.cancelled = false;
+        state.fraction = 0.0;
+        let tweens = [tween(from, &leaving, duration)?, tween(to, &arriving, duration)?];
+        state.animations.extend(tweens.iter().cloned());
+        state.preset.extend(tweens);
         Ok(())
     }
 
+    /// Hero transition: fly a floating copy of `from` into the box of `to`,
+    /// animating position, size and border radius. Both elements are hidden
+    /// while it flies; the one it lands on is shown again and the copy removed.
+    /// Joins the interactive methods like any other coordinated animation.
+    /// Options: `{ duration: 400, easing: "ease-in-out", zIndex: 1000 }`
+    #[wasm_bindgen(js_name = sharedElement)]
+    pub fn shared_element(&mut self, from: HtmlElement, to: HtmlElement, options: JsValue) -> Result<(), JsValue> {
+        let options: SharedElementOptions = if options.is_undefined() || options.is_null() {
+            SharedElementOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|e| JsValue::from_str(&format!("Invalid shared element options: {:?}", e)))?
+        };
+
+        let first = measure::element_rect(&from);
+        let last = measure::element_rect(&to);
+        let radius = (border_radius(&from), border_radius(&to));
+        let hero = float_clone(&from, &first, options.z_index.unwrap_or(1000))?;
+        from.style().set_property("visibility", "hidden")?;
+        to.style().set_property("visibility", "hidden")?;
+
+        let animation = crate::Animation::new(hero.clone().into())?
+            .ease_in_out(options.duration.unwrap_or(400.0).max(0.0));
+        let mut animation = match options.easing {
+            Some(easing) => animation.ease(easing)?,
+            None => animation,
+        };
+        let number = |v: f64| AnimatableValue::Number(v);
+        let px = |v: f64| AnimatableValue::Length(v, LengthUnit::Px);
+        for (property_type, start, end) in [
+            (PropertyType::X, number(first.x), number(last.x)),
+            (PropertyType::Y, number(first.y), number(last.y)),
+            (PropertyType::Width, px(first.width), px(last.width)),
+            (PropertyType::Height, px(first.height), px(last.height)),
+            (PropertyType::BorderRadius, px(radius.0), px(radius.1)),
+        ] {
+            animation.properties.push(AnimationProperty {
+                property_type,
+                current: start.clone(),
+                start,
+                end,
+            });
+        }
+        let animation = Rc::clone(&animation.start()?.animation);
+        self.state.borrow_mut().animations.push(Rc::clone(&animation));
+
+        // Swap visibility once the flight ends, whichever way a cancel sent it
+        ticker::subscribe(Box::new(move |_now, _dt| {
+            let anim = animation.borrow();
+            if anim.get_state() != crate::AnimationState::Completed { return true; }
+            let x = anim.live_number(PropertyType::X, first.x);
+            let y = anim.live_number(PropertyType::Y, first.y);
+            let landed = if (x - last.x).abs() + (y - last.y).abs() <= (x - first.x).abs() + (y - first.y).abs() { &to } else { &from };
+            let _ = landed.style().remove_property("visibility");
+            hero.remove();
+            false
+        }))?;
+        Ok(())
+    }
+
+    /// Run a ListTransition update and coordinate its enter, exit and move
+    /// animations with the rest of this transition. Returns how many children animate.
+    #[wasm_bindgen(js_name = updateList)]
+    pub fn update_list(&mut self, list: &mut ListTransition, mutate: js_sys::Function) -> Result<usize, JsValue> {
+        let count = list.update(mutate)?;
+        self.state.borrow_mut().animations.extend(list.animations().iter().cloned());
+        Ok(count)
+    }
+
+    /// Start interactive transition. Grabbing a transition that's still
+    /// settling from a previous release continues from where it is.
+    #[wasm_bindgen(js_name = beginInteractive)]
+    pub fn begin_interactive(&mut self) -> Result<(), JsValue> {
+        self.state.borrow_mut().begin_interactive()
+    }
+
     /// Update all animations to match progress
     #[wasm_bindgen(js_name = updateInteractive)]
     pub fn update_interactive(&mut self, fraction: f64) -> Result<(), JsValue> {
-        if !self.interactive { return Ok(()); }
-        
-        self.fraction = fraction.clamp(0.0, 1.0);
-        
-        // Scrub all animations to this fraction
-        for anim in &self.animations {
-            anim.borrow_mut().set_fraction_complete(self.fraction)?;
-        }
-        
-        Ok(())
+        self.state.borrow_mut().update_interactive(fraction)
+    }
+
+    /// Gesture travel in px that corresponds to the whole transition, used to
+    /// turn release velocity into progress. Defaults to the viewport height
+    /// for Present/Dismiss and width for Push/Pop.
+    #[wasm_bindgen(setter, js_name = interactiveDistance)]
+    pub fn set_interactive_distance(&mut self, distance: f64) {
+        self.state.borrow_mut().distance = if distance > 0.0 { Some(distance) } else { None };
     }
 
-    /// Finish interactive transition (auto-complete or cancel)
+    /// Finish interactive transition (auto-complete or cancel). `velocity` is
+    /// the gesture velocity in px/s, positive towards completion. The
+    /// transition springs from the current fraction to its end, carrying the
+    /// release velocity.
     #[wasm_bindgen(js_name = finishInteractive)]
     pub fn finish_interactive(&mut self, velocity: f64) -> Result<bool, JsValue> {
-        self.interactive = false;
-        
-        let should_complete = self.fraction > 0.5 || velocity > 0.3;
-        
-        if should_complete {
-            // Complete all animations
-            for anim in &self.animations {
-                anim.borrow_mut().resume()?;
-            }
-            Ok(true)
-        } else {
-            // Cancel - reverse all animations
-            self.cancelled = true;
-            for anim in &self.animations {
-                let mut a = anim.borrow_mut();
-                a.reverse()?;
-                a.resume()?;
-            }
-            Ok(false)
-        }
+        finish_interactive(&self.state, velocity)
     }
 
-    /// Cancel interactive transition
+    /// Cancel interactive transition, springing back to the start
     #[wasm_bindgen(js_name = cancelInteractive)]
     pub fn cancel_interactive(&mut self) -> Result<(), JsValue> {
-        self.cancelled = true;
-        self.interactive = false;
-        
-        // Reverse all animations back to start
-        for anim in &self.animations {
-            let mut a = anim.borrow_mut();
-            a.reverse()?;
-            a.resume()?;
+        {
+            let mut state = self.state.borrow_mut();
+            state.cancelled = true;
+            state.interactive = false;
+        }
+        settle(&self.state, 0.0, 0.0)
+    }
+
+    /// Drive the interactive methods from a gesture: dragging `distance` px
+    /// along `axis` (X or Y) completes the transition, and release velocity
+    /// decides whether it finishes or springs back. A negative distance drives
+    /// it with movement the other way, e.g. -width for a leftward swipe.
+    /// Replaces any other Rust-side listener on the controller.
+    #[wasm_bindgen(js_name = driveWithGesture)]
+    pub fn drive_with_gesture(&mut self, controller: &mut GestureController, axis: GestureAxis, distance: f64) -> Result<(), JsValue> {
+        let vertical = match axis {
+            GestureAxis::X => false,
+            GestureAxis::Y => true,
+            GestureAxis::Both => return Err(JsValue::from_str("Gesture axis must be X or Y")),
+        };
+        if distance == 0.0 || !distance.is_finite() {
+            return Err(JsValue::from_str("Distance must be non-zero"));
         }
-        
+        controller.set_axis(axis);
+        self.state.borrow_mut().distance = Some(distance.abs());
+
+        let state = Rc::clone(&self.state);
+        controller.set_listener(Some(Rc::new(move |event| {
+            let _ = match event {
+                GestureEvent::Start => state.borrow_mut().begin_interactive(),
+                GestureEvent::Move(x, y) => {
+                    let offset = if vertical { y } else { x };
+                    state.borrow_mut().update_interactive(offset / distance)
+                }
+                GestureEvent::End(vx, vy) => {
+                    let velocity = if vertical { vy } else { vx };
+                    finish_interactive(&state, velocity * distance.signum()).map(|_| ())
+                }
+            };
+        })));
         Ok(())
     }
 
     // Properties
     #[wasm_bindgen(getter)]
     pub fn fraction(&self) -> f64 {
-        self.fraction
+        self.state.borrow().fraction
     }
 
     #[wasm_bindgen(getter, js_name = isInteractive)]
     pub fn is_interactive(&self) -> bool {
-        self.interactive
+        self.state.borrow().interactive
     }
 
     #[wasm_bindgen(getter, js_name = isCancelled)]
     pub fn is_cancelled(&self) -> bool {
-        self.cancelled
+        self.state.borrow().cancelled
     }
 
     #[wasm_bindgen(getter)]
     pub fn context(&self) -> u8 {
-        self.context as u8
+        self.state.borrow().context as u8
     }
 }
 
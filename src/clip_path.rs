@@ -0,0 +1,336 @@
+use crate::cubic::CubicBezier;
+use crate::measure;
+use crate::ticker;
+use js_sys::Function;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlElement;
+
+// ============================================================================
+// CLIP PATH MORPH - Interpolated clip-path: polygon(...) reveals
+// ============================================================================
+
+/// A clip-path coordinate, `percent`% + `px`px, so percentages and lengths
+/// can be mixed and interpolated without knowing the element's size
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Coord {
+    percent: f64,
+    px: f64,
+}
+
+impl Coord {
+    fn parse(value: &str) -> Result<Coord, JsValue> {
+        let number = |n: &str| {
+            n.trim().parse::<f64>().map_err(|_| {
+                JsValue::from_str(&format!(
+                    "Invalid polygon coordinate '{}': use % or px",
+                    value
+                ))
+            })
+        };
+
+        if let Some(percent) = value.strip_suffix('%') {
+            Ok(Coord {
+                percent: number(percent)?,
+                px: 0.0,
+            })
+        } else {
+            // Unitless numbers only make sense as 0, which is the same in px
+            Ok(Coord {
+                percent: 0.0,
+                px: number(value.strip_suffix("px").unwrap_or(value))?,
+            })
+        }
+    }
+
+    fn lerp(self, other: Coord, t: f64) -> Coord {
+        Coord {
+            percent: self.percent + (other.percent - self.percent) * t,
+            px: self.px + (other.px - self.px) * t,
+        }
+    }
+
+    /// In pixels, for an element `size` px long on this axis
+    fn resolve(self, size: f64) -> f64 {
+        self.percent / 100.0 * size + self.px
+    }
+
+    fn css(self) -> String {
+        match (self.percent != 0.0, self.px != 0.0) {
+            (true, false) => format!("{}%", self.percent),
+            (false, _) => format!("{}px", self.px),
+            (true, true) => format!("calc({}% + {}px)", self.percent, self.px),
+        }
+    }
+}
+
+type ClipPoint = (Coord, Coord);
+
+/// Points and fill rule of a `polygon(...)` value
+struct Polygon {
+    fill_rule: Option<String>,
+    points: Vec<ClipPoint>,
+}
+
+impl Polygon {
+    fn parse(value: &str) -> Result<Polygon, JsValue> {
+        let inner = value
+            .trim()
+            .strip_prefix("polygon(")
+            .and_then(|v| v.strip_suffix(')'))
+            .ok_or_else(|| {
+                JsValue::from_str("Expected a clip-path like polygon(0 0, 100% 0, ...)")
+            })?;
+
+        let mut fill_rule = None;
+        let mut points = Vec::new();
+        for (i, item) in inner.split(',').map(str::trim).enumerate() {
+            if i == 0 && (item == "nonzero" || item == "evenodd") {
+                fill_rule = Some(item.to_string());
+                continue;
+            }
+            let coords: Vec<&str> = item.split_whitespace().collect();
+            let [x, y] = coords[..] else {
+                return Err(JsValue::from_str(&format!(
+                    "Polygon point '{}' needs an x and a y",
+                    item
+                )));
+            };
+            points.push((Coord::parse(x)?, Coord::parse(y)?));
+        }
+
+        if points.len() < 3 {
+            return Err(JsValue::from_str("A polygon needs at least 3 points"));
+        }
+        Ok(Polygon { fill_rule, points })
+    }
+
+    /// Split the longest edges of the closed outline at their midpoints
+    /// until there are `count` points
+    fn resample(&mut self, count: usize, size: (f64, f64)) {
+        while self.points.len() < count {
+            let n = self.points.len();
+            let edge = |i: usize| {
+                let (a, b) = (self.points[i], self.points[(i + 1) % n]);
+                distance_squared(a, b, size)
+            };
+            let longest = (0..n)
+                .max_by(|&a, &b| edge(a).total_cmp(&edge(b)))
+                .unwrap_or(0);
+            let (a, b) = (self.points[longest], self.points[(longest + 1) % n]);
+            self.points
+                .insert(longest + 1, (a.0.lerp(b.0, 0.5), a.1.lerp(b.1, 0.5)));
+        }
+    }
+
+    /// Rotate the point list so each point travels the least total distance
+    /// to the same-numbered point of `target`
+    fn align_to(&mut self, target: &Polygon, size: (f64, f64)) {
+        let n = self.points.len();
+        let travel = |offset: usize| -> f64 {
+            target
+                .points
+                .iter()
+                .enumerate()
+                .map(|(i, &point)| distance_squared(self.points[(i + offset) % n], point, size))
+                .sum()
+        };
+        let offset = (0..n)
+            .min_by(|&a, &b| travel(a).total_cmp(&travel(b)))
+            .unwrap_or(0);
+        self.points.rotate_left(offset);
+    }
+}
+
+fn distance_squared(a: ClipPoint, b: ClipPoint, size: (f64, f64)) -> f64 {
+    let dx = a.0.resolve(size.0) - b.0.resolve(size.0);
+    let dy = a.1.resolve(size.1) - b.1.resolve(size.1);
+    dx * dx + dy * dy
+}
+
+struct ClipState {
+    element: HtmlElement,
+    fill_rule: Option<String>,
+    start: Vec<ClipPoint>,
+    end: Vec<ClipPoint>,
+    progress: f64,
+    from: f64,
+    to: f64,
+    duration: f64,
+    elapsed: f64,
+    easing: CubicBezier,
+    ticker_id: Option<u32>,
+    on_complete: Option<Function>,
+}
+
+impl ClipState {
+    fn clip_path_at(&self, t: f64) -> String {
+        let points: Vec<String> = self
+            .start
+            .iter()
+            .zip(&self.end)
+            .map(|(a, b)| format!("{} {}", a.0.lerp(b.0, t).css(), a.1.lerp(b.1, t).css()))
+            .collect();
+        match &self.fill_rule {
+            Some(rule) => format!("polygon({}, {})", rule, points.join(", ")),
+            None => format!("polygon({})", points.join(", ")),
+        }
+    }
+
+    fn write(&self) -> Result<(), JsValue> {
+        self.element
+            .style()
+            .set_property("clip-path", &self.clip_path_at(self.progress))
+    }
+
+    fn stop(&mut self) {
+        if let Some(id) = self.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct ClipPathMorph {
+    state: Rc<RefCell<ClipState>>,
+}
+
+#[wasm_bindgen]
+impl ClipPathMorph {
+    /// Morph `element`'s clip-path between two `polygon(...)` values. The
+    /// polygon with fewer points gets extra points on its longest edges,
+    /// and the end polygon is rotated so points travel as little as
+    /// possible. Coordinates may be %, px or mixed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(element: HtmlElement, start: &str, end: &str) -> Result<ClipPathMorph, JsValue> {
+        let mut start = Polygon::parse(start)?;
+        let mut end = Polygon::parse(end)?;
+
+        // Percentages resolve against the element's box for edge lengths
+        let rect = measure::element_rect(&element);
+        let size = (rect.width.max(1.0), rect.height.max(1.0));
+        let count = start.points.len().max(end.points.len());
+        start.resample(count, size);
+        end.resample(count, size);
+        end.align_to(&start, size);
+
+        let state = ClipState {
+            element,
+            fill_rule: start.fill_rule.or(end.fill_rule),
+            start: start.points,
+            end: end.points,
+            progress: 0.0,
+            from: 0.0,
+            to: 0.0,
+            duration: 0.0,
+            elapsed: 0.0,
+            easing: CubicBezier::ease_in_out(),
+            ticker_id: None,
+            on_complete: None,
+        };
+        state.write()?;
+
+        Ok(ClipPathMorph {
+            state: Rc::new(RefCell::new(state)),
+        })
+    }
+
+    /// Apply the clip-path at `progress` (0..1) and return it
+    #[wasm_bindgen(js_name = updateProgress)]
+    pub fn update_progress(&mut self, progress: f64) -> Result<String, JsValue> {
+        let mut state = self.state.borrow_mut();
+        state.stop();
+        state.progress = progress.clamp(0.0, 1.0);
+        state.write()?;
+        Ok(state.clip_path_at(state.progress))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> f64 {
+        self.state.borrow().progress
+    }
+
+    /// The clip-path value at `progress` without applying it
+    #[wasm_bindgen(js_name = getClipPathAt)]
+    pub fn get_clip_path_at(&self, progress: f64) -> String {
+        self.state.borrow().clip_path_at(progress.clamp(0.0, 1.0))
+    }
+
+    /// Animate from the current progress to `to` over `duration` ms, with a
+    /// CSS easing ("ease-out", "cubic-bezier(...)", default ease-in-out)
+    #[wasm_bindgen]
+    pub fn animate(
+        &mut self,
+        to: f64,
+        duration: f64,
+        easing: Option<String>,
+    ) -> Result<(), JsValue> {
+        let easing = match easing {
+            Some(easing) => CubicBezier::from_css(&easing)?,
+            None => CubicBezier::ease_in_out(),
+        };
+
+        let mut state = self.state.borrow_mut();
+        state.stop();
+        state.from = state.progress;
+        state.to = to.clamp(0.0, 1.0);
+        state.duration = duration.max(0.0);
+        state.elapsed = 0.0;
+        state.easing = easing;
+
+        let tick_state = Rc::clone(&self.state);
+        state.ticker_id = Some(ticker::subscribe(Box::new(move |_now, dt| {
+            let mut s = tick_state.borrow_mut();
+            s.elapsed += dt * 1000.0;
+            let fraction = if s.duration > 0.0 {
+                (s.elapsed / s.duration).min(1.0)
+            } else {
+                1.0
+            };
+            s.progress = s.from + (s.to - s.from) * s.easing.solve(fraction);
+            let _ = s.write();
+            if fraction < 1.0 {
+                return true;
+            }
+
+            s.ticker_id = None;
+            let callback = s.on_complete.clone();
+            drop(s);
+            if let Some(callback) = callback {
+                let _ = callback.call0(&JsValue::NULL);
+            }
+            false
+        }))?);
+        Ok(())
+    }
+
+    /// Reveal: animate to the end polygon
+    #[wasm_bindgen]
+    pub fn play(&mut self, duration: f64, easing: Option<String>) -> Result<(), JsValue> {
+        self.animate(1.0, duration, easing)
+    }
+
+    /// Animate back to the start polygon
+    #[wasm_bindgen]
+    pub fn reverse(&mut self, duration: f64, easing: Option<String>) -> Result<(), JsValue> {
+        self.animate(0.0, duration, easing)
+    }
+
+    /// Stop the running animation where it is
+    #[wasm_bindgen]
+    pub fn stop(&mut self) {
+        self.state.borrow_mut().stop();
+    }
+
+    /// Called each time an animation reaches its target
+    #[wasm_bindgen(js_name = onComplete)]
+    pub fn on_complete(&mut self, callback: Option<Function>) {
+        self.state.borrow_mut().on_complete = callback;
+    }
+
+    #[wasm_bindgen(getter, js_name = isAnimating)]
+    pub fn is_animating(&self) -> bool {
+        self.state.borrow().ticker_id.is_some()
+    }
+}
@@ -1,14 +1,47 @@
+const SAMPLE_COUNT: usize = 65;
+const SAMPLE_STEP: f64 = 1.0 / (SAMPLE_COUNT - 1) as f64;
+const NEWTON_ITERATIONS: usize = 4;
+const NEWTON_MIN_SLOPE: f64 = 0.001;
+const PRECISION: f64 = 1e-6;
+
 #[derive(Clone)]
 pub struct CubicBezier {
     pub x1: f64,
     pub y1: f64,
     pub x2: f64,
     pub y2: f64,
+    // Polynomial coefficients: x(t) = ((ax * t + bx) * t + cx) * t
+    coefficients: [f64; 6],
+    // t(x) sampled at evenly spaced x, used to seed Newton-Raphson
+    samples: [f64; SAMPLE_COUNT],
+}
+
+impl Default for CubicBezier {
+    fn default() -> Self {
+        Self::new(0.25, 0.1, 0.25, 1.0)
+    }
 }
 
 impl CubicBezier {
     pub fn new(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
-        Self { x1, y1, x2, y2 }
+        let cx = 3.0 * x1;
+        let bx = 3.0 * (x2 - x1) - cx;
+        let cy = 3.0 * y1;
+        let by = 3.0 * (y2 - y1) - cy;
+
+        let mut bezier = Self {
+            x1,
+            y1,
+            x2,
+            y2,
+            coefficients: [1.0 - cx - bx, bx, cx, 1.0 - cy - by, by, cy],
+            samples: [0.0; SAMPLE_COUNT],
+        };
+        for i in 1..SAMPLE_COUNT - 1 {
+            bezier.samples[i] = bezier.bisect(i as f64 * SAMPLE_STEP, 0.0, 1.0);
+        }
+        bezier.samples[SAMPLE_COUNT - 1] = 1.0;
+        bezier
     }
 
     pub fn linear() -> Self {
@@ -47,14 +80,45 @@ impl CubicBezier {
         Self::new(0.68, -0.55, 0.265, 1.55)
     }
 
-    pub fn default() -> Self {
-        Self::new(0.25, 0.1, 0.25, 1.0)
-    }
-
     pub fn emphasized() -> Self {
         Self::new(0.4, 0.0, 0.6, 1.0)
     }
 
+    /// Parse a CSS easing: `cubic-bezier(x1, y1, x2, y2)` or a keyword
+    /// (`linear`, `ease`, `ease-in`, `ease-out`, `ease-in-out`)
+    pub fn from_css(value: &str) -> Result<Self, String> {
+        let value = value.trim().to_lowercase();
+
+        match value.as_str() {
+            "linear" => return Ok(Self::linear()),
+            "ease" => return Ok(Self::default()),
+            "ease-in" => return Ok(Self::ease_in()),
+            "ease-out" => return Ok(Self::ease_out()),
+            "ease-in-out" => return Ok(Self::ease_in_out()),
+            _ => {}
+        }
+
+        let args = value
+            .strip_prefix("cubic-bezier(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .ok_or_else(|| format!("Unsupported easing: {}", value))?;
+
+        let nums = args
+            .split(',')
+            .map(|part| part.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| format!("Invalid cubic-bezier arguments: {}", args))?;
+
+        if nums.len() != 4 {
+            return Err("cubic-bezier requires 4 values".to_string());
+        }
+        if !(0.0..=1.0).contains(&nums[0]) || !(0.0..=1.0).contains(&nums[2]) {
+            return Err("cubic-bezier x values must be between 0 and 1".to_string());
+        }
+
+        Ok(Self::new(nums[0], nums[1], nums[2], nums[3]))
+    }
+
     pub fn solve(&self, t: f64) -> f64 {
         if t <= 0.0 {
             return 0.0;
@@ -62,32 +126,111 @@ impl CubicBezier {
         if t >= 1.0 {
             return 1.0;
         }
+        if self.x1 == self.y1 && self.x2 == self.y2 {
+            return t;
+        }
+
+        self.bezier_y(self.t_for_x(t))
+    }
+
+    /// Slope dY/dX of the eased curve at progress `t`, i.e. eased-progress
+    /// velocity per unit of linear progress. Multiply by distance / duration
+    /// to get a value velocity for handing off to a spring or decay.
+    pub fn velocity_at(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        if self.x1 == self.y1 && self.x2 == self.y2 {
+            return 1.0;
+        }
+
+        let mut s = self.t_for_x(t);
+        let mut dx = self.bezier_x_derivative(s);
+
+        // Curves with a control point on an endpoint have dX = dY = 0 there;
+        // step inside the curve to get the limiting slope
+        if dx.abs() < 1e-6 {
+            s = if s < 0.5 { s + 1e-4 } else { s - 1e-4 };
+            dx = self.bezier_x_derivative(s);
+        }
+        if dx.abs() < 1e-12 {
+            return 0.0;
+        }
+
+        self.bezier_y_derivative(s) / dx
+    }
+
+    /// Invert x(t): table lookup for an initial guess, then Newton-Raphson,
+    /// falling back to bisection where the curve is too flat for Newton or it
+    /// doesn't converge
+    fn t_for_x(&self, x: f64) -> f64 {
+        let position = x * (SAMPLE_COUNT - 1) as f64;
+        let index = (position as usize).min(SAMPLE_COUNT - 2);
+        let low = self.samples[index];
+        let high = self.samples[index + 1];
+        let guess = low + (high - low) * (position - index as f64);
+
+        let slope = self.bezier_x_derivative(guess);
+        if slope >= NEWTON_MIN_SLOPE {
+            if let Some(t) = self.newton(x, guess, slope) {
+                return t;
+            }
+        }
+        self.bisect(x, low, high)
+    }
 
-        let mut start = 0.0;
-        let mut end = 1.0;
-        const EPSILON: f64 = 0.001;
+    /// t in [0, 1], or None if Newton didn't converge within NEWTON_ITERATIONS
+    fn newton(&self, x: f64, mut t: f64, mut slope: f64) -> Option<f64> {
+        for _ in 0..NEWTON_ITERATIONS {
+            let error = self.bezier_x(t) - x;
+            if error.abs() < PRECISION {
+                return Some(t.clamp(0.0, 1.0));
+            }
+            if slope == 0.0 {
+                return None;
+            }
+            t -= error / slope;
+            slope = self.bezier_x_derivative(t);
+        }
+        ((self.bezier_x(t) - x).abs() < PRECISION).then(|| t.clamp(0.0, 1.0))
+    }
 
-        while end - start > EPSILON {
-            let mid = (start + end) / 2.0;
-            let x = self.bezier_x(mid);
-            if x < t {
-                start = mid;
+    fn bisect(&self, x: f64, mut start: f64, mut end: f64) -> f64 {
+        let mut t = (start + end) / 2.0;
+        for _ in 0..32 {
+            let error = self.bezier_x(t) - x;
+            if error.abs() < PRECISION {
+                break;
+            }
+            if error > 0.0 {
+                end = t;
             } else {
-                end = mid;
+                start = t;
             }
+            t = (start + end) / 2.0;
         }
+        t
+    }
 
-        let final_t = (start + end) / 2.0;
-        self.bezier_y(final_t)
+    #[inline]
+    fn bezier_x_derivative(&self, t: f64) -> f64 {
+        let [ax, bx, cx, ..] = self.coefficients;
+        (3.0 * ax * t + 2.0 * bx) * t + cx
     }
 
+    #[inline]
+    fn bezier_y_derivative(&self, t: f64) -> f64 {
+        let [.., ay, by, cy] = self.coefficients;
+        (3.0 * ay * t + 2.0 * by) * t + cy
+    }
+
+    #[inline]
     fn bezier_x(&self, t: f64) -> f64 {
-        let u = 1.0 - t;
-        3.0 * u * u * t * self.x1 + 3.0 * u * t * t * self.x2 + t * t * t
+        let [ax, bx, cx, ..] = self.coefficients;
+        ((ax * t + bx) * t + cx) * t
     }
 
+    #[inline]
     fn bezier_y(&self, t: f64) -> f64 {
-        let u = 1.0 - t;
-        3.0 * u * u * t * self.y1 + 3.0 * u * t * t * self.y2 + t * t * t
+        let [.., ay, by, cy] = self.coefficients;
+        ((ay * t + by) * t + cy) * t
     }
-}
\ No newline at end of file
+}
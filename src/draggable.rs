@@ -0,0 +1,222 @@
+use crate::gesture::{FlingPhysics, GestureAxis, GestureController, GestureEvent};
+use crate::spring::Spring2D;
+use crate::ticker;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{Element, HtmlElement};
+
+// ============================================================================
+// DRAGGABLE - Direct element dragging with configurable release
+// ============================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DragRelease {
+    /// Stay where the pointer left it
+    Stay = 0,
+    /// Spring back to where the element started
+    SpringToOrigin = 1,
+    /// Keep gliding with the release velocity, then decelerate
+    Fling = 2,
+}
+
+struct DragState {
+    element: HtmlElement,
+    release: DragRelease,
+    // Translation when the current gesture began
+    base: (f64, f64),
+    position: (f64, f64),
+    dragging: bool,
+    spring: Spring2D,
+    spring_ticker: Option<u32>,
+}
+
+impl DragState {
+    fn write(&self) -> Result<(), JsValue> {
+        self.element.style().set_property(
+            "transform",
+            &format!(
+                "translate3d({}px, {}px, 0)",
+                self.position.0, self.position.1
+            ),
+        )
+    }
+
+    fn stop_spring(&mut self) {
+        if let Some(id) = self.spring_ticker.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct Draggable {
+    controller: GestureController,
+    state: Rc<RefCell<DragState>>,
+}
+
+#[wasm_bindgen]
+impl Draggable {
+    /// Binds pointer events on `element` and moves it with transforms
+    #[wasm_bindgen(constructor)]
+    pub fn new(element: HtmlElement) -> Result<Draggable, JsValue> {
+        let state = Rc::new(RefCell::new(DragState {
+            element: element.clone(),
+            release: DragRelease::Stay,
+            base: (0.0, 0.0),
+            position: (0.0, 0.0),
+            dragging: false,
+            spring: Spring2D::default(),
+            spring_ticker: None,
+        }));
+
+        let mut controller = GestureController::new();
+        controller.set_axis(GestureAxis::Both);
+        controller.attach(element.into(), JsValue::UNDEFINED)?;
+
+        let listener_state = Rc::clone(&state);
+        controller.set_listener(Some(Rc::new(move |event| match event {
+            GestureEvent::Start => {
+                let mut state = listener_state.borrow_mut();
+                state.stop_spring();
+                state.base = state.position;
+                state.dragging = true;
+            }
+            GestureEvent::Move(x, y) => {
+                let mut state = listener_state.borrow_mut();
+                state.position = (state.base.0 + x, state.base.1 + y);
+                let _ = state.write();
+            }
+            GestureEvent::End(vx, vy) => {
+                listener_state.borrow_mut().dragging = false;
+                if listener_state.borrow().release == DragRelease::SpringToOrigin {
+                    let _ = spring_to_origin(&listener_state, (vx, vy));
+                }
+            }
+        })));
+
+        Ok(Draggable { controller, state })
+    }
+
+    // ========================================================================
+    // CONFIGURATION
+    // ========================================================================
+
+    /// Behaviour on release
+    #[wasm_bindgen(js_name = setRelease)]
+    pub fn set_release(&mut self, release: DragRelease) -> Result<(), JsValue> {
+        if release == DragRelease::Fling {
+            self.controller.set_inertia(0.998, FlingPhysics::Decay)?;
+        } else {
+            self.controller.disable_inertia();
+        }
+        self.state.borrow_mut().release = release;
+        Ok(())
+    }
+
+    /// Keep the element inside `container`; `elastic` as in GestureController.constrainTo
+    #[wasm_bindgen(js_name = constrainTo)]
+    pub fn constrain_to(&mut self, container: Element, elastic: f64) {
+        let target: Element = self.state.borrow().element.clone().into();
+        self.controller.constrain_to(target, container, elastic);
+    }
+
+    #[wasm_bindgen(js_name = clearConstraints)]
+    pub fn clear_constraints(&mut self) {
+        self.controller.clear_constraints();
+    }
+
+    /// Spring used by SpringToOrigin
+    #[wasm_bindgen(js_name = setSpring)]
+    pub fn set_spring(&mut self, stiffness: f64, damping: f64) {
+        let mut state = self.state.borrow_mut();
+        let spring = &mut state.spring;
+        spring.x.stiffness = stiffness;
+        spring.x.damping = damping;
+        spring.y.stiffness = stiffness;
+        spring.y.damping = damping;
+    }
+
+    // ========================================================================
+    // CONTROL
+    // ========================================================================
+
+    /// Jump back to the starting position, cancelling any fling or spring
+    #[wasm_bindgen]
+    pub fn reset(&mut self) -> Result<(), JsValue> {
+        self.controller.core().borrow_mut().cancel_fling();
+        let mut state = self.state.borrow_mut();
+        state.stop_spring();
+        state.base = (0.0, 0.0);
+        state.position = (0.0, 0.0);
+        state.write()
+    }
+
+    /// Stop listening to pointer events; the element keeps its position
+    #[wasm_bindgen]
+    pub fn detach(&mut self) {
+        self.controller.detach();
+        self.state.borrow_mut().stop_spring();
+    }
+
+    // ========================================================================
+    // PROPERTIES
+    // ========================================================================
+
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> f64 {
+        self.state.borrow().position.0
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> f64 {
+        self.state.borrow().position.1
+    }
+
+    #[wasm_bindgen(getter, js_name = velocityX)]
+    pub fn velocity_x(&self) -> f64 {
+        self.controller.velocity_x()
+    }
+
+    #[wasm_bindgen(getter, js_name = velocityY)]
+    pub fn velocity_y(&self) -> f64 {
+        self.controller.velocity_y()
+    }
+
+    #[wasm_bindgen(getter, js_name = isDragging)]
+    pub fn is_dragging(&self) -> bool {
+        self.state.borrow().dragging
+    }
+}
+
+/// Animate the element back to (0, 0) on the shared ticker
+fn spring_to_origin(state: &Rc<RefCell<DragState>>, velocity: (f64, f64)) -> Result<(), JsValue> {
+    let mut s = state.borrow_mut();
+    s.stop_spring();
+    let position = s.position;
+    s.spring.reset(position);
+    s.spring.x.velocity = velocity.0;
+    s.spring.y.velocity = velocity.1;
+
+    let tick_state = Rc::clone(state);
+    s.spring_ticker = Some(ticker::subscribe(Box::new(move |_now, dt| {
+        let mut s = tick_state.borrow_mut();
+        s.position = s.spring.update((0.0, 0.0), dt.min(0.032));
+        let settled = s.spring.at_rest((0.0, 0.0), 0.5);
+        if settled {
+            s.spring.reset((0.0, 0.0));
+            s.position = (0.0, 0.0);
+            s.spring_ticker = None;
+        }
+        let _ = s.write();
+        !settled
+    }))?);
+    Ok(())
+}
+
+impl Drop for Draggable {
+    fn drop(&mut self) {
+        self.detach();
+    }
+}
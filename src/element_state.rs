@@ -0,0 +1,167 @@
+use crate::types::{AnimatableValue, AnimationProperty, PropertyType};
+use js_sys::{FinalizationRegistry, WeakMap};
+use std::cell::RefCell;
+use std::mem::discriminant;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{Element, HtmlElement};
+
+// ============================================================================
+// ELEMENT STATE - Last values the engine left on each element
+// ============================================================================
+//
+// Kept inside the engine rather than on the element, so markup stays as the
+// page wrote it. continue_animate() starts from these values and carries the
+// ones it doesn't animate, so transforms and filters it leaves alone aren't
+// dropped when it rewrites the element's transform or filter.
+//
+// An element's own transform is read before the engine first writes one and
+// kept for as long as the element lives, so animated parts can go on top of
+// it however often the element is detached and put back.
+
+/// Recorded values, one slot per element; SLOTS maps each element to its slot
+#[derive(Default)]
+struct Slab {
+    slots: Vec<Vec<(PropertyType, AnimatableValue)>>,
+    free: Vec<usize>,
+}
+
+impl Slab {
+    fn insert(&mut self) -> usize {
+        match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Vec::new());
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, index: usize) {
+        self.slots[index].clear();
+        self.free.push(index);
+    }
+}
+
+/// Frees an element's slot once the element is collected
+struct Reclaim {
+    registry: FinalizationRegistry,
+    _callback: Closure<dyn FnMut(JsValue)>,
+}
+
+impl Reclaim {
+    fn new() -> Reclaim {
+        let callback = Closure::wrap(Box::new(|index: JsValue| {
+            if let Some(index) = index.as_f64() {
+                STATES.with(|states| states.borrow_mut().release(index as usize));
+            }
+        }) as Box<dyn FnMut(JsValue)>);
+        Reclaim {
+            registry: FinalizationRegistry::new(callback.as_ref().unchecked_ref()),
+            _callback: callback,
+        }
+    }
+}
+
+thread_local! {
+    static STATES: RefCell<Slab> = RefCell::new(Slab::default());
+    static SLOTS: WeakMap = WeakMap::new();
+    static RECLAIM: Reclaim = Reclaim::new();
+    static BASE_TRANSFORMS: WeakMap = WeakMap::new();
+}
+
+fn slot(element: &Element) -> Option<usize> {
+    SLOTS
+        .with(|slots| slots.get(element).as_f64())
+        .map(|index| index as usize)
+}
+
+/// Remember the current values of `properties` on `element`, over any
+/// recorded before
+pub(crate) fn record(element: &Element, properties: &[AnimationProperty]) {
+    let index = slot(element).unwrap_or_else(|| {
+        let index = STATES.with(|states| states.borrow_mut().insert());
+        let held = JsValue::from_f64(index as f64);
+        SLOTS.with(|slots| slots.set(element, &held));
+        RECLAIM.with(|reclaim| {
+            reclaim
+                .registry
+                .register_with_token(element, &held, element)
+        });
+        index
+    });
+
+    STATES.with(|states| {
+        let values = &mut states.borrow_mut().slots[index];
+        for prop in properties {
+            match values.iter_mut().find(|(p, _)| *p == prop.property_type) {
+                Some((_, value)) => *value = prop.current.clone(),
+                None => values.push((prop.property_type, prop.current.clone())),
+            }
+        }
+    });
+}
+
+/// Drop what was recorded for `properties`, once they've been put back to
+/// the element's own styles
+pub(crate) fn forget(element: &Element, properties: &[AnimationProperty]) {
+    let Some(index) = slot(element) else {
+        return;
+    };
+    let emptied = STATES.with(|states| {
+        let mut states = states.borrow_mut();
+        let values = &mut states.slots[index];
+        values.retain(|(p, _)| !properties.iter().any(|prop| prop.property_type == *p));
+        if !values.is_empty() {
+            return false;
+        }
+        states.release(index);
+        true
+    });
+    if emptied {
+        SLOTS.with(|slots| slots.delete(element));
+        RECLAIM.with(|reclaim| reclaim.registry.unregister(element));
+    }
+}
+
+/// Values last recorded on `element`
+pub(crate) fn recorded(element: &Element) -> Vec<(PropertyType, AnimatableValue)> {
+    slot(element)
+        .map(|index| STATES.with(|states| states.borrow().slots[index].clone()))
+        .unwrap_or_default()
+}
+
+/// Whether an animation towards `target` can start from `value`: the same
+/// kind of value, and lengths in the same unit
+pub(crate) fn continues(value: &AnimatableValue, target: &AnimatableValue) -> bool {
+    match (value, target) {
+        (AnimatableValue::Length(_, from), AnimatableValue::Length(_, to)) => {
+            from.as_str() == to.as_str()
+        }
+        _ => discriminant(value) == discriminant(target),
+    }
+}
+
+/// The transform `element` had before the engine first wrote one: its inline
+/// transform, or else the one its stylesheets give it. Empty for none.
+pub(crate) fn base_transform(element: &Element) -> String {
+    if let Some(base) = BASE_TRANSFORMS.with(|bases| bases.get(element).as_string()) {
+        return base;
+    }
+    let inline = element
+        .dyn_ref::<HtmlElement>()
+        .and_then(|html| html.style().get_property_value("transform").ok())
+        .filter(|value| !value.is_empty());
+    let base = inline
+        .or_else(|| {
+            web_sys::window()?
+                .get_computed_style(element)
+                .ok()??
+                .get_property_value("transform")
+                .ok()
+        })
+        .filter(|value| value != "none")
+        .unwrap_or_default();
+    BASE_TRANSFORMS.with(|bases| bases.set(element, &JsValue::from_str(&base)));
+    base
+}
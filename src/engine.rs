@@ -0,0 +1,233 @@
+use crate::spec::{self, Easing};
+use crate::viewport_trigger::{ViewportConfig, ViewportTrigger};
+use crate::visibility;
+use crate::{Animation, AnimationHandle};
+use js_sys::{Array, Object, Reflect};
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use web_sys::Element;
+
+// ============================================================================
+// ENGINE - Page-wide entry points
+// ============================================================================
+
+/// Offsets an entrance preset starts from, as keyframe properties; each
+/// animates to its rest value
+pub fn preset(name: &str) -> Option<&'static [(&'static str, f64)]> {
+    let offsets: &'static [(&'static str, f64)] = match name {
+        "fade" => &[("opacity", 0.0)],
+        "fade-up" => &[("opacity", 0.0), ("y", 40.0)],
+        "fade-down" => &[("opacity", 0.0), ("y", -40.0)],
+        "fade-left" => &[("opacity", 0.0), ("x", 40.0)],
+        "fade-right" => &[("opacity", 0.0), ("x", -40.0)],
+        "zoom-in" => &[("opacity", 0.0), ("scale", 0.8)],
+        "zoom-out" => &[("opacity", 0.0), ("scale", 1.2)],
+        "slide-up" => &[("y", 80.0)],
+        "slide-down" => &[("y", -80.0)],
+        "slide-left" => &[("x", 80.0)],
+        "slide-right" => &[("x", -80.0)],
+        "blur-in" => &[("opacity", 0.0), ("blur", 10.0)],
+        _ => return None,
+    };
+    Some(offsets)
+}
+
+/// Where a preset property comes to rest
+pub fn rest_value(property: &str) -> f64 {
+    match property {
+        "opacity" | "scale" => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// What scanning wired up for one element, kept alive until released
+struct Scanned {
+    element: Element,
+    _trigger: Option<ViewportTrigger>,
+    _handle: Option<AnimationHandle>,
+}
+
+thread_local! {
+    static SCANNED: RefCell<Vec<Scanned>> = const { RefCell::new(Vec::new()) };
+}
+
+fn attribute_number(element: &Element, name: &str, default: f64) -> Result<f64, JsValue> {
+    match element.get_attribute(name) {
+        Some(value) => value
+            .trim()
+            .parse::<f64>()
+            .map_err(|_| JsValue::from_str(&format!("Invalid {}: {}", name, value))),
+        None => Ok(default),
+    }
+}
+
+fn keyframe(
+    time: f64,
+    values: impl Iterator<Item = (&'static str, f64)>,
+) -> Result<JsValue, JsValue> {
+    let keyframe = Object::new();
+    Reflect::set(&keyframe, &"time".into(), &time.into())?;
+    for (property, value) in values {
+        Reflect::set(&keyframe, &property.into(), &value.into())?;
+    }
+    Ok(keyframe.into())
+}
+
+/// The entrance animation described by an element's data-anim attributes
+fn entrance(element: &Element, name: &str) -> Result<Animation, JsValue> {
+    let offsets = preset(name)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown data-anim preset: {}", name)))?;
+
+    let keyframes = Array::new();
+    keyframes.push(&keyframe(0.0, offsets.iter().copied())?);
+    keyframes.push(&keyframe(
+        1.0,
+        offsets
+            .iter()
+            .map(|&(property, _)| (property, rest_value(property))),
+    )?);
+
+    let mut animation = Animation::new(element.clone())?;
+    animation.load_keyframes(keyframes.into())?;
+    animation.duration = attribute_number(element, "data-anim-duration", 600.0)?.max(0.0);
+    animation.delay = attribute_number(element, "data-anim-delay", 0.0)?.max(0.0);
+    if let Some(easing) = element.get_attribute("data-anim-easing") {
+        match spec::parse_easing(&easing)? {
+            Easing::Curve(curve) => animation.bezier = Some(*curve),
+            Easing::Spring => {
+                return Err(JsValue::from_str("data-anim-easing can't be spring"));
+            }
+        }
+    }
+    Ok(animation)
+}
+
+fn wire(element: Element) -> Result<Scanned, JsValue> {
+    let name = element.get_attribute("data-anim").unwrap_or_default();
+    let mut animation = entrance(&element, name.trim())?;
+
+    let trigger = element.get_attribute("data-anim-trigger");
+    let (once, reverse_on_leave) = match trigger.as_deref().map(str::trim) {
+        None | Some("view") => (true, false),
+        Some("toggle") => (false, true),
+        Some("load") => {
+            // Hidden in its starting state through any delay
+            animation.set_fraction_complete(0.0)?;
+            return Ok(Scanned {
+                element,
+                _trigger: None,
+                _handle: Some(animation.start()?),
+            });
+        }
+        Some(other) => {
+            return Err(JsValue::from_str(&format!(
+                "Unknown data-anim-trigger: {} (use view, toggle or load)",
+                other
+            )));
+        }
+    };
+
+    let mut viewport = ViewportTrigger::observe(
+        element.clone(),
+        ViewportConfig {
+            threshold: Some(0.1),
+            once: Some(once),
+            reverse_on_leave: Some(reverse_on_leave),
+            ..ViewportConfig::default()
+        },
+    )?;
+    viewport.set_enter(animation)?;
+    Ok(Scanned {
+        element,
+        _trigger: Some(viewport),
+        _handle: None,
+    })
+}
+
+fn scan_root(root: Option<Element>) -> Result<Element, JsValue> {
+    match root {
+        Some(root) => Ok(root),
+        None => web_sys::window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.document_element())
+            .ok_or_else(|| JsValue::from_str("No document")),
+    }
+}
+
+#[wasm_bindgen]
+pub struct Engine;
+
+#[wasm_bindgen]
+impl Engine {
+    /// Wire up every element under `root` (default: the whole document)
+    /// with a `data-anim` preset such as `fade-up`. Optional attributes:
+    /// `data-anim-duration` and `data-anim-delay` in ms (600, 0),
+    /// `data-anim-easing`, and `data-anim-trigger`: `view` plays once on
+    /// scrolling into view (the default), `toggle` also reverses on
+    /// leaving, `load` plays right away. Elements already wired are skipped,
+    /// so it's safe to scan again after adding content. Returns the number
+    /// of elements wired.
+    #[wasm_bindgen]
+    pub fn scan(root: Option<Element>) -> Result<u32, JsValue> {
+        let root = scan_root(root)?;
+        let mut candidates: Vec<Element> = Vec::new();
+        if root.has_attribute("data-anim") {
+            candidates.push(root.clone());
+        }
+        let found = root.query_selector_all("[data-anim]")?;
+        for index in 0..found.length() {
+            if let Some(element) = found.item(index).and_then(|n| n.dyn_into().ok()) {
+                candidates.push(element);
+            }
+        }
+
+        let mut wired = 0;
+        for element in candidates {
+            let known = SCANNED.with(|s| s.borrow().iter().any(|entry| entry.element == element));
+            if known {
+                continue;
+            }
+            let scanned = wire(element)?;
+            SCANNED.with(|s| s.borrow_mut().push(scanned));
+            wired += 1;
+        }
+        Ok(wired)
+    }
+
+    /// Stop watching scanned elements under `root` (default: all of them),
+    /// e.g. before removing a page's content. Running animations finish.
+    #[wasm_bindgen]
+    pub fn release(root: Option<Element>) -> u32 {
+        // Dropped after the registry is released, as dropping a trigger
+        // disconnects it
+        let released: Vec<Scanned> = SCANNED.with(|s| {
+            let mut scanned = s.borrow_mut();
+            let (released, kept) = std::mem::take(&mut *scanned)
+                .into_iter()
+                .partition(|entry| match &root {
+                    Some(root) => root.contains(Some(&entry.element)),
+                    None => true,
+                });
+            *scanned = kept;
+            released
+        });
+        released.len() as u32
+    }
+
+    /// What animations do while the tab is in the background, where
+    /// browsers stop animation frames. `"fastForward"` (the default) keeps
+    /// to the wall clock: on coming back they're where they would be by
+    /// now, springs included. `"pause"` holds them and carries on from
+    /// there, timelines too. Animations can set their own with
+    /// backgroundPolicy().
+    #[wasm_bindgen(js_name = setBackgroundPolicy)]
+    pub fn set_background_policy(policy: String) -> Result<(), JsValue> {
+        visibility::set_policy(visibility::parse_or_err(&policy)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = backgroundPolicy)]
+    pub fn background_policy() -> String {
+        visibility::policy().name().to_string()
+    }
+}
@@ -0,0 +1,145 @@
+use js_sys::{Function, Object, Reflect};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+
+// ============================================================================
+// EVENTS - start/update/complete/cancel/interrupted/repeat listeners on an animation
+// ============================================================================
+//
+// Events are queued as they happen and delivered in a microtask, after the
+// frame loop, sequencer or gesture driving the animation has let go of it, so
+// listeners can call back into the handle.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimationEvent {
+    /// First frame after any delay
+    Start,
+    /// Every frame and every scrub
+    Update,
+    Complete,
+    /// Stopped before completing
+    Cancel,
+    /// The element left the document mid-animation
+    Interrupted,
+    /// Another iteration began
+    Repeat,
+}
+
+impl AnimationEvent {
+    pub fn parse(name: &str) -> Option<AnimationEvent> {
+        match name {
+            "start" => Some(AnimationEvent::Start),
+            "update" => Some(AnimationEvent::Update),
+            "complete" => Some(AnimationEvent::Complete),
+            "cancel" => Some(AnimationEvent::Cancel),
+            "interrupted" => Some(AnimationEvent::Interrupted),
+            "repeat" => Some(AnimationEvent::Repeat),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnimationEvent::Start => "start",
+            AnimationEvent::Update => "update",
+            AnimationEvent::Complete => "complete",
+            AnimationEvent::Cancel => "cancel",
+            AnimationEvent::Interrupted => "interrupted",
+            AnimationEvent::Repeat => "repeat",
+        }
+    }
+}
+
+pub(crate) fn parse_or_err(name: &str) -> Result<AnimationEvent, JsValue> {
+    AnimationEvent::parse(name).ok_or_else(|| {
+        JsValue::from_str(&format!(
+            "Unknown animation event: {} (use start, update, complete, cancel, interrupted or repeat)",
+            name
+        ))
+    })
+}
+
+#[derive(Default)]
+struct Registry {
+    entries: Vec<(AnimationEvent, Function)>,
+    // Emitted with the fraction complete at the time
+    queued: Vec<(AnimationEvent, f64)>,
+    flush_scheduled: bool,
+}
+
+/// Listeners for one animation. Held apart from the animation so events can
+/// be delivered without borrowing it.
+#[derive(Default)]
+pub(crate) struct Listeners {
+    registry: Rc<RefCell<Registry>>,
+}
+
+impl Listeners {
+    /// Adding the same callback for the same event twice has no effect
+    pub(crate) fn add(&self, event: AnimationEvent, callback: Function) {
+        let mut registry = self.registry.borrow_mut();
+        if !registry
+            .entries
+            .iter()
+            .any(|(e, c)| *e == event && *c == callback)
+        {
+            registry.entries.push((event, callback));
+        }
+    }
+
+    pub(crate) fn remove(&self, event: AnimationEvent, callback: &Function) {
+        self.registry
+            .borrow_mut()
+            .entries
+            .retain(|(e, c)| !(*e == event && c == callback));
+    }
+
+    /// Queue `event` if anyone is listening for it, to be delivered in a
+    /// microtask once whatever is driving the animation has returned
+    pub(crate) fn emit(&self, event: AnimationEvent, progress: f64) {
+        let mut registry = self.registry.borrow_mut();
+        if !registry.entries.iter().any(|(e, _)| *e == event) {
+            return;
+        }
+        registry.queued.push((event, progress));
+        if registry.flush_scheduled {
+            return;
+        }
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        registry.flush_scheduled = true;
+        let flushed = Rc::clone(&self.registry);
+        let callback = Closure::once_into_js(move || flush(&flushed));
+        window.queue_microtask(callback.unchecked_ref());
+    }
+}
+
+fn flush(registry: &Rc<RefCell<Registry>>) {
+    let calls: Vec<_> = {
+        let mut registry = registry.borrow_mut();
+        registry.flush_scheduled = false;
+        let queued = std::mem::take(&mut registry.queued);
+        queued
+            .into_iter()
+            .flat_map(|(event, progress)| {
+                registry
+                    .entries
+                    .iter()
+                    .filter(move |(e, _)| *e == event)
+                    .map(move |(_, callback)| (callback.clone(), event, progress))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    };
+
+    // Listeners may add or remove listeners, or emit more events
+    for (callback, event, progress) in calls {
+        let detail = Object::new();
+        let _ = Reflect::set(&detail, &"type".into(), &event.name().into());
+        let _ = Reflect::set(&detail, &"progress".into(), &progress.into());
+        let _ = callback.call1(&JsValue::NULL, &detail);
+    }
+}
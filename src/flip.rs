@@ -0,0 +1,235 @@
+use crate::measure::{self, Rect};
+use crate::ticker;
+use crate::types::{AnimatableValue, AnimationProperty, PropertyType};
+use crate::AnimationState;
+use js_sys::{Array, WeakMap};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{Element, HtmlElement};
+
+// ============================================================================
+// FLIP - First, Last, Invert, Play layout transitions
+// ============================================================================
+
+thread_local! {
+    /// `[inline transform-origin before any flip, id of the latest flip]`
+    /// for elements a flip has pinned to `0 0`
+    static ORIGINS: WeakMap = WeakMap::new();
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct FlipConfig {
+    duration: Option<f64>,
+    /// CSS easing name or cubic-bezier(); ignored when `spring` is set
+    easing: Option<String>,
+    spring: bool,
+}
+
+impl FlipConfig {
+    pub(crate) fn parse(config: JsValue) -> Result<FlipConfig, JsValue> {
+        if config.is_undefined() || config.is_null() {
+            return Ok(FlipConfig::default());
+        }
+        serde_wasm_bindgen::from_value(config)
+            .map_err(|e| JsValue::from_str(&format!("Invalid flip config: {:?}", e)))
+    }
+}
+
+/// Record element boxes with capture(), change the layout, then call play()
+/// to animate every element from its old box to its new one
+#[wasm_bindgen]
+pub struct Flip {
+    first: Vec<(Element, Rect)>,
+    animations: Vec<Rc<RefCell<crate::Animation>>>,
+}
+
+impl Default for Flip {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl Flip {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Flip {
+        Flip {
+            first: Vec::new(),
+            animations: Vec::new(),
+        }
+    }
+
+    /// Measure where the elements are now. Elements mid-way through a previous
+    /// play() are captured where they appear, so reorders can be interrupted.
+    #[wasm_bindgen]
+    pub fn capture(&mut self, elements: Vec<Element>) {
+        self.first = elements
+            .into_iter()
+            .map(|element| {
+                let rect = measure::element_rect(&element);
+                (element, rect)
+            })
+            .collect();
+    }
+
+    /// Animate captured elements from their old boxes to where the layout has
+    /// put them. Config: `{ duration: 300, easing: "ease-out", spring: false }`.
+    /// Returns how many elements moved; hidden or unmoved elements are skipped.
+    #[wasm_bindgen]
+    pub fn play(&mut self, config: JsValue) -> Result<usize, JsValue> {
+        let config = FlipConfig::parse(config)?;
+
+        // Drop the inverted transforms of a previous play so the new layout
+        // box is measured without them
+        self.stop()?;
+        for (element, _) in &self.first {
+            if let Some(html) = element.dyn_ref::<HtmlElement>() {
+                html.style().remove_property("transform")?;
+            }
+        }
+
+        for (element, first) in std::mem::take(&mut self.first) {
+            if let Some(animation) = flip_from(element, &first, &config)? {
+                self.animations.push(animation);
+            }
+        }
+
+        Ok(self.animations.len())
+    }
+
+    /// Stop running transitions where they are
+    #[wasm_bindgen]
+    pub fn stop(&mut self) -> Result<(), JsValue> {
+        for animation in self.animations.drain(..) {
+            animation.borrow_mut().stop()?;
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter, js_name = isRunning)]
+    pub fn is_running(&self) -> bool {
+        self.animations
+            .iter()
+            .any(|a| a.borrow().get_state() == AnimationState::Running)
+    }
+
+    #[wasm_bindgen(getter, js_name = capturedCount)]
+    pub fn captured_count(&self) -> usize {
+        self.first.len()
+    }
+}
+
+/// Animate `element` from the `first` box to where it is now. None when it's
+/// hidden or hasn't moved.
+pub(crate) fn flip_from(
+    element: Element,
+    first: &Rect,
+    config: &FlipConfig,
+) -> Result<Option<Rc<RefCell<crate::Animation>>>, JsValue> {
+    let last = measure::element_rect(&element);
+    if last.width <= 0.0 || last.height <= 0.0 {
+        return Ok(None);
+    }
+
+    let dx = first.x - last.x;
+    let dy = first.y - last.y;
+    let sx = first.width / last.width;
+    let sy = first.height / last.height;
+    if dx.abs() < 0.5 && dy.abs() < 0.5 && (sx - 1.0).abs() < 0.001 && (sy - 1.0).abs() < 0.001 {
+        return Ok(None);
+    }
+
+    pin_origin(&element)?;
+    let inverted = [
+        (PropertyType::X, dx, 0.0),
+        (PropertyType::Y, dy, 0.0),
+        (PropertyType::ScaleX, sx, 1.0),
+        (PropertyType::ScaleY, sy, 1.0),
+    ];
+    let animation = animate(element.clone(), &inverted, config)?;
+    let id = animation.borrow().id;
+    let entry = ORIGINS.with(|origins| origins.get(&element));
+    if let Some(entry) = entry.dyn_ref::<Array>() {
+        entry.set(1, id.into());
+    }
+
+    // Give the origin back once it finishes or is stopped, unless a later
+    // flip of the same element has taken it over
+    let watched = Rc::clone(&animation);
+    ticker::subscribe(Box::new(move |_now, _dt| {
+        if watched.borrow().get_state() != AnimationState::Completed {
+            return true;
+        }
+        let _ = release_origin(&element, id);
+        false
+    }))?;
+    Ok(Some(animation))
+}
+
+/// Scale from the top left corner, keeping the element's own inline
+/// transform-origin to put back. Flips that interrupt one another keep the
+/// value from before the first.
+fn pin_origin(element: &Element) -> Result<(), JsValue> {
+    if let Some(html) = element.dyn_ref::<HtmlElement>() {
+        let style = html.style();
+        ORIGINS.with(|origins| {
+            if !origins.has(element) {
+                let saved = style.get_property_value("transform-origin")?;
+                origins.set(element, &Array::of2(&saved.into(), &0.into()));
+            }
+            Ok::<_, JsValue>(())
+        })?;
+        style.set_property("transform-origin", "0 0")?;
+    }
+    Ok(())
+}
+
+fn release_origin(element: &Element, id: u32) -> Result<(), JsValue> {
+    let Some(saved) = ORIGINS.with(|origins| {
+        let entry = origins.get(element).dyn_into::<Array>().ok()?;
+        if entry.get(1).as_f64() != Some(id as f64) {
+            return None;
+        }
+        origins.delete(element);
+        entry.get(0).as_string()
+    }) else {
+        return Ok(());
+    };
+
+    if let Some(html) = element.dyn_ref::<HtmlElement>() {
+        if saved.is_empty() {
+            html.style().remove_property("transform-origin")?;
+        } else {
+            html.style().set_property("transform-origin", &saved)?;
+        }
+    }
+    Ok(())
+}
+
+/// Start an animation of `element` between explicit from/to values
+pub(crate) fn animate(
+    element: Element,
+    inverted: &[(PropertyType, f64, f64)],
+    config: &FlipConfig,
+) -> Result<Rc<RefCell<crate::Animation>>, JsValue> {
+    let duration = config.duration.unwrap_or(300.0).max(0.0);
+    let animation = crate::Animation::new(element)?.ease_out(duration);
+    let mut animation = match config.easing {
+        _ if config.spring => animation.spring_default(),
+        Some(ref easing) => animation.ease(easing.clone())?,
+        None => animation,
+    };
+
+    for &(property_type, from, to) in inverted {
+        animation.properties.push(AnimationProperty {
+            property_type,
+            start: AnimatableValue::Number(from),
+            end: AnimatableValue::Number(to),
+            current: AnimatableValue::Number(from),
+        });
+    }
+    Ok(Rc::clone(&animation.start()?.animation))
+}
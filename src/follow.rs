@@ -0,0 +1,299 @@
+use crate::measure;
+use crate::renderer::{self, Renderer};
+use crate::spring::{Spring, Spring2D};
+use crate::ticker;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, AddEventListenerOptions, Element, Event, EventTarget, PointerEvent};
+
+// ============================================================================
+// FOLLOW - An element trailing the pointer or another element on a spring
+// ============================================================================
+
+/// Translation that puts the point at `anchor` (fractions of `size`) of an
+/// element resting at `rest` onto `goal` + `offset`
+pub fn translation_to(
+    goal: (f64, f64),
+    rest: (f64, f64),
+    size: (f64, f64),
+    anchor: (f64, f64),
+    offset: (f64, f64),
+) -> (f64, f64) {
+    (
+        goal.0 + offset.0 - rest.0 - size.0 * anchor.0,
+        goal.1 + offset.1 - rest.1 - size.1 * anchor.1,
+    )
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct FollowConfig {
+    stiffness: Option<f64>,
+    damping: Option<f64>,
+    offset_x: f64,
+    offset_y: f64,
+    anchor_x: Option<f64>,
+    anchor_y: Option<f64>,
+}
+
+enum Leader {
+    Pointer,
+    Element(Element),
+}
+
+struct FollowState {
+    element: Element,
+    renderer: Box<dyn Renderer>,
+    leader: Leader,
+    // Where the element sits without our translation, and its size
+    rest: (f64, f64),
+    size: (f64, f64),
+    anchor: (f64, f64),
+    offset: (f64, f64),
+    // Latest pointer position; None until the pointer first moves
+    pointer: Option<(f64, f64)>,
+    spring: Spring2D,
+    position: (f64, f64),
+    // The first target is jumped to rather than sprung toward
+    placed: bool,
+    ticker_id: Option<u32>,
+}
+
+impl FollowState {
+    fn measure(&mut self) {
+        let rect = measure::element_rect(&self.element);
+        self.rest = (rect.x - self.position.0, rect.y - self.position.1);
+        self.size = (rect.width, rect.height);
+    }
+
+    fn goal(&self) -> Option<(f64, f64)> {
+        match &self.leader {
+            Leader::Pointer => self.pointer,
+            Leader::Element(leader) => {
+                let rect = measure::element_rect(leader);
+                Some((rect.center_x(), rect.center_y()))
+            }
+        }
+    }
+
+    fn write(&mut self) -> Result<(), JsValue> {
+        self.renderer.apply(
+            "translate",
+            &format!("{}px {}px", self.position.0, self.position.1),
+        )?;
+        self.renderer.flush()
+    }
+
+    /// Returns whether to keep ticking
+    fn tick(&mut self, dt: f64) -> bool {
+        let following_element = matches!(self.leader, Leader::Element(_));
+        let Some(goal) = self.goal() else {
+            return false;
+        };
+        let target = translation_to(goal, self.rest, self.size, self.anchor, self.offset);
+
+        if !self.placed {
+            self.placed = true;
+            self.spring.reset(target);
+        }
+        self.position = self.spring.update(target, dt.min(0.032));
+        let settled = self.spring.at_rest(target, 0.5);
+        if settled {
+            self.spring.reset(target);
+            self.position = target;
+        }
+        let _ = self.write();
+        // An element can move without telling us, so keep watching it
+        following_element || !settled
+    }
+}
+
+fn schedule(state: &Rc<RefCell<FollowState>>) {
+    if state.borrow().ticker_id.is_some() {
+        return;
+    }
+    let tick_state = Rc::clone(state);
+    let id = ticker::subscribe(Box::new(move |_now, dt| {
+        let mut s = tick_state.borrow_mut();
+        let keep = s.tick(dt);
+        if !keep {
+            s.ticker_id = None;
+        }
+        keep
+    }))
+    .ok();
+    state.borrow_mut().ticker_id = id;
+}
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+/// Moves an element after the pointer, or after another element, on a
+/// spring. Config: `{ stiffness: 300, damping: 30, offsetX: 0, offsetY: 0,
+/// anchorX: 0.5, anchorY: 0.5 }`; the anchor is the point of the element,
+/// as fractions of its size, that lands on the target.
+#[wasm_bindgen]
+pub struct Follow {
+    state: Rc<RefCell<FollowState>>,
+    listener: Option<(EventTarget, EventCallback)>,
+}
+
+#[wasm_bindgen]
+impl Follow {
+    /// Starts following the pointer. Works best on `position: fixed`
+    /// elements; call refresh() if the element's layout moves.
+    #[wasm_bindgen(constructor)]
+    pub fn new(element: Element, config: JsValue) -> Result<Follow, JsValue> {
+        let config: FollowConfig = if config.is_undefined() || config.is_null() {
+            FollowConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)
+                .map_err(|e| JsValue::from_str(&format!("Invalid follow config: {:?}", e)))?
+        };
+        let defaults = Spring::default();
+        let stiffness = config.stiffness.unwrap_or(defaults.stiffness);
+        let damping = config.damping.unwrap_or(defaults.damping);
+
+        let renderer = renderer::for_element(&element);
+        let state = Rc::new(RefCell::new(FollowState {
+            element,
+            renderer,
+            leader: Leader::Pointer,
+            rest: (0.0, 0.0),
+            size: (0.0, 0.0),
+            anchor: (
+                config.anchor_x.unwrap_or(0.5),
+                config.anchor_y.unwrap_or(0.5),
+            ),
+            offset: (config.offset_x, config.offset_y),
+            pointer: None,
+            spring: Spring2D {
+                x: Spring::new(stiffness, damping),
+                y: Spring::new(stiffness, damping),
+            },
+            position: (0.0, 0.0),
+            placed: false,
+            ticker_id: None,
+        }));
+        state.borrow_mut().measure();
+
+        let mut follow = Follow {
+            state,
+            listener: None,
+        };
+        follow.listen()?;
+        Ok(follow)
+    }
+
+    /// Trail the pointer (the default)
+    #[wasm_bindgen(js_name = followPointer)]
+    pub fn follow_pointer(&mut self) -> Result<(), JsValue> {
+        self.state.borrow_mut().leader = Leader::Pointer;
+        self.listen()?;
+        schedule(&self.state);
+        Ok(())
+    }
+
+    /// Trail the center of `leader`, e.g. a tooltip chasing its anchor
+    #[wasm_bindgen(js_name = followElement)]
+    pub fn follow_element(&mut self, leader: Element) {
+        self.unlisten();
+        self.state.borrow_mut().leader = Leader::Element(leader);
+        schedule(&self.state);
+    }
+
+    #[wasm_bindgen(js_name = setOffset)]
+    pub fn set_offset(&mut self, x: f64, y: f64) {
+        self.state.borrow_mut().offset = (x, y);
+        schedule(&self.state);
+    }
+
+    #[wasm_bindgen(js_name = setSpring)]
+    pub fn set_spring(&mut self, stiffness: f64, damping: f64) {
+        let mut state = self.state.borrow_mut();
+        let spring = &mut state.spring;
+        spring.x.stiffness = stiffness;
+        spring.x.damping = damping;
+        spring.y.stiffness = stiffness;
+        spring.y.damping = damping;
+    }
+
+    /// Jump onto the target on the next frame instead of springing there
+    #[wasm_bindgen]
+    pub fn snap(&mut self) {
+        self.state.borrow_mut().placed = false;
+        schedule(&self.state);
+    }
+
+    /// Re-measure the element after its layout changed
+    #[wasm_bindgen]
+    pub fn refresh(&mut self) {
+        self.state.borrow_mut().measure();
+        schedule(&self.state);
+    }
+
+    /// Stop following; the element stays where it is
+    #[wasm_bindgen]
+    pub fn detach(&mut self) {
+        self.unlisten();
+        if let Some(id) = self.state.borrow_mut().ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn x(&self) -> f64 {
+        self.state.borrow().position.0
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn y(&self) -> f64 {
+        self.state.borrow().position.1
+    }
+}
+
+impl Follow {
+    fn listen(&mut self) -> Result<(), JsValue> {
+        if self.listener.is_some() {
+            return Ok(());
+        }
+        let target: EventTarget = window()
+            .ok_or_else(|| JsValue::from_str("No window available"))?
+            .into();
+        let state = Rc::clone(&self.state);
+        let callback: EventCallback = Closure::wrap(Box::new(move |event: Event| {
+            if let Some(pointer) = event.dyn_ref::<PointerEvent>() {
+                let point = (pointer.client_x() as f64, pointer.client_y() as f64);
+                state.borrow_mut().pointer = Some(point);
+                schedule(&state);
+            }
+        }) as Box<dyn FnMut(Event)>);
+
+        let options = AddEventListenerOptions::new();
+        options.set_passive(true);
+        target.add_event_listener_with_callback_and_add_event_listener_options(
+            "pointermove",
+            callback.as_ref().unchecked_ref(),
+            &options,
+        )?;
+        self.listener = Some((target, callback));
+        Ok(())
+    }
+
+    fn unlisten(&mut self) {
+        if let Some((target, callback)) = self.listener.take() {
+            let _ = target.remove_event_listener_with_callback(
+                "pointermove",
+                callback.as_ref().unchecked_ref(),
+            );
+        }
+    }
+}
+
+impl Drop for Follow {
+    fn drop(&mut self) {
+        self.detach();
+    }
+}
@@ -0,0 +1,104 @@
+use std::cell::Cell;
+use wasm_bindgen::prelude::*;
+
+// ============================================================================
+// FRAME CLOCK - Frame rate cap and fixed simulation timestep
+// ============================================================================
+//
+// A frame rate cap skips display frames in the shared ticker and animation
+// loops, e.g. 30fps to save battery. A fixed timestep runs spring and
+// particle physics in equal steps, so a 60Hz and a 120Hz display compute
+// exactly the same motion.
+
+// Most steps run in one frame; after a long stall the rest is dropped
+// rather than spent catching up
+const MAX_STEPS: f64 = 16.0;
+
+thread_local! {
+    // ms between frames; None runs at the display rate
+    static FRAME_INTERVAL: Cell<Option<f64>> = const { Cell::new(None) };
+    // Seconds per physics step; None steps by each frame's delta
+    static FIXED_STEP: Cell<Option<f64>> = const { Cell::new(None) };
+}
+
+/// Splits frame deltas into whole fixed steps, carrying the remainder over
+/// to the next frame
+#[derive(Clone, Debug, Default)]
+pub struct FixedStep {
+    accumulator: f64,
+}
+
+impl FixedStep {
+    /// Steps of `step` seconds to run for a frame `dt` seconds long
+    pub fn steps(&mut self, dt: f64, step: f64) -> u32 {
+        self.accumulator += dt.max(0.0);
+        // Tolerance so 1/60s holds exactly two 1/120s steps
+        let steps = (self.accumulator / step + 1e-6).floor();
+        if steps > MAX_STEPS {
+            self.accumulator = 0.0;
+            return MAX_STEPS as u32;
+        }
+        self.accumulator = (self.accumulator - steps * step).max(0.0);
+        steps as u32
+    }
+
+    /// Seconds carried over to the next frame
+    pub fn remainder(&self) -> f64 {
+        self.accumulator
+    }
+}
+
+#[wasm_bindgen]
+pub struct FrameClock;
+
+#[wasm_bindgen]
+impl FrameClock {
+    /// Run engine frames at most `fps` times a second, e.g. 30 for a battery
+    /// saver mode; 0 (or none) follows the display
+    #[wasm_bindgen(js_name = setTargetFps)]
+    pub fn set_target_fps(fps: Option<f64>) {
+        let interval = fps.filter(|&fps| fps > 0.0).map(|fps| 1000.0 / fps);
+        FRAME_INTERVAL.with(|i| i.set(interval));
+    }
+
+    /// Frame rate cap, 0 when following the display
+    #[wasm_bindgen(js_name = targetFps)]
+    pub fn target_fps() -> f64 {
+        frame_interval().map_or(0.0, |interval| 1000.0 / interval)
+    }
+
+    /// Step spring and particle physics `hz` times a simulated second
+    /// (e.g. 120), however often frames arrive; 0 (or none) steps once per
+    /// frame by its delta
+    #[wasm_bindgen(js_name = setFixedTimestep)]
+    pub fn set_fixed_timestep(hz: Option<f64>) {
+        let step = hz.filter(|&hz| hz > 0.0).map(|hz| 1.0 / hz);
+        FIXED_STEP.with(|s| s.set(step));
+    }
+
+    /// Physics steps per simulated second, 0 when stepping per frame
+    #[wasm_bindgen(js_name = fixedTimestep)]
+    pub fn fixed_timestep() -> f64 {
+        fixed_step().map_or(0.0, |step| 1.0 / step)
+    }
+}
+
+/// ms between capped frames
+pub(crate) fn frame_interval() -> Option<f64> {
+    FRAME_INTERVAL.with(|i| i.get())
+}
+
+/// Seconds per physics step
+pub(crate) fn fixed_step() -> Option<f64> {
+    FIXED_STEP.with(|s| s.get())
+}
+
+/// Whether a loop whose last frame ran at `last` should run one at `now`.
+/// Frames up to a tenth of the interval early count, as display frames
+/// don't land exactly on the cap.
+pub(crate) fn frame_due(last: Option<f64>, now: f64) -> bool {
+    match (frame_interval(), last) {
+        (Some(interval), Some(last)) => now - last >= interval * 0.9,
+        _ => true,
+    }
+}
@@ -1,64 +1,195 @@
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::closure::Closure;
+use crate::measure;
+use crate::spring::Spring;
+use crate::ticker;
+use crate::types::PropertyType;
+use js_sys::Function;
+use serde::Deserialize;
 use std::cell::RefCell;
 use std::rc::Rc;
+use web_sys::{AddEventListenerOptions, Element, Event, HtmlElement, PointerEvent, TouchEvent, WheelEvent};
 
 // ============================================================================
 // GESTURE CONTROL - Integrated with Animation
 // ============================================================================
 
 #[wasm_bindgen]
-pub struct GestureController {
+#[derive(Clone, Copy, PartialEq)]
+pub enum GestureAxis {
+    X = 0,
+    Y = 1,
+    Both = 2,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum FlingPhysics {
+    /// Glide to the projected position, slowing exponentially
+    Decay = 0,
+    /// Spring to the nearer end (0 or 1) of the projected fraction
+    Spring = 1,
+}
+
+/// Lifecycle notifications for Rust-side helpers built on the controller
+#[derive(Clone, Copy)]
+pub(crate) enum GestureEvent {
+    Start,
+    /// Drag (or fling) offset from the gesture start, after constraints
+    Move(f64, f64),
+    /// Released and any fling or snap-back has settled, with the release
+    /// velocity (px/s)
+    End(f64, f64),
+}
+
+pub(crate) type GestureListener = Rc<dyn Fn(GestureEvent)>;
+
+/// Keeps drag offsets inside a container, measured when each drag begins
+struct DragConstraint {
+    target: Element,
+    container: Element,
+    elastic: f64,
+    // Allowed offsets from the drag start: (min_x, max_x, min_y, max_y)
+    bounds: (f64, f64, f64, f64),
+    size: (f64, f64),
+}
+
+impl DragConstraint {
+    fn measure(&mut self) {
+        let target = measure::element_rect(&self.target);
+        let container = measure::element_rect(&self.container);
+        self.bounds = (
+            container.x - target.x,
+            (container.right() - target.right()).max(container.x - target.x),
+            container.y - target.y,
+            (container.bottom() - target.bottom()).max(container.y - target.y),
+        );
+        self.size = (container.width.max(1.0), container.height.max(1.0));
+    }
+
+    /// Offset with overflow rubber-banded (or clamped when elastic is 0)
+    fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            rubber_band(x, self.bounds.0, self.bounds.1, self.size.0, self.elastic),
+            rubber_band(y, self.bounds.2, self.bounds.3, self.size.1, self.elastic),
+        )
+    }
+
+    fn clamp(&self, x: f64, y: f64) -> (f64, f64) {
+        (x.clamp(self.bounds.0, self.bounds.1), y.clamp(self.bounds.2, self.bounds.3))
+    }
+}
+
+/// Resistance past the edges: approaches `dimension` asymptotically
+fn rubber_band(value: f64, min: f64, max: f64, dimension: f64, elastic: f64) -> f64 {
+    let resist = |over: f64| (1.0 - 1.0 / (over * elastic / dimension + 1.0)) * dimension;
+    if value < min {
+        min - resist(min - value)
+    } else if value > max {
+        max + resist(value - max)
+    } else {
+        value
+    }
+}
+
+struct Fling {
+    ticker_id: Option<u32>,
+    physics: FlingPhysics,
+    rate: f64,
+    from: (f64, f64),
+    to: (f64, f64),
+    fraction_from: f64,
+    fraction_to: f64,
+    elapsed: f64,
+    spring: Spring,
+}
+
+/// Pixels per line for wheel events reported in lines (deltaMode 1)
+const WHEEL_LINE_HEIGHT: f64 = 16.0;
+/// Quiet time after which a wheel gesture counts as released
+const WHEEL_IDLE_MS: f64 = 150.0;
+/// Consecutive shrinking deltas that mark OS-generated trackpad momentum
+const WHEEL_MOMENTUM_RUN: u32 = 3;
+
+/// Wheel input is fed through the drag path as a virtual pointer
+#[derive(Default)]
+struct WheelState {
+    active: bool,
+    last_time: f64,
+    last_magnitude: f64,
+    decaying: u32,
+    momentum: bool,
+    idle_ticker: Option<u32>,
+}
+
+/// Gesture state shared between the JS-facing controller and its event listeners
+pub struct GestureCore {
     fraction: f64,
     tracking: bool,
     velocity: f64,
-    
+    velocity_x: f64,
+    velocity_y: f64,
+    axis: GestureAxis,
+
     // Physics
     friction: f64,
+    velocity_smoothing: f64,
     spring_tension: f64,
-    
+
     // Position
     start_x: f64,
     start_y: f64,
     current_x: f64,
     current_y: f64,
     last_time: f64,
-    
+
+    // Inertia: deceleration rate per ms and physics used after release
+    inertia: Option<(f64, FlingPhysics)>,
+    fling: Option<Fling>,
+    projected: (f64, f64),
+    on_fling: Option<Function>,
+    constraint: Option<DragConstraint>,
+    on_drag: Option<Function>,
+    listener: Option<GestureListener>,
+    wheel: WheelState,
+
     // Connected animation
     animation: Option<Rc<RefCell<crate::Animation>>>,
 }
 
-#[wasm_bindgen]
-impl GestureController {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> GestureController {
-        GestureController {
+impl GestureCore {
+    fn new() -> GestureCore {
+        GestureCore {
             fraction: 0.0,
             tracking: false,
             velocity: 0.0,
+            velocity_x: 0.0,
+            velocity_y: 0.0,
+            axis: GestureAxis::Y,
             friction: 0.92,
+            velocity_smoothing: 0.92,
             spring_tension: 0.3,
             start_x: 0.0,
             start_y: 0.0,
             current_x: 0.0,
             current_y: 0.0,
             last_time: 0.0,
+            inertia: None,
+            fling: None,
+            projected: (0.0, 0.0),
+            on_fling: None,
+            constraint: None,
+            on_drag: None,
+            listener: None,
+            wheel: WheelState::default(),
             animation: None,
         }
     }
 
-    /// Connect to an existing animation for gesture control
-    #[wasm_bindgen(js_name = connectAnimation)]
-    pub fn connect_animation(&mut self, handle: &crate::AnimationHandle) {
-        // Clone the Rc to share ownership
-        self.animation = Some(Rc::clone(&handle.animation));
-    }
-
-    // ========================================================================
-    // GESTURE HANDLERS
-    // ========================================================================
-
-    #[wasm_bindgen(js_name = onTapDown)]
-    pub fn on_tap_down(&mut self, x: f64, y: f64, timestamp: f64) {
+    pub(crate) fn on_tap_down(&mut self, x: f64, y: f64, timestamp: f64) {
+        self.cancel_fling();
+        // A real pointer takes over from any wheel gesture in progress
+        self.wheel.active = false;
         self.tracking = true;
         self.start_x = x;
         self.start_y = y;
@@ -66,6 +197,13 @@ impl GestureController {
         self.current_y = y;
         self.last_time = timestamp;
         self.velocity = 0.0;
+        self.velocity_x = 0.0;
+        self.velocity_y = 0.0;
+
+        if let Some(ref mut constraint) = self.constraint {
+            constraint.measure();
+        }
+        self.notify(GestureEvent::Start);
 
         // Pause connected animation
         if let Some(ref anim) = self.animation {
@@ -73,20 +211,30 @@ impl GestureController {
         }
     }
 
-    #[wasm_bindgen(js_name = onTapMove)]
-    pub fn on_tap_move(&mut self, x: f64, y: f64, timestamp: f64) {
-        if !self.tracking { return; }
+    /// Follow the pointer; returns the new offset for tap_move() to report,
+    /// or None when not tracking
+    fn on_tap_move(&mut self, x: f64, y: f64, timestamp: f64) -> Option<(f64, f64)> {
+        if !self.tracking { return None; }
 
+        let dx = x - self.current_x;
         let dy = y - self.current_y;
         let dt = (timestamp - self.last_time).max(1.0);
-        
-        self.velocity = (dy / dt) * self.friction;
+
+        // Velocities are px/s samples scaled by friction, then smoothed by
+        // weighting the newest sample against the last
+        let blend = |previous: f64, sample: f64| {
+            previous + (sample * self.friction - previous) * self.velocity_smoothing
+        };
+        self.velocity_x = blend(self.velocity_x, dx / dt * 1000.0);
+        self.velocity_y = blend(self.velocity_y, dy / dt * 1000.0);
+        self.velocity = self.along_axis(self.velocity_x, self.velocity_y);
+        self.current_x = x;
         self.current_y = y;
         self.last_time = timestamp;
 
         // Update connected animation's fraction
         if let Some(ref anim) = self.animation {
-            let displacement = self.current_y - self.start_y;
+            let displacement = self.displacement();
             let mut anim_ref = anim.borrow_mut();
             let current_fraction = anim_ref.get_fraction_complete();
             let delta = (displacement / 500.0).clamp(-0.1, 0.1);
@@ -99,18 +247,19 @@ impl GestureController {
         } else {
             self.fraction
         };
+
+        Some(self.offset())
     }
 
-    #[wasm_bindgen(js_name = onTapUp)]
-    pub fn on_tap_up(&mut self) {
+    pub(crate) fn on_tap_up(&mut self) {
         self.tracking = false;
-        
+
         if let Some(ref anim) = self.animation {
             let current = anim.borrow().get_fraction_complete();
-            
+
             // Determine completion based on velocity and position
-            let should_complete = current > 0.5 || self.velocity > 0.3;
-            
+            let should_complete = current > 0.5 || self.velocity > 300.0;
+
             if should_complete {
                 let _ = anim.borrow_mut().resume();
             } else {
@@ -121,47 +270,1511 @@ impl GestureController {
         }
     }
 
+    // ========================================================================
+    // INERTIA
+    // ========================================================================
+
+    /// Project the release velocity forward and set up the fling toward it
+    fn begin_fling(&mut self, rate: f64, physics: FlingPhysics) {
+        self.tracking = false;
+
+        // Distance covered by v (px/ms) decaying by `rate` every ms: v * rate / (1 - rate)
+        let reach = rate / (1.0 - rate) / 1000.0;
+        let extra_x = if self.axis == GestureAxis::Y { 0.0 } else { self.velocity_x * reach };
+        let extra_y = if self.axis == GestureAxis::X { 0.0 } else { self.velocity_y * reach };
+
+        let from = self.offset();
+        let mut to = (from.0 + extra_x, from.1 + extra_y);
+        if let Some(ref constraint) = self.constraint {
+            to = constraint.clamp(to.0, to.1);
+        }
+        self.projected = to;
+
+        let fraction_from = match self.animation {
+            Some(ref anim) => anim.borrow().get_fraction_complete(),
+            None => self.fraction,
+        };
+        let projected_fraction = (fraction_from - self.along_axis(extra_x, extra_y) / 500.0).clamp(0.0, 1.0);
+        let fraction_to = match physics {
+            FlingPhysics::Decay => projected_fraction,
+            FlingPhysics::Spring => if projected_fraction >= 0.5 { 1.0 } else { 0.0 },
+        };
+
+        self.fling = Some(Fling {
+            ticker_id: None,
+            physics,
+            rate,
+            from,
+            to,
+            fraction_from,
+            fraction_to,
+            elapsed: 0.0,
+            spring: self.fling_spring(from, to),
+        });
+    }
+
+    /// After an unforced release, spring any elastic overflow back inside the container.
+    /// Returns false when already in bounds.
+    fn begin_snap_back(&mut self) -> bool {
+        let from = self.offset();
+        let to = match self.constraint {
+            Some(ref constraint) => constraint.clamp(from.0, from.1),
+            None => return false,
+        };
+        if from == to {
+            return false;
+        }
+
+        self.projected = to;
+        self.fling = Some(Fling {
+            ticker_id: None,
+            physics: FlingPhysics::Spring,
+            rate: 0.0,
+            from,
+            to,
+            fraction_from: self.fraction,
+            fraction_to: self.fraction,
+            elapsed: 0.0,
+            spring: self.fling_spring(from, to),
+        });
+        true
+    }
+
+    /// Spring for a fling from `from` to `to` that sets off at the release
+    /// velocity: the spring runs from 0 to 1 over the travel, so px/s along
+    /// the travel become travel lengths per second
+    fn fling_spring(&self, from: (f64, f64), to: (f64, f64)) -> Spring {
+        let (vx, vy) = match self.axis {
+            GestureAxis::X => (self.velocity_x, 0.0),
+            GestureAxis::Y => (0.0, self.velocity_y),
+            GestureAxis::Both => (self.velocity_x, self.velocity_y),
+        };
+        let travel = (to.0 - from.0, to.1 - from.1);
+        let length_sq = travel.0 * travel.0 + travel.1 * travel.1;
+
+        let mut spring = Spring::default();
+        if length_sq > f64::EPSILON {
+            spring.velocity = (vx * travel.0 + vy * travel.1) / length_sq;
+        }
+        spring
+    }
+
+    fn notify(&self, event: GestureEvent) {
+        if let Some(ref listener) = self.listener {
+            listener(event);
+        }
+    }
+
+    /// Drag offset from the gesture start, after container constraints
+    fn offset(&self) -> (f64, f64) {
+        let (x, y) = (self.current_x - self.start_x, self.current_y - self.start_y);
+        match self.constraint {
+            // Fling positions are already in constrained space
+            Some(ref constraint) if self.fling.is_none() => constraint.apply(x, y),
+            _ => (x, y),
+        }
+    }
+
+    /// Advance the fling by dt seconds; returns the new offset for
+    /// step_fling() to report and whether the fling has settled, or None
+    /// without a fling
+    fn advance_fling(&mut self, dt: f64) -> Option<((f64, f64), bool)> {
+        let (progress, done) = match self.fling {
+            Some(ref mut fling) => {
+                fling.elapsed += dt * 1000.0;
+                match fling.physics {
+                    FlingPhysics::Decay => {
+                        let remaining = fling.rate.powf(fling.elapsed);
+                        (1.0 - remaining, remaining < 0.001)
+                    }
+                    FlingPhysics::Spring => {
+                        let value = fling.spring.update(1.0, dt.min(0.032));
+                        let at_rest = fling.spring.velocity.abs() < 0.01 && (1.0 - value).abs() < 0.001;
+                        (value, at_rest)
+                    }
+                }
+            }
+            None => return None,
+        };
+
+        let progress = if done { 1.0 } else { progress };
+        let fling = self.fling.as_ref().unwrap();
+        let x = fling.from.0 + (fling.to.0 - fling.from.0) * progress;
+        let y = fling.from.1 + (fling.to.1 - fling.from.1) * progress;
+        let fraction = fling.fraction_from + (fling.fraction_to - fling.fraction_from) * progress;
+
+        self.current_x = self.start_x + x;
+        self.current_y = self.start_y + y;
+
+        if let Some(ref anim) = self.animation {
+            let _ = anim.borrow_mut().set_fraction_complete(fraction);
+        }
+        self.fraction = fraction;
+
+        if done {
+            self.fling = None;
+        }
+        Some(((x, y), done))
+    }
+
+    pub(crate) fn cancel_fling(&mut self) {
+        if let Some(id) = self.fling.take().and_then(|f| f.ticker_id) {
+            ticker::unsubscribe(id);
+        }
+    }
+
+    fn displacement(&self) -> f64 {
+        self.along_axis(self.current_x - self.start_x, self.current_y - self.start_y)
+    }
+
+    fn along_axis(&self, x: f64, y: f64) -> f64 {
+        match self.axis {
+            GestureAxis::X => x,
+            GestureAxis::Y => y,
+            GestureAxis::Both => {
+                if x.abs() > y.abs() { x } else { y }
+            }
+        }
+    }
+}
+
+/// Report a drag or fling offset to `callback` and the listener. The core isn't
+/// borrowed meanwhile, so either can call back into the controller.
+fn emit_move(core: &Rc<RefCell<GestureCore>>, callback: Option<Function>, (x, y): (f64, f64)) {
+    if let Some(callback) = callback {
+        let _ = callback.call2(&JsValue::NULL, &JsValue::from_f64(x), &JsValue::from_f64(y));
+    }
+    let listener = core.borrow().listener.clone();
+    if let Some(listener) = listener {
+        listener(GestureEvent::Move(x, y));
+    }
+}
+
+/// Move the pointer and report the new offset
+fn tap_move(core: &Rc<RefCell<GestureCore>>, x: f64, y: f64, timestamp: f64) {
+    let moved = core.borrow_mut().on_tap_move(x, y, timestamp);
+    if let Some(offset) = moved {
+        let callback = core.borrow().on_drag.clone();
+        emit_move(core, callback, offset);
+    }
+}
+
+/// Advance the fling by dt seconds and report it; false once it has settled
+fn step_fling(core: &Rc<RefCell<GestureCore>>, dt: f64) -> bool {
+    let Some((offset, done)) = core.borrow_mut().advance_fling(dt) else {
+        return false;
+    };
+    let callback = core.borrow().on_fling.clone();
+    emit_move(core, callback, offset);
+
+    if done {
+        let (listener, velocity_x, velocity_y) = {
+            let core = core.borrow();
+            (core.listener.clone(), core.velocity_x, core.velocity_y)
+        };
+        if let Some(listener) = listener {
+            listener(GestureEvent::End(velocity_x, velocity_y));
+        }
+    }
+    !done
+}
+
+/// Finish a gesture, flinging with inertia when enabled
+fn release(core: &Rc<RefCell<GestureCore>>) {
+    let inertia = core.borrow().inertia;
+    match inertia {
+        Some((rate, physics)) => core.borrow_mut().begin_fling(rate, physics),
+        None => {
+            core.borrow_mut().on_tap_up();
+            if !core.borrow_mut().begin_snap_back() {
+                let core = core.borrow();
+                return core.notify(GestureEvent::End(core.velocity_x, core.velocity_y));
+            }
+        }
+    }
+
+    let c = Rc::clone(core);
+    match ticker::subscribe(Box::new(move |_now, dt| step_fling(&c, dt))) {
+        Ok(id) => {
+            if let Some(ref mut fling) = core.borrow_mut().fling {
+                fling.ticker_id = Some(id);
+            }
+        }
+        // No frame loop available: jump straight to the destination
+        Err(_) => {
+            if let Some(ref mut fling) = core.borrow_mut().fling {
+                fling.physics = FlingPhysics::Decay;
+            }
+            step_fling(core, f64::INFINITY);
+        }
+    }
+}
+
+/// Convert a wheel delta in pixels, lines or pages to pixels
+fn normalize_wheel_delta(dx: f64, dy: f64, mode: u32) -> (f64, f64) {
+    let scale = match mode {
+        1 => WHEEL_LINE_HEIGHT,
+        2 => measure::viewport_rect().map(|r| r.height).unwrap_or(800.0),
+        _ => 1.0,
+    };
+    (dx * scale, dy * scale)
+}
+
+/// Feed one wheel event through the drag path. The first event presses a virtual
+/// pointer; it is released after WHEEL_IDLE_MS of quiet or as soon as trackpad
+/// momentum is detected, so the controller's own release logic (complete/cancel
+/// or inertia) replaces the OS momentum tail, which is then ignored.
+fn wheel(core: &Rc<RefCell<GestureCore>>, dx: f64, dy: f64, mode: u32, timestamp: f64) {
+    let (dx, dy) = normalize_wheel_delta(dx, dy, mode);
+    let mut c = core.borrow_mut();
+
+    let magnitude = c.along_axis(dx, dy).abs();
+    let continuing = timestamp - c.wheel.last_time < WHEEL_IDLE_MS;
+    let shrinking = continuing && magnitude < c.wheel.last_magnitude;
+    c.wheel.last_time = timestamp;
+    c.wheel.last_magnitude = magnitude;
+
+    if c.wheel.momentum {
+        // A growing delta or a pause means the user is scrolling again
+        if shrinking || (continuing && magnitude == 0.0) {
+            return;
+        }
+        c.wheel.momentum = false;
+    }
+    c.wheel.decaying = if shrinking { c.wheel.decaying + 1 } else { 0 };
+
+    if !c.wheel.active {
+        // Don't hijack a pointer drag in progress
+        if c.tracking {
+            return;
+        }
+        c.on_tap_down(0.0, 0.0, timestamp);
+        c.wheel.active = true;
+        drop(c);
+        watch_wheel_idle(core);
+        c = core.borrow_mut();
+    }
+
+    // Scrolling down moves content up, like dragging upward
+    let (x, y) = (c.current_x - dx, c.current_y - dy);
+    drop(c);
+    tap_move(core, x, y, timestamp);
+
+    let mut c = core.borrow_mut();
+    if c.wheel.decaying >= WHEEL_MOMENTUM_RUN {
+        c.wheel.momentum = true;
+        drop(c);
+        end_wheel(core);
+    }
+}
+
+fn watch_wheel_idle(core: &Rc<RefCell<GestureCore>>) {
+    let c = Rc::clone(core);
+    let id = ticker::subscribe(Box::new(move |now, _dt| {
+        let (active, idle) = {
+            let core = c.borrow();
+            (core.wheel.active, now - core.wheel.last_time > WHEEL_IDLE_MS)
+        };
+        if active && idle {
+            end_wheel(&c);
+        }
+        active && !idle
+    }));
+    core.borrow_mut().wheel.idle_ticker = id.ok();
+}
+
+fn end_wheel(core: &Rc<RefCell<GestureCore>>) {
+    let was_active = {
+        let mut c = core.borrow_mut();
+        if let Some(id) = c.wheel.idle_ticker.take() {
+            ticker::unsubscribe(id);
+        }
+        std::mem::replace(&mut c.wheel.active, false)
+    };
+    if was_active && core.borrow().tracking {
+        release(core);
+    }
+}
+
+/// One sample of a recorded or scripted pointer path; `t` is ms from the start
+#[derive(Deserialize, Clone, Copy)]
+pub struct GesturePoint {
+    x: f64,
+    y: f64,
+    t: f64,
+}
+
+/// Replay a pointer path through the controller on the shared ticker: down at
+/// the first point, moves in between, release at the last. Returns the ticker
+/// id (for cancelling) and the path duration in ms.
+pub(crate) fn play_synthetic(
+    core: &Rc<RefCell<GestureCore>>,
+    points: Vec<GesturePoint>,
+) -> Result<(u32, f64), JsValue> {
+    if points.len() < 2 {
+        return Err(JsValue::from_str("A gesture needs at least two points"));
+    }
+    let duration = points[points.len() - 1].t - points[0].t;
+
+    let c = Rc::clone(core);
+    let mut next = 0;
+    let mut origin: Option<f64> = None;
+    let id = ticker::subscribe(Box::new(move |now, _dt| {
+        let start = *origin.get_or_insert(now);
+        let elapsed = now - start + points[0].t;
+
+        while next < points.len() && points[next].t <= elapsed {
+            let p = points[next];
+            let timestamp = start + p.t - points[0].t;
+            if next == 0 {
+                c.borrow_mut().on_tap_down(p.x, p.y, timestamp);
+            } else {
+                tap_move(&c, p.x, p.y, timestamp);
+            }
+            next += 1;
+        }
+
+        if next == points.len() {
+            release(&c);
+            return false;
+        }
+        true
+    }))?;
+
+    Ok((id, duration))
+}
+
+#[wasm_bindgen]
+pub struct GestureController {
+    core: Rc<RefCell<GestureCore>>,
+    binding: Option<PointerBinding>,
+}
+
+impl Default for GestureController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl GestureController {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GestureController {
+        GestureController {
+            core: Rc::new(RefCell::new(GestureCore::new())),
+            binding: None,
+        }
+    }
+
+    /// Connect to an existing animation for gesture control
+    #[wasm_bindgen(js_name = connectAnimation)]
+    pub fn connect_animation(&mut self, handle: &crate::AnimationHandle) {
+        // Clone the Rc to share ownership
+        self.core.borrow_mut().animation = Some(Rc::clone(&handle.animation));
+    }
+
+    /// End the gesture and start `animation` from where it left off: its X/Y
+    /// start values are shifted by the drag offset and its springs pick up the
+    /// release velocity (px/s), so neither position nor speed jumps
+    #[wasm_bindgen(js_name = handOffTo)]
+    pub fn hand_off_to(&mut self, mut animation: crate::Animation) -> Result<crate::AnimationHandle, JsValue> {
+        let (offset, velocity) = {
+            let mut core = self.core.borrow_mut();
+            core.cancel_fling();
+            core.tracking = false;
+            core.wheel.active = false;
+            let (x, y) = core.offset();
+            match core.axis {
+                GestureAxis::X => ((x, 0.0), (core.velocity_x, 0.0)),
+                GestureAxis::Y => ((0.0, y), (0.0, core.velocity_y)),
+                GestureAxis::Both => ((x, y), (core.velocity_x, core.velocity_y)),
+            }
+        };
+
+        animation.continue_from(PropertyType::X, offset.0, velocity.0);
+        animation.continue_from(PropertyType::Y, offset.1, velocity.1);
+        animation.start()
+    }
+
+    // ========================================================================
+    // GESTURE HANDLERS
+    // ========================================================================
+
+    #[wasm_bindgen(js_name = onTapDown)]
+    pub fn on_tap_down(&mut self, x: f64, y: f64, timestamp: f64) {
+        self.core.borrow_mut().on_tap_down(x, y, timestamp);
+    }
+
+    #[wasm_bindgen(js_name = onTapMove)]
+    pub fn on_tap_move(&mut self, x: f64, y: f64, timestamp: f64) {
+        tap_move(&self.core, x, y, timestamp);
+    }
+
+    #[wasm_bindgen(js_name = onTapUp)]
+    pub fn on_tap_up(&mut self) {
+        release(&self.core);
+    }
+
+    /// Scrub with a wheel or trackpad: pass `deltaX`, `deltaY`, `deltaMode`
+    /// (0=pixels, 1=lines, 2=pages) and `timeStamp` from a WheelEvent
+    #[wasm_bindgen(js_name = onWheel)]
+    pub fn on_wheel(&mut self, delta_x: f64, delta_y: f64, delta_mode: u32, timestamp: f64) {
+        wheel(&self.core, delta_x, delta_y, delta_mode, timestamp);
+    }
+
+    /// Target scale for a press; bindPress() animates it for you
     #[wasm_bindgen(js_name = onPress)]
     pub fn on_press(&mut self, pressed: bool) -> f64 {
         if pressed { 0.95 } else { 1.0 }
     }
 
+    /// Target scale for a hover; bindHover() animates it for you
     #[wasm_bindgen(js_name = onHover)]
     pub fn on_hover(&mut self, hovering: bool) -> f64 {
         if hovering { 1.05 } else { 1.0 }
     }
 
+    /// Replay a pointer path `[{ x, y, t }]` (t in ms) as if a user performed it
+    #[wasm_bindgen(js_name = playGesture)]
+    pub fn play_gesture(&mut self, points: JsValue) -> Result<f64, JsValue> {
+        let points: Vec<GesturePoint> = serde_wasm_bindgen::from_value(points)
+            .map_err(|e| JsValue::from_str(&format!("Invalid gesture points: {:?}", e)))?;
+        let (_, duration) = play_synthetic(&self.core, points)?;
+        Ok(duration)
+    }
+
     // ========================================================================
-    // PROPERTIES
+    // INERTIA
     // ========================================================================
 
-    #[wasm_bindgen(getter)]
-    pub fn fraction(&self) -> f64 {
-        self.fraction
+    /// Fling after release instead of deciding complete/cancel. `deceleration_rate`
+    /// is the fraction of velocity kept per ms (0.998 normal, 0.99 fast);
+    /// physics picks how the fling ends
+    #[wasm_bindgen(js_name = setInertia)]
+    pub fn set_inertia(&mut self, deceleration_rate: f64, physics: FlingPhysics) -> Result<(), JsValue> {
+        if !(deceleration_rate > 0.0 && deceleration_rate < 1.0) {
+            return Err(JsValue::from_str("Deceleration rate must be between 0 and 1"));
+        }
+        self.core.borrow_mut().inertia = Some((deceleration_rate, physics));
+        Ok(())
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn velocity(&self) -> f64 {
-        self.velocity
+    #[wasm_bindgen(js_name = disableInertia)]
+    pub fn disable_inertia(&mut self) {
+        let mut core = self.core.borrow_mut();
+        core.cancel_fling();
+        core.inertia = None;
     }
 
-    #[wasm_bindgen(getter, js_name = isTracking)]
-    pub fn is_tracking(&self) -> bool {
-        self.tracking
+    /// Called every fling frame with the (x, y) displacement, for driving raw positions
+    #[wasm_bindgen(js_name = onFling)]
+    pub fn on_fling(&mut self, callback: Function) {
+        self.core.borrow_mut().on_fling = Some(callback);
     }
 
-    #[wasm_bindgen(js_name = displacement)]
-    pub fn displacement(&self) -> f64 {
-        self.current_y - self.start_y
+    /// Projected X displacement of the last fling
+    #[wasm_bindgen(getter, js_name = projectedX)]
+    pub fn projected_x(&self) -> f64 {
+        self.core.borrow().projected.0
     }
 
-    #[wasm_bindgen(setter)]
-    pub fn set_friction(&mut self, value: f64) {
-        self.friction = value.clamp(0.0, 1.0);
+    /// Projected Y displacement of the last fling
+    #[wasm_bindgen(getter, js_name = projectedY)]
+    pub fn projected_y(&self) -> f64 {
+        self.core.borrow().projected.1
     }
 
-    #[wasm_bindgen(setter, js_name = springTension)]
-    pub fn set_spring_tension(&mut self, value: f64) {
-        self.spring_tension = value.clamp(0.0, 1.0);
+    // ========================================================================
+    // DRAG CONSTRAINTS
+    // ========================================================================
+
+    /// Keep the drag offset of `target` inside `container`'s bounding rect.
+    /// `elastic` 0 clamps hard; around 0.55 gives iOS-like rubber-banding,
+    /// springing back inside on release.
+    #[wasm_bindgen(js_name = constrainTo)]
+    pub fn constrain_to(&mut self, target: Element, container: Element, elastic: f64) {
+        let mut constraint = DragConstraint {
+            target,
+            container,
+            elastic: elastic.clamp(0.0, 1.0),
+            bounds: (0.0, 0.0, 0.0, 0.0),
+            size: (1.0, 1.0),
+        };
+        constraint.measure();
+        self.core.borrow_mut().constraint = Some(constraint);
+    }
+
+    #[wasm_bindgen(js_name = clearConstraints)]
+    pub fn clear_constraints(&mut self) {
+        self.core.borrow_mut().constraint = None;
+    }
+
+    /// Called on every move with the constrained (x, y) drag offset
+    #[wasm_bindgen(js_name = onDrag)]
+    pub fn on_drag(&mut self, callback: Function) {
+        self.core.borrow_mut().on_drag = Some(callback);
+    }
+
+    /// Drag offset after constraints
+    #[wasm_bindgen(getter, js_name = offsetX)]
+    pub fn offset_x(&self) -> f64 {
+        self.core.borrow().offset().0
+    }
+
+    #[wasm_bindgen(getter, js_name = offsetY)]
+    pub fn offset_y(&self) -> f64 {
+        self.core.borrow().offset().1
+    }
+
+    #[wasm_bindgen(getter, js_name = isFlinging)]
+    pub fn is_flinging(&self) -> bool {
+        self.core.borrow().fling.is_some()
+    }
+
+    /// True while the OS momentum tail of a trackpad scroll is being ignored
+    #[wasm_bindgen(getter, js_name = isWheelMomentum)]
+    pub fn is_wheel_momentum(&self) -> bool {
+        self.core.borrow().wheel.momentum
+    }
+
+    // ========================================================================
+    // ELEMENT BINDING
+    // ========================================================================
+
+    /// Listen to pointer (or touch) events on the element and feed them into
+    /// this controller. Options: `{ capture: true, passive: true, wheel: false }`
+    #[wasm_bindgen]
+    pub fn attach(&mut self, element: Element, options: JsValue) -> Result<(), JsValue> {
+        let options: AttachOptions = if options.is_undefined() || options.is_null() {
+            AttachOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|e| JsValue::from_str(&format!("Invalid attach options: {:?}", e)))?
+        };
+
+        // Replacing the binding drops (and unregisters) any previous listeners
+        self.binding = None;
+        self.binding = Some(PointerBinding::new(element, Rc::clone(&self.core), &options)?);
+        Ok(())
+    }
+
+    /// Remove listeners installed by attach()
+    #[wasm_bindgen]
+    pub fn detach(&mut self) {
+        self.binding = None;
+    }
+
+    #[wasm_bindgen(getter, js_name = isAttached)]
+    pub fn is_attached(&self) -> bool {
+        self.binding.is_some()
+    }
+
+    // ========================================================================
+    // PROPERTIES
+    // ========================================================================
+
+    #[wasm_bindgen(getter)]
+    pub fn fraction(&self) -> f64 {
+        self.core.borrow().fraction
+    }
+
+    /// Velocity along the axis in px/s
+    #[wasm_bindgen(getter)]
+    pub fn velocity(&self) -> f64 {
+        self.core.borrow().velocity
+    }
+
+    #[wasm_bindgen(getter, js_name = isTracking)]
+    pub fn is_tracking(&self) -> bool {
+        self.core.borrow().tracking
+    }
+
+    #[wasm_bindgen(getter, js_name = velocityX)]
+    pub fn velocity_x(&self) -> f64 {
+        self.core.borrow().velocity_x
+    }
+
+    #[wasm_bindgen(getter, js_name = velocityY)]
+    pub fn velocity_y(&self) -> f64 {
+        self.core.borrow().velocity_y
+    }
+
+    /// Displacement along the configured axis
+    #[wasm_bindgen(js_name = displacement)]
+    pub fn displacement(&self) -> f64 {
+        self.core.borrow().displacement()
+    }
+
+    #[wasm_bindgen(js_name = displacementX)]
+    pub fn displacement_x(&self) -> f64 {
+        let core = self.core.borrow();
+        core.current_x - core.start_x
+    }
+
+    #[wasm_bindgen(js_name = displacementY)]
+    pub fn displacement_y(&self) -> f64 {
+        let core = self.core.borrow();
+        core.current_y - core.start_y
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn axis(&self) -> GestureAxis {
+        self.core.borrow().axis
+    }
+
+    /// Axis used for displacement/velocity; with Both the dominant axis wins
+    #[wasm_bindgen(js_name = setAxis)]
+    pub fn set_axis(&mut self, axis: GestureAxis) {
+        self.core.borrow_mut().axis = axis;
+    }
+
+    /// Multiplier (0-1) applied to each velocity sample; 1 keeps the raw px/s
+    #[wasm_bindgen(setter)]
+    pub fn set_friction(&mut self, value: f64) {
+        self.core.borrow_mut().friction = value.clamp(0.0, 1.0);
+    }
+
+    /// Weight (0-1) of the newest sample when smoothing velocity; 1 disables smoothing
+    #[wasm_bindgen(setter, js_name = velocitySmoothing)]
+    pub fn set_velocity_smoothing(&mut self, value: f64) {
+        self.core.borrow_mut().velocity_smoothing = value.clamp(0.0, 1.0);
+    }
+
+    #[wasm_bindgen(setter, js_name = springTension)]
+    pub fn set_spring_tension(&mut self, value: f64) {
+        self.core.borrow_mut().spring_tension = value.clamp(0.0, 1.0);
+    }
+}
+
+impl GestureController {
+    pub(crate) fn core(&self) -> Rc<RefCell<GestureCore>> {
+        Rc::clone(&self.core)
+    }
+
+    pub(crate) fn set_listener(&mut self, listener: Option<GestureListener>) {
+        self.core.borrow_mut().listener = listener;
+    }
+}
+
+// ============================================================================
+// POINTER BINDING
+// ============================================================================
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct AttachOptions {
+    /// Capture the pointer so moves outside the element keep tracking
+    capture: bool,
+    /// Passive listeners can't preventDefault; set false to block page scrolling
+    passive: bool,
+    /// Also scrub with wheel and trackpad scrolling over the element
+    wheel: bool,
+}
+
+impl Default for AttachOptions {
+    fn default() -> Self {
+        AttachOptions { capture: true, passive: true, wheel: false }
+    }
+}
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+/// Listeners installed by attach(); removed again when dropped
+struct PointerBinding {
+    element: Element,
+    listeners: Vec<(&'static str, EventCallback)>,
+    previous_touch_action: Option<String>,
+}
+
+impl PointerBinding {
+    fn new(
+        element: Element,
+        core: Rc<RefCell<GestureCore>>,
+        options: &AttachOptions,
+    ) -> Result<PointerBinding, JsValue> {
+        let mut binding = PointerBinding {
+            element: element.clone(),
+            listeners: Vec::with_capacity(4),
+            previous_touch_action: None,
+        };
+
+        // Let the browser keep scrolling on the axis we don't track
+        if let Some(html) = element.dyn_ref::<HtmlElement>() {
+            let style = html.style();
+            binding.previous_touch_action = style.get_property_value("touch-action").ok();
+            let touch_action = match core.borrow().axis {
+                GestureAxis::X => "pan-y",
+                GestureAxis::Y => "pan-x",
+                GestureAxis::Both => "none",
+            };
+            let _ = style.set_property("touch-action", touch_action);
+        }
+
+        let has_pointer_events = web_sys::window()
+            .map(|w| js_sys::Reflect::has(&w, &JsValue::from_str("PointerEvent")).unwrap_or(false))
+            .unwrap_or(false);
+
+        if has_pointer_events {
+            binding.bind_pointer_events(&core, options)?;
+        } else {
+            binding.bind_touch_events(&core, options)?;
+        }
+
+        if options.wheel {
+            let passive = options.passive;
+            binding.listen("wheel", options, move |event: Event| {
+                if let Some(event) = event.dyn_ref::<WheelEvent>() {
+                    if !passive {
+                        event.prevent_default();
+                    }
+                    wheel(&core, event.delta_x(), event.delta_y(), event.delta_mode(), event.time_stamp());
+                }
+            })?;
+        }
+
+        Ok(binding)
+    }
+
+    fn bind_pointer_events(
+        &mut self,
+        core: &Rc<RefCell<GestureCore>>,
+        options: &AttachOptions,
+    ) -> Result<(), JsValue> {
+        let capture = options.capture;
+        let passive = options.passive;
+
+        let (c, element) = (Rc::clone(core), self.element.clone());
+        self.listen("pointerdown", options, move |event: Event| {
+            if let Some(event) = event.dyn_ref::<PointerEvent>() {
+                if !event.is_primary() { return; }
+                if capture {
+                    let _ = element.set_pointer_capture(event.pointer_id());
+                }
+                c.borrow_mut().on_tap_down(event.client_x() as f64, event.client_y() as f64, event.time_stamp());
+            }
+        })?;
+
+        let c = Rc::clone(core);
+        self.listen("pointermove", options, move |event: Event| {
+            if let Some(event) = event.dyn_ref::<PointerEvent>() {
+                if !event.is_primary() || !c.borrow().tracking { return; }
+                if !passive {
+                    event.prevent_default();
+                }
+                tap_move(&c, event.client_x() as f64, event.client_y() as f64, event.time_stamp());
+            }
+        })?;
+
+        for name in ["pointerup", "pointercancel"] {
+            let (c, element) = (Rc::clone(core), self.element.clone());
+            self.listen(name, options, move |event: Event| {
+                if let Some(event) = event.dyn_ref::<PointerEvent>() {
+                    if !event.is_primary() || !c.borrow().tracking { return; }
+                    if capture {
+                        let _ = element.release_pointer_capture(event.pointer_id());
+                    }
+                    release(&c);
+                }
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Fallback for browsers without Pointer Events
+    fn bind_touch_events(
+        &mut self,
+        core: &Rc<RefCell<GestureCore>>,
+        options: &AttachOptions,
+    ) -> Result<(), JsValue> {
+        let passive = options.passive;
+
+        let c = Rc::clone(core);
+        self.listen("touchstart", options, move |event: Event| {
+            if let Some(event) = event.dyn_ref::<TouchEvent>() {
+                if let Some(touch) = event.touches().get(0) {
+                    c.borrow_mut().on_tap_down(touch.client_x() as f64, touch.client_y() as f64, event.time_stamp());
+                }
+            }
+        })?;
+
+        let c = Rc::clone(core);
+        self.listen("touchmove", options, move |event: Event| {
+            if let Some(event) = event.dyn_ref::<TouchEvent>() {
+                if !passive {
+                    event.prevent_default();
+                }
+                if let Some(touch) = event.touches().get(0) {
+                    tap_move(&c, touch.client_x() as f64, touch.client_y() as f64, event.time_stamp());
+                }
+            }
+        })?;
+
+        for name in ["touchend", "touchcancel"] {
+            let c = Rc::clone(core);
+            self.listen(name, options, move |_event: Event| {
+                if c.borrow().tracking {
+                    release(&c);
+                }
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn listen(
+        &mut self,
+        name: &'static str,
+        options: &AttachOptions,
+        handler: impl FnMut(Event) + 'static,
+    ) -> Result<(), JsValue> {
+        let callback: EventCallback = Closure::wrap(Box::new(handler) as Box<dyn FnMut(Event)>);
+        let listener_options = AddEventListenerOptions::new();
+        listener_options.set_passive(options.passive);
+
+        self.element.add_event_listener_with_callback_and_add_event_listener_options(
+            name,
+            callback.as_ref().unchecked_ref(),
+            &listener_options,
+        )?;
+        self.listeners.push((name, callback));
+        Ok(())
+    }
+}
+
+impl Drop for PointerBinding {
+    fn drop(&mut self) {
+        for (name, callback) in &self.listeners {
+            let _ = self
+                .element
+                .remove_event_listener_with_callback(name, callback.as_ref().unchecked_ref());
+        }
+
+        if let Some(html) = self.element.dyn_ref::<HtmlElement>() {
+            let style = html.style();
+            match self.previous_touch_action.as_deref() {
+                Some(value) if !value.is_empty() => {
+                    let _ = style.set_property("touch-action", value);
+                }
+                _ => {
+                    let _ = style.remove_property("touch-action");
+                }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// PINCH / ROTATE - Two-pointer recognizer
+// ============================================================================
+
+#[wasm_bindgen]
+pub struct PinchGesture {
+    pointers: Vec<(i32, f64, f64)>,
+    active: bool,
+
+    start_distance: f64,
+    start_angle: f64,
+    scale: f64,
+    rotation: f64,
+    scale_velocity: f64,
+    rotation_velocity: f64,
+    last_time: f64,
+
+    // Connected animation, with its scale/rotate when the gesture began
+    animation: Option<Rc<RefCell<crate::Animation>>>,
+    base_scale: f64,
+    base_rotation: f64,
+    // The gesture paused the animation mid-run; it resumes on release
+    resume_on_end: bool,
+}
+
+impl Default for PinchGesture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl PinchGesture {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> PinchGesture {
+        PinchGesture {
+            pointers: Vec::with_capacity(2),
+            active: false,
+            start_distance: 0.0,
+            start_angle: 0.0,
+            scale: 1.0,
+            rotation: 0.0,
+            scale_velocity: 0.0,
+            rotation_velocity: 0.0,
+            last_time: 0.0,
+            animation: None,
+            base_scale: 1.0,
+            base_rotation: 0.0,
+            resume_on_end: false,
+        }
+    }
+
+    /// Drive the animation's scale and rotate properties from the gesture.
+    /// If it was running when a pinch began it resumes once the pinch ends.
+    #[wasm_bindgen(js_name = connectAnimation)]
+    pub fn connect_animation(&mut self, handle: &crate::AnimationHandle) {
+        self.animation = Some(Rc::clone(&handle.animation));
+    }
+
+    // ========================================================================
+    // POINTER HANDLERS
+    // ========================================================================
+
+    #[wasm_bindgen(js_name = onPointerDown)]
+    pub fn on_pointer_down(&mut self, id: i32, x: f64, y: f64, timestamp: f64) {
+        if self.pointers.iter().any(|p| p.0 == id) || self.pointers.len() >= 2 {
+            return;
+        }
+        self.pointers.push((id, x, y));
+
+        if self.pointers.len() == 2 {
+            self.begin(timestamp);
+        }
+    }
+
+    #[wasm_bindgen(js_name = onPointerMove)]
+    pub fn on_pointer_move(&mut self, id: i32, x: f64, y: f64, timestamp: f64) {
+        match self.pointers.iter_mut().find(|p| p.0 == id) {
+            Some(pointer) => {
+                pointer.1 = x;
+                pointer.2 = y;
+            }
+            None => return,
+        }
+        if !self.active { return; }
+
+        let (distance, angle) = self.span();
+        let scale = if self.start_distance > 0.0 { distance / self.start_distance } else { 1.0 };
+        let rotation = self.rotation + wrap_degrees(angle - self.start_angle - self.rotation);
+
+        let dt = (timestamp - self.last_time).max(1.0);
+        self.scale_velocity = (scale - self.scale) / dt * 1000.0;
+        self.rotation_velocity = (rotation - self.rotation) / dt * 1000.0;
+        self.scale = scale;
+        self.rotation = rotation;
+        self.last_time = timestamp;
+
+        if let Some(ref anim) = self.animation {
+            let mut anim_ref = anim.borrow_mut();
+            let _ = anim_ref.set_live_number(crate::PropertyType::Scale, self.base_scale * scale);
+            let _ = anim_ref.set_live_number(crate::PropertyType::Rotate, self.base_rotation + rotation);
+        }
+    }
+
+    #[wasm_bindgen(js_name = onPointerUp)]
+    pub fn on_pointer_up(&mut self, id: i32) {
+        self.pointers.retain(|p| p.0 != id);
+        if self.active && self.pointers.len() < 2 {
+            self.active = false;
+            if std::mem::replace(&mut self.resume_on_end, false) {
+                if let Some(ref anim) = self.animation {
+                    let _ = anim.borrow_mut().resume();
+                }
+            }
+        }
+    }
+
+    // ========================================================================
+    // PROPERTIES
+    // ========================================================================
+
+    /// Scale relative to the pointer span when the gesture began
+    #[wasm_bindgen(getter)]
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Rotation in degrees since the gesture began
+    #[wasm_bindgen(getter)]
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    /// Scale change per second
+    #[wasm_bindgen(getter, js_name = scaleVelocity)]
+    pub fn scale_velocity(&self) -> f64 {
+        self.scale_velocity
+    }
+
+    /// Degrees per second
+    #[wasm_bindgen(getter, js_name = rotationVelocity)]
+    pub fn rotation_velocity(&self) -> f64 {
+        self.rotation_velocity
+    }
+
+    #[wasm_bindgen(getter, js_name = isActive)]
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    #[wasm_bindgen(getter, js_name = pointerCount)]
+    pub fn pointer_count(&self) -> usize {
+        self.pointers.len()
+    }
+}
+
+impl PinchGesture {
+    fn begin(&mut self, timestamp: f64) {
+        let (distance, angle) = self.span();
+        self.active = true;
+        self.start_distance = distance;
+        self.start_angle = angle;
+        self.scale = 1.0;
+        self.rotation = 0.0;
+        self.scale_velocity = 0.0;
+        self.rotation_velocity = 0.0;
+        self.last_time = timestamp;
+
+        if let Some(ref anim) = self.animation {
+            let mut anim_ref = anim.borrow_mut();
+            self.resume_on_end = anim_ref.get_state() == crate::AnimationState::Running;
+            let _ = anim_ref.pause();
+            self.base_scale = anim_ref.live_number(crate::PropertyType::Scale, 1.0);
+            self.base_rotation = anim_ref.live_number(crate::PropertyType::Rotate, 0.0);
+        }
+    }
+
+    /// Distance and angle (degrees) between the two pointers
+    fn span(&self) -> (f64, f64) {
+        let (_, x1, y1) = self.pointers[0];
+        let (_, x2, y2) = self.pointers[1];
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        (dx.hypot(dy), dy.atan2(dx).to_degrees())
+    }
+}
+
+/// Wrap an angle delta into (-180, 180] so crossing the atan2 seam doesn't spin
+fn wrap_degrees(delta: f64) -> f64 {
+    let wrapped = (delta + 180.0).rem_euclid(360.0) - 180.0;
+    if wrapped == -180.0 { 180.0 } else { wrapped }
+}
+
+// ============================================================================
+// SWIPE - Directional swipe recognizer
+// ============================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SwipeDirection {
+    Left = 0,
+    Right = 1,
+    Up = 2,
+    Down = 3,
+}
+
+struct SwipeState {
+    tracking: bool,
+    start_x: f64,
+    start_y: f64,
+    last_x: f64,
+    last_y: f64,
+    last_time: f64,
+    velocity_x: f64,
+    velocity_y: f64,
+
+    distance_threshold: f64,
+    velocity_threshold: f64,
+    last_swipe: Option<SwipeDirection>,
+
+    callback: Option<Function>,
+    // Animations resumed when a swipe in their direction is recognised
+    animations: Vec<(SwipeDirection, Rc<RefCell<crate::Animation>>)>,
+}
+
+impl SwipeState {
+    fn track(&mut self, x: f64, y: f64, timestamp: f64) {
+        let dt = (timestamp - self.last_time).max(1.0);
+        self.velocity_x = (x - self.last_x) / dt * 1000.0;
+        self.velocity_y = (y - self.last_y) / dt * 1000.0;
+        self.last_x = x;
+        self.last_y = y;
+        self.last_time = timestamp;
+    }
+}
+
+/// Handlers take `&self` and run the callback once the state is released, so
+/// the callback can use the recognizer
+#[wasm_bindgen]
+pub struct SwipeRecognizer {
+    state: RefCell<SwipeState>,
+}
+
+impl Default for SwipeRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl SwipeRecognizer {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SwipeRecognizer {
+        SwipeRecognizer {
+            state: RefCell::new(SwipeState {
+                tracking: false,
+                start_x: 0.0,
+                start_y: 0.0,
+                last_x: 0.0,
+                last_y: 0.0,
+                last_time: 0.0,
+                velocity_x: 0.0,
+                velocity_y: 0.0,
+                distance_threshold: 50.0,
+                velocity_threshold: 300.0,
+                last_swipe: None,
+                callback: None,
+                animations: Vec::new(),
+            }),
+        }
+    }
+
+    /// Called as callback(direction, distance, velocity in px/s) for each recognised swipe
+    #[wasm_bindgen(js_name = onSwipe)]
+    pub fn on_swipe(&mut self, callback: Function) {
+        self.state.borrow_mut().callback = Some(callback);
+    }
+
+    /// Resume a (paused) animation when a swipe in `direction` is recognised
+    #[wasm_bindgen(js_name = connectAnimation)]
+    pub fn connect_animation(&mut self, handle: &crate::AnimationHandle, direction: SwipeDirection) {
+        self.state.borrow_mut().animations.push((direction, Rc::clone(&handle.animation)));
+    }
+
+    /// Minimum travel along the swipe axis in px
+    #[wasm_bindgen(setter, js_name = distanceThreshold)]
+    pub fn set_distance_threshold(&mut self, value: f64) {
+        self.state.borrow_mut().distance_threshold = value.max(0.0);
+    }
+
+    /// Minimum release velocity along the swipe axis in px/s
+    #[wasm_bindgen(setter, js_name = velocityThreshold)]
+    pub fn set_velocity_threshold(&mut self, value: f64) {
+        self.state.borrow_mut().velocity_threshold = value.max(0.0);
+    }
+
+    // ========================================================================
+    // POINTER HANDLERS
+    // ========================================================================
+
+    #[wasm_bindgen(js_name = onPointerDown)]
+    pub fn on_pointer_down(&self, x: f64, y: f64, timestamp: f64) {
+        let mut state = self.state.borrow_mut();
+        state.tracking = true;
+        state.start_x = x;
+        state.start_y = y;
+        state.last_x = x;
+        state.last_y = y;
+        state.last_time = timestamp;
+        state.velocity_x = 0.0;
+        state.velocity_y = 0.0;
+    }
+
+    #[wasm_bindgen(js_name = onPointerMove)]
+    pub fn on_pointer_move(&self, x: f64, y: f64, timestamp: f64) {
+        let mut state = self.state.borrow_mut();
+        if !state.tracking { return; }
+        state.track(x, y, timestamp);
+    }
+
+    /// Finish the gesture; returns the recognised direction, if any
+    #[wasm_bindgen(js_name = onPointerUp)]
+    pub fn on_pointer_up(&self, x: f64, y: f64, timestamp: f64) -> Option<SwipeDirection> {
+        let (direction, distance, velocity, callback, animations) = {
+            let mut state = self.state.borrow_mut();
+            if !state.tracking { return None; }
+            // A release where the last move landed adds no new motion; keep that move's velocity
+            if x != state.last_x || y != state.last_y {
+                state.track(x, y, timestamp);
+            }
+            state.tracking = false;
+
+            let dx = state.last_x - state.start_x;
+            let dy = state.last_y - state.start_y;
+
+            // Dominant axis decides the direction; release velocity must point the same way
+            let (direction, distance, velocity) = if dx.abs() >= dy.abs() {
+                let direction = if dx < 0.0 { SwipeDirection::Left } else { SwipeDirection::Right };
+                (direction, dx.abs(), state.velocity_x * dx.signum())
+            } else {
+                let direction = if dy < 0.0 { SwipeDirection::Up } else { SwipeDirection::Down };
+                (direction, dy.abs(), state.velocity_y * dy.signum())
+            };
+
+            if distance < state.distance_threshold || velocity < state.velocity_threshold {
+                state.last_swipe = None;
+                return None;
+            }
+
+            state.last_swipe = Some(direction);
+            let animations: Vec<_> = state
+                .animations
+                .iter()
+                .filter(|(wanted, _)| *wanted == direction)
+                .map(|(_, anim)| Rc::clone(anim))
+                .collect();
+            (direction, distance, velocity, state.callback.clone(), animations)
+        };
+
+        for anim in animations {
+            let _ = anim.borrow_mut().resume();
+        }
+
+        if let Some(callback) = callback {
+            let _ = callback.call3(
+                &JsValue::NULL,
+                &JsValue::from_f64(direction as u8 as f64),
+                &JsValue::from_f64(distance),
+                &JsValue::from_f64(velocity),
+            );
+        }
+
+        Some(direction)
+    }
+
+    #[wasm_bindgen(js_name = onPointerCancel)]
+    pub fn on_pointer_cancel(&self) {
+        self.state.borrow_mut().tracking = false;
+    }
+
+    // ========================================================================
+    // PROPERTIES
+    // ========================================================================
+
+    #[wasm_bindgen(getter, js_name = isTracking)]
+    pub fn is_tracking(&self) -> bool {
+        self.state.borrow().tracking
+    }
+
+    /// Direction of the most recent recognised swipe
+    #[wasm_bindgen(getter, js_name = lastSwipe)]
+    pub fn last_swipe(&self) -> Option<SwipeDirection> {
+        self.state.borrow().last_swipe
+    }
+}
+
+// ============================================================================
+// LONG PRESS - Hold recognizer with per-frame progress
+// ============================================================================
+
+struct LongPressState {
+    pressing: bool,
+    completed: bool,
+    start_x: f64,
+    start_y: f64,
+    elapsed: f64,
+    progress: f64,
+
+    duration: f64,
+    move_tolerance: f64,
+
+    on_progress: Option<Function>,
+    on_complete: Option<Function>,
+    on_cancel: Option<Function>,
+    // Scrubbed with the hold progress, reset to 0 on cancel
+    animation: Option<Rc<RefCell<crate::Animation>>>,
+    ticker_id: Option<u32>,
+}
+
+/// Advance the hold by `dt` ms; false once it completed or stopped. Callbacks
+/// run after the state is released, so they can use the recognizer.
+fn advance_press(state: &Rc<RefCell<LongPressState>>, dt: f64) -> bool {
+    let (progress, done, on_progress, on_complete) = {
+        let mut state = state.borrow_mut();
+        if !state.pressing { return false; }
+
+        state.elapsed += dt;
+        state.progress = (state.elapsed / state.duration).min(1.0);
+
+        if let Some(ref anim) = state.animation {
+            let _ = anim.borrow_mut().set_fraction_complete(state.progress);
+        }
+
+        let done = state.progress >= 1.0;
+        if done {
+            state.pressing = false;
+            state.completed = true;
+            state.ticker_id = None;
+        }
+        let on_complete = if done { state.on_complete.clone() } else { None };
+        (state.progress, done, state.on_progress.clone(), on_complete)
+    };
+
+    if let Some(callback) = on_progress {
+        let _ = callback.call1(&JsValue::NULL, &JsValue::from_f64(progress));
+    }
+    if let Some(callback) = on_complete {
+        let _ = callback.call0(&JsValue::NULL);
+    }
+    !done
+}
+
+fn cancel_press(state: &Rc<RefCell<LongPressState>>) {
+    let (on_cancel, progress) = {
+        let mut state = state.borrow_mut();
+        if let Some(id) = state.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+        if !state.pressing { return; }
+
+        state.pressing = false;
+        if let Some(ref anim) = state.animation {
+            let _ = anim.borrow_mut().set_fraction_complete(0.0);
+        }
+        (state.on_cancel.clone(), state.progress)
+    };
+
+    if let Some(callback) = on_cancel {
+        let _ = callback.call1(&JsValue::NULL, &JsValue::from_f64(progress));
+    }
+}
+
+#[wasm_bindgen]
+pub struct LongPressRecognizer {
+    state: Rc<RefCell<LongPressState>>,
+}
+
+impl Default for LongPressRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl LongPressRecognizer {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> LongPressRecognizer {
+        LongPressRecognizer {
+            state: Rc::new(RefCell::new(LongPressState {
+                pressing: false,
+                completed: false,
+                start_x: 0.0,
+                start_y: 0.0,
+                elapsed: 0.0,
+                progress: 0.0,
+                duration: 500.0,
+                move_tolerance: 10.0,
+                on_progress: None,
+                on_complete: None,
+                on_cancel: None,
+                animation: None,
+                ticker_id: None,
+            })),
+        }
+    }
+
+    /// Hold time in ms before the press completes
+    #[wasm_bindgen(setter)]
+    pub fn set_duration(&mut self, value: f64) {
+        self.state.borrow_mut().duration = value.max(1.0);
+    }
+
+    /// Movement in px allowed before the press is cancelled
+    #[wasm_bindgen(setter, js_name = moveTolerance)]
+    pub fn set_move_tolerance(&mut self, value: f64) {
+        self.state.borrow_mut().move_tolerance = value.max(0.0);
+    }
+
+    /// Called every frame while held with the hold progress (0-1)
+    #[wasm_bindgen(js_name = onProgress)]
+    pub fn on_progress(&mut self, callback: Function) {
+        self.state.borrow_mut().on_progress = Some(callback);
+    }
+
+    #[wasm_bindgen(js_name = onComplete)]
+    pub fn on_complete(&mut self, callback: Function) {
+        self.state.borrow_mut().on_complete = Some(callback);
+    }
+
+    /// Called as callback(progress) when released, moved or cancelled early
+    #[wasm_bindgen(js_name = onCancel)]
+    pub fn on_cancel(&mut self, callback: Function) {
+        self.state.borrow_mut().on_cancel = Some(callback);
+    }
+
+    /// Scrub an animation (e.g. a filling ring) with the hold progress
+    #[wasm_bindgen(js_name = connectAnimation)]
+    pub fn connect_animation(&mut self, handle: &crate::AnimationHandle) {
+        let _ = handle.animation.borrow_mut().pause();
+        self.state.borrow_mut().animation = Some(Rc::clone(&handle.animation));
+    }
+
+    // ========================================================================
+    // POINTER HANDLERS
+    // ========================================================================
+
+    #[wasm_bindgen(js_name = onPointerDown)]
+    pub fn on_pointer_down(&mut self, x: f64, y: f64) -> Result<(), JsValue> {
+        cancel_press(&self.state);
+        {
+            let mut state = self.state.borrow_mut();
+            state.pressing = true;
+            state.completed = false;
+            state.start_x = x;
+            state.start_y = y;
+            state.elapsed = 0.0;
+            state.progress = 0.0;
+        }
+
+        let state = Rc::clone(&self.state);
+        let id = ticker::subscribe(Box::new(move |_now, dt| advance_press(&state, dt * 1000.0)))?;
+        self.state.borrow_mut().ticker_id = Some(id);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = onPointerMove)]
+    pub fn on_pointer_move(&mut self, x: f64, y: f64) {
+        let moved_away = {
+            let state = self.state.borrow();
+            state.pressing && (x - state.start_x).hypot(y - state.start_y) > state.move_tolerance
+        };
+        if moved_away {
+            cancel_press(&self.state);
+        }
+    }
+
+    /// Releasing before the hold completes cancels it
+    #[wasm_bindgen(js_name = onPointerUp)]
+    pub fn on_pointer_up(&mut self) {
+        cancel_press(&self.state);
+    }
+
+    #[wasm_bindgen(js_name = onPointerCancel)]
+    pub fn on_pointer_cancel(&mut self) {
+        cancel_press(&self.state);
+    }
+
+    // ========================================================================
+    // PROPERTIES
+    // ========================================================================
+
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> f64 {
+        self.state.borrow().progress
+    }
+
+    #[wasm_bindgen(getter, js_name = isPressing)]
+    pub fn is_pressing(&self) -> bool {
+        self.state.borrow().pressing
+    }
+
+    #[wasm_bindgen(getter, js_name = isComplete)]
+    pub fn is_complete(&self) -> bool {
+        self.state.borrow().completed
+    }
+}
+
+impl Drop for LongPressRecognizer {
+    fn drop(&mut self) {
+        let mut state = self.state.borrow_mut();
+        if let Some(id) = state.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
     }
 }
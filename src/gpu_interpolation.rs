@@ -0,0 +1,490 @@
+use crate::batch;
+use crate::cubic::CubicBezier;
+use js_sys::{Array, Float32Array, Function, Object, Promise, Reflect};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+
+// ============================================================================
+// GPU INTERPOLATION - WebGPU compute shader for batches of eased values
+// ============================================================================
+
+// WebGPU flags (GPUBufferUsage, GPUMapMode), which web-sys only exposes
+// behind its unstable APIs
+const USAGE_MAP_READ: u32 = 0x0001;
+const USAGE_COPY_SRC: u32 = 0x0004;
+const USAGE_COPY_DST: u32 = 0x0008;
+const USAGE_UNIFORM: u32 = 0x0040;
+const USAGE_STORAGE: u32 = 0x0080;
+const MAP_READ: u32 = 0x0001;
+
+const WORKGROUP_SIZE: usize = 64;
+/// f32s per track: from, to, delay, duration, x1, y1, x2, y2
+const TRACK_FLOATS: usize = 8;
+
+const SHADER: &str = r#"
+struct Track {
+    from_value: f32,
+    to_value: f32,
+    delay: f32,
+    duration: f32,
+    x1: f32,
+    y1: f32,
+    x2: f32,
+    y2: f32,
+};
+
+struct Params {
+    time: f32,
+    count: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> tracks: array<Track>;
+@group(0) @binding(2) var<storage, read_write> values: array<f32>;
+
+// One axis of a cubic bezier from 0 to 1 with control values a and b
+fn bezier(a: f32, b: f32, t: f32) -> f32 {
+    let mt = 1.0 - t;
+    return 3.0 * mt * mt * t * a + 3.0 * mt * t * t * b + t * t * t;
+}
+
+fn bezier_slope(a: f32, b: f32, t: f32) -> f32 {
+    let mt = 1.0 - t;
+    return 3.0 * mt * mt * a + 6.0 * mt * t * (b - a) + 3.0 * t * t * (1.0 - b);
+}
+
+fn ease(track: Track, x: f32) -> f32 {
+    // Newton-Raphson for the curve parameter at x
+    var t = x;
+    for (var i = 0; i < 8; i++) {
+        let error = bezier(track.x1, track.x2, t) - x;
+        let slope = bezier_slope(track.x1, track.x2, t);
+        if (abs(error) < 1e-5 || abs(slope) < 1e-6) {
+            break;
+        }
+        t = clamp(t - error / slope, 0.0, 1.0);
+    }
+    return bezier(track.y1, track.y2, t);
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if (i >= params.count) {
+        return;
+    }
+    let track = tracks[i];
+    var progress = select(0.0, 1.0, params.time >= track.delay);
+    if (track.duration > 0.0) {
+        progress = clamp((params.time - track.delay) / track.duration, 0.0, 1.0);
+    }
+    values[i] = mix(track.from_value, track.to_value, ease(track, progress));
+}
+"#;
+
+/// Latest values read back from the GPU
+#[derive(Default)]
+struct Readback {
+    values: Vec<f32>,
+    pending: bool,
+    callback: Option<Function>,
+}
+
+/// Buffers sized for `capacity` tracks
+struct Buffers {
+    capacity: usize,
+    tracks: JsValue,
+    output: JsValue,
+    staging: JsValue,
+    bind_group: JsValue,
+}
+
+/// Interpolates many (from, to, delay, duration, easing) tracks at once in
+/// a WebGPU compute shader, for particle systems and large staggers. Values
+/// are read back asynchronously, or the output buffer can be bound directly
+/// by a WebGPU renderer.
+#[wasm_bindgen]
+pub struct GpuInterpolator {
+    device: JsValue,
+    pipeline: JsValue,
+    params: JsValue,
+    buffers: Option<Buffers>,
+    tracks: Vec<[f32; TRACK_FLOATS]>,
+    easings: Vec<CubicBezier>,
+    dirty: bool,
+    readback: Rc<RefCell<Readback>>,
+}
+
+#[wasm_bindgen]
+impl GpuInterpolator {
+    /// Build the compute pipeline on a GPUDevice
+    #[wasm_bindgen(constructor)]
+    pub fn new(device: JsValue) -> Result<GpuInterpolator, JsValue> {
+        let module = call(
+            &device,
+            "createShaderModule",
+            &[&object(&[("code", SHADER.into())])?],
+        )?;
+        let pipeline = call(
+            &device,
+            "createComputePipeline",
+            &[&object(&[
+                ("layout", "auto".into()),
+                (
+                    "compute",
+                    object(&[("module", module), ("entryPoint", "main".into())])?,
+                ),
+            ])?],
+        )?;
+        let params = create_buffer(&device, 16, USAGE_UNIFORM | USAGE_COPY_DST)?;
+
+        Ok(GpuInterpolator {
+            device,
+            pipeline,
+            params,
+            buffers: None,
+            tracks: Vec::new(),
+            easings: Vec::new(),
+            dirty: false,
+            readback: Rc::new(RefCell::new(Readback::default())),
+        })
+    }
+
+    /// Request an adapter and device and resolve to a GpuInterpolator.
+    /// Rejects when WebGPU isn't available.
+    #[wasm_bindgen]
+    pub fn request() -> Result<Promise, JsValue> {
+        let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window available"))?;
+        let gpu = Reflect::get(&window.navigator(), &"gpu".into())?;
+        if gpu.is_undefined() {
+            return Err(JsValue::from_str("WebGPU not supported"));
+        }
+
+        let adapter = call(&gpu, "requestAdapter", &[])?;
+        let on_adapter = Closure::once_into_js(|adapter: JsValue| {
+            if adapter.is_null() {
+                return Err(JsValue::from_str("No WebGPU adapter available"));
+            }
+            call(&adapter, "requestDevice", &[])
+        });
+        let device = call(&adapter, "then", &[&on_adapter])?;
+
+        let on_device = Closure::once_into_js(|device: JsValue| {
+            GpuInterpolator::new(device).map(JsValue::from)
+        });
+        call(&device, "then", &[&on_device])?.dyn_into()
+    }
+
+    /// Add a track from `from` to `to` starting `delay` ms after time 0, with
+    /// a CSS easing (default ease). Returns its index.
+    #[wasm_bindgen(js_name = addTrack)]
+    pub fn add_track(
+        &mut self,
+        from: f32,
+        to: f32,
+        delay: f32,
+        duration: f32,
+        easing: Option<String>,
+    ) -> Result<u32, JsValue> {
+        let easing = match easing {
+            Some(easing) => CubicBezier::from_css(&easing)?,
+            None => CubicBezier::default(),
+        };
+        let index = self.tracks.len() as u32;
+        self.tracks.push([
+            from,
+            to,
+            delay.max(0.0),
+            duration.max(0.0),
+            easing.x1 as f32,
+            easing.y1 as f32,
+            easing.x2 as f32,
+            easing.y2 as f32,
+        ]);
+        self.easings.push(easing);
+        self.dirty = true;
+        Ok(index)
+    }
+
+    /// Add `count` identical tracks, each starting `stagger` ms after the
+    /// previous one. Returns the index of the first.
+    #[wasm_bindgen]
+    pub fn stagger(
+        &mut self,
+        count: u32,
+        from: f32,
+        to: f32,
+        duration: f32,
+        stagger: f32,
+        easing: Option<String>,
+    ) -> Result<u32, JsValue> {
+        let first = self.tracks.len() as u32;
+        for i in 0..count {
+            self.add_track(from, to, i as f32 * stagger, duration, easing.clone())?;
+        }
+        Ok(first)
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+        self.easings.clear();
+        self.dirty = true;
+        self.readback.borrow_mut().values.clear();
+    }
+
+    #[wasm_bindgen(getter, js_name = trackCount)]
+    pub fn track_count(&self) -> u32 {
+        self.tracks.len() as u32
+    }
+
+    /// Interpolate every track at `time` ms on the GPU. A readback is
+    /// started unless one is still in flight; its values arrive through
+    /// values() and onValues.
+    #[wasm_bindgen]
+    pub fn dispatch(&mut self, time: f64) -> Result<(), JsValue> {
+        let count = self.tracks.len();
+        if count == 0 {
+            return Ok(());
+        }
+        self.upload()?;
+        // Still waiting to grow the buffers
+        let Some(buffers) = self.buffers.as_ref().filter(|b| b.capacity >= count) else {
+            return Ok(());
+        };
+
+        let queue = Reflect::get(&self.device, &"queue".into())?;
+        let mut params = [0u8; 16];
+        params[..4].copy_from_slice(&(time as f32).to_le_bytes());
+        params[4..8].copy_from_slice(&(count as u32).to_le_bytes());
+        call(
+            &queue,
+            "writeBuffer",
+            &[
+                &self.params,
+                &0.into(),
+                &js_sys::Uint8Array::from(&params[..]),
+            ],
+        )?;
+
+        let encoder = call(&self.device, "createCommandEncoder", &[])?;
+        let pass = call(&encoder, "beginComputePass", &[])?;
+        call(&pass, "setPipeline", &[&self.pipeline])?;
+        call(&pass, "setBindGroup", &[&0.into(), &buffers.bind_group])?;
+        let workgroups = count.div_ceil(WORKGROUP_SIZE) as u32;
+        call(&pass, "dispatchWorkgroups", &[&workgroups.into()])?;
+        call(&pass, "end", &[])?;
+
+        let read = !self.readback.borrow().pending;
+        let bytes = (count * 4) as u32;
+        if read {
+            call(
+                &encoder,
+                "copyBufferToBuffer",
+                &[
+                    &buffers.output,
+                    &0.into(),
+                    &buffers.staging,
+                    &0.into(),
+                    &bytes.into(),
+                ],
+            )?;
+        }
+        let commands = call(&encoder, "finish", &[])?;
+        call(&queue, "submit", &[&Array::of1(&commands)])?;
+
+        if read {
+            self.read_back(buffers.staging.clone(), bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Interpolate every track at `time` ms on the CPU instead, e.g. when
+    /// WebGPU isn't available. Values are ready immediately.
+    #[wasm_bindgen(js_name = sampleCpu)]
+    pub fn sample_cpu(&mut self, time: f64) {
+        let count = self.tracks.len();
+        let (mut from, mut to, mut eased) = (
+            Vec::with_capacity(count),
+            Vec::with_capacity(count),
+            Vec::with_capacity(count),
+        );
+        for (track, easing) in self.tracks.iter().zip(&self.easings) {
+            let [start, end, delay, duration, ..] = track.map(f64::from);
+            let progress = if duration > 0.0 {
+                ((time - delay) / duration).clamp(0.0, 1.0)
+            } else if time >= delay {
+                1.0
+            } else {
+                0.0
+            };
+            from.push(start);
+            to.push(end);
+            eased.push(easing.solve(progress));
+        }
+
+        // Easing is per track; the lerp itself runs in SIMD batches
+        let mut values = vec![0.0; count];
+        batch::lerp(&from, &to, &eased, &mut values);
+        self.readback.borrow_mut().values = values.into_iter().map(|v| v as f32).collect();
+    }
+
+    /// Latest values, one per track
+    #[wasm_bindgen]
+    pub fn values(&self) -> Float32Array {
+        Float32Array::from(&self.readback.borrow().values[..])
+    }
+
+    /// Latest value of one track (0 before the first readback)
+    #[wasm_bindgen]
+    pub fn value(&self, index: u32) -> f32 {
+        let readback = self.readback.borrow();
+        readback.values.get(index as usize).copied().unwrap_or(0.0)
+    }
+
+    /// Called with a Float32Array of values after each GPU readback
+    #[wasm_bindgen(js_name = onValues)]
+    pub fn on_values(&mut self, callback: Option<Function>) {
+        self.readback.borrow_mut().callback = callback;
+    }
+
+    /// The GPUBuffer holding one f32 per track, for rendering without a
+    /// readback. Replaced when tracks outgrow it; undefined before dispatch.
+    #[wasm_bindgen(getter, js_name = outputBuffer)]
+    pub fn output_buffer(&self) -> JsValue {
+        self.buffers
+            .as_ref()
+            .map_or(JsValue::UNDEFINED, |b| b.output.clone())
+    }
+}
+
+impl GpuInterpolator {
+    /// Write changed tracks to the GPU, growing the buffers when needed
+    fn upload(&mut self) -> Result<(), JsValue> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let count = self.tracks.len();
+        if self.buffers.as_ref().is_none_or(|b| b.capacity < count) {
+            // A readback in flight would map the buffer we're replacing
+            if self.readback.borrow().pending {
+                return Ok(());
+            }
+            self.buffers = Some(self.create_buffers(count.next_power_of_two())?);
+        }
+        let Some(buffers) = &self.buffers else {
+            return Ok(());
+        };
+
+        let data: Vec<f32> = self.tracks.iter().flatten().copied().collect();
+        let queue = Reflect::get(&self.device, &"queue".into())?;
+        call(
+            &queue,
+            "writeBuffer",
+            &[&buffers.tracks, &0.into(), &Float32Array::from(&data[..])],
+        )?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn create_buffers(&self, capacity: usize) -> Result<Buffers, JsValue> {
+        let tracks = create_buffer(
+            &self.device,
+            capacity * TRACK_FLOATS * 4,
+            USAGE_STORAGE | USAGE_COPY_DST,
+        )?;
+        let output = create_buffer(&self.device, capacity * 4, USAGE_STORAGE | USAGE_COPY_SRC)?;
+        let staging = create_buffer(&self.device, capacity * 4, USAGE_MAP_READ | USAGE_COPY_DST)?;
+
+        let layout = call(&self.pipeline, "getBindGroupLayout", &[&0.into()])?;
+        let entry = |binding: u32, buffer: &JsValue| {
+            object(&[
+                ("binding", binding.into()),
+                ("resource", object(&[("buffer", buffer.clone())])?),
+            ])
+        };
+        let entries = Array::of3(
+            &entry(0, &self.params)?,
+            &entry(1, &tracks)?,
+            &entry(2, &output)?,
+        );
+        let bind_group = call(
+            &self.device,
+            "createBindGroup",
+            &[&object(&[("layout", layout), ("entries", entries.into())])?],
+        )?;
+
+        Ok(Buffers {
+            capacity,
+            tracks,
+            output,
+            staging,
+            bind_group,
+        })
+    }
+
+    /// Map the staging buffer and copy `bytes` of values out once the GPU
+    /// has finished
+    fn read_back(&self, staging: JsValue, bytes: u32) -> Result<(), JsValue> {
+        self.readback.borrow_mut().pending = true;
+        let mapped = call(
+            &staging,
+            "mapAsync",
+            &[&MAP_READ.into(), &0.into(), &bytes.into()],
+        )?;
+
+        let readback = Rc::clone(&self.readback);
+        let on_mapped = Closure::once_into_js(move |_: JsValue| -> Result<JsValue, JsValue> {
+            let range = call(&staging, "getMappedRange", &[&0.into(), &bytes.into()])?;
+            let values = Float32Array::new(&range).to_vec();
+            call(&staging, "unmap", &[])?;
+
+            let callback = {
+                let mut readback = readback.borrow_mut();
+                readback.pending = false;
+                readback.values = values;
+                readback.callback.clone()
+            };
+            if let Some(callback) = callback {
+                let values = Float32Array::from(&readback.borrow().values[..]);
+                callback.call1(&JsValue::NULL, &values)?;
+            }
+            Ok(JsValue::UNDEFINED)
+        });
+        let readback = Rc::clone(&self.readback);
+        let on_failed = Closure::once_into_js(move |_: JsValue| {
+            readback.borrow_mut().pending = false;
+        });
+        call(&mapped, "then", &[&on_mapped, &on_failed])?;
+        Ok(())
+    }
+}
+
+fn create_buffer(device: &JsValue, size: usize, usage: u32) -> Result<JsValue, JsValue> {
+    call(
+        device,
+        "createBuffer",
+        &[&object(&[
+            ("size", (size as u32).into()),
+            ("usage", usage.into()),
+        ])?],
+    )
+}
+
+/// Call `target[method](...args)`
+fn call(target: &JsValue, method: &str, args: &[&JsValue]) -> Result<JsValue, JsValue> {
+    let function: Function = Reflect::get(target, &method.into())?.dyn_into()?;
+    let args: Array = args.iter().copied().collect();
+    function.apply(target, &args)
+}
+
+/// A plain JS object from key/value pairs
+fn object(entries: &[(&str, JsValue)]) -> Result<JsValue, JsValue> {
+    let object = Object::new();
+    for (key, value) in entries {
+        Reflect::set(&object, &(*key).into(), value)?;
+    }
+    Ok(object.into())
+}
@@ -0,0 +1,223 @@
+use crate::spring::Spring;
+use crate::ticker;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{Event, HtmlElement, PointerEvent};
+
+// ============================================================================
+// MICRO-INTERACTIONS - Spring-driven press and hover feedback
+// ============================================================================
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct InteractionConfig {
+    scale: Option<f64>,
+    opacity: Option<f64>,
+    stiffness: Option<f64>,
+    damping: Option<f64>,
+}
+
+struct InteractionState {
+    element: HtmlElement,
+    // Values while pressed/hovered; at rest both are 1
+    scale_to: f64,
+    opacity_to: f64,
+    active: bool,
+    scale: Spring,
+    opacity: Spring,
+    ticker_id: Option<u32>,
+}
+
+impl InteractionState {
+    fn targets(&self) -> (f64, f64) {
+        if self.active {
+            (self.scale_to, self.opacity_to)
+        } else {
+            (1.0, 1.0)
+        }
+    }
+
+    /// Uses the individual `scale` property so transforms set elsewhere
+    /// (e.g. a Draggable's translate) are left alone
+    fn write(&self) {
+        let style = self.element.style();
+        if self.scale_to != 1.0 {
+            let _ = style.set_property("scale", &self.scale.current.to_string());
+        }
+        if self.opacity_to != 1.0 {
+            let _ = style.set_property("opacity", &self.opacity.current.to_string());
+        }
+    }
+
+    fn at_rest(&self) -> bool {
+        let (scale, opacity) = self.targets();
+        (self.scale.current - scale).abs() < 0.001
+            && (self.opacity.current - opacity).abs() < 0.001
+            && self.scale.velocity.abs() < 0.01
+            && self.opacity.velocity.abs() < 0.01
+    }
+}
+
+/// Retarget the springs; an interrupted animation carries its velocity into
+/// the new direction instead of restarting
+fn set_active(state: &Rc<RefCell<InteractionState>>, active: bool) {
+    let mut s = state.borrow_mut();
+    if s.active == active {
+        return;
+    }
+    s.active = active;
+    if s.ticker_id.is_some() {
+        return;
+    }
+
+    let tick_state = Rc::clone(state);
+    s.ticker_id = ticker::subscribe(Box::new(move |_now, dt| {
+        let mut s = tick_state.borrow_mut();
+        let dt = dt.min(0.032);
+        let (scale, opacity) = s.targets();
+        s.scale.update(scale, dt);
+        s.opacity.update(opacity, dt);
+
+        let settled = s.at_rest();
+        if settled {
+            s.scale.reset(scale);
+            s.opacity.reset(opacity);
+            s.ticker_id = None;
+        }
+        s.write();
+        !settled
+    }))
+    .ok();
+}
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+/// Listeners installed by bindPress/bindHover; removed by unbind() or when dropped
+#[wasm_bindgen]
+pub struct MicroInteraction {
+    state: Rc<RefCell<InteractionState>>,
+    listeners: Vec<(&'static str, EventCallback)>,
+}
+
+#[wasm_bindgen]
+impl MicroInteraction {
+    #[wasm_bindgen]
+    pub fn unbind(&mut self) {
+        let element = self.state.borrow().element.clone();
+        for (name, callback) in self.listeners.drain(..) {
+            let _ = element
+                .remove_event_listener_with_callback(name, callback.as_ref().unchecked_ref());
+        }
+        let mut state = self.state.borrow_mut();
+        if let Some(id) = state.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+
+    /// Whether the element is currently pressed (or hovered)
+    #[wasm_bindgen(getter, js_name = isActive)]
+    pub fn is_active(&self) -> bool {
+        self.state.borrow().active
+    }
+}
+
+impl MicroInteraction {
+    fn new(element: HtmlElement, config: JsValue, scale: f64) -> Result<MicroInteraction, JsValue> {
+        let config: InteractionConfig = if config.is_undefined() || config.is_null() {
+            InteractionConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)
+                .map_err(|e| JsValue::from_str(&format!("Invalid interaction config: {:?}", e)))?
+        };
+
+        let spring = Spring::new(
+            config.stiffness.unwrap_or(400.0),
+            config.damping.unwrap_or(30.0),
+        );
+        let mut state = InteractionState {
+            element,
+            scale_to: config.scale.unwrap_or(scale),
+            opacity_to: config.opacity.unwrap_or(1.0),
+            active: false,
+            scale: spring.clone(),
+            opacity: spring,
+            ticker_id: None,
+        };
+        state.scale.reset(1.0);
+        state.opacity.reset(1.0);
+
+        Ok(MicroInteraction {
+            state: Rc::new(RefCell::new(state)),
+            listeners: Vec::with_capacity(4),
+        })
+    }
+
+    fn listen(
+        &mut self,
+        name: &'static str,
+        handler: impl FnMut(Event) + 'static,
+    ) -> Result<(), JsValue> {
+        let callback: EventCallback = Closure::wrap(Box::new(handler) as Box<dyn FnMut(Event)>);
+        self.state
+            .borrow()
+            .element
+            .add_event_listener_with_callback(name, callback.as_ref().unchecked_ref())?;
+        self.listeners.push((name, callback));
+        Ok(())
+    }
+}
+
+impl Drop for MicroInteraction {
+    fn drop(&mut self) {
+        self.unbind();
+    }
+}
+
+/// Shrink the element while pressed and spring back on release.
+/// Config: `{ scale: 0.95, opacity: 1, stiffness: 400, damping: 30 }`
+#[wasm_bindgen(js_name = bindPress)]
+pub fn bind_press(element: HtmlElement, config: JsValue) -> Result<MicroInteraction, JsValue> {
+    let mut interaction = MicroInteraction::new(element, config, 0.95)?;
+
+    let state = Rc::clone(&interaction.state);
+    interaction.listen("pointerdown", move |event: Event| {
+        if event
+            .dyn_ref::<PointerEvent>()
+            .is_some_and(|e| e.is_primary())
+        {
+            set_active(&state, true);
+        }
+    })?;
+
+    for name in ["pointerup", "pointercancel", "pointerleave"] {
+        let state = Rc::clone(&interaction.state);
+        interaction.listen(name, move |_event: Event| set_active(&state, false))?;
+    }
+
+    Ok(interaction)
+}
+
+/// Grow the element while a mouse or pen hovers it. Touch is ignored so taps
+/// don't leave a sticky hover state.
+/// Config: `{ scale: 1.05, opacity: 1, stiffness: 400, damping: 30 }`
+#[wasm_bindgen(js_name = bindHover)]
+pub fn bind_hover(element: HtmlElement, config: JsValue) -> Result<MicroInteraction, JsValue> {
+    let mut interaction = MicroInteraction::new(element, config, 1.05)?;
+
+    for (name, active) in [("pointerenter", true), ("pointerleave", false)] {
+        let state = Rc::clone(&interaction.state);
+        interaction.listen(name, move |event: Event| {
+            let is_touch = event
+                .dyn_ref::<PointerEvent>()
+                .is_some_and(|e| e.pointer_type() == "touch");
+            if !is_touch {
+                set_active(&state, active);
+            }
+        })?;
+    }
+
+    Ok(interaction)
+}
@@ -1,34 +1,170 @@
 use crate::cubic::CubicBezier;
+use crate::frame_clock::FixedStep;
+use crate::shape_morphing::MorphTrack;
 use crate::spring::Spring;
+use crate::time_warp::TimeWarp;
 use crate::types::*;
+use crate::visibility::BackgroundPolicy;
 use js_sys::{self, Function};
 use serde_wasm_bindgen::from_value;
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::prelude::*;
-use web_sys::{window, Element, HtmlElement, Performance, SvgElement};
+use web_sys::{window, Element, HtmlElement, Performance};
 
+mod batch;
 mod choreographer;
+mod clip_path;
 mod cubic;
+mod draggable;
+mod element_state;
+mod engine;
+mod events;
+mod flip;
+mod follow;
+mod frame_clock;
 mod gesture;
+mod gpu_interpolation;
+mod interaction;
+mod lottie;
+mod magnetic;
+mod measure;
 mod metal_acceleration;
+mod metrics;
+mod migration;
+mod motion;
+mod noise;
+mod opacity_group;
+mod parallax;
 mod particle_effects;
+mod particle_gl;
+mod particle_worker;
+mod performance_monitor;
+mod presence;
+mod renderer;
+mod script;
+mod scroll_trigger;
+mod scroll_velocity;
 mod sequencer;
 mod shape_morphing;
+mod smart_tooltip;
+mod smooth_scroll;
+mod spec;
 mod spring;
+mod svg_draw;
+mod svg_path;
+mod ticker;
+mod tilt;
+mod time_warp;
 mod transaction;
 mod types;
+mod variants;
+mod viewport_trigger;
+mod visibility;
 
-pub use choreographer::Choreographer;
+pub use choreographer::{Choreographer, TransitionContext};
+pub use clip_path::ClipPathMorph;
 pub use cubic::CubicBezier as CubicBezierCurve;
-pub use gesture::GestureController;
+pub use draggable::{DragRelease, Draggable};
+pub use engine::Engine;
+pub use flip::Flip;
+pub use follow::Follow;
+pub use frame_clock::FrameClock;
+pub use gesture::{
+    FlingPhysics, GestureAxis, GestureController, LongPressRecognizer, PinchGesture,
+    SwipeDirection, SwipeRecognizer,
+};
+pub use gpu_interpolation::GpuInterpolator;
+pub use interaction::{bind_hover, bind_press, MicroInteraction};
+pub use lottie::LottiePlayer;
+pub use magnetic::Magnetic;
 pub use metal_acceleration::GPUAccelerator;
-pub use particle_effects::ParticleEmitter;
-pub use sequencer::Sequencer;
+pub use metrics::EngineMetrics;
+pub use migration::ConfigSchema;
+pub use opacity_group::{OpacityGroup, OpacityGroupMode};
+pub use parallax::Parallax;
+pub use particle_effects::{EmitDistribution, ParticleEmitter, ParticleShape};
+pub use particle_worker::{ParticleWorker, ParticleWorkerHost};
+pub use performance_monitor::{PerformanceMonitor, QualityLevel};
+pub use presence::{animate_out, ListTransition, Presence};
+pub use renderer::{DomRenderer, Renderer, RendererRegistry};
+pub use script::Script;
+pub use scroll_trigger::ScrollTrigger;
+pub use scroll_velocity::ScrollVelocity;
+pub use sequencer::{Sequencer, StaggerFrom};
 pub use shape_morphing::PathMorph;
+pub use smart_tooltip::SmartTooltip;
+pub use smooth_scroll::{scroll_to, ScrollToHandle};
 pub use spring::Spring as SpringPhysics;
+pub use svg_draw::{DrawSvg, SvgDrawIn};
+pub use svg_path::{PathPoint, SvgPath};
+pub use tilt::Tilt;
 pub use transaction::AnimationTransaction;
+pub use variants::{animate_to_variant, Variants};
+pub use viewport_trigger::ViewportTrigger;
+
+/// DOM-free pieces of the engine for the native tests and benches; not part
+/// of the public API
+#[doc(hidden)]
+pub mod testing {
+    pub mod batch {
+        pub use crate::batch::*;
+    }
+    pub mod engine {
+        pub use crate::engine::{preset, rest_value};
+    }
+    pub mod follow {
+        pub use crate::follow::translation_to;
+    }
+    pub mod frame_clock {
+        pub use crate::frame_clock::FixedStep;
+    }
+    pub mod lottie {
+        pub use crate::lottie::{bezier_path, Segment, Track, Transform};
+    }
+    pub mod magnetic {
+        pub use crate::magnetic::pull;
+    }
+    pub mod motion {
+        pub use crate::motion::*;
+    }
+    pub mod parallax {
+        pub use crate::parallax::{LayerFactors, LayerStyle};
+    }
+    pub mod particle_effects {
+        pub use crate::particle_effects::{Bounds, SpriteSheet};
+    }
+    pub mod script {
+        pub use crate::script::{tick, ScriptAction, ScriptCallback, ScriptState, Target};
+    }
+    pub mod sequencer {
+        pub use crate::sequencer::{
+            crosses, iteration_fraction, parse_position, stagger_ranks, PositionBase, StaggerFrom,
+        };
+    }
+    pub mod scroll_trigger {
+        pub use crate::scroll_trigger::{progress, smooth_toward, Edge, ScrollPosition, Smoothing};
+    }
+    pub mod scroll_velocity {
+        pub use crate::scroll_velocity::{intensity, VelocityEffect};
+    }
+    pub mod smooth_scroll {
+        pub use crate::smooth_scroll::ScrollTween;
+    }
+    pub mod spec {
+        pub use crate::spec::{parse_easing, Easing};
+    }
+    pub mod tilt {
+        pub use crate::tilt::tilt_angles;
+    }
+    pub mod types {
+        pub use crate::types::{parse_css_length, LengthUnit};
+    }
+    pub mod visibility {
+        pub use crate::visibility::{BackgroundPolicy, Gap};
+    }
+}
 
 #[wasm_bindgen]
 #[derive(Clone, Copy, PartialEq)]
@@ -41,11 +177,14 @@ pub enum AnimationState {
 
 #[wasm_bindgen]
 pub struct Animation {
+    id: u32,
     element: Element,
     properties: Vec<AnimationProperty>,
     springs: Vec<Spring>,
+    spring_steps: FixedStep,
     keyframes: Vec<Keyframe>,
     bezier: Option<CubicBezier>,
+    time_warp: TimeWarp,
     duration: f64,
     delay: f64,
     start_time: f64,
@@ -65,6 +204,26 @@ pub struct Animation {
     transform_origin: (String, String, String),
     shadow_layers: Vec<ShadowValue>,
     continue_animate: bool,
+    align_frames: u8,
+    renderer: RefCell<Box<dyn Renderer>>,
+    path_morph: Option<MorphTrack>,
+    // Holds a compositor layer (will-change) while running
+    promoted: bool,
+    // Open transaction this animation counts toward until it finishes
+    transaction: Option<Rc<transaction::Scope>>,
+    listeners: events::Listeners,
+    // The start event has gone out for this run
+    start_emitted: bool,
+    // Overrides the page-wide policy for time spent in a background tab
+    background: Option<BackgroundPolicy>,
+    // Shared with the frame callback, which holds the animation while running
+    frame_loop: Rc<RefCell<FrameLoop>>,
+    // The element was in the document at start, so leaving it interrupts
+    attached: bool,
+    // Transform the animated one goes on top of; None reads the element's own
+    base_transform: Option<String>,
+    // Inline styles it writes, as they were before it first wrote them
+    original_styles: Option<Vec<(String, Option<String>)>>,
 }
 
 #[wasm_bindgen]
@@ -84,8 +243,15 @@ impl AnimationHandle {
         self.animation.borrow_mut().resume()
     }
 
-    #[wasm_bindgen]
-    pub fn stop(&self) -> Result<(), JsValue> {
+    /// Stop and leave the element as it is (`"keep"`, the default), at the
+    /// end values (`"end"`), or with the inline styles it had before the
+    /// animation first wrote any (`"restore"`)
+    #[wasm_bindgen(js_name = stop)]
+    pub fn stop_with(&self, behavior: Option<String>) -> Result<(), JsValue> {
+        self.animation.borrow_mut().stop_with(behavior)
+    }
+
+    pub(crate) fn stop(&self) -> Result<(), JsValue> {
         self.animation.borrow_mut().stop()
     }
 
@@ -108,6 +274,22 @@ impl AnimationHandle {
     pub fn get_state(&self) -> AnimationState {
         self.animation.borrow().get_state()
     }
+
+    /// Call `callback({ type, progress })` on "start", "update", "complete",
+    /// "cancel" or "repeat". Any number of listeners can be added.
+    #[wasm_bindgen]
+    pub fn add_event_listener(&self, event: String, callback: Function) -> Result<(), JsValue> {
+        let event = events::parse_or_err(&event)?;
+        self.animation.borrow().listeners.add(event, callback);
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn remove_event_listener(&self, event: String, callback: Function) -> Result<(), JsValue> {
+        let event = events::parse_or_err(&event)?;
+        self.animation.borrow().listeners.remove(event, &callback);
+        Ok(())
+    }
 }
 
 #[wasm_bindgen]
@@ -119,12 +301,17 @@ impl Animation {
             .performance()
             .ok_or_else(|| JsValue::from_str("No performance API"))?;
 
+        let renderer = RefCell::new(renderer::for_element(&element));
+
         Ok(Animation {
+            id: metrics::next_animation_id(),
             element,
             properties: Vec::with_capacity(32),
             springs: Vec::with_capacity(32),
+            spring_steps: FixedStep::default(),
             keyframes: Vec::with_capacity(16),
             bezier: Some(CubicBezier::smooth()),
+            time_warp: TimeWarp::default(),
             duration: 400.0,
             delay: 0.0,
             start_time: 0.0,
@@ -144,6 +331,18 @@ impl Animation {
             transform_origin: ("50%".to_string(), "50%".to_string(), "0".to_string()),
             shadow_layers: Vec::new(),
             continue_animate: false,
+            align_frames: 0,
+            renderer,
+            path_morph: None,
+            promoted: false,
+            transaction: None,
+            listeners: events::Listeners::default(),
+            start_emitted: false,
+            background: None,
+            frame_loop: Rc::default(),
+            attached: false,
+            base_transform: None,
+            original_styles: None,
         })
     }
 
@@ -219,10 +418,33 @@ impl Animation {
         self
     }
 
+    /// Use a CSS easing string, e.g. "cubic-bezier(0.2, 0, 0, 1)" or "ease-in-out"
+    #[wasm_bindgen]
+    pub fn ease(mut self, easing: String) -> Result<Animation, JsValue> {
+        self.bezier = Some(CubicBezier::from_css(&easing)?);
+        self.use_spring = false;
+        Ok(self)
+    }
+
     // ========================================================================
     // SPRING PHYSICS
     // ========================================================================
 
+    /// Play the timeline between two fractions `factor` times slower, applied
+    /// before easing. The rest of the timeline speeds up to keep the duration.
+    #[wasm_bindgen]
+    pub fn slow_zone(
+        mut self,
+        from_fraction: f64,
+        to_fraction: f64,
+        factor: f64,
+    ) -> Result<Animation, JsValue> {
+        self.time_warp
+            .add_zone(from_fraction, to_fraction, factor)
+            .map_err(|e| JsValue::from_str(&e))?;
+        Ok(self)
+    }
+
     #[wasm_bindgen]
     pub fn spring(mut self, _stiffness: f64, _damping: f64) -> Self {
         self.use_spring = true;
@@ -281,15 +503,61 @@ impl Animation {
         self
     }
 
+    /// What to do while the tab is in the background, instead of the
+    /// page-wide Engine.setBackgroundPolicy: "pause" carries on from where
+    /// it was, "fastForward" jumps to where it would be by now
+    #[wasm_bindgen(js_name = backgroundPolicy)]
+    pub fn background_policy(mut self, policy: String) -> Result<Animation, JsValue> {
+        self.background = Some(visibility::parse_or_err(&policy)?);
+        Ok(self)
+    }
+
+    /// Send frames to a custom renderer: any object with `apply(property, value)`
+    /// and an optional `flush()`, instead of the element's inline style
+    #[wasm_bindgen]
+    pub fn with_renderer(mut self, renderer: JsValue) -> Result<Animation, JsValue> {
+        self.renderer = RefCell::new(Box::new(renderer::JsRenderer::from_js(renderer)?));
+        // The element's own transform isn't the renderer's business
+        self.base_transform.get_or_insert_with(String::new);
+        Ok(self)
+    }
+
+    /// Transform to put the animated one after, e.g. `"translate(-50%, -50%)"`
+    /// for a centered element. By default it's the transform the element had
+    /// before the engine first animated it, inline or from a stylesheet;
+    /// `"none"` replaces it instead.
+    #[wasm_bindgen(js_name = baseTransform)]
+    pub fn base_transform(mut self, transform: String) -> Animation {
+        let transform = transform.trim();
+        self.base_transform = Some(if transform == "none" {
+            String::new()
+        } else {
+            transform.to_string()
+        });
+        self
+    }
+
+    /// Morph the element's `d` attribute from `start_d` to `end_d` on every
+    /// frame, following this animation's curve, keyframe timing or spring.
+    /// The paths are normalized, so their commands don't have to match.
+    #[wasm_bindgen]
+    pub fn morph_path(mut self, start_d: String, end_d: String) -> Result<Animation, JsValue> {
+        let morph = PathMorph::normalize(start_d, end_d, None)?;
+        self.path_morph = Some(MorphTrack::new(morph));
+        Ok(self)
+    }
+
     #[wasm_bindgen]
     pub fn on_complete(mut self, callback: Function) -> Self {
         self.completion_callback = Some(callback);
         self
     }
 
+    /// Initial spring velocity for a property in units per second (px/s for
+    /// positions, matching GestureController velocities)
     #[wasm_bindgen]
     pub fn with_velocity(mut self, property: String, velocity: f64) -> Self {
-        if let Some(prop_type) = PropertyType::from_str(&property) {
+        if let Ok(prop_type) = property.parse::<PropertyType>() {
             self.gesture_velocity.push((prop_type, velocity));
         }
         self
@@ -329,20 +597,24 @@ impl Animation {
 
     #[wasm_bindgen]
     pub fn animate(mut self, config: JsValue) -> Result<Animation, JsValue> {
-        let cfg: AnimateConfig = from_value(config)
-            .map_err(|e| JsValue::from_str(&format!("Invalid config: {:?}", e)))?;
-
-        self.setup_properties(&cfg)?;
+        self.configure(config)?;
         Ok(self)
     }
 
+    /// Build an animation from a JSON spec (a string or the parsed object)
+    /// instead of builder calls; see the README for the schema. Not started.
+    #[wasm_bindgen(js_name = fromSpec)]
+    pub fn from_spec(spec: JsValue) -> Result<Animation, JsValue> {
+        spec::animation(spec)
+    }
+
     // ========================================================================
     // KEYFRAMES
     // ========================================================================
 
     #[wasm_bindgen]
     pub fn add_keyframe(mut self, config: JsValue) -> Result<Animation, JsValue> {
-        let kf: KeyframeConfig = from_value(config)
+        let kf: KeyframeConfig = from_value(migration::migrate(config)?)
             .map_err(|e| JsValue::from_str(&format!("Invalid keyframe: {:?}", e)))?;
 
         self.push_keyframe(kf)?;
@@ -352,14 +624,7 @@ impl Animation {
 
     #[wasm_bindgen]
     pub fn add_keyframes(mut self, configs: JsValue) -> Result<Animation, JsValue> {
-        let keyframe_configs: Vec<KeyframeConfig> = from_value(configs)
-            .map_err(|e| JsValue::from_str(&format!("Invalid keyframes config: {:?}", e)))?;
-
-        for kf in keyframe_configs {
-            self.push_keyframe(kf)?;
-        }
-
-        self.use_keyframes = true;
+        self.load_keyframes(configs)?;
         Ok(self)
     }
 
@@ -368,40 +633,81 @@ impl Animation {
     // ========================================================================
 
     #[wasm_bindgen]
-    pub fn start(mut self) -> Result<AnimationHandle, JsValue> {
-        if self.state == AnimationState::Running {
-            return Err(JsValue::from_str("Animation already running"));
-        }
-
-        self.capture_start_values()?;
+    pub fn start(self) -> Result<AnimationHandle, JsValue> {
+        self.start_aligned(0)
+    }
 
-        let now = self.performance.now();
-        self.start_time = now + self.delay;
-        self.last_time = now;
-        self.state = AnimationState::Running;
-        self.fraction_complete = 0.0;
-        self.current_repeat = 0;
+    /// Start the clock at the next animation frame instead of now, so an
+    /// animation triggered mid-event doesn't jump on its first frame
+    #[wasm_bindgen]
+    pub fn start_aligned_to_next_frame(self) -> Result<AnimationHandle, JsValue> {
+        self.start_aligned(1)
+    }
 
-        let animation = Rc::new(RefCell::new(self));
-        spawn_animation_loop(animation.clone())?;
+    /// Start the clock on the first frame after the browser has painted
+    #[wasm_bindgen]
+    pub fn start_after_paint(self) -> Result<AnimationHandle, JsValue> {
+        self.start_aligned(2)
+    }
 
-        Ok(AnimationHandle { animation })
+    /// Start several animations on one shared timestamp, so lockstep
+    /// choreography doesn't drift by the time it takes to start each
+    #[wasm_bindgen]
+    pub fn start_synced(animations: Vec<Animation>) -> Result<Vec<AnimationHandle>, JsValue> {
+        let Some(first) = animations.first() else {
+            return Ok(Vec::new());
+        };
+        let now = first.performance.now();
+
+        let mut handles = Vec::with_capacity(animations.len());
+        for mut animation in animations {
+            animation.start_internal_at(now)?;
+            let animation = Rc::new(RefCell::new(animation));
+            spawn_animation_loop(animation.clone())?;
+            handles.push(AnimationHandle { animation });
+        }
+        Ok(handles)
     }
 
     #[wasm_bindgen]
     pub fn start_internal(&mut self) -> Result<(), JsValue> {
+        let now = self.performance.now();
+        self.start_internal_at(now)
+    }
+
+    fn start_internal_at(&mut self, now: f64) -> Result<(), JsValue> {
         if self.state == AnimationState::Running {
             return Err(JsValue::from_str("Animation already running"));
         }
 
         self.capture_start_values()?;
+        self.capture_original_styles();
+        visibility::watch();
+        self.attached = self.element.is_connected();
+        // Read before the first frame writes a transform of its own
+        if self.base_transform.is_none() {
+            self.base_transform = Some(element_state::base_transform(&self.element));
+        }
+        if self.transaction.is_none() {
+            transaction::join(self);
+        }
 
-        let now = self.performance.now();
+        // Animations started in one transaction share its start time
+        let now = match &self.transaction {
+            Some(scope) => scope.start_time(now),
+            None => now,
+        };
         self.start_time = now + self.delay;
         self.last_time = now;
         self.state = AnimationState::Running;
         self.fraction_complete = 0.0;
         self.current_repeat = 0;
+        self.start_emitted = false;
+        metrics::mark_start(&self.performance, self.id);
+        if !self.promoted {
+            let properties = self.properties.iter().map(|p| p.property_type);
+            self.promoted = metal_acceleration::promote(&self.element, properties);
+        }
 
         Ok(())
     }
@@ -425,17 +731,78 @@ impl Animation {
         Ok(())
     }
 
-    #[wasm_bindgen]
-    pub fn stop(&mut self) -> Result<(), JsValue> {
+    /// Stop and leave the element as it is (`"keep"`, the default), at the
+    /// end values (`"end"`), or with the inline styles it had before the
+    /// animation first wrote any (`"restore"`, also after completing)
+    #[wasm_bindgen(js_name = stop)]
+    pub fn stop_with(&mut self, behavior: Option<String>) -> Result<(), JsValue> {
+        let behavior = match behavior {
+            Some(name) => StopBehavior::parse(&name)?,
+            None => StopBehavior::Keep,
+        };
+        self.stop_as(behavior)
+    }
+
+    pub(crate) fn stop(&mut self) -> Result<(), JsValue> {
+        self.stop_as(StopBehavior::Keep)
+    }
+
+    pub(crate) fn stop_as(&mut self, behavior: StopBehavior) -> Result<(), JsValue> {
+        if matches!(self.state, AnimationState::Running | AnimationState::Paused) {
+            self.listeners
+                .emit(events::AnimationEvent::Cancel, self.fraction_complete);
+            if behavior == StopBehavior::End {
+                self.snap_to_end();
+                self.apply_properties()?;
+            }
+            if behavior != StopBehavior::Restore {
+                element_state::record(&self.element, &self.properties);
+            }
+        }
+        if behavior == StopBehavior::Restore {
+            if let Some(original) = &self.original_styles {
+                renderer::restore_inline(&self.element, original)?;
+            }
+            element_state::forget(&self.element, &self.properties);
+        }
         self.state = AnimationState::Completed;
+        self.release_layer();
+        self.leave_transaction();
+        self.frame_loop.borrow_mut().end();
         Ok(())
     }
 
+    /// Note the inline styles this animation will write, for stop("restore")
+    fn capture_original_styles(&mut self) {
+        if self.original_styles.is_some() {
+            return;
+        }
+        let mut written =
+            motion::written_properties(&self.properties, &self.keyframes, &self.transform_origin);
+        if self.path_morph.is_some() {
+            written.push("d".to_string());
+        }
+        self.original_styles = Some(renderer::read_inline(&self.element, &written));
+    }
+
+    /// Stop for good because the element was removed from the document
+    fn interrupt(&mut self) {
+        self.listeners
+            .emit(events::AnimationEvent::Interrupted, self.fraction_complete);
+        self.state = AnimationState::Completed;
+        self.release_layer();
+        self.leave_transaction();
+        self.frame_loop.borrow_mut().end();
+    }
+
     #[wasm_bindgen]
     pub fn reverse(&mut self) -> Result<(), JsValue> {
         for prop in self.properties.iter_mut() {
             std::mem::swap(&mut prop.start, &mut prop.end);
         }
+        if let Some(track) = &mut self.path_morph {
+            track.reverse();
+        }
 
         self.start_time = self.performance.now();
         self.fraction_complete = 0.0;
@@ -449,22 +816,29 @@ impl Animation {
 
     #[wasm_bindgen]
     pub fn set_fraction_complete(&mut self, fraction: f64) -> Result<(), JsValue> {
+        self.capture_original_styles();
         self.fraction_complete = fraction.clamp(0.0, 1.0);
+        let progress = self.time_warp.apply(self.fraction_complete);
 
         let eased = match &self.bezier {
-            Some(bezier) => bezier.solve(self.fraction_complete),
-            None => self.fraction_complete,
+            Some(bezier) => bezier.solve(progress),
+            None => progress,
         };
 
         if self.use_keyframes {
-            self.update_keyframes(self.fraction_complete)?;
+            self.update_keyframes(progress)?;
         } else {
             for prop in self.properties.iter_mut() {
                 prop.current = interpolate_value(&prop.start, &prop.end, eased);
             }
         }
+        if let Some(track) = &mut self.path_morph {
+            track.sample(eased);
+        }
 
         self.apply_properties()?;
+        self.listeners
+            .emit(events::AnimationEvent::Update, self.fraction_complete);
         Ok(())
     }
 
@@ -478,10 +852,59 @@ impl Animation {
         self.state
     }
 
+    /// Current velocity of a property in units per second, for handing off
+    /// to a spring or decay without a visible speed jump
+    #[wasm_bindgen]
+    pub fn get_velocity(&self, property: String) -> f64 {
+        let prop_type = match property.parse::<PropertyType>() {
+            Ok(prop_type) => prop_type,
+            Err(_) => return 0.0,
+        };
+
+        let index = match self
+            .properties
+            .iter()
+            .position(|p| p.property_type == prop_type)
+        {
+            Some(index) => index,
+            None => return 0.0,
+        };
+
+        if self.use_spring {
+            return self.springs.get(index).map(|s| s.velocity).unwrap_or(0.0);
+        }
+
+        if self.duration <= 0.0 || self.use_keyframes {
+            return 0.0;
+        }
+
+        let prop = &self.properties[index];
+        let distance = extract_number(&prop.end) - extract_number(&prop.start);
+        let progress = self.time_warp.apply(self.fraction_complete);
+        let slope = match &self.bezier {
+            Some(bezier) => bezier.velocity_at(progress),
+            None => 1.0,
+        } * self.time_warp.rate_at(self.fraction_complete);
+
+        slope * distance / self.duration * 1000.0
+    }
+
     // ========================================================================
     // INTERNAL METHODS
     // ========================================================================
 
+    /// Start and spawn the frame loop. The clock is anchored `align_frames`
+    /// animation frames from now (0 = immediately).
+    fn start_aligned(mut self, align_frames: u8) -> Result<AnimationHandle, JsValue> {
+        self.start_internal()?;
+        self.align_frames = align_frames;
+
+        let animation = Rc::new(RefCell::new(self));
+        spawn_animation_loop(animation.clone())?;
+
+        Ok(AnimationHandle { animation })
+    }
+
     fn push_keyframe(&mut self, kf: KeyframeConfig) -> Result<(), JsValue> {
         let mut props = Vec::with_capacity(20);
 
@@ -641,89 +1064,27 @@ impl Animation {
         setup_length!(cfg.perspective_origin_x, PropertyType::PerspectiveOriginX);
         setup_length!(cfg.perspective_origin_y, PropertyType::PerspectiveOriginY);
 
-        // ✨ If continue_animate, read stored values and add as frozen properties
+        // Carry on from the values the last animation left, and keep the
+        // ones this config doesn't animate
         if self.continue_animate {
-            if let Ok(html_elem) = self.element.clone().dyn_into::<HtmlElement>() {
-                let get_attr = |name: &str| -> Option<String> { html_elem.get_attribute(name) };
-
-                // Read stored X
-                if cfg.x.is_none() {
-                    if let Some(x_str) = get_attr("data-anim-x") {
-                        if let Ok(x_val) = x_str.parse::<f64>() {
-                            if x_val != 0.0 {
-                                self.properties.push(AnimationProperty {
-                                    property_type: PropertyType::X,
-                                    start: AnimatableValue::Number(x_val),
-                                    end: AnimatableValue::Number(x_val),
-                                    current: AnimatableValue::Number(x_val),
-                                });
-                            }
-                        }
-                    }
-                }
-
-                // Read stored Y
-                if cfg.y.is_none() {
-                    if let Some(y_str) = get_attr("data-anim-y") {
-                        if let Ok(y_val) = y_str.parse::<f64>() {
-                            if y_val != 0.0 {
-                                self.properties.push(AnimationProperty {
-                                    property_type: PropertyType::Y,
-                                    start: AnimatableValue::Number(y_val),
-                                    end: AnimatableValue::Number(y_val),
-                                    current: AnimatableValue::Number(y_val),
-                                });
-                            }
-                        }
-                    }
-                }
-
-                // Read stored Z
-                if cfg.z.is_none() {
-                    if let Some(z_str) = get_attr("data-anim-z") {
-                        if let Ok(z_val) = z_str.parse::<f64>() {
-                            if z_val != 0.0 {
-                                self.properties.push(AnimationProperty {
-                                    property_type: PropertyType::Z,
-                                    start: AnimatableValue::Number(z_val),
-                                    end: AnimatableValue::Number(z_val),
-                                    current: AnimatableValue::Number(z_val),
-                                });
-                            }
-                        }
-                    }
-                }
-
-                // Read stored Scale
-                if cfg.scale.is_none() {
-                    if let Some(scale_str) = get_attr("data-anim-scale") {
-                        if let Ok(scale_val) = scale_str.parse::<f64>() {
-                            if scale_val != 1.0 {
-                                self.properties.push(AnimationProperty {
-                                    property_type: PropertyType::Scale,
-                                    start: AnimatableValue::Number(scale_val),
-                                    end: AnimatableValue::Number(scale_val),
-                                    current: AnimatableValue::Number(scale_val),
-                                });
-                            }
-                        }
-                    }
-                }
-
-                // Read stored Opacity
-                if cfg.opacity.is_none() {
-                    if let Some(opacity_str) = get_attr("data-anim-opacity") {
-                        if let Ok(opacity_val) = opacity_str.parse::<f64>() {
-                            if opacity_val != 1.0 {
-                                self.properties.push(AnimationProperty {
-                                    property_type: PropertyType::Opacity,
-                                    start: AnimatableValue::Number(opacity_val),
-                                    end: AnimatableValue::Number(opacity_val),
-                                    current: AnimatableValue::Number(opacity_val),
-                                });
-                            }
+            for (property_type, value) in element_state::recorded(&self.element) {
+                match self
+                    .properties
+                    .iter_mut()
+                    .find(|p| p.property_type == property_type)
+                {
+                    Some(prop) => {
+                        if element_state::continues(&value, &prop.end) {
+                            prop.start = value.clone();
+                            prop.current = value;
                         }
                     }
+                    None => self.properties.push(AnimationProperty {
+                        property_type,
+                        start: value.clone(),
+                        end: value.clone(),
+                        current: value,
+                    }),
                 }
             }
         }
@@ -961,6 +1322,9 @@ impl Animation {
         for prop in self.properties.iter_mut() {
             prop.current = prop.start.clone();
         }
+        if let Some(track) = &mut self.path_morph {
+            track.reset();
+        }
 
         if self.use_spring && !self.properties.is_empty() {
             self.springs = self
@@ -968,6 +1332,7 @@ impl Animation {
                 .iter()
                 .map(|prop| {
                     let mut spring = Spring::default();
+                    spring.reset(extract_number(&prop.start));
 
                     if let Some(&(_, velocity)) = self
                         .gesture_velocity
@@ -977,7 +1342,6 @@ impl Animation {
                         spring.velocity = velocity;
                     }
 
-                    spring.reset(extract_number(&prop.start));
                     spring
                 })
                 .collect();
@@ -990,14 +1354,48 @@ impl Animation {
         if self.state != AnimationState::Running {
             return Ok(());
         }
+        // Styles written to a removed element would go nowhere
+        if self.attached && !self.element.is_connected() {
+            self.interrupt();
+            return Ok(());
+        }
 
         let now = self.performance.now();
 
+        if self.align_frames > 0 {
+            self.align_frames -= 1;
+            if self.align_frames > 0 {
+                return Ok(());
+            }
+            let anchor = match &self.transaction {
+                Some(scope) => scope.start_time(now),
+                None => now,
+            };
+            self.start_time = anchor + self.delay;
+            self.last_time = now;
+        }
+
+        // No frames run while the tab is hidden; a pause since the last frame
+        // already moved the clock on in resume()
+        if self.pause_time <= self.last_time {
+            if let Some(missed) = visibility::last_gap().and_then(|gap| gap.missed(self.last_time))
+            {
+                self.catch_up(now, missed);
+            }
+        }
+
         if now < self.start_time {
             return Ok(());
         }
+        if !self.start_emitted {
+            self.start_emitted = true;
+            self.listeners
+                .emit(events::AnimationEvent::Start, self.fraction_complete);
+        }
 
-        let delta = (now - self.last_time).min(32.0);
+        // Long deltas are clamped, allowing for frames spaced out by a cap
+        let max_delta = frame_clock::frame_interval().map_or(32.0, |i| (i * 2.0).max(32.0));
+        let delta = (now - self.last_time).min(max_delta);
         self.last_time = now;
 
         let should_continue = if self.use_spring {
@@ -1008,7 +1406,20 @@ impl Animation {
             self.update_cubic(now)?
         };
 
+        // Adaptive quality: finish now, or skip straight past costly repaints
+        let should_continue = if performance_monitor::snap_to_end() {
+            self.snap_to_end();
+            false
+        } else {
+            if performance_monitor::skip_effects() {
+                self.settle_effects();
+            }
+            should_continue
+        };
+
         self.apply_properties()?;
+        self.listeners
+            .emit(events::AnimationEvent::Update, self.fraction_complete);
 
         if !should_continue {
             self.handle_completion()?;
@@ -1018,43 +1429,13 @@ impl Animation {
     }
 
     fn handle_completion(&mut self) -> Result<(), JsValue> {
-        // ✨ Store final values on the element as data attributes
-        if let Ok(html_elem) = self.element.clone().dyn_into::<HtmlElement>() {
-            for prop in &self.properties {
-                match prop.property_type {
-                    PropertyType::X => {
-                        if let AnimatableValue::Number(val) = prop.current {
-                            let _ = html_elem.set_attribute("data-anim-x", &val.to_string());
-                        }
-                    }
-                    PropertyType::Y => {
-                        if let AnimatableValue::Number(val) = prop.current {
-                            let _ = html_elem.set_attribute("data-anim-y", &val.to_string());
-                        }
-                    }
-                    PropertyType::Z => {
-                        if let AnimatableValue::Number(val) = prop.current {
-                            let _ = html_elem.set_attribute("data-anim-z", &val.to_string());
-                        }
-                    }
-                    PropertyType::Scale => {
-                        if let AnimatableValue::Number(val) = prop.current {
-                            let _ = html_elem.set_attribute("data-anim-scale", &val.to_string());
-                        }
-                    }
-                    PropertyType::Opacity => {
-                        if let AnimatableValue::Number(val) = prop.current {
-                            let _ = html_elem.set_attribute("data-anim-opacity", &val.to_string());
-                        }
-                    }
-                    _ => {}
-                }
-            }
-        }
+        element_state::record(&self.element, &self.properties);
 
         self.current_repeat += 1;
 
         if self.repeat_count < 0 || self.current_repeat < self.repeat_count {
+            self.listeners
+                .emit(events::AnimationEvent::Repeat, self.fraction_complete);
             if self.auto_reverse {
                 self.reverse()?;
             } else {
@@ -1063,10 +1444,15 @@ impl Animation {
             }
         } else {
             self.state = AnimationState::Completed;
+            metrics::mark_complete(&self.performance, self.id);
+            self.release_layer();
+            self.listeners
+                .emit(events::AnimationEvent::Complete, self.fraction_complete);
 
             if let Some(ref callback) = self.completion_callback {
                 let _ = callback.call0(&JsValue::NULL);
             }
+            self.leave_transaction();
         }
 
         Ok(())
@@ -1078,34 +1464,61 @@ impl Animation {
         let progress = (elapsed / self.duration).min(1.0);
         self.fraction_complete = progress;
 
-        let eased = match &self.bezier {
-            Some(bezier) => bezier.solve(progress),
-            None => progress,
-        };
-
-        for prop in self.properties.iter_mut() {
-            prop.current = interpolate_value(&prop.start, &prop.end, eased);
-        }
+        let warped = self.time_warp.apply(progress);
+        motion::sample_cubic(&mut self.properties, self.bezier.as_ref(), warped);
+        self.sample_path_morph(warped);
 
         Ok(progress < 1.0)
     }
 
+    /// Account for `missed` ms spent in a background tab since the last frame
+    fn catch_up(&mut self, now: f64, missed: f64) {
+        match self.background.unwrap_or_else(visibility::policy) {
+            BackgroundPolicy::Pause => {
+                self.start_time += missed;
+            }
+            BackgroundPolicy::FastForward => {
+                // Curves and keyframes follow the clock already; springs are
+                // stepped through the time they were running for
+                let running = missed.min(now - self.start_time).max(0.0) / 1000.0;
+                if self.use_spring && running > 0.0 {
+                    let step = frame_clock::fixed_step().unwrap_or(1.0 / 60.0);
+                    let mut remaining = running.min(visibility::MAX_CATCH_UP_SECS);
+                    while remaining > 0.0 && self.step_spring(remaining.min(step)) {
+                        remaining -= step;
+                    }
+                }
+            }
+        }
+        // Leaves this frame's delta for the time actually shown
+        self.last_time += missed;
+    }
+
     #[inline]
     fn update_spring(&mut self, delta_time: f64) -> Result<bool, JsValue> {
-        let mut at_rest = true;
-
-        for (prop, spring) in self.properties.iter_mut().zip(self.springs.iter_mut()) {
-            let target = extract_number(&prop.end);
-            let value = spring.update(target, delta_time);
+        let Some(step) = frame_clock::fixed_step() else {
+            return Ok(self.step_spring(delta_time));
+        };
 
-            if spring.velocity.abs() > 0.01 || (value - target).abs() > 0.01 {
-                at_rest = false;
+        // Fixed timestep: the same steps whatever the display rate. A frame
+        // shorter than a step moves nothing and leaves the springs running.
+        let mut moving = true;
+        for _ in 0..self.spring_steps.steps(delta_time, step) {
+            moving = self.step_spring(step);
+            if !moving {
+                break;
             }
-
-            prop.current = create_value_with_number(&prop.end, value);
         }
+        Ok(moving)
+    }
 
-        Ok(!at_rest)
+    fn step_spring(&mut self, delta_time: f64) -> bool {
+        let moving = motion::step_springs(&mut self.properties, &mut self.springs, delta_time);
+        let morphing = match &mut self.path_morph {
+            Some(track) => track.step(delta_time),
+            None => false,
+        };
+        moving || morphing
     }
 
     #[inline]
@@ -1114,491 +1527,199 @@ impl Animation {
         let progress = (elapsed / self.duration).min(1.0);
         self.fraction_complete = progress;
 
-        self.update_keyframes(progress)?;
+        let warped = self.time_warp.apply(progress);
+        self.update_keyframes(warped)?;
+        self.sample_path_morph(warped);
         Ok(progress < 1.0)
     }
 
-    fn update_keyframes(&mut self, progress: f64) -> Result<(), JsValue> {
-        if self.keyframes.is_empty() {
-            return Ok(());
-        }
-
-        let mut sorted_kf = self.keyframes.clone();
-        sorted_kf.sort_by(|a, b| {
-            a.time
-                .partial_cmp(&b.time)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        let (start_kf, end_kf, local_progress) = self.find_keyframe_range(&sorted_kf, progress);
-
-        let eased = match &self.bezier {
-            Some(bezier) => bezier.solve(local_progress),
-            None => local_progress,
-        };
-
-        for prop in self.properties.iter_mut() {
-            if let (Some(start_val), Some(end_val)) = (
-                start_kf
-                    .properties
-                    .iter()
-                    .find(|(p, _)| p == &prop.property_type)
-                    .map(|(_, v)| v),
-                end_kf
-                    .properties
-                    .iter()
-                    .find(|(p, _)| p == &prop.property_type)
-                    .map(|(_, v)| v),
-            ) {
-                prop.current = interpolate_value(start_val, end_val, eased);
-            }
+    /// Give up the compositor layer taken in start_internal
+    fn release_layer(&mut self) {
+        if std::mem::take(&mut self.promoted) {
+            metal_acceleration::release_layer(&self.element);
         }
-
-        Ok(())
     }
 
-    fn find_keyframe_range<'a>(
-        &self,
-        sorted_kf: &'a [Keyframe],
-        progress: f64,
-    ) -> (&'a Keyframe, &'a Keyframe, f64) {
-        let mut start_kf = &sorted_kf[0];
-        let mut end_kf = &sorted_kf[sorted_kf.len() - 1];
-        let mut local_progress = 0.0;
-
-        for i in 0..sorted_kf.len() - 1 {
-            if progress >= sorted_kf[i].time && progress <= sorted_kf[i + 1].time {
-                start_kf = &sorted_kf[i];
-                end_kf = &sorted_kf[i + 1];
-                local_progress = (progress - start_kf.time) / (end_kf.time - start_kf.time);
-                break;
-            }
+    /// Let the transaction it started in finish once nothing else is running
+    fn leave_transaction(&mut self) {
+        if let Some(scope) = self.transaction.take() {
+            transaction::leave(&scope);
         }
-
-        (start_kf, end_kf, local_progress)
     }
 
-    fn apply_properties(&self) -> Result<(), JsValue> {
-        let mut transform_parts = Vec::with_capacity(16);
-        let mut filter_parts = Vec::with_capacity(8);
-        let mut has_translate = false;
-
-        for prop in self.properties.iter() {
-            match prop.property_type {
-                // Transform Group
-                PropertyType::X | PropertyType::Y | PropertyType::Z => {
-                    if !has_translate {
-                        self.apply_translate(&mut transform_parts);
-                        has_translate = true;
-                    }
-                }
-                PropertyType::Scale => {
-                    if let AnimatableValue::Number(val) = prop.current {
-                        transform_parts.push(format!("scale({})", val));
-                    }
-                }
-                PropertyType::ScaleX => {
-                    if let AnimatableValue::Number(val) = prop.current {
-                        transform_parts.push(format!("scaleX({})", val));
-                    }
-                }
-                PropertyType::ScaleY => {
-                    if let AnimatableValue::Number(val) = prop.current {
-                        transform_parts.push(format!("scaleY({})", val));
-                    }
-                }
-                PropertyType::Rotate
-                | PropertyType::RotateX
-                | PropertyType::RotateY
-                | PropertyType::RotateZ => {
-                    self.apply_rotation(&mut transform_parts, prop);
-                }
-                PropertyType::SkewX | PropertyType::SkewY => {
-                    self.apply_skew(&mut transform_parts, prop);
-                }
-                PropertyType::Perspective => {
-                    if let AnimatableValue::Number(val) = prop.current {
-                        transform_parts.push(format!("perspective({}px)", val));
-                    }
-                }
-                PropertyType::PerspectiveOriginX | PropertyType::PerspectiveOriginY => {
-                    self.apply_perspective_origin()?;
-                }
-                PropertyType::TransformOriginX
-                | PropertyType::TransformOriginY
-                | PropertyType::TransformOriginZ => {
-                    self.apply_transform_origin()?;
-                }
-                PropertyType::BackfaceVisibility => {
-                    // Backface visibility - hidden or visible
-                    if let AnimatableValue::Number(val) = prop.current {
-                        let visibility = if val > 0.5 { "visible" } else { "hidden" };
-                        self.set_element_property("backfaceVisibility", visibility)?;
-                    }
-                }
-
-                // Layout
-                PropertyType::Width
-                | PropertyType::Height
-                | PropertyType::MinWidth
-                | PropertyType::MinHeight
-                | PropertyType::MaxWidth
-                | PropertyType::MaxHeight => {
-                    self.apply_layout(prop)?;
-                }
-
-                // Visual
-                PropertyType::Opacity => {
-                    if let AnimatableValue::Number(val) = prop.current {
-                        self.set_element_property("opacity", &val.to_string())?;
-                    }
-                }
-                PropertyType::BackgroundColor | PropertyType::Color | PropertyType::BorderColor => {
-                    self.set_color_property(prop)?;
-                }
-                PropertyType::BorderRadius | PropertyType::BorderWidth => {
-                    self.apply_border(prop)?;
-                }
-
-                PropertyType::Visibility => {
-                    // ✨ NEW
-                    if let AnimatableValue::Visibility(val) = &prop.current {
-                        self.set_element_property("visibility", val.as_str())?;
-                    }
-                }
-
-                // Shadows
-                PropertyType::ShadowOffsetX
-                | PropertyType::ShadowOffsetY
-                | PropertyType::ShadowBlur
-                | PropertyType::ShadowSpread
-                | PropertyType::ShadowColor => {
-                    let shadow_string = self.build_shadow_string();
-                    if !shadow_string.is_empty() {
-                        self.set_element_property("boxShadow", &shadow_string)?;
-                    }
-                }
-
-                // Filters
-                PropertyType::Blur
-                | PropertyType::Brightness
-                | PropertyType::Contrast
-                | PropertyType::Saturate
-                | PropertyType::Hue
-                | PropertyType::Grayscale
-                | PropertyType::Invert
-                | PropertyType::Sepia => {
-                    self.apply_filter(&mut filter_parts, prop);
-                }
-                PropertyType::Dropoff => {
-                    // Dropoff filter (drop shadow filter)
-                    if let AnimatableValue::Number(val) = prop.current {
-                        filter_parts
-                            .push(format!("drop-shadow(0px 0px {}px rgba(0, 0, 0, 0.5))", val));
-                    }
-                }
-                PropertyType::BackgroundBlur => {
-                    // Backdrop filter for background blur effect
-                    if let AnimatableValue::Number(val) = prop.current {
-                        self.set_element_property("backdropFilter", &format!("blur({}px)", val))?;
-                    }
-                }
-
-                // SVG
-                PropertyType::StrokeDashOffset
-                | PropertyType::StrokeWidth
-                | PropertyType::FillOpacity
-                | PropertyType::StrokeOpacity => {
-                    self.apply_svg(prop)?;
-                }
-
-                PropertyType::StrokeDashArray => {
-                    // Handled separately if needed
-                }
-
-                PropertyType::Inset => {
-                    // Inset shadow property
-                    if let AnimatableValue::Number(val) = prop.current {
-                        let inset_value = if val > 0.5 { "inset" } else { "outset" };
-                        // Note: CSS doesn't have outset, so we handle inset boolean value
-                        if val > 0.5 {
-                            self.set_element_property(
-                                "boxShadow",
-                                &format!("{} 0px 0px 0px rgba(0,0,0,0.5)", inset_value),
-                            )?;
-                        }
-                    }
-                }
+    /// Property values at the end of the timeline, sampled into `properties`
+    fn sample_end(&self, properties: &mut [AnimationProperty]) {
+        let end = self.time_warp.apply(1.0);
+        if self.use_keyframes {
+            motion::sample_keyframes(properties, &self.keyframes, self.bezier.as_ref(), end);
+        } else if self.use_spring {
+            for prop in properties.iter_mut() {
+                prop.current = prop.end.clone();
             }
+        } else {
+            motion::sample_cubic(properties, self.bezier.as_ref(), end);
         }
+    }
 
-        if !transform_parts.is_empty() {
-            self.set_element_property("transform", &transform_parts.join(" "))?;
-        }
-
-        if !filter_parts.is_empty() {
-            self.set_element_property("filter", &filter_parts.join(" "))?;
+    /// Jump to the final frame
+    fn snap_to_end(&mut self) {
+        let mut properties = std::mem::take(&mut self.properties);
+        self.sample_end(&mut properties);
+        self.properties = properties;
+        self.fraction_complete = 1.0;
+        if let Some(track) = &mut self.path_morph {
+            track.sample(1.0);
         }
-
-        Ok(())
     }
 
-    #[inline]
-    fn apply_perspective_origin(&self) -> Result<(), JsValue> {
-        let origin_x = self
+    /// Show shadows and filters at their final values instead of repainting
+    /// them every frame
+    fn settle_effects(&mut self) {
+        if !self
             .properties
             .iter()
-            .find(|p| p.property_type == PropertyType::PerspectiveOriginX)
-            .and_then(|p| match &p.current {
-                AnimatableValue::Length(val, unit) => Some(format!("{}{}", val, unit.as_str())),
-                _ => None,
-            })
-            .unwrap_or_else(|| "50%".to_string());
-
-        let origin_y = self
-            .properties
-            .iter()
-            .find(|p| p.property_type == PropertyType::PerspectiveOriginY)
-            .and_then(|p| match &p.current {
-                AnimatableValue::Length(val, unit) => Some(format!("{}{}", val, unit.as_str())),
-                _ => None,
-            })
-            .unwrap_or_else(|| "50%".to_string());
-
-        let origin_string = format!("{} {}", origin_x, origin_y);
-        self.set_element_property("perspectiveOrigin", &origin_string)?;
-        Ok(())
-    }
-
-    #[inline]
-    fn apply_translate(&self, transform_parts: &mut Vec<String>) {
-        let x = self.get_number_value(PropertyType::X).round();
-        let y = self.get_number_value(PropertyType::Y).round();
-        let z = self.get_number_value(PropertyType::Z).round();
-
-        if x != 0.0 || y != 0.0 || z != 0.0 {
-            transform_parts.push(format!(
-                "translate3d({}px, {}px, {}px)",
-                x as i32, y as i32, z as i32
-            ));
+            .any(|p| p.property_type.is_paint_effect())
+        {
+            return;
         }
-    }
-
-    #[inline]
-    fn apply_rotation(&self, transform_parts: &mut Vec<String>, prop: &AnimationProperty) {
-        if let AnimatableValue::Number(val) = prop.current {
-            match prop.property_type {
-                PropertyType::Rotate => transform_parts.push(format!("rotate({}deg)", val)),
-                PropertyType::RotateX => transform_parts.push(format!("rotateX({}deg)", val)),
-                PropertyType::RotateY => transform_parts.push(format!("rotateY({}deg)", val)),
-                PropertyType::RotateZ => transform_parts.push(format!("rotateZ({}deg)", val)),
-                _ => {}
+        let mut end = self.properties.clone();
+        self.sample_end(&mut end);
+        for (prop, end) in self.properties.iter_mut().zip(end) {
+            if prop.property_type.is_paint_effect() {
+                prop.current = end.current;
             }
         }
     }
 
-    #[inline]
-    fn apply_skew(&self, transform_parts: &mut Vec<String>, prop: &AnimationProperty) {
-        if let AnimatableValue::Number(val) = prop.current {
-            match prop.property_type {
-                PropertyType::SkewX => transform_parts.push(format!("skewX({}deg)", val)),
-                PropertyType::SkewY => transform_parts.push(format!("skewY({}deg)", val)),
-                _ => {}
-            }
+    /// Ease the path morph over the whole timeline
+    fn sample_path_morph(&mut self, progress: f64) {
+        if let Some(track) = &mut self.path_morph {
+            let eased = match &self.bezier {
+                Some(bezier) => bezier.solve(progress),
+                None => progress,
+            };
+            track.sample(eased);
         }
     }
 
-    #[inline]
-    fn apply_layout(&self, prop: &AnimationProperty) -> Result<(), JsValue> {
-        let property_name = match prop.property_type {
-            PropertyType::Width => "width",
-            PropertyType::Height => "height",
-            PropertyType::MinWidth => "minWidth",
-            PropertyType::MinHeight => "minHeight",
-            PropertyType::MaxWidth => "maxWidth",
-            PropertyType::MaxHeight => "maxHeight",
-            _ => return Ok(()),
-        };
-        self.set_element_dimension(property_name, &prop.current)?;
+    fn update_keyframes(&mut self, progress: f64) -> Result<(), JsValue> {
+        motion::sample_keyframes(
+            &mut self.properties,
+            &self.keyframes,
+            self.bezier.as_ref(),
+            progress,
+        );
         Ok(())
     }
 
-    #[inline]
-    fn apply_border(&self, prop: &AnimationProperty) -> Result<(), JsValue> {
-        if let AnimatableValue::Length(val, unit) = &prop.current {
-            let property_name = match prop.property_type {
-                PropertyType::BorderRadius => "border-radius",
-                PropertyType::BorderWidth => "border-width",
-                _ => return Ok(()),
-            };
-            self.set_element_property(property_name, &format!("{}{}", val, unit.as_str()))?;
+    fn apply_properties(&self) -> Result<(), JsValue> {
+        if let Some(track) = &self.path_morph {
+            track.apply(&self.element)?;
         }
-        Ok(())
-    }
 
-    #[inline]
-    fn apply_filter(&self, filter_parts: &mut Vec<String>, prop: &AnimationProperty) {
-        if let AnimatableValue::Number(val) = prop.current {
-            match prop.property_type {
-                PropertyType::Blur => filter_parts.push(format!("blur({}px)", val)),
-                PropertyType::Brightness => filter_parts.push(format!("brightness({})", val)),
-                PropertyType::Contrast => filter_parts.push(format!("contrast({})", val)),
-                PropertyType::Saturate => filter_parts.push(format!("saturate({})", val)),
-                PropertyType::Hue => filter_parts.push(format!("hue-rotate({}deg)", val)),
-                PropertyType::Grayscale => {
-                    filter_parts.push(format!("grayscale({}%)", (val * 100.0).round() as i32))
-                }
-                PropertyType::Invert => {
-                    filter_parts.push(format!("invert({}%)", (val * 100.0).round() as i32))
-                }
-                PropertyType::Sepia => {
-                    filter_parts.push(format!("sepia({}%)", (val * 100.0).round() as i32))
-                }
-                PropertyType::Dropoff
-                | PropertyType::BackgroundBlur
-                | PropertyType::Inset
-                | PropertyType::BackfaceVisibility => {
-                    // Handled in apply_properties
-                }
-                _ => {}
-            }
-        }
-    }
+        let base = match &self.base_transform {
+            Some(base) => base.clone(),
+            None => element_state::base_transform(&self.element),
+        };
+        let mut renderer = self.renderer.borrow_mut();
+        let writes = motion::render_over(
+            &self.properties,
+            &self.transform_origin,
+            &base,
+            &mut **renderer,
+        )?;
+        metrics::record_style_writes(&self.performance, &self.element, writes);
+        renderer.flush()
+    }
+
+    pub(crate) fn load_keyframes(&mut self, configs: JsValue) -> Result<(), JsValue> {
+        let keyframe_configs: Vec<KeyframeConfig> =
+            from_value(migration::migrate_list(configs)?)
+                .map_err(|e| JsValue::from_str(&format!("Invalid keyframes config: {:?}", e)))?;
 
-    #[inline]
-    fn apply_svg(&self, prop: &AnimationProperty) -> Result<(), JsValue> {
-        if let AnimatableValue::Number(val) = prop.current {
-            let attribute = match prop.property_type {
-                PropertyType::StrokeDashOffset => "stroke-dashoffset",
-                PropertyType::StrokeWidth => "stroke-width",
-                PropertyType::FillOpacity => "fill-opacity",
-                PropertyType::StrokeOpacity => "stroke-opacity",
-                _ => return Ok(()),
-            };
-            self.set_svg_attribute(attribute, &val.to_string())?;
+        for kf in keyframe_configs {
+            self.push_keyframe(kf)?;
         }
+
+        self.use_keyframes = true;
         Ok(())
     }
 
-    #[inline]
-    fn get_number_value(&self, prop_type: PropertyType) -> f64 {
-        self.properties
-            .iter()
-            .find(|p| p.property_type == prop_type)
-            .and_then(|p| match p.current {
-                AnimatableValue::Number(n) => Some(n),
-                AnimatableValue::Length(n, _) => Some(n),
-                _ => None,
-            })
-            .unwrap_or(0.0)
+    /// Set up properties from an animate() config, reading start values from
+    /// the element as it is now
+    pub(crate) fn configure(&mut self, config: JsValue) -> Result<(), JsValue> {
+        let cfg: AnimateConfig = from_value(migration::migrate(config)?)
+            .map_err(|e| JsValue::from_str(&format!("Invalid config: {:?}", e)))?;
+
+        self.setup_properties(&cfg)
     }
 
-    #[inline]
-    fn set_element_property(&self, property: &str, value: &str) -> Result<(), JsValue> {
-        if let Ok(html_element) = self.element.clone().dyn_into::<HtmlElement>() {
-            html_element
-                .style()
-                .set_property(property, value)
-                .map_err(|_| JsValue::from_str(&format!("Failed to set {}", property)))?;
+    /// Current numeric value of a property, or `fallback` if it isn't animated
+    pub(crate) fn live_number(&self, prop_type: PropertyType, fallback: f64) -> f64 {
+        if self.properties.iter().any(|p| p.property_type == prop_type) {
+            self.get_number_value(prop_type)
+        } else {
+            fallback
         }
-        Ok(())
     }
 
-    #[inline]
-    fn set_element_dimension(
-        &self,
-        property: &str,
-        value: &AnimatableValue,
+    /// Drive a property directly (e.g. from a gesture) and render it
+    pub(crate) fn set_live_number(
+        &mut self,
+        prop_type: PropertyType,
+        value: f64,
     ) -> Result<(), JsValue> {
-        match value {
-            AnimatableValue::Length(val, unit) => {
-                self.set_element_property(property, &format!("{}{}", val, unit.as_str()))?;
-            }
-            AnimatableValue::Number(val) => {
-                self.set_element_property(property, &format!("{}px", val))?;
-            }
-            _ => {}
+        match self
+            .properties
+            .iter_mut()
+            .find(|p| p.property_type == prop_type)
+        {
+            Some(prop) => prop.current = create_value_with_number(&prop.current, value),
+            None => self.properties.push(AnimationProperty {
+                property_type: prop_type,
+                start: AnimatableValue::Number(value),
+                end: AnimatableValue::Number(value),
+                current: AnimatableValue::Number(value),
+            }),
         }
-        Ok(())
+        self.apply_properties()
     }
 
-    #[inline]
-    fn set_color_property(&self, prop: &AnimationProperty) -> Result<(), JsValue> {
-        let property_name = match prop.property_type {
-            PropertyType::BackgroundColor => "background-color",
-            PropertyType::Color => "color",
-            PropertyType::BorderColor => "border-color",
-            _ => return Ok(()),
-        };
-
-        if let AnimatableValue::Color(r, g, b, a) = prop.current {
-            let css_value = format!(
-                "rgba({}, {}, {}, {})",
-                r.round() as u8,
-                g.round() as u8,
-                b.round() as u8,
-                a
-            );
-            self.set_element_property(property_name, &css_value)?;
+    /// Start number properties from the values the element has now, as
+    /// continue_animate() does, for steps configured once earlier ones ran
+    pub(crate) fn start_numbers_from_element(&mut self) {
+        let values: Vec<f64> = self
+            .properties
+            .iter()
+            .map(|prop| self.get_current_number_value(prop.property_type))
+            .collect();
+        for (prop, value) in self.properties.iter_mut().zip(values) {
+            if matches!(prop.start, AnimatableValue::Number(_)) {
+                prop.start = AnimatableValue::Number(value);
+                prop.current = AnimatableValue::Number(value);
+            }
         }
-        Ok(())
     }
 
-    #[inline]
-    fn set_svg_attribute(&self, attribute: &str, value: &str) -> Result<(), JsValue> {
-        if let Ok(svg_element) = self.element.clone().dyn_into::<SvgElement>() {
-            svg_element.set_attribute(attribute, value).map_err(|_| {
-                JsValue::from_str(&format!("Failed to set SVG attribute {}", attribute))
-            })?;
+    /// Pick up where a gesture left off: shift the start of `prop_type` by
+    /// `offset` and seed its spring with `velocity` (units per second)
+    pub(crate) fn continue_from(&mut self, prop_type: PropertyType, offset: f64, velocity: f64) {
+        if let Some(prop) = self
+            .properties
+            .iter_mut()
+            .find(|p| p.property_type == prop_type)
+        {
+            let start = extract_number(&prop.start) + offset;
+            prop.start = create_value_with_number(&prop.start, start);
+            prop.current = prop.start.clone();
         }
-        Ok(())
+        self.gesture_velocity.retain(|(p, _)| *p != prop_type);
+        self.gesture_velocity.push((prop_type, velocity));
     }
 
     #[inline]
-    fn apply_transform_origin(&self) -> Result<(), JsValue> {
-        let origin_string = format!(
-            "{} {} {}",
-            self.transform_origin.0, self.transform_origin.1, self.transform_origin.2
-        );
-        self.set_element_property("transformOrigin", &origin_string)?;
-        Ok(())
-    }
-
-    #[inline]
-    fn build_shadow_string(&self) -> String {
-        let offset_x = self.get_number_value(PropertyType::ShadowOffsetX);
-        let offset_y = self.get_number_value(PropertyType::ShadowOffsetY);
-        let blur = self.get_number_value(PropertyType::ShadowBlur);
-        let spread = self.get_number_value(PropertyType::ShadowSpread);
-
-        if offset_x == 0.0 && offset_y == 0.0 && blur == 0.0 && spread == 0.0 {
-            return String::new();
-        }
-
-        let color = self
-            .properties
-            .iter()
-            .find(|p| p.property_type == PropertyType::ShadowColor)
-            .and_then(|p| match &p.current {
-                AnimatableValue::Color(r, g, b, a) => Some(format!(
-                    "rgba({}, {}, {}, {})",
-                    r.round() as u8,
-                    g.round() as u8,
-                    b.round() as u8,
-                    a
-                )),
-                _ => None,
-            })
-            .unwrap_or_else(|| "rgba(0, 0, 0, 0.5)".to_string());
-
-        format!(
-            "{}px {}px {}px {}px {}",
-            offset_x.round() as i32,
-            offset_y.round() as i32,
-            blur.round() as i32,
-            spread.round() as i32,
-            color
-        )
+    fn get_number_value(&self, prop_type: PropertyType) -> f64 {
+        motion::number_value(&self.properties, prop_type)
     }
 
     // ========================================================================
@@ -1613,7 +1734,7 @@ impl Animation {
         false_config: JsValue,
     ) -> Result<Animation, JsValue> {
         let config = if condition { true_config } else { false_config };
-        let cfg: AnimateConfig = from_value(config)
+        let cfg: AnimateConfig = from_value(migration::migrate(config)?)
             .map_err(|e| JsValue::from_str(&format!("Invalid config: {:?}", e)))?;
 
         self.setup_properties(&cfg)?;
@@ -1639,15 +1760,20 @@ impl Animation {
             if let Some(key_str) = key_val.as_string() {
                 if key_str == value_str {
                     match js_sys::Reflect::get(cases_obj, &key_val) {
-                        Ok(config) => match from_value::<AnimateConfig>(config) {
-                            Ok(cfg) => {
-                                self.setup_properties(&cfg)?;
-                                break;
+                        Ok(config) => {
+                            match from_value::<AnimateConfig>(migration::migrate(config)?) {
+                                Ok(cfg) => {
+                                    self.setup_properties(&cfg)?;
+                                    break;
+                                }
+                                Err(e) => {
+                                    return Err(JsValue::from_str(&format!(
+                                        "Invalid config: {:?}",
+                                        e
+                                    )));
+                                }
                             }
-                            Err(e) => {
-                                return Err(JsValue::from_str(&format!("Invalid config: {:?}", e)));
-                            }
-                        },
+                        }
                         Err(_) => {
                             return Err(JsValue::from_str("Failed to get case config"));
                         }
@@ -1669,7 +1795,7 @@ impl Animation {
     ) -> Result<Animation, JsValue> {
         let target = if condition { true_val } else { false_val };
 
-        if let Some(prop_type) = PropertyType::from_str(&property) {
+        if let Ok(prop_type) = property.parse::<PropertyType>() {
             self.add_number_property(prop_type, target);
         } else {
             return Err(JsValue::from_str(&format!(
@@ -1682,35 +1808,99 @@ impl Animation {
     }
 }
 
+impl Drop for Animation {
+    fn drop(&mut self) {
+        self.release_layer();
+        self.leave_transaction();
+    }
+}
+
 // ============================================================================
 // ANIMATION LOOP SPAWNING
 // ============================================================================
 
 type AnimationCallback = Closure<dyn FnMut()>;
 
+/// Play an animation held elsewhere by handle: resume it if paused, or restart
+/// it with a fresh frame loop once it has completed
+pub(crate) fn replay(animation: &Rc<RefCell<Animation>>) -> Result<(), JsValue> {
+    let state = animation.borrow().state;
+    match state {
+        AnimationState::Running => Ok(()),
+        AnimationState::Paused => animation.borrow_mut().resume(),
+        AnimationState::Idle | AnimationState::Completed => {
+            animation.borrow_mut().start_internal()?;
+            spawn_animation_loop(Rc::clone(animation))
+        }
+    }
+}
+
+/// The requestAnimationFrame loop driving a started animation
+#[derive(Default)]
+struct FrameLoop {
+    // Pending frame, cancelled if the animation stops before it fires
+    request: Option<i32>,
+    callback: Option<AnimationCallback>,
+}
+
+impl FrameLoop {
+    fn request(&mut self, window: &web_sys::Window) -> Result<(), JsValue> {
+        if let Some(ref callback) = self.callback {
+            self.request = Some(window.request_animation_frame(callback.as_ref().unchecked_ref())?);
+        }
+        Ok(())
+    }
+
+    /// Cancel the pending frame and drop the callback, letting go of the
+    /// animation it holds
+    fn end(&mut self) {
+        if let (Some(request), Some(window)) = (self.request.take(), window()) {
+            let _ = window.cancel_animation_frame(request);
+        }
+        // Dropped mid-call when the loop ends itself; wasm-bindgen frees it
+        // once the call returns
+        self.callback = None;
+    }
+}
+
 fn spawn_animation_loop(animation: Rc<RefCell<Animation>>) -> Result<(), JsValue> {
     let window = window().ok_or_else(|| JsValue::from_str("No window available"))?;
 
+    let frame_loop = Rc::clone(&animation.borrow().frame_loop);
+    // A restart replaces any loop still running
+    frame_loop.borrow_mut().end();
+
     let animation_clone = animation.clone();
     let window_clone = window.clone();
+    let loop_clone = frame_loop.clone();
 
-    let closure: Rc<RefCell<Option<AnimationCallback>>> = Rc::new(RefCell::new(None));
-    let closure_clone = closure.clone();
-
+    let mut last_frame = None;
     let animate = move || {
         let mut anim = animation_clone.borrow_mut();
+        loop_clone.borrow_mut().request = None;
+
+        // Skip display frames under a frame rate cap
+        let now = anim.performance.now();
+        if !frame_clock::frame_due(last_frame, now) {
+            let _ = loop_clone.borrow_mut().request(&window_clone);
+            return;
+        }
+        last_frame = Some(now);
+
+        let started = performance_monitor::frame_start();
+        metrics::frame_begin(&anim.performance, anim.id);
         let _ = anim.animate_frame();
+        metrics::frame_end(&anim.performance, anim.id);
+        performance_monitor::add_work(started);
 
         if anim.state != AnimationState::Completed {
-            if let Some(ref callback) = *closure_clone.borrow() {
-                let _ = window_clone.request_animation_frame(callback.as_ref().unchecked_ref());
-            }
+            let _ = loop_clone.borrow_mut().request(&window_clone);
+        } else {
+            loop_clone.borrow_mut().end();
         }
     };
 
-    let c = Closure::wrap(Box::new(animate) as Box<dyn FnMut()>);
-    window.request_animation_frame(c.as_ref().unchecked_ref())?;
-    *closure.borrow_mut() = Some(c);
-
-    Ok(())
+    let mut frame_loop = frame_loop.borrow_mut();
+    frame_loop.callback = Some(Closure::wrap(Box::new(animate) as Box<dyn FnMut()>));
+    frame_loop.request(&window)
 }
@@ -0,0 +1,1166 @@
+use crate::cubic::CubicBezier;
+use crate::shape_morphing::PathMorph;
+use crate::spec;
+use crate::ticker;
+use js_sys::{Array, Function};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::Element;
+
+// ============================================================================
+// LOTTIE - Playback of a subset of Bodymovin JSON as generated SVG
+// ============================================================================
+//
+// Supported: shape layers and null layers with parenting, layer transforms
+// (anchor, position, scale, rotation, opacity), groups with their transforms,
+// paths, solid fills and strokes. Keyframes ease with the engine's cubic
+// curves and animated paths morph through PathMorph. Expressions are
+// rejected; other layer and shape types (rects, ellipses, trims, gradients,
+// masks, ...) are skipped and listed by `unsupported`.
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+
+// ----------------------------------------------------------------------------
+// Model
+// ----------------------------------------------------------------------------
+
+/// A keyframe span: `from` at `from_frame` easing to `to` at `to_frame`.
+/// No easing holds `from` until `to_frame`.
+pub struct Segment {
+    pub from_frame: f64,
+    pub to_frame: f64,
+    pub from: Vec<f64>,
+    pub to: Vec<f64>,
+    pub easing: Option<CubicBezier>,
+}
+
+/// An animatable value with one or more components
+pub enum Track {
+    Static(Vec<f64>),
+    Animated(Vec<Segment>),
+}
+
+impl Track {
+    pub fn sample(&self, frame: f64) -> Vec<f64> {
+        let segments = match self {
+            Track::Static(value) => return value.clone(),
+            Track::Animated(segments) => segments,
+        };
+        let Some(first) = segments.first() else {
+            return Vec::new();
+        };
+        if frame <= first.from_frame {
+            return first.from.clone();
+        }
+        for segment in segments {
+            if frame >= segment.to_frame {
+                continue;
+            }
+            let Some(easing) = &segment.easing else {
+                return segment.from.clone();
+            };
+            let span = (segment.to_frame - segment.from_frame).max(f64::EPSILON);
+            let eased = easing.solve(((frame - segment.from_frame) / span).clamp(0.0, 1.0));
+            return segment
+                .from
+                .iter()
+                .zip(&segment.to)
+                .map(|(from, to)| from + (to - from) * eased)
+                .collect();
+        }
+        segments.last().map(|s| s.to.clone()).unwrap_or_default()
+    }
+
+    /// Component `index` at `frame`, or `fallback` if the value has fewer
+    pub fn component(&self, frame: f64, index: usize, fallback: f64) -> f64 {
+        self.sample(frame).get(index).copied().unwrap_or(fallback)
+    }
+
+    pub fn is_animated(&self) -> bool {
+        matches!(self, Track::Animated(_))
+    }
+}
+
+/// A layer or group transform
+pub struct Transform {
+    pub anchor: Track,
+    pub position: Track,
+    /// Percent
+    pub scale: Track,
+    /// Degrees
+    pub rotation: Track,
+    /// Percent
+    pub opacity: Track,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            anchor: Track::Static(vec![0.0, 0.0]),
+            position: Track::Static(vec![0.0, 0.0]),
+            scale: Track::Static(vec![100.0, 100.0]),
+            rotation: Track::Static(vec![0.0]),
+            opacity: Track::Static(vec![100.0]),
+        }
+    }
+}
+
+impl Transform {
+    /// SVG `transform` attribute: the anchor is moved to the position, then
+    /// rotated and scaled about it
+    pub fn svg_at(&self, frame: f64) -> String {
+        let position = self.position.sample(frame);
+        let anchor = self.anchor.sample(frame);
+        let scale = self.scale.sample(frame);
+        let at = |v: &[f64], i: usize, fallback: f64| v.get(i).copied().unwrap_or(fallback);
+        format!(
+            "translate({} {}) rotate({}) scale({} {}) translate({} {})",
+            at(&position, 0, 0.0),
+            at(&position, 1, 0.0),
+            self.rotation.component(frame, 0, 0.0),
+            at(&scale, 0, 100.0) / 100.0,
+            at(&scale, 1, 100.0) / 100.0,
+            -at(&anchor, 0, 0.0),
+            -at(&anchor, 1, 0.0),
+        )
+    }
+
+    /// 0-1
+    pub fn opacity_at(&self, frame: f64) -> f64 {
+        (self.opacity.component(frame, 0, 100.0) / 100.0).clamp(0.0, 1.0)
+    }
+}
+
+/// SVG path data for a Lottie bezier: vertices with in and out tangents
+/// relative to them
+pub fn bezier_path(
+    vertices: &[[f64; 2]],
+    in_tangents: &[[f64; 2]],
+    out_tangents: &[[f64; 2]],
+    closed: bool,
+) -> String {
+    let Some(first) = vertices.first() else {
+        return String::new();
+    };
+    let tangent = |list: &[[f64; 2]], i: usize| list.get(i).copied().unwrap_or([0.0, 0.0]);
+    let mut d = format!("M{} {}", first[0], first[1]);
+    let count = vertices.len();
+    let segments = if closed { count } else { count - 1 };
+    for i in 0..segments {
+        let j = (i + 1) % count;
+        let (from, to) = (vertices[i], vertices[j]);
+        let (out, inn) = (tangent(out_tangents, i), tangent(in_tangents, j));
+        d.push_str(&format!(
+            "C{} {} {} {} {} {}",
+            from[0] + out[0],
+            from[1] + out[1],
+            to[0] + inn[0],
+            to[1] + inn[1],
+            to[0],
+            to[1]
+        ));
+    }
+    if closed {
+        d.push('Z');
+    }
+    d
+}
+
+struct MorphSegment {
+    from_frame: f64,
+    to_frame: f64,
+    // None holds the start path
+    easing: Option<CubicBezier>,
+    morph: PathMorph,
+}
+
+enum PathTrack {
+    Static(String),
+    Animated(Vec<MorphSegment>),
+}
+
+impl PathTrack {
+    fn sample(&self, frame: f64) -> String {
+        let segments = match self {
+            PathTrack::Static(d) => return d.clone(),
+            PathTrack::Animated(segments) => segments,
+        };
+        let Some(first) = segments.first() else {
+            return String::new();
+        };
+        if frame <= first.from_frame {
+            return first.morph.get_path_at(0.0);
+        }
+        for segment in segments {
+            if frame >= segment.to_frame {
+                continue;
+            }
+            let progress = match &segment.easing {
+                Some(easing) => {
+                    let span = (segment.to_frame - segment.from_frame).max(f64::EPSILON);
+                    easing.solve(((frame - segment.from_frame) / span).clamp(0.0, 1.0))
+                }
+                None => 0.0,
+            };
+            return segment.morph.get_path_at(progress);
+        }
+        segments
+            .last()
+            .map(|s| s.morph.get_path_at(1.0))
+            .unwrap_or_default()
+    }
+}
+
+struct Paint {
+    color: Track,
+    opacity: Track,
+    // Strokes only
+    width: Option<Track>,
+    line_cap: Option<&'static str>,
+    line_join: Option<&'static str>,
+}
+
+enum Item {
+    Group(Rc<Group>),
+    Path(PathTrack),
+}
+
+#[derive(Default)]
+struct Group {
+    transform: Option<Transform>,
+    fill: Option<Paint>,
+    stroke: Option<Paint>,
+    items: Vec<Item>,
+}
+
+struct Layer {
+    index: Option<i64>,
+    parent: Option<i64>,
+    in_point: f64,
+    out_point: f64,
+    start_time: f64,
+    transform: Transform,
+    // None for null layers, which only carry a transform for children
+    content: Option<Rc<Group>>,
+}
+
+struct Composition {
+    width: f64,
+    height: f64,
+    frame_rate: f64,
+    in_point: f64,
+    out_point: f64,
+    layers: Vec<Layer>,
+    unsupported: Vec<String>,
+}
+
+// ----------------------------------------------------------------------------
+// Bodymovin JSON
+// ----------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct RawComposition {
+    w: f64,
+    h: f64,
+    fr: f64,
+    ip: f64,
+    op: f64,
+    #[serde(default)]
+    layers: Vec<RawLayer>,
+}
+
+#[derive(Deserialize)]
+struct RawLayer {
+    #[serde(default)]
+    nm: String,
+    ty: u32,
+    ind: Option<i64>,
+    parent: Option<i64>,
+    #[serde(default)]
+    ip: f64,
+    #[serde(default)]
+    op: f64,
+    #[serde(default)]
+    st: f64,
+    ks: Option<RawTransform>,
+    #[serde(default)]
+    shapes: Vec<RawShape>,
+}
+
+#[derive(Deserialize)]
+struct RawTransform {
+    a: Option<RawProperty>,
+    p: Option<RawPosition>,
+    s: Option<RawProperty>,
+    // 3D layers name their z rotation rz
+    #[serde(alias = "rz")]
+    r: Option<RawProperty>,
+    o: Option<RawProperty>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawPosition {
+    Split { x: RawProperty, y: RawProperty },
+    Whole(RawProperty),
+}
+
+#[derive(Deserialize)]
+struct RawProperty {
+    k: RawValue,
+    // An expression
+    x: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawValue {
+    Number(f64),
+    Vector(Vec<f64>),
+    Keys(Vec<RawKey>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawComponents {
+    One(f64),
+    Many(Vec<f64>),
+}
+
+impl RawComponents {
+    fn into_vec(self) -> Vec<f64> {
+        match self {
+            RawComponents::One(value) => vec![value],
+            RawComponents::Many(values) => values,
+        }
+    }
+
+    fn first(&self) -> f64 {
+        match self {
+            RawComponents::One(value) => *value,
+            RawComponents::Many(values) => values.first().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawTangent {
+    x: RawComponents,
+    y: RawComponents,
+}
+
+#[derive(Deserialize)]
+struct RawKey {
+    t: f64,
+    s: Option<RawComponents>,
+    // End value in older exports; newer ones use the next key's start
+    e: Option<RawComponents>,
+    i: Option<RawTangent>,
+    o: Option<RawTangent>,
+    #[serde(default)]
+    h: u8,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "ty")]
+enum RawShape {
+    #[serde(rename = "gr")]
+    Group {
+        #[serde(default)]
+        it: Vec<RawShape>,
+    },
+    #[serde(rename = "sh")]
+    Path { ks: RawShapeProperty },
+    #[serde(rename = "fl")]
+    Fill { c: RawProperty, o: RawProperty },
+    #[serde(rename = "st")]
+    Stroke {
+        c: RawProperty,
+        o: RawProperty,
+        w: RawProperty,
+        lc: Option<u8>,
+        lj: Option<u8>,
+    },
+    #[serde(rename = "tr")]
+    Transform(RawTransform),
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Deserialize)]
+struct RawShapeProperty {
+    k: RawShapeValue,
+    x: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawShapeValue {
+    Static(RawBezier),
+    Keys(Vec<RawShapeKey>),
+}
+
+#[derive(Deserialize)]
+struct RawBezier {
+    #[serde(default)]
+    c: bool,
+    #[serde(default)]
+    i: Vec<[f64; 2]>,
+    #[serde(default)]
+    o: Vec<[f64; 2]>,
+    #[serde(default)]
+    v: Vec<[f64; 2]>,
+}
+
+impl RawBezier {
+    fn path(&self) -> String {
+        bezier_path(&self.v, &self.i, &self.o, self.c)
+    }
+}
+
+#[derive(Deserialize)]
+struct RawShapeKey {
+    t: f64,
+    // Shapes are wrapped in a one-element array
+    s: Option<Vec<RawBezier>>,
+    e: Option<Vec<RawBezier>>,
+    i: Option<RawTangent>,
+    o: Option<RawTangent>,
+    #[serde(default)]
+    h: u8,
+}
+
+fn key_easing(hold: u8, out: Option<&RawTangent>, inn: Option<&RawTangent>) -> Option<CubicBezier> {
+    if hold == 1 {
+        return None;
+    }
+    Some(match (out, inn) {
+        (Some(out), Some(inn)) => CubicBezier::new(
+            out.x.first().clamp(0.0, 1.0),
+            out.y.first(),
+            inn.x.first().clamp(0.0, 1.0),
+            inn.y.first(),
+        ),
+        _ => CubicBezier::linear(),
+    })
+}
+
+fn no_expression(x: &Option<String>) -> Result<(), JsValue> {
+    match x {
+        Some(_) => Err(JsValue::from_str("Lottie expressions aren't supported")),
+        None => Ok(()),
+    }
+}
+
+fn track(property: RawProperty) -> Result<Track, JsValue> {
+    no_expression(&property.x)?;
+    let keys = match property.k {
+        RawValue::Number(value) => return Ok(Track::Static(vec![value])),
+        RawValue::Vector(values) => return Ok(Track::Static(values)),
+        RawValue::Keys(keys) => keys,
+    };
+
+    let mut segments: Vec<Segment> = Vec::with_capacity(keys.len());
+    let mut keys = keys.into_iter().peekable();
+    while let Some(key) = keys.next() {
+        let Some(next) = keys.peek() else {
+            // The last key only marks where the previous span ends
+            if segments.is_empty() {
+                let value = key.s.map(RawComponents::into_vec).unwrap_or_default();
+                return Ok(Track::Static(value));
+            }
+            break;
+        };
+        let from = match key.s {
+            Some(s) => s.into_vec(),
+            None => segments.last().map(|s| s.to.clone()).unwrap_or_default(),
+        };
+        let to = match (key.e, &next.s) {
+            (Some(e), _) => e.into_vec(),
+            (None, Some(RawComponents::One(value))) => vec![*value],
+            (None, Some(RawComponents::Many(values))) => values.clone(),
+            (None, None) => from.clone(),
+        };
+        segments.push(Segment {
+            from_frame: key.t,
+            to_frame: next.t,
+            easing: key_easing(key.h, key.o.as_ref(), key.i.as_ref()),
+            from,
+            to,
+        });
+    }
+    Ok(Track::Animated(segments))
+}
+
+fn optional_track(property: Option<RawProperty>, default: Track) -> Result<Track, JsValue> {
+    match property {
+        Some(property) => track(property),
+        None => Ok(default),
+    }
+}
+
+/// Two animated tracks as one [x, y] track, segmented where either has a key
+fn join_position(x: Track, y: Track) -> Track {
+    let mut frames: Vec<f64> = [&x, &y]
+        .iter()
+        .flat_map(|track| match track {
+            Track::Static(_) => Vec::new(),
+            Track::Animated(segments) => segments
+                .iter()
+                .flat_map(|s| [s.from_frame, s.to_frame])
+                .collect(),
+        })
+        .collect();
+    frames.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    frames.dedup();
+    if frames.len() < 2 {
+        return Track::Static(vec![x.component(0.0, 0, 0.0), y.component(0.0, 0, 0.0)]);
+    }
+    // Each part keeps its own easing by sampling it densely enough
+    let mut segments = Vec::new();
+    for pair in frames.windows(2) {
+        let steps = ((pair[1] - pair[0]).ceil() as usize).clamp(1, 240);
+        for step in 0..steps {
+            let from_frame = pair[0] + (pair[1] - pair[0]) * step as f64 / steps as f64;
+            let to_frame = pair[0] + (pair[1] - pair[0]) * (step + 1) as f64 / steps as f64;
+            segments.push(Segment {
+                from_frame,
+                to_frame,
+                from: vec![
+                    x.component(from_frame, 0, 0.0),
+                    y.component(from_frame, 0, 0.0),
+                ],
+                to: vec![x.component(to_frame, 0, 0.0), y.component(to_frame, 0, 0.0)],
+                easing: Some(CubicBezier::linear()),
+            });
+        }
+    }
+    Track::Animated(segments)
+}
+
+fn transform(raw: RawTransform) -> Result<Transform, JsValue> {
+    let defaults = Transform::default();
+    let position = match raw.p {
+        Some(RawPosition::Whole(property)) => track(property)?,
+        Some(RawPosition::Split { x, y }) => join_position(track(x)?, track(y)?),
+        None => defaults.position,
+    };
+    Ok(Transform {
+        anchor: optional_track(raw.a, defaults.anchor)?,
+        position,
+        scale: optional_track(raw.s, defaults.scale)?,
+        rotation: optional_track(raw.r, defaults.rotation)?,
+        opacity: optional_track(raw.o, defaults.opacity)?,
+    })
+}
+
+fn path_track(property: RawShapeProperty) -> Result<PathTrack, JsValue> {
+    no_expression(&property.x)?;
+    let keys = match property.k {
+        RawShapeValue::Static(bezier) => return Ok(PathTrack::Static(bezier.path())),
+        RawShapeValue::Keys(keys) => keys,
+    };
+
+    let mut segments: Vec<MorphSegment> = Vec::with_capacity(keys.len());
+    let mut last_path = String::new();
+    let mut keys = keys.into_iter().peekable();
+    while let Some(key) = keys.next() {
+        let from = key
+            .s
+            .as_ref()
+            .and_then(|s| s.first())
+            .map(RawBezier::path)
+            .unwrap_or_else(|| last_path.clone());
+        let Some(next) = keys.peek() else {
+            if segments.is_empty() {
+                return Ok(PathTrack::Static(from));
+            }
+            break;
+        };
+        let to = key
+            .e
+            .as_ref()
+            .or(next.s.as_ref())
+            .and_then(|s| s.first())
+            .map(RawBezier::path)
+            .unwrap_or_else(|| from.clone());
+        last_path = to.clone();
+        segments.push(MorphSegment {
+            from_frame: key.t,
+            to_frame: next.t,
+            easing: key_easing(key.h, key.o.as_ref(), key.i.as_ref()),
+            morph: PathMorph::normalize(from, to, None)?,
+        });
+    }
+    Ok(PathTrack::Animated(segments))
+}
+
+fn paint(
+    color: RawProperty,
+    opacity: RawProperty,
+    width: Option<RawProperty>,
+) -> Result<Paint, JsValue> {
+    Ok(Paint {
+        color: track(color)?,
+        opacity: track(opacity)?,
+        width: width.map(track).transpose()?,
+        line_cap: None,
+        line_join: None,
+    })
+}
+
+fn group(shapes: Vec<RawShape>, unsupported: &mut Vec<String>) -> Result<Group, JsValue> {
+    let mut group = Group::default();
+    for shape in shapes {
+        match shape {
+            RawShape::Group { it } => group
+                .items
+                .push(Item::Group(Rc::new(self::group(it, unsupported)?))),
+            RawShape::Path { ks } => group.items.push(Item::Path(path_track(ks)?)),
+            // The first fill and stroke win, as the topmost in After Effects
+            RawShape::Fill { c, o } => {
+                if group.fill.is_none() {
+                    group.fill = Some(paint(c, o, None)?);
+                }
+            }
+            RawShape::Stroke { c, o, w, lc, lj } => {
+                if group.stroke.is_none() {
+                    let mut stroke = paint(c, o, Some(w))?;
+                    stroke.line_cap = match lc {
+                        Some(2) => Some("round"),
+                        Some(3) => Some("square"),
+                        _ => Some("butt"),
+                    };
+                    stroke.line_join = match lj {
+                        Some(2) => Some("round"),
+                        Some(3) => Some("bevel"),
+                        _ => Some("miter"),
+                    };
+                    group.stroke = Some(stroke);
+                }
+            }
+            RawShape::Transform(raw) => group.transform = Some(transform(raw)?),
+            RawShape::Unsupported => note(unsupported, "shape"),
+        }
+    }
+    Ok(group)
+}
+
+fn note(unsupported: &mut Vec<String>, what: &str) {
+    if !unsupported.iter().any(|u| u == what) {
+        unsupported.push(what.to_string());
+    }
+}
+
+impl Composition {
+    fn parse(data: JsValue) -> Result<Composition, JsValue> {
+        let raw: RawComposition = serde_wasm_bindgen::from_value(spec::parse_document(data)?)
+            .map_err(|e| JsValue::from_str(&format!("Invalid Lottie data: {:?}", e)))?;
+
+        let mut unsupported = Vec::new();
+        let mut layers = Vec::with_capacity(raw.layers.len());
+        for layer in raw.layers {
+            let content = match layer.ty {
+                // Null
+                3 => None,
+                // Shape
+                4 => Some(Rc::new(group(layer.shapes, &mut unsupported)?)),
+                other => {
+                    note(&mut unsupported, &format!("layer type {}", other));
+                    continue;
+                }
+            };
+            let transform = match layer.ks {
+                Some(ks) => transform(ks).map_err(|e| layer_error(&layer.nm, e))?,
+                None => Transform::default(),
+            };
+            layers.push(Layer {
+                index: layer.ind,
+                parent: layer.parent,
+                in_point: layer.ip,
+                out_point: layer.op,
+                start_time: layer.st,
+                transform,
+                content,
+            });
+        }
+
+        Ok(Composition {
+            width: raw.w,
+            height: raw.h,
+            frame_rate: if raw.fr > 0.0 { raw.fr } else { 30.0 },
+            in_point: raw.ip,
+            out_point: raw.op.max(raw.ip),
+            layers,
+            unsupported,
+        })
+    }
+
+    /// Indices of `layer` and its ancestors, outermost first
+    fn chain(&self, layer: usize) -> Vec<usize> {
+        let mut chain = vec![layer];
+        let mut current = layer;
+        while let Some(parent) = self.layers[current].parent {
+            match self.layers.iter().position(|l| l.index == Some(parent)) {
+                // Guard against parenting loops
+                Some(found) if !chain.contains(&found) => {
+                    chain.push(found);
+                    current = found;
+                }
+                _ => break,
+            }
+        }
+        chain.reverse();
+        chain
+    }
+}
+
+fn layer_error(name: &str, error: JsValue) -> JsValue {
+    match error.as_string() {
+        Some(message) => JsValue::from_str(&format!("{} (layer \"{}\")", message, name)),
+        None => error,
+    }
+}
+
+// ----------------------------------------------------------------------------
+// SVG
+// ----------------------------------------------------------------------------
+
+fn color_css(rgba: &[f64]) -> String {
+    let channel = |i: usize| (rgba.get(i).copied().unwrap_or(0.0).clamp(0.0, 1.0) * 255.0).round();
+    format!("rgb({}, {}, {})", channel(0), channel(1), channel(2))
+}
+
+/// What a generated element shows
+enum Binding {
+    Layer,
+    Group(Rc<Group>),
+    Path(Rc<Group>, usize),
+}
+
+struct BoundElement {
+    element: Element,
+    // Whose time the tracks are in
+    layer: usize,
+    binding: Binding,
+    // Last written attribute values, so unchanged ones aren't rewritten
+    written: Vec<String>,
+}
+
+impl BoundElement {
+    fn attributes(&self, composition: &Composition, frame: f64) -> Vec<(&'static str, String)> {
+        let layer = &composition.layers[self.layer];
+        let local = frame - layer.start_time;
+        match &self.binding {
+            Binding::Layer => {
+                // Parents apply outside their children
+                let transform = composition
+                    .chain(self.layer)
+                    .into_iter()
+                    .map(|i| {
+                        let ancestor = &composition.layers[i];
+                        ancestor.transform.svg_at(frame - ancestor.start_time)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let visible = frame >= layer.in_point && frame < layer.out_point;
+                vec![
+                    ("transform", transform),
+                    ("opacity", layer.transform.opacity_at(local).to_string()),
+                    (
+                        "visibility",
+                        if visible { "visible" } else { "hidden" }.to_string(),
+                    ),
+                ]
+            }
+            Binding::Group(group) => {
+                let mut attributes = Vec::with_capacity(6);
+                if let Some(transform) = &group.transform {
+                    attributes.push(("transform", transform.svg_at(local)));
+                    attributes.push(("opacity", transform.opacity_at(local).to_string()));
+                }
+                if let Some(fill) = &group.fill {
+                    attributes.push(("fill", color_css(&fill.color.sample(local))));
+                    attributes.push(("fill-opacity", opacity(&fill.opacity, local)));
+                }
+                if let Some(stroke) = &group.stroke {
+                    attributes.push(("stroke", color_css(&stroke.color.sample(local))));
+                    attributes.push(("stroke-opacity", opacity(&stroke.opacity, local)));
+                    if let Some(width) = &stroke.width {
+                        attributes
+                            .push(("stroke-width", width.component(local, 0, 1.0).to_string()));
+                    }
+                }
+                attributes
+            }
+            Binding::Path(group, index) => match group.items.get(*index) {
+                Some(Item::Path(track)) => vec![("d", track.sample(local))],
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    fn render(&mut self, composition: &Composition, frame: f64) -> Result<(), JsValue> {
+        let attributes = self.attributes(composition, frame);
+        self.written.resize(attributes.len(), String::new());
+        for ((name, value), written) in attributes.into_iter().zip(self.written.iter_mut()) {
+            if *written != value {
+                self.element.set_attribute(name, &value)?;
+                *written = value;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn opacity(track: &Track, frame: f64) -> String {
+    (track.component(frame, 0, 100.0) / 100.0)
+        .clamp(0.0, 1.0)
+        .to_string()
+}
+
+fn svg_element(document: &web_sys::Document, name: &str) -> Result<Element, JsValue> {
+    document.create_element_ns(Some(SVG_NS), name)
+}
+
+/// The generated `<svg>` and the elements to update each frame
+struct Scene {
+    root: Element,
+    elements: Vec<BoundElement>,
+}
+
+impl Scene {
+    fn build(composition: &Composition, container: &Element) -> Result<Scene, JsValue> {
+        let document = web_sys::window()
+            .and_then(|w| w.document())
+            .ok_or_else(|| JsValue::from_str("No document"))?;
+        let root = svg_element(&document, "svg")?;
+        root.set_attribute(
+            "viewBox",
+            &format!("0 0 {} {}", composition.width, composition.height),
+        )?;
+        root.set_attribute("width", "100%")?;
+        root.set_attribute("height", "100%")?;
+        root.set_attribute("preserveAspectRatio", "xMidYMid meet")?;
+
+        let mut scene = Scene {
+            root,
+            elements: Vec::new(),
+        };
+        // Lottie lists the topmost layer first; SVG paints later elements on top
+        for (index, layer) in composition.layers.iter().enumerate().rev() {
+            let Some(content) = &layer.content else {
+                continue;
+            };
+            let element = svg_element(&document, "g")?;
+            // Nothing is filled unless a fill says so
+            element.set_attribute("fill", "none")?;
+            scene.root.append_child(&element)?;
+            scene.bind(&element, index, Binding::Layer);
+            scene.add_group(&document, &element, index, content)?;
+        }
+        container.append_child(&scene.root)?;
+        Ok(scene)
+    }
+
+    fn bind(&mut self, element: &Element, layer: usize, binding: Binding) {
+        self.elements.push(BoundElement {
+            element: element.clone(),
+            layer,
+            binding,
+            written: Vec::new(),
+        });
+    }
+
+    /// Fills and strokes go on the group's `<g>` and are inherited by its
+    /// paths and nested groups, as in After Effects
+    fn add_group(
+        &mut self,
+        document: &web_sys::Document,
+        parent: &Element,
+        layer: usize,
+        group: &Rc<Group>,
+    ) -> Result<(), JsValue> {
+        let element = svg_element(document, "g")?;
+        parent.append_child(&element)?;
+        if let Some(stroke) = &group.stroke {
+            if let Some(cap) = stroke.line_cap {
+                element.set_attribute("stroke-linecap", cap)?;
+            }
+            if let Some(join) = stroke.line_join {
+                element.set_attribute("stroke-linejoin", join)?;
+            }
+        }
+        self.bind(&element, layer, Binding::Group(Rc::clone(group)));
+
+        for (index, item) in group.items.iter().enumerate() {
+            match item {
+                Item::Path(_) => {
+                    let path = svg_element(document, "path")?;
+                    element.append_child(&path)?;
+                    self.bind(&path, layer, Binding::Path(Rc::clone(group), index));
+                }
+                Item::Group(child) => self.add_group(document, &element, layer, child)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn render(&mut self, composition: &Composition, frame: f64) -> Result<(), JsValue> {
+        for element in &mut self.elements {
+            element.render(composition, frame)?;
+        }
+        Ok(())
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Player
+// ----------------------------------------------------------------------------
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct LottieConfig {
+    #[serde(rename = "loop")]
+    looping: Option<bool>,
+    autoplay: Option<bool>,
+    speed: Option<f64>,
+}
+
+struct PlayerState {
+    composition: Composition,
+    scene: Scene,
+    frame: f64,
+    speed: f64,
+    looping: bool,
+    playing: bool,
+    completion_callback: Option<Function>,
+    ticker_id: Option<u32>,
+}
+
+impl PlayerState {
+    fn seek(&mut self, frame: f64) -> Result<(), JsValue> {
+        let composition = &self.composition;
+        self.frame = frame.clamp(composition.in_point, composition.out_point);
+        self.scene.render(&self.composition, self.frame)
+    }
+
+    /// Advance by `dt` seconds. Returns whether playback reached an end.
+    fn advance(&mut self, dt: f64) -> bool {
+        let (start, end) = (self.composition.in_point, self.composition.out_point);
+        let span = end - start;
+        let mut frame = self.frame + dt * self.composition.frame_rate * self.speed;
+        let mut finished = false;
+        if frame >= end || frame < start {
+            if self.looping && span > 0.0 {
+                frame = start + (frame - start).rem_euclid(span);
+            } else {
+                finished = true;
+            }
+        }
+        let _ = self.seek(frame);
+        finished
+    }
+}
+
+fn schedule(state: &Rc<RefCell<PlayerState>>) {
+    if state.borrow().ticker_id.is_some() {
+        return;
+    }
+    let tick_state = Rc::clone(state);
+    let id = ticker::subscribe(Box::new(move |_now, dt| {
+        let callback = {
+            let mut s = tick_state.borrow_mut();
+            if !s.playing {
+                s.ticker_id = None;
+                return false;
+            }
+            if !s.advance(dt) {
+                return true;
+            }
+            s.playing = false;
+            s.ticker_id = None;
+            s.completion_callback.clone()
+        };
+        // Called without the state borrowed, so it can play() again
+        if let Some(callback) = callback {
+            let _ = callback.call0(&JsValue::NULL);
+        }
+        false
+    }))
+    .ok();
+    state.borrow_mut().ticker_id = id;
+}
+
+/// Plays a Bodymovin (Lottie) export as SVG generated inside `container`.
+/// Only a subset is supported: shape and null layers, transforms, opacity,
+/// paths, fills and strokes, without expressions. Config: `{ loop: true,
+/// autoplay: true, speed: 1 }`; data is the JSON string or parsed object.
+#[wasm_bindgen]
+pub struct LottiePlayer {
+    state: Rc<RefCell<PlayerState>>,
+}
+
+#[wasm_bindgen]
+impl LottiePlayer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        container: Element,
+        data: JsValue,
+        config: JsValue,
+    ) -> Result<LottiePlayer, JsValue> {
+        let config: LottieConfig = if config.is_undefined() || config.is_null() {
+            LottieConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)
+                .map_err(|e| JsValue::from_str(&format!("Invalid lottie config: {:?}", e)))?
+        };
+        let composition = Composition::parse(data)?;
+        let scene = Scene::build(&composition, &container)?;
+        let frame = composition.in_point;
+
+        let state = Rc::new(RefCell::new(PlayerState {
+            composition,
+            scene,
+            frame,
+            speed: config.speed.unwrap_or(1.0),
+            looping: config.looping.unwrap_or(true),
+            playing: false,
+            completion_callback: None,
+            ticker_id: None,
+        }));
+        state.borrow_mut().seek(frame)?;
+
+        let mut player = LottiePlayer { state };
+        if config.autoplay.unwrap_or(true) {
+            player.play();
+        }
+        Ok(player)
+    }
+
+    /// Play from the current frame, or from the start if it had finished
+    #[wasm_bindgen]
+    pub fn play(&mut self) {
+        {
+            let mut s = self.state.borrow_mut();
+            let (start, end) = (s.composition.in_point, s.composition.out_point);
+            let at_end = if s.speed >= 0.0 {
+                s.frame >= end
+            } else {
+                s.frame <= start
+            };
+            if at_end {
+                let restart = if s.speed >= 0.0 { start } else { end };
+                let _ = s.seek(restart);
+            }
+            s.playing = true;
+        }
+        schedule(&self.state);
+    }
+
+    #[wasm_bindgen]
+    pub fn pause(&mut self) {
+        self.state.borrow_mut().playing = false;
+    }
+
+    /// Pause and go back to the first frame
+    #[wasm_bindgen]
+    pub fn stop(&mut self) -> Result<(), JsValue> {
+        let mut s = self.state.borrow_mut();
+        s.playing = false;
+        let start = s.composition.in_point;
+        s.seek(start)
+    }
+
+    /// Show a frame, in the composition's frame numbers
+    #[wasm_bindgen(js_name = goToFrame)]
+    pub fn go_to_frame(&mut self, frame: f64) -> Result<(), JsValue> {
+        self.state.borrow_mut().seek(frame)
+    }
+
+    /// Show the frame at a fraction (0-1) of the animation
+    #[wasm_bindgen(js_name = seekTo)]
+    pub fn seek_to(&mut self, fraction: f64) -> Result<(), JsValue> {
+        let mut s = self.state.borrow_mut();
+        let (start, end) = (s.composition.in_point, s.composition.out_point);
+        s.seek(start + (end - start) * fraction.clamp(0.0, 1.0))
+    }
+
+    /// Playback rate; negative plays backwards
+    #[wasm_bindgen(js_name = setSpeed)]
+    pub fn set_speed(&mut self, speed: f64) {
+        self.state.borrow_mut().speed = speed;
+    }
+
+    #[wasm_bindgen(js_name = setLoop)]
+    pub fn set_loop(&mut self, looping: bool) {
+        self.state.borrow_mut().looping = looping;
+    }
+
+    /// Called when a non-looping animation reaches its end
+    #[wasm_bindgen(js_name = onComplete)]
+    pub fn on_complete(&mut self, callback: Function) {
+        self.state.borrow_mut().completion_callback = Some(callback);
+    }
+
+    /// Stop and remove the generated SVG
+    #[wasm_bindgen]
+    pub fn destroy(&mut self) {
+        let mut s = self.state.borrow_mut();
+        s.playing = false;
+        if let Some(id) = s.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+        s.scene.root.remove();
+    }
+
+    #[wasm_bindgen(getter, js_name = isPlaying)]
+    pub fn is_playing(&self) -> bool {
+        self.state.borrow().playing
+    }
+
+    #[wasm_bindgen(getter, js_name = currentFrame)]
+    pub fn current_frame(&self) -> f64 {
+        self.state.borrow().frame
+    }
+
+    #[wasm_bindgen(getter, js_name = totalFrames)]
+    pub fn total_frames(&self) -> f64 {
+        let s = self.state.borrow();
+        s.composition.out_point - s.composition.in_point
+    }
+
+    #[wasm_bindgen(getter, js_name = frameRate)]
+    pub fn frame_rate(&self) -> f64 {
+        self.state.borrow().composition.frame_rate
+    }
+
+    /// Length in ms at normal speed
+    #[wasm_bindgen(getter)]
+    pub fn duration(&self) -> f64 {
+        let s = self.state.borrow();
+        (s.composition.out_point - s.composition.in_point) / s.composition.frame_rate * 1000.0
+    }
+
+    /// Features in the data that were skipped, e.g. "shape" or "layer type 2"
+    #[wasm_bindgen(getter)]
+    pub fn unsupported(&self) -> Array {
+        self.state
+            .borrow()
+            .composition
+            .unsupported
+            .iter()
+            .map(|u| JsValue::from_str(u))
+            .collect()
+    }
+}
+
+impl Drop for LottiePlayer {
+    fn drop(&mut self) {
+        if let Some(id) = self.state.borrow_mut().ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::measure;
+use crate::renderer::{self, Renderer};
+use crate::spring::{Spring, Spring2D};
+use crate::ticker;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, AddEventListenerOptions, Element, Event, EventTarget, PointerEvent};
+
+// ============================================================================
+// MAGNETIC - Elements drawn toward a nearby cursor
+// ============================================================================
+
+/// Translation toward `pointer` for an element centered at `center` with
+/// `half_size`: `strength` of the offset from the center while the pointer
+/// is within `radius` px of the element's edges, nothing further out
+pub fn pull(
+    pointer: (f64, f64),
+    center: (f64, f64),
+    half_size: (f64, f64),
+    radius: f64,
+    strength: f64,
+) -> (f64, f64) {
+    let dx = pointer.0 - center.0;
+    let dy = pointer.1 - center.1;
+    let outside_x = (dx.abs() - half_size.0).max(0.0);
+    let outside_y = (dy.abs() - half_size.1).max(0.0);
+    if outside_x.hypot(outside_y) > radius {
+        return (0.0, 0.0);
+    }
+    (dx * strength, dy * strength)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct MagneticConfig {
+    strength: Option<f64>,
+    radius: Option<f64>,
+    stiffness: Option<f64>,
+    damping: Option<f64>,
+}
+
+struct MagneticState {
+    element: Element,
+    renderer: Box<dyn Renderer>,
+    strength: f64,
+    radius: f64,
+    // Latest pointer position; None once it leaves the window
+    pointer: Option<(f64, f64)>,
+    spring: Spring2D,
+    position: (f64, f64),
+    active: bool,
+    ticker_id: Option<u32>,
+}
+
+impl MagneticState {
+    /// Returns whether still moving
+    fn tick(&mut self, dt: f64) -> bool {
+        let target = match self.pointer {
+            Some(pointer) => {
+                // Measured without our own translation, so the pull doesn't
+                // chase itself
+                let rect = measure::element_rect(&self.element);
+                let center = (
+                    rect.center_x() - self.position.0,
+                    rect.center_y() - self.position.1,
+                );
+                let half_size = (rect.width * 0.5, rect.height * 0.5);
+                pull(pointer, center, half_size, self.radius, self.strength)
+            }
+            None => (0.0, 0.0),
+        };
+        self.active = target != (0.0, 0.0);
+
+        self.position = self.spring.update(target, dt.min(0.032));
+        let settled = self.spring.at_rest(target, 0.5);
+        if settled {
+            self.spring.reset(target);
+            self.position = target;
+        }
+        let _ = self.renderer.apply(
+            "translate",
+            &format!("{}px {}px", self.position.0, self.position.1),
+        );
+        let _ = self.renderer.flush();
+        !settled
+    }
+}
+
+fn schedule(state: &Rc<RefCell<MagneticState>>) {
+    if state.borrow().ticker_id.is_some() {
+        return;
+    }
+    let tick_state = Rc::clone(state);
+    let id = ticker::subscribe(Box::new(move |_now, dt| {
+        let mut s = tick_state.borrow_mut();
+        let moving = s.tick(dt);
+        if !moving {
+            s.ticker_id = None;
+        }
+        moving
+    }))
+    .ok();
+    state.borrow_mut().ticker_id = id;
+}
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+/// Pulls an element toward the cursor while it's near, springing back to
+/// its origin when it moves away. Config: `{ strength: 0.3, radius: 80,
+/// stiffness: 300, damping: 30 }`.
+#[wasm_bindgen]
+pub struct Magnetic {
+    state: Rc<RefCell<MagneticState>>,
+    listeners: Vec<(EventTarget, &'static str, EventCallback)>,
+}
+
+#[wasm_bindgen]
+impl Magnetic {
+    #[wasm_bindgen(constructor)]
+    pub fn new(element: Element, config: JsValue) -> Result<Magnetic, JsValue> {
+        let config: MagneticConfig = if config.is_undefined() || config.is_null() {
+            MagneticConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)
+                .map_err(|e| JsValue::from_str(&format!("Invalid magnetic config: {:?}", e)))?
+        };
+        let defaults = Spring::default();
+        let stiffness = config.stiffness.unwrap_or(defaults.stiffness);
+        let damping = config.damping.unwrap_or(defaults.damping);
+
+        let renderer = renderer::for_element(&element);
+        let state = Rc::new(RefCell::new(MagneticState {
+            element,
+            renderer,
+            strength: config.strength.unwrap_or(0.3),
+            radius: config.radius.unwrap_or(80.0).max(0.0),
+            pointer: None,
+            spring: Spring2D {
+                x: Spring::new(stiffness, damping),
+                y: Spring::new(stiffness, damping),
+            },
+            position: (0.0, 0.0),
+            active: false,
+            ticker_id: None,
+        }));
+
+        let mut magnetic = Magnetic {
+            state,
+            listeners: Vec::with_capacity(2),
+        };
+        magnetic.bind()?;
+        Ok(magnetic)
+    }
+
+    #[wasm_bindgen(js_name = setStrength)]
+    pub fn set_strength(&mut self, strength: f64) {
+        self.state.borrow_mut().strength = strength;
+        schedule(&self.state);
+    }
+
+    #[wasm_bindgen(js_name = setRadius)]
+    pub fn set_radius(&mut self, radius: f64) {
+        self.state.borrow_mut().radius = radius.max(0.0);
+        schedule(&self.state);
+    }
+
+    /// Whether the cursor is close enough to pull the element
+    #[wasm_bindgen(getter, js_name = isActive)]
+    pub fn is_active(&self) -> bool {
+        self.state.borrow().active
+    }
+
+    /// Stop reacting to the pointer and spring back to the origin
+    #[wasm_bindgen]
+    pub fn unbind(&mut self) {
+        for (target, name, callback) in self.listeners.drain(..) {
+            let _ =
+                target.remove_event_listener_with_callback(name, callback.as_ref().unchecked_ref());
+        }
+        self.state.borrow_mut().pointer = None;
+        schedule(&self.state);
+    }
+}
+
+impl Magnetic {
+    fn bind(&mut self) -> Result<(), JsValue> {
+        let window: EventTarget = window()
+            .ok_or_else(|| JsValue::from_str("No window available"))?
+            .into();
+
+        // The radius reaches past the element, so listen on the window
+        let state = Rc::clone(&self.state);
+        self.listen(&window, "pointermove", move |event| {
+            if let Some(pointer) = event.dyn_ref::<PointerEvent>() {
+                let point = (pointer.client_x() as f64, pointer.client_y() as f64);
+                state.borrow_mut().pointer = Some(point);
+                schedule(&state);
+            }
+        })?;
+        let state = Rc::clone(&self.state);
+        self.listen(&window, "pointerout", move |event| {
+            // Only leaving the window itself, not moving between elements
+            let left_window = event
+                .dyn_ref::<PointerEvent>()
+                .is_some_and(|pointer| pointer.related_target().is_none());
+            if left_window {
+                state.borrow_mut().pointer = None;
+                schedule(&state);
+            }
+        })
+    }
+
+    fn listen(
+        &mut self,
+        target: &EventTarget,
+        name: &'static str,
+        handler: impl FnMut(Event) + 'static,
+    ) -> Result<(), JsValue> {
+        let callback: EventCallback = Closure::wrap(Box::new(handler) as Box<dyn FnMut(Event)>);
+        let options = AddEventListenerOptions::new();
+        options.set_passive(true);
+        target.add_event_listener_with_callback_and_add_event_listener_options(
+            name,
+            callback.as_ref().unchecked_ref(),
+            &options,
+        )?;
+        self.listeners.push((target.clone(), name, callback));
+        Ok(())
+    }
+}
+
+impl Drop for Magnetic {
+    fn drop(&mut self) {
+        // Spring back is moot once dropped; just stop everything
+        self.unbind();
+        if let Some(id) = self.state.borrow_mut().ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, Element};
+
+// ============================================================================
+// MEASUREMENT - Layout rects in viewport coordinates
+// ============================================================================
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub(crate) struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub(crate) fn new(x: f64, y: f64, width: f64, height: f64) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub(crate) fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub(crate) fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    pub(crate) fn center_x(&self) -> f64 {
+        self.x + self.width * 0.5
+    }
+
+    pub(crate) fn center_y(&self) -> f64 {
+        self.y + self.height * 0.5
+    }
+
+    /// Shrink (or grow, for negative amounts) on every side
+    pub(crate) fn inset(&self, amount: f64) -> Rect {
+        Rect::new(
+            self.x + amount,
+            self.y + amount,
+            (self.width - amount * 2.0).max(0.0),
+            (self.height - amount * 2.0).max(0.0),
+        )
+    }
+
+    /// Area shared with another rect (0 when they don't touch)
+    pub(crate) fn overlap_area(&self, other: &Rect) -> f64 {
+        let w = self.right().min(other.right()) - self.x.max(other.x);
+        let h = self.bottom().min(other.bottom()) - self.y.max(other.y);
+        w.max(0.0) * h.max(0.0)
+    }
+
+    /// Area of self lying outside `bounds`
+    pub(crate) fn overflow_area(&self, bounds: &Rect) -> f64 {
+        self.width * self.height - self.overlap_area(bounds)
+    }
+}
+
+/// Border box of an element relative to the viewport
+pub(crate) fn element_rect(element: &Element) -> Rect {
+    let r = element.get_bounding_client_rect();
+    Rect::new(r.x(), r.y(), r.width(), r.height())
+}
+
+/// Border box of an element relative to the document, i.e. page coordinates
+pub(crate) fn page_rect(element: &Element) -> Rect {
+    let (scroll_x, scroll_y) = window()
+        .map(|w| (w.scroll_x().unwrap_or(0.0), w.scroll_y().unwrap_or(0.0)))
+        .unwrap_or((0.0, 0.0));
+    let rect = element_rect(element);
+    Rect::new(
+        rect.x + scroll_x,
+        rect.y + scroll_y,
+        rect.width,
+        rect.height,
+    )
+}
+
+pub(crate) fn viewport_rect() -> Result<Rect, JsValue> {
+    let window = window().ok_or_else(|| JsValue::from_str("No window available"))?;
+    let width = window.inner_width()?.as_f64().unwrap_or(0.0);
+    let height = window.inner_height()?.as_f64().unwrap_or(0.0);
+    Ok(Rect::new(0.0, 0.0, width, height))
+}
@@ -1,4 +1,39 @@
+use crate::types::PropertyType;
+use std::cell::RefCell;
 use wasm_bindgen::prelude::*;
+use web_sys::{Element, HtmlElement};
+
+// Compositor layers promoted at once, across animations and manual hints
+const DEFAULT_LAYER_BUDGET: usize = 24;
+
+/// An element promoted with will-change, and the inline styles to restore
+struct Layer {
+    element: HtmlElement,
+    // Animations and apply_gpu_hints calls holding the promotion
+    holders: u32,
+    will_change: String,
+    // Only set by apply_gpu_hints, which also forces a 3D layer
+    hints: Option<[String; 3]>,
+}
+
+struct LayerBudget {
+    max: usize,
+    auto_promote: bool,
+    layers: Vec<Layer>,
+}
+
+thread_local! {
+    static LAYERS: RefCell<LayerBudget> = const {
+        RefCell::new(LayerBudget {
+            max: DEFAULT_LAYER_BUDGET,
+            auto_promote: true,
+            layers: Vec::new(),
+        })
+    };
+}
+
+// Properties apply_gpu_hints overrides, restored by remove_gpu_hints
+const HINT_PROPERTIES: [&str; 3] = ["transform", "backface-visibility", "perspective"];
 
 /// Metal Acceleration - GPU-accelerated animation rendering hints
 #[wasm_bindgen]
@@ -8,6 +43,12 @@ pub struct GPUAccelerator {
     optimization_level: u8,
 }
 
+impl Default for GPUAccelerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl GPUAccelerator {
     #[wasm_bindgen(constructor)]
@@ -64,32 +105,213 @@ impl GPUAccelerator {
         self.optimization_level
     }
 
-    /// Apply GPU-friendly CSS properties
+    /// Apply GPU-friendly CSS properties. The element counts against the
+    /// layer budget until remove_gpu_hints; returns false when the budget is
+    /// full and nothing was applied.
     #[wasm_bindgen]
-    pub fn apply_gpu_hints(&self, element: &web_sys::Element) -> Result<(), JsValue> {
-        if let Ok(html_elem) = element.clone().dyn_into::<web_sys::HtmlElement>() {
-            let style = html_elem.style();
-
-            // Apply will-change for optimal GPU rendering
-            if self.use_gpu {
-                let _ = style.set_property("will-change", "transform, opacity");
-                let _ = style.set_property("transform", "translateZ(0)");
-                let _ = style.set_property("backface-visibility", "hidden");
-                let _ = style.set_property("perspective", "1000px");
+    pub fn apply_gpu_hints(&self, element: &Element) -> Result<bool, JsValue> {
+        if !self.use_gpu {
+            return Ok(false);
+        }
+        // Hints hold one layer however often they're applied
+        let applied = LAYERS.with(|budget| {
+            let budget = budget.borrow();
+            budget.layers.iter().any(|l| *l.element == *element && l.hints.is_some())
+        });
+        if applied {
+            return Ok(true);
+        }
+        let Some(layer) = hold_layer(element, "transform, opacity") else {
+            return Ok(false);
+        };
+
+        LAYERS.with(|budget| {
+            let mut budget = budget.borrow_mut();
+            let layer = &mut budget.layers[layer];
+            let style = layer.element.style();
+            layer.hints = Some(HINT_PROPERTIES.map(|p| {
+                style.get_property_value(p).unwrap_or_default()
+            }));
+            style.set_property("transform", "translateZ(0)")?;
+            style.set_property("backface-visibility", "hidden")?;
+            style.set_property("perspective", "1000px")?;
+            Ok(true)
+        })
+    }
+
+    /// Remove GPU hints, restoring the element's previous inline styles
+    /// once no animation holds its layer either
+    #[wasm_bindgen]
+    pub fn remove_gpu_hints(&self, element: &Element) -> Result<(), JsValue> {
+        let held = LAYERS.with(|budget| {
+            let mut budget = budget.borrow_mut();
+            let hints = budget
+                .layers
+                .iter_mut()
+                .find(|l| *l.element == *element)
+                .and_then(|l| Some((l.element.style(), l.hints.take()?)));
+            let Some((style, hints)) = hints else {
+                return false;
+            };
+            for (property, value) in HINT_PROPERTIES.iter().zip(hints) {
+                restore(&style, property, &value);
             }
+            true
+        });
+        // Animations may still hold the layer
+        if held {
+            release_layer(element);
         }
         Ok(())
     }
 
-    /// Remove GPU hints
+    // ========================================================================
+    // LAYER BUDGET
+    // ========================================================================
+
+    /// Most elements promoted to compositor layers at once, by animations
+    /// and apply_gpu_hints together (default 24). Further requests are
+    /// skipped until a layer is released.
+    #[wasm_bindgen]
+    pub fn set_layer_budget(max: usize) {
+        LAYERS.with(|budget| budget.borrow_mut().max = max);
+    }
+
+    #[wasm_bindgen]
+    pub fn layer_budget() -> usize {
+        LAYERS.with(|budget| budget.borrow().max)
+    }
+
+    /// Elements currently promoted
     #[wasm_bindgen]
-    pub fn remove_gpu_hints(&self, element: &web_sys::Element) -> Result<(), JsValue> {
-        if let Ok(html_elem) = element.clone().dyn_into::<web_sys::HtmlElement>() {
-            let style = html_elem.style();
-            let _ = style.remove_property("will-change");
-            let _ = style.remove_property("backface-visibility");
+    pub fn promoted_count() -> usize {
+        LAYERS.with(|budget| budget.borrow().layers.len())
+    }
+
+    /// Whether running animations of transforms, opacity or filters set
+    /// will-change on their element, removing it when they finish (default
+    /// on). Animations that also change layout are never promoted.
+    #[wasm_bindgen]
+    pub fn set_auto_promote(enabled: bool) {
+        LAYERS.with(|budget| budget.borrow_mut().auto_promote = enabled);
+    }
+
+    #[wasm_bindgen]
+    pub fn is_auto_promote() -> bool {
+        LAYERS.with(|budget| budget.borrow().auto_promote)
+    }
+}
+
+/// Promote `element` for an animation of `properties`, if worthwhile and
+/// within budget. Returns whether it holds a layer to release later.
+pub(crate) fn promote(
+    element: &Element,
+    properties: impl Iterator<Item = PropertyType>,
+) -> bool {
+    if !LAYERS.with(|budget| budget.borrow().auto_promote) {
+        return false;
+    }
+
+    let (mut transform, mut opacity, mut filter) = (false, false, false);
+    for property in properties {
+        match property {
+            // Layout is recalculated every frame anyway, so a layer only
+            // costs memory
+            PropertyType::Width
+            | PropertyType::Height
+            | PropertyType::MinWidth
+            | PropertyType::MinHeight
+            | PropertyType::MaxWidth
+            | PropertyType::MaxHeight
+            | PropertyType::BorderWidth
+            | PropertyType::Inset => return false,
+            PropertyType::X
+            | PropertyType::Y
+            | PropertyType::Z
+            | PropertyType::Scale
+            | PropertyType::ScaleX
+            | PropertyType::ScaleY
+            | PropertyType::Rotate
+            | PropertyType::RotateX
+            | PropertyType::RotateY
+            | PropertyType::RotateZ
+            | PropertyType::SkewX
+            | PropertyType::SkewY => transform = true,
+            PropertyType::Opacity => opacity = true,
+            PropertyType::Blur
+            | PropertyType::Brightness
+            | PropertyType::Contrast
+            | PropertyType::Saturate
+            | PropertyType::Hue
+            | PropertyType::Grayscale
+            | PropertyType::Invert
+            | PropertyType::Sepia => filter = true,
+            _ => {}
         }
-        Ok(())
+    }
+
+    let used = [(transform, "transform"), (opacity, "opacity"), (filter, "filter")];
+    let will_change: Vec<&str> = used
+        .into_iter()
+        .filter_map(|(used, name)| used.then_some(name))
+        .collect();
+    if will_change.is_empty() {
+        return false;
+    }
+    hold_layer(element, &will_change.join(", ")).is_some()
+}
+
+/// Take a hold on `element`'s layer, promoting it with `will_change` if it
+/// isn't yet. Returns its index, or None when the budget is full.
+fn hold_layer(element: &Element, will_change: &str) -> Option<usize> {
+    let html = element.dyn_ref::<HtmlElement>()?;
+    LAYERS.with(|budget| {
+        let mut budget = budget.borrow_mut();
+        if let Some(index) = budget.layers.iter().position(|l| l.element == *html) {
+            budget.layers[index].holders += 1;
+            return Some(index);
+        }
+        if budget.layers.len() >= budget.max {
+            return None;
+        }
+
+        let style = html.style();
+        let previous = style.get_property_value("will-change").unwrap_or_default();
+        style.set_property("will-change", will_change).ok()?;
+        budget.layers.push(Layer {
+            element: html.clone(),
+            holders: 1,
+            will_change: previous,
+            hints: None,
+        });
+        Some(budget.layers.len() - 1)
+    })
+}
+
+/// Drop a hold taken by promote or apply_gpu_hints, restoring will-change
+/// once nothing holds the layer
+pub(crate) fn release_layer(element: &Element) {
+    LAYERS.with(|budget| {
+        let mut budget = budget.borrow_mut();
+        let Some(index) = budget.layers.iter().position(|l| *l.element == *element) else {
+            return;
+        };
+        let layer = &mut budget.layers[index];
+        layer.holders -= 1;
+        if layer.holders > 0 {
+            return;
+        }
+        let layer = budget.layers.swap_remove(index);
+        restore(&layer.element.style(), "will-change", &layer.will_change);
+    });
+}
+
+/// Put back an inline style value read before it was overridden
+fn restore(style: &web_sys::CssStyleDeclaration, property: &str, value: &str) {
+    if value.is_empty() {
+        let _ = style.remove_property(property);
+    } else {
+        let _ = style.set_property(property, value);
     }
 }
 
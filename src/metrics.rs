@@ -0,0 +1,326 @@
+use js_sys::WeakMap;
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+use web_sys::{Element, HtmlElement, Performance};
+
+// ============================================================================
+// ENGINE METRICS - User Timing export for DevTools performance traces
+// ============================================================================
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static NEXT_ID: Cell<u32> = const { Cell::new(1) };
+    static HEATMAP_ENABLED: Cell<bool> = const { Cell::new(false) };
+    static HEATMAP: RefCell<Heatmap> = RefCell::new(Heatmap::default());
+    static HEAT_SLOTS: WeakMap = WeakMap::new();
+}
+
+/// Style write counters for one element
+struct HeatEntry {
+    element: Element,
+    total_writes: u32,
+    window_start: f64,
+    window_writes: u32,
+    writes_per_second: f64,
+    /// Inline outline the element had before outlineHottest drew over it
+    saved_outline: Option<String>,
+}
+
+/// Length of the window write rates are measured over, in ms
+const RATE_WINDOW_MS: f64 = 1000.0;
+
+impl HeatEntry {
+    /// Rate over the last full window while the current one is running;
+    /// once the current window outlasts RATE_WINDOW_MS without a write closing
+    /// it, the rate falls off with the time since it opened
+    fn rate(&self, now: f64) -> f64 {
+        let elapsed = now - self.window_start;
+        if elapsed <= 0.0 || (self.writes_per_second > 0.0 && elapsed < RATE_WINDOW_MS) {
+            self.writes_per_second
+        } else {
+            self.window_writes as f64 * 1000.0 / elapsed.max(16.0)
+        }
+    }
+
+    fn outline(&mut self, intensity: f64) {
+        if let Some(html) = self.element.dyn_ref::<HtmlElement>() {
+            let style = html.style();
+            if self.saved_outline.is_none() {
+                self.saved_outline = Some(style.get_property_value("outline").unwrap_or_default());
+            }
+            let _ = style.set_property(
+                "outline",
+                &format!("2px solid rgba(255, 0, 0, {:.2})", intensity),
+            );
+        }
+    }
+
+    /// Put back the outline the element had before it was highlighted
+    fn clear_outline(&mut self) {
+        let Some(saved) = self.saved_outline.take() else {
+            return;
+        };
+        if let Some(html) = self.element.dyn_ref::<HtmlElement>() {
+            let style = html.style();
+            if saved.is_empty() {
+                let _ = style.remove_property("outline");
+            } else {
+                let _ = style.set_property("outline", &saved);
+            }
+        }
+    }
+}
+
+/// Counters, one slot per element; HEAT_SLOTS maps each element to its slot
+#[derive(Default)]
+struct Heatmap {
+    slots: Vec<Option<HeatEntry>>,
+    free: Vec<usize>,
+}
+
+impl Heatmap {
+    fn entry(&mut self, element: &Element, now: f64) -> &mut HeatEntry {
+        let index = match HEAT_SLOTS.with(|slots| slots.get(element).as_f64()) {
+            Some(index) => index as usize,
+            None => {
+                // Only new elements can bring the table up, so sweep then
+                self.prune();
+                let index = self.free.pop().unwrap_or_else(|| {
+                    self.slots.push(None);
+                    self.slots.len() - 1
+                });
+                self.slots[index] = Some(HeatEntry {
+                    element: element.clone(),
+                    total_writes: 0,
+                    window_start: now,
+                    window_writes: 0,
+                    writes_per_second: 0.0,
+                    saved_outline: None,
+                });
+                HEAT_SLOTS.with(|slots| slots.set(element, &(index as f64).into()));
+                index
+            }
+        };
+        self.slots[index].as_mut().unwrap()
+    }
+
+    /// Drop elements that have left the document
+    fn prune(&mut self) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.as_ref().is_some_and(|e| !e.element.is_connected()) {
+                let mut entry = slot.take().unwrap();
+                entry.clear_outline();
+                HEAT_SLOTS.with(|slots| slots.delete(&entry.element));
+                self.free.push(index);
+            }
+        }
+    }
+}
+
+const PREFIX: &str = "anim-engine";
+
+#[wasm_bindgen]
+pub struct EngineMetrics;
+
+#[wasm_bindgen]
+impl EngineMetrics {
+    /// Emit performance.mark/measure entries for animation lifecycle and frame cost
+    #[wasm_bindgen]
+    pub fn enable() {
+        ENABLED.with(|e| e.set(true));
+    }
+
+    #[wasm_bindgen]
+    pub fn disable() {
+        ENABLED.with(|e| e.set(false));
+    }
+
+    #[wasm_bindgen(js_name = isEnabled)]
+    pub fn is_enabled() -> bool {
+        enabled()
+    }
+
+    // ========================================================================
+    // STYLE WRITE HEATMAP
+    // ========================================================================
+
+    /// Count style writes per element (diagnostics only, adds per-write overhead)
+    #[wasm_bindgen(js_name = enableHeatmap)]
+    pub fn enable_heatmap() {
+        HEATMAP_ENABLED.with(|e| e.set(true));
+    }
+
+    #[wasm_bindgen(js_name = disableHeatmap)]
+    pub fn disable_heatmap() {
+        HEATMAP_ENABLED.with(|e| e.set(false));
+    }
+
+    /// Ranked list of `{ element, writesPerSecond, totalWrites }`, hottest first
+    #[wasm_bindgen(js_name = heatmapReport)]
+    pub fn heatmap_report(limit: usize) -> js_sys::Array {
+        let now = now();
+        let report = js_sys::Array::new();
+
+        HEATMAP.with(|heatmap| {
+            let mut heatmap = heatmap.borrow_mut();
+            heatmap.prune();
+            for (index, rate) in ranked(&heatmap, now).into_iter().take(limit) {
+                let entry = heatmap.slots[index].as_ref().unwrap();
+                let row = js_sys::Object::new();
+                let _ = js_sys::Reflect::set(&row, &"element".into(), &entry.element);
+                let _ = js_sys::Reflect::set(&row, &"writesPerSecond".into(), &rate.into());
+                let _ = js_sys::Reflect::set(
+                    &row,
+                    &"totalWrites".into(),
+                    &(entry.total_writes as f64).into(),
+                );
+                report.push(&row);
+            }
+        });
+
+        report
+    }
+
+    /// Outline the most-written elements, strongest color for the hottest
+    #[wasm_bindgen(js_name = outlineHottest)]
+    pub fn outline_hottest(count: usize) {
+        let now = now();
+
+        HEATMAP.with(|heatmap| {
+            let mut heatmap = heatmap.borrow_mut();
+            heatmap.prune();
+            let ranked = ranked(&heatmap, now);
+            let shown = ranked.len().min(count);
+            let mut intensities = vec![None; heatmap.slots.len()];
+            for (rank, (index, _)) in ranked.into_iter().take(count).enumerate() {
+                intensities[index] = Some(1.0 - rank as f64 / shown.max(1) as f64 * 0.75);
+            }
+
+            for (slot, intensity) in heatmap.slots.iter_mut().zip(intensities) {
+                if let Some(entry) = slot {
+                    match intensity {
+                        Some(intensity) => entry.outline(intensity),
+                        None => entry.clear_outline(),
+                    }
+                }
+            }
+        });
+    }
+
+    /// Reset counters and remove any outlines drawn by outlineHottest
+    #[wasm_bindgen(js_name = clearHeatmap)]
+    pub fn clear_heatmap() {
+        HEATMAP.with(|heatmap| {
+            let mut heatmap = heatmap.borrow_mut();
+            for mut entry in heatmap.slots.drain(..).flatten() {
+                entry.clear_outline();
+                HEAT_SLOTS.with(|slots| slots.delete(&entry.element));
+            }
+            heatmap.free.clear();
+        });
+    }
+}
+
+#[inline]
+pub(crate) fn enabled() -> bool {
+    ENABLED.with(|e| e.get())
+}
+
+/// Engine-wide animation id, used to name timeline entries
+pub(crate) fn next_animation_id() -> u32 {
+    NEXT_ID.with(|id| {
+        let value = id.get();
+        id.set(value.wrapping_add(1));
+        value
+    })
+}
+
+pub(crate) fn mark_start(performance: &Performance, id: u32) {
+    if enabled() {
+        let _ = performance.mark(&format!("{}:{}:start", PREFIX, id));
+    }
+}
+
+/// Mark completion and measure the whole run from its start mark, then drop
+/// both marks and the measure from the buffer as frame_end() does
+pub(crate) fn mark_complete(performance: &Performance, id: u32) {
+    if !enabled() {
+        return;
+    }
+
+    let start_mark = format!("{}:{}:start", PREFIX, id);
+    let end_mark = format!("{}:{}:complete", PREFIX, id);
+    let measure = format!("{}:{}", PREFIX, id);
+    let _ = performance.mark(&end_mark);
+    let _ = performance.measure_with_start_mark_and_end_mark(&measure, &start_mark, &end_mark);
+    performance.clear_marks_with_mark_name(&start_mark);
+    performance.clear_marks_with_mark_name(&end_mark);
+    performance.clear_measures_with_measure_name(&measure);
+}
+
+pub(crate) fn frame_begin(performance: &Performance, id: u32) {
+    if enabled() {
+        let _ = performance.mark(&format!("{}:{}:frame", PREFIX, id));
+    }
+}
+
+/// Measure engine cost of one frame, then drop the per-frame mark and
+/// measure so the entry buffer doesn't grow unbounded. Traces and
+/// PerformanceObservers have the measure by then; it's only taken out of the
+/// buffer getEntries() reads.
+pub(crate) fn frame_end(performance: &Performance, id: u32) {
+    if !enabled() {
+        return;
+    }
+
+    let mark = format!("{}:{}:frame", PREFIX, id);
+    let measure = format!("{}:{} frame", PREFIX, id);
+    let _ = performance.measure_with_start_mark(&measure, &mark);
+    performance.clear_marks_with_mark_name(&mark);
+    performance.clear_measures_with_measure_name(&measure);
+}
+
+/// Count style writes against the element when the heatmap is enabled
+pub(crate) fn record_style_writes(performance: &Performance, element: &Element, writes: usize) {
+    if writes == 0 {
+        return;
+    }
+    if !HEATMAP_ENABLED.with(|e| e.get()) {
+        return;
+    }
+
+    let now = performance.now();
+    HEATMAP.with(|heatmap| {
+        let mut heatmap = heatmap.borrow_mut();
+        let entry = heatmap.entry(element, now);
+
+        let elapsed = now - entry.window_start;
+        if elapsed >= RATE_WINDOW_MS {
+            entry.writes_per_second = entry.window_writes as f64 * 1000.0 / elapsed;
+            entry.window_start = now;
+            entry.window_writes = 0;
+        }
+
+        entry.total_writes += writes as u32;
+        entry.window_writes += writes as u32;
+    });
+}
+
+/// Slot indices with their write rates, hottest first
+fn ranked(heatmap: &Heatmap, now: f64) -> Vec<(usize, f64)> {
+    let mut ranked: Vec<(usize, f64)> = heatmap
+        .slots
+        .iter()
+        .enumerate()
+        .filter_map(|(index, slot)| slot.as_ref().map(|e| (index, e.rate(now))))
+        .collect();
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    ranked
+}
+
+fn now() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,95 @@
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+// ============================================================================
+// CONFIG SCHEMA MIGRATION - Keep persisted animation definitions loadable
+// ============================================================================
+
+/// Schema version produced by this crate. Configs saved before versioning
+/// existed are in this schema too, so they load without a `version` field.
+pub const CURRENT_VERSION: u32 = 1;
+
+type Migration = fn(&Object) -> Result<(), JsValue>;
+
+/// MIGRATIONS[n] upgrades a config from version n + 1 to n + 2. When a
+/// release changes the schema, it bumps CURRENT_VERSION and adds the step
+/// that carries the previous version's configs forward.
+const MIGRATIONS: [Migration; 0] = [];
+
+#[wasm_bindgen]
+pub struct ConfigSchema;
+
+#[wasm_bindgen]
+impl ConfigSchema {
+    #[wasm_bindgen(js_name = currentVersion)]
+    pub fn current_version() -> u32 {
+        CURRENT_VERSION
+    }
+
+    /// Upgrade a config (or array of configs) to the current schema, e.g. before re-saving it
+    #[wasm_bindgen]
+    pub fn migrate(config: JsValue) -> Result<JsValue, JsValue> {
+        if Array::is_array(&config) {
+            migrate_list(config)
+        } else {
+            migrate(config)
+        }
+    }
+}
+
+/// Migrate a single config object. Configs without a `version` field are
+/// taken as current and left as they are.
+pub(crate) fn migrate(config: JsValue) -> Result<JsValue, JsValue> {
+    // Non-objects are left for serde to reject with its own message
+    if !config.is_object() {
+        return Ok(config);
+    }
+
+    let source: &Object = config.unchecked_ref();
+    let version = read_version(source)?;
+    if version == CURRENT_VERSION {
+        return Ok(config);
+    }
+
+    // Work on a copy so the caller's object is never mutated
+    let migrated = Object::assign(&Object::new(), source);
+    for step in &MIGRATIONS[(version - 1) as usize..] {
+        step(&migrated)?;
+    }
+    Reflect::set(&migrated, &"version".into(), &CURRENT_VERSION.into())?;
+
+    Ok(migrated.into())
+}
+
+pub(crate) fn migrate_list(configs: JsValue) -> Result<JsValue, JsValue> {
+    if !Array::is_array(&configs) {
+        return Ok(configs);
+    }
+
+    let migrated = Array::new();
+    for config in Array::from(&configs).iter() {
+        migrated.push(&migrate(config)?);
+    }
+    Ok(migrated.into())
+}
+
+fn read_version(config: &Object) -> Result<u32, JsValue> {
+    let value = Reflect::get(config, &"version".into())?;
+    if value.is_undefined() || value.is_null() {
+        return Ok(CURRENT_VERSION);
+    }
+
+    let version = value
+        .as_f64()
+        .filter(|v| v.fract() == 0.0 && *v >= 1.0)
+        .ok_or_else(|| JsValue::from_str("Config version must be a positive integer"))?;
+
+    if version > CURRENT_VERSION as f64 {
+        return Err(JsValue::from_str(&format!(
+            "Unsupported config version {} (this engine supports up to {})",
+            version, CURRENT_VERSION
+        )));
+    }
+
+    Ok(version as u32)
+}
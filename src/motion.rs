@@ -0,0 +1,650 @@
+use crate::batch;
+use crate::renderer::{css_name, Renderer};
+use crate::types::{create_value_with_number, extract_number, interpolate_value};
+use wasm_bindgen::JsValue;
+
+pub use crate::cubic::CubicBezier;
+pub use crate::spring::Spring;
+pub use crate::types::{AnimatableValue, AnimationProperty, Keyframe, LengthUnit, PropertyType};
+
+// ============================================================================
+// MOTION CORE - DOM-free per-frame evaluation
+// ============================================================================
+//
+// Everything an Animation does each frame except talking to the browser:
+// interpolating properties for a progress/time step and turning them into
+// (property, value) writes on a Renderer. Native tests drive these directly.
+
+/// Interpolate every property start -> end at `progress` (0-1) through the easing
+pub fn sample_cubic(
+    properties: &mut [AnimationProperty],
+    bezier: Option<&CubicBezier>,
+    progress: f64,
+) {
+    let eased = match bezier {
+        Some(bezier) => bezier.solve(progress),
+        None => progress,
+    };
+
+    // Plain numbers are lerped together in one batch, the rest one by one
+    let mut from = Vec::with_capacity(properties.len());
+    let mut to = Vec::with_capacity(properties.len());
+    for prop in properties.iter_mut() {
+        match (&prop.start, &prop.end) {
+            (AnimatableValue::Number(start), AnimatableValue::Number(end)) => {
+                from.push(*start);
+                to.push(*end);
+            }
+            _ => prop.current = interpolate_value(&prop.start, &prop.end, eased),
+        }
+    }
+    if from.is_empty() {
+        return;
+    }
+
+    let mut values = vec![0.0; from.len()];
+    batch::lerp_uniform(&from, &to, eased, &mut values);
+    let numbers = properties.iter_mut().filter(|prop| {
+        matches!(
+            (&prop.start, &prop.end),
+            (AnimatableValue::Number(_), AnimatableValue::Number(_))
+        )
+    });
+    for (prop, value) in numbers.zip(values) {
+        prop.current = AnimatableValue::Number(value);
+    }
+}
+
+/// Advance one spring per property by `delta_time` seconds. Returns true while
+/// any spring is still moving.
+pub fn step_springs(
+    properties: &mut [AnimationProperty],
+    springs: &mut [Spring],
+    delta_time: f64,
+) -> bool {
+    let mut at_rest = true;
+
+    for (prop, spring) in properties.iter_mut().zip(springs.iter_mut()) {
+        let target = extract_number(&prop.end);
+        let value = spring.update(target, delta_time);
+
+        if spring.velocity.abs() > 0.01 || (value - target).abs() > 0.01 {
+            at_rest = false;
+        }
+
+        prop.current = create_value_with_number(&prop.end, value);
+    }
+
+    !at_rest
+}
+
+/// Set each property from the keyframe pair around `progress`, easing within the pair
+pub fn sample_keyframes(
+    properties: &mut [AnimationProperty],
+    keyframes: &[Keyframe],
+    bezier: Option<&CubicBezier>,
+    progress: f64,
+) {
+    if keyframes.is_empty() {
+        return;
+    }
+
+    let mut sorted_kf = keyframes.to_vec();
+    sorted_kf.sort_by(|a, b| {
+        a.time
+            .partial_cmp(&b.time)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let (start_kf, end_kf, local_progress) = find_keyframe_range(&sorted_kf, progress);
+
+    let eased = match bezier {
+        Some(bezier) => bezier.solve(local_progress),
+        None => local_progress,
+    };
+
+    for prop in properties.iter_mut() {
+        if let (Some(start_val), Some(end_val)) = (
+            start_kf
+                .properties
+                .iter()
+                .find(|(p, _)| p == &prop.property_type)
+                .map(|(_, v)| v),
+            end_kf
+                .properties
+                .iter()
+                .find(|(p, _)| p == &prop.property_type)
+                .map(|(_, v)| v),
+        ) {
+            prop.current = interpolate_value(start_val, end_val, eased);
+        }
+    }
+}
+
+fn find_keyframe_range(sorted_kf: &[Keyframe], progress: f64) -> (&Keyframe, &Keyframe, f64) {
+    let mut start_kf = &sorted_kf[0];
+    let mut end_kf = &sorted_kf[sorted_kf.len() - 1];
+    let mut local_progress = 0.0;
+
+    for i in 0..sorted_kf.len() - 1 {
+        if progress >= sorted_kf[i].time && progress <= sorted_kf[i + 1].time {
+            start_kf = &sorted_kf[i];
+            end_kf = &sorted_kf[i + 1];
+            local_progress = (progress - start_kf.time) / (end_kf.time - start_kf.time);
+            break;
+        }
+    }
+
+    (start_kf, end_kf, local_progress)
+}
+
+/// Numeric part of a property's current value (0 when absent)
+pub fn number_value(properties: &[AnimationProperty], prop_type: PropertyType) -> f64 {
+    properties
+        .iter()
+        .find(|p| p.property_type == prop_type)
+        .and_then(|p| match p.current {
+            AnimatableValue::Number(n) => Some(n),
+            AnimatableValue::Length(n, _) => Some(n),
+            _ => None,
+        })
+        .unwrap_or(0.0)
+}
+
+/// Write the current values as CSS/SVG to `sink`. Returns the number of writes;
+/// flushing is left to the caller.
+pub fn render(
+    properties: &[AnimationProperty],
+    transform_origin: &(String, String, String),
+    sink: &mut dyn Renderer,
+) -> Result<usize, JsValue> {
+    render_over(properties, transform_origin, "", sink)
+}
+
+/// As render, with the animated transform put after `base_transform` (say a
+/// stylesheet's centering translate) rather than replacing it
+pub fn render_over(
+    properties: &[AnimationProperty],
+    transform_origin: &(String, String, String),
+    base_transform: &str,
+    sink: &mut dyn Renderer,
+) -> Result<usize, JsValue> {
+    let mut writer = StyleWriter {
+        properties,
+        transform_origin,
+        base_transform,
+        sink,
+        writes: 0,
+    };
+    writer.write_all()?;
+    Ok(writer.writes)
+}
+
+/// CSS properties (and SVG attributes) rendering `properties` writes at
+/// their start and end values and at each keyframe. Some writes are skipped
+/// at identity values, so one frame alone can miss them.
+pub fn written_properties(
+    properties: &[AnimationProperty],
+    keyframes: &[Keyframe],
+    transform_origin: &(String, String, String),
+) -> Vec<String> {
+    struct Names(Vec<String>);
+
+    impl Renderer for Names {
+        fn apply(&mut self, property: &str, _value: &str) -> Result<(), JsValue> {
+            if !self.0.iter().any(|name| name == property) {
+                self.0.push(property.to_string());
+            }
+            Ok(())
+        }
+    }
+
+    let at = |value: fn(&AnimationProperty) -> &AnimatableValue| -> Vec<AnimationProperty> {
+        properties
+            .iter()
+            .map(|prop| AnimationProperty {
+                current: value(prop).clone(),
+                ..prop.clone()
+            })
+            .collect()
+    };
+    let mut samples = vec![at(|p| &p.start), at(|p| &p.end)];
+    for keyframe in keyframes {
+        let mut sample = properties.to_vec();
+        for (property_type, value) in &keyframe.properties {
+            if let Some(prop) = sample
+                .iter_mut()
+                .find(|p| p.property_type == *property_type)
+            {
+                prop.current = value.clone();
+            }
+        }
+        samples.push(sample);
+    }
+
+    let mut names = Names(Vec::new());
+    for sample in &samples {
+        let _ = render(sample, transform_origin, &mut names);
+    }
+    names.0
+}
+
+struct StyleWriter<'a> {
+    properties: &'a [AnimationProperty],
+    transform_origin: &'a (String, String, String),
+    base_transform: &'a str,
+    sink: &'a mut dyn Renderer,
+    writes: usize,
+}
+
+impl StyleWriter<'_> {
+    fn write_all(&mut self) -> Result<(), JsValue> {
+        let mut transform_parts = Vec::with_capacity(16);
+        let mut filter_parts = Vec::with_capacity(8);
+        let mut has_translate = false;
+
+        let properties = self.properties;
+        for prop in properties.iter() {
+            match prop.property_type {
+                // Transform Group
+                PropertyType::X | PropertyType::Y | PropertyType::Z => {
+                    if !has_translate {
+                        self.apply_translate(&mut transform_parts);
+                        has_translate = true;
+                    }
+                }
+                PropertyType::Scale => {
+                    if let AnimatableValue::Number(val) = prop.current {
+                        transform_parts.push(format!("scale({})", val));
+                    }
+                }
+                PropertyType::ScaleX => {
+                    if let AnimatableValue::Number(val) = prop.current {
+                        transform_parts.push(format!("scaleX({})", val));
+                    }
+                }
+                PropertyType::ScaleY => {
+                    if let AnimatableValue::Number(val) = prop.current {
+                        transform_parts.push(format!("scaleY({})", val));
+                    }
+                }
+                PropertyType::Rotate
+                | PropertyType::RotateX
+                | PropertyType::RotateY
+                | PropertyType::RotateZ => {
+                    self.apply_rotation(&mut transform_parts, prop);
+                }
+                PropertyType::SkewX | PropertyType::SkewY => {
+                    self.apply_skew(&mut transform_parts, prop);
+                }
+                PropertyType::Perspective => {
+                    if let AnimatableValue::Number(val) = prop.current {
+                        transform_parts.push(format!("perspective({}px)", val));
+                    }
+                }
+                PropertyType::PerspectiveOriginX | PropertyType::PerspectiveOriginY => {
+                    self.apply_perspective_origin()?;
+                }
+                PropertyType::TransformOriginX
+                | PropertyType::TransformOriginY
+                | PropertyType::TransformOriginZ => {
+                    self.apply_transform_origin()?;
+                }
+                PropertyType::BackfaceVisibility => {
+                    // Backface visibility - hidden or visible
+                    if let AnimatableValue::Number(val) = prop.current {
+                        let visibility = if val > 0.5 { "visible" } else { "hidden" };
+                        self.set_element_property("backfaceVisibility", visibility)?;
+                    }
+                }
+
+                // Layout
+                PropertyType::Width
+                | PropertyType::Height
+                | PropertyType::MinWidth
+                | PropertyType::MinHeight
+                | PropertyType::MaxWidth
+                | PropertyType::MaxHeight => {
+                    self.apply_layout(prop)?;
+                }
+
+                // Visual
+                PropertyType::Opacity => {
+                    if let AnimatableValue::Number(val) = prop.current {
+                        self.set_element_property("opacity", &val.to_string())?;
+                    }
+                }
+                PropertyType::BackgroundColor | PropertyType::Color | PropertyType::BorderColor => {
+                    self.set_color_property(prop)?;
+                }
+                PropertyType::BorderRadius | PropertyType::BorderWidth => {
+                    self.apply_border(prop)?;
+                }
+
+                PropertyType::Visibility => {
+                    // ✨ NEW
+                    if let AnimatableValue::Visibility(val) = &prop.current {
+                        self.set_element_property("visibility", val.as_str())?;
+                    }
+                }
+
+                // Shadows
+                PropertyType::ShadowOffsetX
+                | PropertyType::ShadowOffsetY
+                | PropertyType::ShadowBlur
+                | PropertyType::ShadowSpread
+                | PropertyType::ShadowColor => {
+                    let shadow_string = self.build_shadow_string();
+                    if !shadow_string.is_empty() {
+                        self.set_element_property("boxShadow", &shadow_string)?;
+                    }
+                }
+
+                // Filters
+                PropertyType::Blur
+                | PropertyType::Brightness
+                | PropertyType::Contrast
+                | PropertyType::Saturate
+                | PropertyType::Hue
+                | PropertyType::Grayscale
+                | PropertyType::Invert
+                | PropertyType::Sepia => {
+                    self.apply_filter(&mut filter_parts, prop);
+                }
+                PropertyType::Dropoff => {
+                    // Dropoff filter (drop shadow filter)
+                    if let AnimatableValue::Number(val) = prop.current {
+                        filter_parts
+                            .push(format!("drop-shadow(0px 0px {}px rgba(0, 0, 0, 0.5))", val));
+                    }
+                }
+                PropertyType::BackgroundBlur => {
+                    // Backdrop filter for background blur effect
+                    if let AnimatableValue::Number(val) = prop.current {
+                        self.set_element_property("backdropFilter", &format!("blur({}px)", val))?;
+                    }
+                }
+
+                // SVG
+                PropertyType::StrokeDashOffset
+                | PropertyType::StrokeWidth
+                | PropertyType::FillOpacity
+                | PropertyType::StrokeOpacity => {
+                    self.apply_svg(prop)?;
+                }
+
+                PropertyType::StrokeDashArray => {
+                    // Handled separately if needed
+                }
+
+                PropertyType::Inset => {
+                    // Inset shadow property
+                    if let AnimatableValue::Number(val) = prop.current {
+                        let inset_value = if val > 0.5 { "inset" } else { "outset" };
+                        // Note: CSS doesn't have outset, so we handle inset boolean value
+                        if val > 0.5 {
+                            self.set_element_property(
+                                "boxShadow",
+                                &format!("{} 0px 0px 0px rgba(0,0,0,0.5)", inset_value),
+                            )?;
+                        }
+                    }
+                }
+            }
+        }
+
+        if !transform_parts.is_empty() {
+            if !self.base_transform.is_empty() {
+                transform_parts.insert(0, self.base_transform.to_string());
+            }
+            self.set_element_property("transform", &transform_parts.join(" "))?;
+        }
+
+        if !filter_parts.is_empty() {
+            self.set_element_property("filter", &filter_parts.join(" "))?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn apply_perspective_origin(&mut self) -> Result<(), JsValue> {
+        let origin_x = self
+            .properties
+            .iter()
+            .find(|p| p.property_type == PropertyType::PerspectiveOriginX)
+            .and_then(|p| match &p.current {
+                AnimatableValue::Length(val, unit) => Some(format!("{}{}", val, unit.as_str())),
+                _ => None,
+            })
+            .unwrap_or_else(|| "50%".to_string());
+
+        let origin_y = self
+            .properties
+            .iter()
+            .find(|p| p.property_type == PropertyType::PerspectiveOriginY)
+            .and_then(|p| match &p.current {
+                AnimatableValue::Length(val, unit) => Some(format!("{}{}", val, unit.as_str())),
+                _ => None,
+            })
+            .unwrap_or_else(|| "50%".to_string());
+
+        let origin_string = format!("{} {}", origin_x, origin_y);
+        self.set_element_property("perspectiveOrigin", &origin_string)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn apply_translate(&self, transform_parts: &mut Vec<String>) {
+        let x = self.get_number_value(PropertyType::X).round();
+        let y = self.get_number_value(PropertyType::Y).round();
+        let z = self.get_number_value(PropertyType::Z).round();
+
+        if x != 0.0 || y != 0.0 || z != 0.0 {
+            transform_parts.push(format!(
+                "translate3d({}px, {}px, {}px)",
+                x as i32, y as i32, z as i32
+            ));
+        }
+    }
+
+    #[inline]
+    fn apply_rotation(&self, transform_parts: &mut Vec<String>, prop: &AnimationProperty) {
+        if let AnimatableValue::Number(val) = prop.current {
+            match prop.property_type {
+                PropertyType::Rotate => transform_parts.push(format!("rotate({}deg)", val)),
+                PropertyType::RotateX => transform_parts.push(format!("rotateX({}deg)", val)),
+                PropertyType::RotateY => transform_parts.push(format!("rotateY({}deg)", val)),
+                PropertyType::RotateZ => transform_parts.push(format!("rotateZ({}deg)", val)),
+                _ => {}
+            }
+        }
+    }
+
+    #[inline]
+    fn apply_skew(&self, transform_parts: &mut Vec<String>, prop: &AnimationProperty) {
+        if let AnimatableValue::Number(val) = prop.current {
+            match prop.property_type {
+                PropertyType::SkewX => transform_parts.push(format!("skewX({}deg)", val)),
+                PropertyType::SkewY => transform_parts.push(format!("skewY({}deg)", val)),
+                _ => {}
+            }
+        }
+    }
+
+    #[inline]
+    fn apply_layout(&mut self, prop: &AnimationProperty) -> Result<(), JsValue> {
+        let property_name = match prop.property_type {
+            PropertyType::Width => "width",
+            PropertyType::Height => "height",
+            PropertyType::MinWidth => "minWidth",
+            PropertyType::MinHeight => "minHeight",
+            PropertyType::MaxWidth => "maxWidth",
+            PropertyType::MaxHeight => "maxHeight",
+            _ => return Ok(()),
+        };
+        self.set_element_dimension(property_name, &prop.current)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn apply_border(&mut self, prop: &AnimationProperty) -> Result<(), JsValue> {
+        if let AnimatableValue::Length(val, unit) = &prop.current {
+            let property_name = match prop.property_type {
+                PropertyType::BorderRadius => "border-radius",
+                PropertyType::BorderWidth => "border-width",
+                _ => return Ok(()),
+            };
+            self.set_element_property(property_name, &format!("{}{}", val, unit.as_str()))?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn apply_filter(&self, filter_parts: &mut Vec<String>, prop: &AnimationProperty) {
+        if let AnimatableValue::Number(val) = prop.current {
+            match prop.property_type {
+                PropertyType::Blur => filter_parts.push(format!("blur({}px)", val)),
+                PropertyType::Brightness => filter_parts.push(format!("brightness({})", val)),
+                PropertyType::Contrast => filter_parts.push(format!("contrast({})", val)),
+                PropertyType::Saturate => filter_parts.push(format!("saturate({})", val)),
+                PropertyType::Hue => filter_parts.push(format!("hue-rotate({}deg)", val)),
+                PropertyType::Grayscale => {
+                    filter_parts.push(format!("grayscale({}%)", (val * 100.0).round() as i32))
+                }
+                PropertyType::Invert => {
+                    filter_parts.push(format!("invert({}%)", (val * 100.0).round() as i32))
+                }
+                PropertyType::Sepia => {
+                    filter_parts.push(format!("sepia({}%)", (val * 100.0).round() as i32))
+                }
+                PropertyType::Dropoff
+                | PropertyType::BackgroundBlur
+                | PropertyType::Inset
+                | PropertyType::BackfaceVisibility => {
+                    // Handled in apply_properties
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[inline]
+    fn apply_svg(&mut self, prop: &AnimationProperty) -> Result<(), JsValue> {
+        if let AnimatableValue::Number(val) = prop.current {
+            let attribute = match prop.property_type {
+                PropertyType::StrokeDashOffset => "stroke-dashoffset",
+                PropertyType::StrokeWidth => "stroke-width",
+                PropertyType::FillOpacity => "fill-opacity",
+                PropertyType::StrokeOpacity => "stroke-opacity",
+                _ => return Ok(()),
+            };
+            self.set_svg_attribute(attribute, &val.to_string())?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn get_number_value(&self, prop_type: PropertyType) -> f64 {
+        number_value(self.properties, prop_type)
+    }
+
+    #[inline]
+    fn set_element_property(&mut self, property: &str, value: &str) -> Result<(), JsValue> {
+        self.writes += 1;
+        self.sink.apply(&css_name(property), value)
+    }
+
+    #[inline]
+    fn set_element_dimension(
+        &mut self,
+        property: &str,
+        value: &AnimatableValue,
+    ) -> Result<(), JsValue> {
+        match value {
+            AnimatableValue::Length(val, unit) => {
+                self.set_element_property(property, &format!("{}{}", val, unit.as_str()))?;
+            }
+            AnimatableValue::Number(val) => {
+                self.set_element_property(property, &format!("{}px", val))?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn set_color_property(&mut self, prop: &AnimationProperty) -> Result<(), JsValue> {
+        let property_name = match prop.property_type {
+            PropertyType::BackgroundColor => "background-color",
+            PropertyType::Color => "color",
+            PropertyType::BorderColor => "border-color",
+            _ => return Ok(()),
+        };
+
+        if let AnimatableValue::Color(r, g, b, a) = prop.current {
+            let css_value = format!(
+                "rgba({}, {}, {}, {})",
+                r.round() as u8,
+                g.round() as u8,
+                b.round() as u8,
+                a
+            );
+            self.set_element_property(property_name, &css_value)?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn set_svg_attribute(&mut self, attribute: &str, value: &str) -> Result<(), JsValue> {
+        self.writes += 1;
+        self.sink.apply(attribute, value)
+    }
+
+    #[inline]
+    fn apply_transform_origin(&mut self) -> Result<(), JsValue> {
+        let origin_string = format!(
+            "{} {} {}",
+            self.transform_origin.0, self.transform_origin.1, self.transform_origin.2
+        );
+        self.set_element_property("transformOrigin", &origin_string)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn build_shadow_string(&self) -> String {
+        let offset_x = self.get_number_value(PropertyType::ShadowOffsetX);
+        let offset_y = self.get_number_value(PropertyType::ShadowOffsetY);
+        let blur = self.get_number_value(PropertyType::ShadowBlur);
+        let spread = self.get_number_value(PropertyType::ShadowSpread);
+
+        if offset_x == 0.0 && offset_y == 0.0 && blur == 0.0 && spread == 0.0 {
+            return String::new();
+        }
+
+        let color = self
+            .properties
+            .iter()
+            .find(|p| p.property_type == PropertyType::ShadowColor)
+            .and_then(|p| match &p.current {
+                AnimatableValue::Color(r, g, b, a) => Some(format!(
+                    "rgba({}, {}, {}, {})",
+                    r.round() as u8,
+                    g.round() as u8,
+                    b.round() as u8,
+                    a
+                )),
+                _ => None,
+            })
+            .unwrap_or_else(|| "rgba(0, 0, 0, 0.5)".to_string());
+
+        format!(
+            "{}px {}px {}px {}px {}",
+            offset_x.round() as i32,
+            offset_y.round() as i32,
+            blur.round() as i32,
+            spread.round() as i32,
+            color
+        )
+    }
+}
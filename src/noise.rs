@@ -0,0 +1,105 @@
+// ============================================================================
+// NOISE - 3D simplex noise for organic motion
+// ============================================================================
+
+// Skewing factors between the cubic and simplex grids
+const F3: f64 = 1.0 / 3.0;
+const G3: f64 = 1.0 / 6.0;
+
+// Gradients towards the edges of a cube
+const GRADIENTS: [[f64; 3]; 12] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+];
+
+/// Smooth noise in roughly -1..1, varying over distances of about 1. Use the
+/// third coordinate as time to make a 2D field evolve.
+pub(crate) fn simplex3(x: f64, y: f64, z: f64) -> f64 {
+    // Cell of the skewed grid containing the point
+    let s = (x + y + z) * F3;
+    let (i, j, k) = ((x + s).floor(), (y + s).floor(), (z + s).floor());
+    let t = (i + j + k) * G3;
+    let (x0, y0, z0) = (x - (i - t), y - (j - t), z - (k - t));
+
+    // Which of the six tetrahedra of the cell the point is in
+    let (i1, j1, k1, i2, j2, k2) = if x0 >= y0 {
+        if y0 >= z0 {
+            (1, 0, 0, 1, 1, 0)
+        } else if x0 >= z0 {
+            (1, 0, 0, 1, 0, 1)
+        } else {
+            (0, 0, 1, 1, 0, 1)
+        }
+    } else if y0 < z0 {
+        (0, 0, 1, 0, 1, 1)
+    } else if x0 < z0 {
+        (0, 1, 0, 0, 1, 1)
+    } else {
+        (0, 1, 0, 1, 1, 0)
+    };
+
+    let (i, j, k) = (i as i64, j as i64, k as i64);
+    let corners = [
+        (0, 0, 0, x0, y0, z0),
+        (
+            i1,
+            j1,
+            k1,
+            x0 - i1 as f64 + G3,
+            y0 - j1 as f64 + G3,
+            z0 - k1 as f64 + G3,
+        ),
+        (
+            i2,
+            j2,
+            k2,
+            x0 - i2 as f64 + 2.0 * G3,
+            y0 - j2 as f64 + 2.0 * G3,
+            z0 - k2 as f64 + 2.0 * G3,
+        ),
+        (
+            1,
+            1,
+            1,
+            x0 - 1.0 + 3.0 * G3,
+            y0 - 1.0 + 3.0 * G3,
+            z0 - 1.0 + 3.0 * G3,
+        ),
+    ];
+
+    let sum: f64 = corners
+        .iter()
+        .map(|&(di, dj, dk, x, y, z)| {
+            let t = 0.6 - x * x - y * y - z * z;
+            if t < 0.0 {
+                return 0.0;
+            }
+            let [gx, gy, gz] = GRADIENTS[hash(i + di, j + dj, k + dk)];
+            let t2 = t * t;
+            t2 * t2 * (gx * x + gy * y + gz * z)
+        })
+        .sum();
+
+    32.0 * sum
+}
+
+/// Gradient index for a lattice point
+fn hash(i: i64, j: i64, k: i64) -> usize {
+    let mut h = (i as u32).wrapping_mul(0x8da6_b343)
+        ^ (j as u32).wrapping_mul(0xd816_3841)
+        ^ (k as u32).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^= h >> 12;
+    (h % GRADIENTS.len() as u32) as usize
+}
@@ -0,0 +1,183 @@
+use crate::renderer::Renderer;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, Element, HtmlElement};
+
+// ============================================================================
+// OPACITY GROUP - Fade a subtree as one composite
+// ============================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum OpacityGroupMode {
+    /// Isolate the root into its own compositing group and fade only the root
+    Isolate = 0,
+    /// Write base * group opacity to each member (for roots that can't be isolated)
+    Distribute = 1,
+}
+
+struct GroupState {
+    root: Element,
+    mode: OpacityGroupMode,
+    opacity: f64,
+    members: Vec<(Element, f64)>,
+}
+
+impl GroupState {
+    fn apply(&mut self, opacity: f64) -> Result<(), JsValue> {
+        self.opacity = opacity.clamp(0.0, 1.0);
+
+        match self.mode {
+            OpacityGroupMode::Isolate => set_opacity(&self.root, self.opacity),
+            OpacityGroupMode::Distribute => {
+                for (member, base) in &self.members {
+                    set_opacity(member, base * self.opacity)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct OpacityGroup {
+    state: Rc<RefCell<GroupState>>,
+}
+
+#[wasm_bindgen]
+impl OpacityGroup {
+    #[wasm_bindgen(constructor)]
+    pub fn new(root: Element, mode: OpacityGroupMode) -> OpacityGroup {
+        let mut state = GroupState {
+            root: root.clone(),
+            mode,
+            opacity: 1.0,
+            members: Vec::new(),
+        };
+
+        match mode {
+            OpacityGroupMode::Isolate => {
+                if let Some(html) = root.dyn_ref::<HtmlElement>() {
+                    let style = html.style();
+                    let _ = style.set_property("isolation", "isolate");
+                    let _ = style.set_property("will-change", "opacity");
+                }
+            }
+            OpacityGroupMode::Distribute => {
+                let children = root.children();
+                for i in 0..children.length() {
+                    if let Some(child) = children.item(i) {
+                        let base = computed_opacity(&child);
+                        state.members.push((child, base));
+                    }
+                }
+            }
+        }
+
+        OpacityGroup {
+            state: Rc::new(RefCell::new(state)),
+        }
+    }
+
+    /// Include an extra descendant in a Distribute group
+    #[wasm_bindgen(js_name = addMember)]
+    pub fn add_member(&mut self, element: Element) {
+        let mut state = self.state.borrow_mut();
+        if state.members.iter().any(|(e, _)| e == &element) {
+            return;
+        }
+        let base = computed_opacity(&element);
+        let group_opacity = state.opacity;
+        let _ = set_opacity(&element, base * group_opacity);
+        state.members.push((element, base));
+    }
+
+    #[wasm_bindgen(js_name = setOpacity)]
+    pub fn set_opacity(&mut self, opacity: f64) -> Result<(), JsValue> {
+        self.state.borrow_mut().apply(opacity)
+    }
+
+    /// Fade the whole group from its current opacity, driven by the engine
+    #[wasm_bindgen(js_name = fadeTo)]
+    pub fn fade_to(&self, opacity: f64, duration: f64) -> Result<crate::AnimationHandle, JsValue> {
+        let root = self.state.borrow().root.clone();
+        let current = self.state.borrow().opacity;
+
+        let mut animation = crate::Animation::new(root)?.smooth(duration);
+        animation.properties.push(crate::types::AnimationProperty {
+            property_type: crate::types::PropertyType::Opacity,
+            start: crate::types::AnimatableValue::Number(current),
+            end: crate::types::AnimatableValue::Number(opacity.clamp(0.0, 1.0)),
+            current: crate::types::AnimatableValue::Number(current),
+        });
+        animation.renderer = RefCell::new(Box::new(GroupRenderer {
+            state: Rc::clone(&self.state),
+        }));
+
+        animation.start()
+    }
+
+    /// Undo isolation hints and restore members' original opacity
+    #[wasm_bindgen]
+    pub fn release(&mut self) {
+        let state = self.state.borrow();
+        match state.mode {
+            OpacityGroupMode::Isolate => {
+                if let Some(html) = state.root.dyn_ref::<HtmlElement>() {
+                    let style = html.style();
+                    let _ = style.remove_property("isolation");
+                    let _ = style.remove_property("will-change");
+                }
+            }
+            OpacityGroupMode::Distribute => {
+                for (member, base) in &state.members {
+                    let _ = set_opacity(member, *base);
+                }
+            }
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn opacity(&self) -> f64 {
+        self.state.borrow().opacity
+    }
+
+    #[wasm_bindgen(getter, js_name = memberCount)]
+    pub fn member_count(&self) -> usize {
+        self.state.borrow().members.len()
+    }
+}
+
+/// Routes an animation's opacity output into the group instead of one element
+struct GroupRenderer {
+    state: Rc<RefCell<GroupState>>,
+}
+
+impl Renderer for GroupRenderer {
+    fn apply(&mut self, property: &str, value: &str) -> Result<(), JsValue> {
+        if property == "opacity" {
+            if let Ok(opacity) = value.parse::<f64>() {
+                return self.state.borrow_mut().apply(opacity);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn set_opacity(element: &Element, opacity: f64) -> Result<(), JsValue> {
+    if let Some(html) = element.dyn_ref::<HtmlElement>() {
+        html.style()
+            .set_property("opacity", &opacity.to_string())
+            .map_err(|_| JsValue::from_str("Failed to set opacity"))?;
+    }
+    Ok(())
+}
+
+fn computed_opacity(element: &Element) -> f64 {
+    window()
+        .and_then(|w| w.get_computed_style(element).ok().flatten())
+        .and_then(|style| style.get_property_value("opacity").ok())
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .unwrap_or(1.0)
+}
@@ -0,0 +1,329 @@
+use crate::measure::{self, Rect};
+use crate::renderer::{self, Renderer};
+use crate::scroll_trigger::smooth_toward;
+use crate::ticker;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, AddEventListenerOptions, Element, Event, EventTarget, PointerEvent};
+
+// ============================================================================
+// PARALLAX - Layers shifted by scroll or pointer position at their own speeds
+// ============================================================================
+//
+// One listener per Parallax feeds a normalized input: how far the container's
+// center is from the viewport's center in viewport heights (scroll), or where
+// the pointer is across the container from -1 to 1 (pointer). Each layer maps
+// it to translate/scale/opacity by its own factors, once a frame, through the
+// same batched renderer animations use.
+
+/// How strongly a layer responds to the input
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LayerFactors {
+    /// Fraction of the input distance the layer moves against it: 0.5 moves
+    /// half as fast as the page (a background), -0.3 runs ahead of it
+    pub speed: f64,
+    /// Scale added at one full unit of input away from the center
+    pub scale: f64,
+    /// Opacity added at one full unit away from the center, e.g. -0.6 fades
+    /// to 0.4
+    pub opacity: f64,
+}
+
+/// Style for one layer at one input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayerStyle {
+    pub x: f64,
+    pub y: f64,
+    pub scale: f64,
+    pub opacity: f64,
+}
+
+impl LayerFactors {
+    /// `input` is the normalized (x, y) offset and `reach` the px a unit of
+    /// input spans on each axis
+    pub fn sample(&self, input: (f64, f64), reach: (f64, f64)) -> LayerStyle {
+        let distance = input.0.hypot(input.1).min(1.0);
+        LayerStyle {
+            x: -input.0 * self.speed * reach.0,
+            y: -input.1 * self.speed * reach.1,
+            scale: (1.0 + distance * self.scale).max(0.0),
+            opacity: (1.0 + distance * self.opacity).clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    Scroll,
+    Pointer,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct ParallaxConfig {
+    source: Option<String>,
+    smoothing: Option<f64>,
+}
+
+struct Layer {
+    factors: LayerFactors,
+    renderer: Box<dyn Renderer>,
+}
+
+impl Layer {
+    fn write(&mut self, style: LayerStyle) -> Result<(), JsValue> {
+        self.renderer
+            .apply("translate", &format!("{}px {}px", style.x, style.y))?;
+        // Individual properties, so transforms from animations are left alone
+        if self.factors.scale != 0.0 {
+            self.renderer.apply("scale", &style.scale.to_string())?;
+        }
+        if self.factors.opacity != 0.0 {
+            self.renderer.apply("opacity", &style.opacity.to_string())?;
+        }
+        self.renderer.flush()
+    }
+}
+
+struct ParallaxState {
+    container: Option<Element>,
+    source: Source,
+    // Seconds the layers take to catch up with the input
+    lag: f64,
+    layers: Vec<Layer>,
+    target: (f64, f64),
+    current: (f64, f64),
+    reach: (f64, f64),
+    ticker_id: Option<u32>,
+}
+
+impl ParallaxState {
+    fn bounds(&self) -> Rect {
+        match &self.container {
+            Some(container) => measure::element_rect(container),
+            None => measure::viewport_rect().unwrap_or_default(),
+        }
+    }
+
+    /// Read the scroll input from layout; pointer input arrives with events
+    fn measure_scroll(&mut self) {
+        let viewport = measure::viewport_rect().unwrap_or_default();
+        let height = viewport.height.max(1.0);
+        let offset = match &self.container {
+            Some(container) => measure::element_rect(container).center_y() - viewport.center_y(),
+            // The page itself: its top, from where the viewport starts
+            None => -window().and_then(|w| w.scroll_y().ok()).unwrap_or(0.0),
+        };
+        self.target = (0.0, offset / height);
+        self.reach = (0.0, height);
+    }
+
+    fn point_at(&mut self, x: f64, y: f64) {
+        let bounds = self.bounds();
+        let half = (bounds.width.max(1.0) * 0.5, bounds.height.max(1.0) * 0.5);
+        self.target = (
+            ((x - bounds.center_x()) / half.0).clamp(-1.0, 1.0),
+            ((y - bounds.center_y()) / half.1).clamp(-1.0, 1.0),
+        );
+        self.reach = half;
+    }
+
+    /// Move toward the input and write every layer; returns whether still
+    /// catching up
+    fn render(&mut self, dt: f64) -> bool {
+        if self.source == Source::Scroll {
+            self.measure_scroll();
+        }
+        self.current = (
+            smooth_toward(self.current.0, self.target.0, dt, self.lag),
+            smooth_toward(self.current.1, self.target.1, dt, self.lag),
+        );
+
+        let (input, reach) = (self.current, self.reach);
+        for layer in self.layers.iter_mut() {
+            let style = layer.factors.sample(input, reach);
+            let _ = layer.write(style);
+        }
+        self.current != self.target
+    }
+}
+
+/// Write the layers on the next frame, however many events arrive first
+fn schedule(state: &Rc<RefCell<ParallaxState>>) {
+    if state.borrow().ticker_id.is_some() {
+        return;
+    }
+    let tick_state = Rc::clone(state);
+    let id = ticker::subscribe(Box::new(move |_now, dt| {
+        let mut s = tick_state.borrow_mut();
+        let moving = s.render(dt);
+        if !moving {
+            s.ticker_id = None;
+        }
+        moving
+    }))
+    .ok();
+    state.borrow_mut().ticker_id = id;
+}
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+/// Config: `{ source: "scroll" | "pointer", smoothing: 0 }`. `smoothing` is
+/// the seconds layers take to catch up, which softens wheel steps and
+/// pointer jitter.
+#[wasm_bindgen]
+pub struct Parallax {
+    state: Rc<RefCell<ParallaxState>>,
+    listeners: Vec<(EventTarget, &'static str, EventCallback)>,
+}
+
+#[wasm_bindgen]
+impl Parallax {
+    /// Layers respond to `container` passing through the viewport (scroll)
+    /// or to the pointer over it; without one, to the page or the window
+    #[wasm_bindgen(constructor)]
+    pub fn new(container: Option<Element>, config: JsValue) -> Result<Parallax, JsValue> {
+        let config: ParallaxConfig = if config.is_undefined() || config.is_null() {
+            ParallaxConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)
+                .map_err(|e| JsValue::from_str(&format!("Invalid parallax config: {:?}", e)))?
+        };
+        let source = match config.source.as_deref() {
+            None | Some("scroll") => Source::Scroll,
+            Some("pointer") => Source::Pointer,
+            Some(other) => {
+                return Err(JsValue::from_str(&format!(
+                    "Invalid parallax source: {} (use \"scroll\" or \"pointer\")",
+                    other
+                )))
+            }
+        };
+
+        let mut parallax = Parallax {
+            state: Rc::new(RefCell::new(ParallaxState {
+                container,
+                source,
+                lag: config.smoothing.unwrap_or(0.0).max(0.0),
+                layers: Vec::new(),
+                target: (0.0, 0.0),
+                current: (0.0, 0.0),
+                reach: (0.0, 0.0),
+                ticker_id: None,
+            })),
+            listeners: Vec::with_capacity(3),
+        };
+        parallax.bind()?;
+        Ok(parallax)
+    }
+
+    /// Add a layer. Config: `{ speed: 0.5, scale: 0, opacity: 0 }`
+    #[wasm_bindgen(js_name = addLayer)]
+    pub fn add_layer(&mut self, element: Element, config: JsValue) -> Result<(), JsValue> {
+        let factors: LayerFactors = if config.is_undefined() || config.is_null() {
+            LayerFactors::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)
+                .map_err(|e| JsValue::from_str(&format!("Invalid parallax layer: {:?}", e)))?
+        };
+
+        let renderer = renderer::for_element(&element);
+        self.state
+            .borrow_mut()
+            .layers
+            .push(Layer { factors, renderer });
+        schedule(&self.state);
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter, js_name = layerCount)]
+    pub fn layer_count(&self) -> usize {
+        self.state.borrow().layers.len()
+    }
+
+    /// Re-render on the next frame, e.g. after layout changes
+    #[wasm_bindgen]
+    pub fn refresh(&self) {
+        schedule(&self.state);
+    }
+
+    /// Remove the listeners; layers keep their last position
+    #[wasm_bindgen]
+    pub fn unbind(&mut self) {
+        for (target, name, callback) in self.listeners.drain(..) {
+            let _ =
+                target.remove_event_listener_with_callback(name, callback.as_ref().unchecked_ref());
+        }
+        if let Some(id) = self.state.borrow_mut().ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+impl Parallax {
+    fn bind(&mut self) -> Result<(), JsValue> {
+        let window = window().ok_or_else(|| JsValue::from_str("No window available"))?;
+        let (source, container) = {
+            let s = self.state.borrow();
+            (s.source, s.container.clone())
+        };
+
+        match source {
+            Source::Scroll => {
+                for name in ["scroll", "resize"] {
+                    let state = Rc::clone(&self.state);
+                    self.listen(window.clone().into(), name, move |_| schedule(&state))?;
+                }
+            }
+            Source::Pointer => {
+                let target: EventTarget = match container {
+                    Some(ref container) => container.clone().into(),
+                    None => window.into(),
+                };
+                let state = Rc::clone(&self.state);
+                self.listen(target.clone(), "pointermove", move |event| {
+                    if let Some(pointer) = event.dyn_ref::<PointerEvent>() {
+                        let (x, y) = (pointer.client_x() as f64, pointer.client_y() as f64);
+                        state.borrow_mut().point_at(x, y);
+                        schedule(&state);
+                    }
+                })?;
+                // Drift back to the center when the pointer leaves
+                let state = Rc::clone(&self.state);
+                self.listen(target, "pointerleave", move |_| {
+                    state.borrow_mut().target = (0.0, 0.0);
+                    schedule(&state);
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn listen(
+        &mut self,
+        target: EventTarget,
+        name: &'static str,
+        handler: impl FnMut(Event) + 'static,
+    ) -> Result<(), JsValue> {
+        let callback: EventCallback = Closure::wrap(Box::new(handler) as Box<dyn FnMut(Event)>);
+        let options = AddEventListenerOptions::new();
+        options.set_passive(true);
+        target.add_event_listener_with_callback_and_add_event_listener_options(
+            name,
+            callback.as_ref().unchecked_ref(),
+            &options,
+        )?;
+        self.listeners.push((target, name, callback));
+        Ok(())
+    }
+}
+
+impl Drop for Parallax {
+    fn drop(&mut self) {
+        self.unbind();
+    }
+}
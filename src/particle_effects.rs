@@ -1,75 +1,1671 @@
+use crate::batch;
+use crate::cubic::CubicBezier;
+use crate::frame_clock::{self, FixedStep};
+use crate::measure::{self, Rect};
+use crate::noise::simplex3;
+use crate::particle_gl::{GlTarget, Instance};
+use crate::performance_monitor;
+use crate::svg_path::SvgPath;
+use crate::ticker;
+use crate::types::parse_css_color;
+use js_sys::Function;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::{Rc, Weak};
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    CanvasRenderingContext2d, Document, Element, HtmlCanvasElement, HtmlElement, HtmlImageElement,
+    OffscreenCanvas,
+};
+
+// Upper bound for setMaxParticles; DOM rendering struggles long before this
+const MAX_PARTICLES: usize = 50_000;
+
+#[wasm_bindgen]
+pub struct ParticleEmitter {
+    state: Rc<RefCell<EmitterState>>,
+}
+
+/// Shared with the ticker subscription started by run()
+struct EmitterState {
+    particles: Vec<Particle>,
+    // Launch direction in degrees clockwise from up, and the width of the
+    // cone around it
+    direction: f64,
+    spread: f64,
+    // Launch speed range in px/s
+    speed: (f64, f64),
+    gravity: f64,
+    lifetime: f64,
+    lifetime_variance: f64,
+    active: bool,
+    max_particles: usize,
+
+    // Continuous emission: particles per second from `source` at `origin`
+    emission_rate: f64,
+    emission_accumulator: f64,
+    origin: (f64, f64),
+    source: Option<ParticleSource>,
+    ticker_id: Option<u32>,
+    // Seconds of update() so far, the clock scheduled bursts run on
+    clock: f64,
+    schedule: Vec<ScheduledBurst>,
+    // Hidden elements of dead particles, reused before creating new ones
+    pool: Vec<Element>,
+    // Remove pooled elements once the frame loop ends, for one-off effects
+    release_when_idle: bool,
+    // World space: elements live in this overlay at the document origin and
+    // positions are page coordinates
+    world: Option<Element>,
+
+    // Lifecycle callbacks, and events waiting to be delivered once the state
+    // is no longer borrowed
+    on_spawn: Option<Function>,
+    on_death: Option<Function>,
+    on_all_dead: Option<Function>,
+    pending: Vec<(Function, JsValue)>,
+    // Particles spawned so far, numbering them for events
+    spawned: u32,
+
+    // Where around the emission point particles appear
+    zone: EmissionZone,
+    distribution: EmitDistribution,
+    // Particles placed so far, for the Even distribution
+    emitted: u64,
+
+    // Accelerations on top of gravity
+    forces: ForceField,
+    // Walls particles bounce off instead of leaving
+    bounds: Option<Bounds>,
+    // Emitter bursting where each particle dies
+    sub_emitter: Option<SubEmitter>,
+    // Image of DOM particles (canvas keeps its own), and its frame grid
+    dom_sprite: Option<String>,
+    sheet: Option<SpriteSheet>,
+    // Past positions kept per particle for canvas trails; 0 disables them
+    trail_length: usize,
+    trail_width: f64,
+
+    // Appearance over each particle's life; None keeps the linear fade and
+    // the renderer's colors
+    opacity_curve: Option<LifetimeCurve>,
+    color_gradient: Option<ColorGradient>,
+    size_curve: Option<LifetimeCurve>,
+    // Spread of each particle's size around 1, as a fraction
+    size_variance: f64,
+    // Without an opacity curve, whether opacity follows the shrinking scale
+    fade: bool,
+
+    renderer: ParticleRenderer,
+    // Leftover time under a fixed timestep
+    steps: FixedStep,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ParticleShape {
+    Rect = 0,
+    Circle = 1,
+    /// The image set with setSprite, scaled to the particle size
+    Sprite = 2,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EmitDistribution {
+    /// Independent random positions in the zone
+    Random = 0,
+    /// Successive particles spread evenly over the zone, without clumps
+    Even = 1,
+}
+
+/// Area particles are spawned in, as offsets from the emission point
+enum EmissionZone {
+    Point,
+    Line((f64, f64), (f64, f64)),
+    Circle { radius: f64, edge: bool },
+    Rect { width: f64, height: f64 },
+    Path(SvgPath),
+}
+
+impl EmissionZone {
+    /// Offset for the unit sample (u, v)
+    fn sample(&self, u: f64, v: f64) -> (f64, f64) {
+        match *self {
+            EmissionZone::Point => (0.0, 0.0),
+            EmissionZone::Line((x1, y1), (x2, y2)) => (x1 + (x2 - x1) * u, y1 + (y2 - y1) * u),
+            EmissionZone::Circle { radius, edge } => {
+                let angle = u * std::f64::consts::TAU;
+                // sqrt keeps the area density uniform
+                let r = if edge { radius } else { radius * v.sqrt() };
+                (angle.cos() * r, angle.sin() * r)
+            }
+            EmissionZone::Rect { width, height } => ((u - 0.5) * width, (v - 0.5) * height),
+            EmissionZone::Path(ref path) => {
+                let point = path.get_point_at(u);
+                (point.x, point.y)
+            }
+        }
+    }
+}
+
+// How fast the turbulence field changes, in noise units per second
+const TURBULENCE_EVOLUTION: f64 = 0.3;
+
+/// Pulls towards (positive strength) or pushes away from a point
+struct PointForce {
+    id: u32,
+    x: f64,
+    y: f64,
+    /// px/s² at the centre, falling off linearly to 0 at `radius`
+    strength: f64,
+    radius: f64,
+}
+
+#[derive(Default)]
+struct ForceField {
+    points: Vec<PointForce>,
+    next_id: u32,
+    /// px/s²
+    wind: (f64, f64),
+    /// Peak turbulence acceleration in px/s²
+    turbulence: f64,
+    /// Size of turbulence swirls in px
+    turbulence_scale: f64,
+    // Seconds simulated, so the turbulence evolves
+    time: f64,
+}
+
+impl ForceField {
+    /// Acceleration on a particle at (x, y), in px/s²
+    fn acceleration(&self, x: f64, y: f64) -> (f64, f64) {
+        let (mut ax, mut ay) = self.wind;
+
+        for point in &self.points {
+            let (dx, dy) = (point.x - x, point.y - y);
+            let distance = dx.hypot(dy);
+            if distance < 0.001 || distance >= point.radius {
+                continue;
+            }
+            let magnitude = point.strength * (1.0 - distance / point.radius);
+            ax += dx / distance * magnitude;
+            ay += dy / distance * magnitude;
+        }
+
+        if self.turbulence > 0.0 && self.turbulence_scale > 0.0 {
+            let (nx, ny) = (x / self.turbulence_scale, y / self.turbulence_scale);
+            let z = self.time * TURBULENCE_EVOLUTION;
+            // Offset the second sample so the axes are uncorrelated
+            ax += simplex3(nx, ny, z) * self.turbulence;
+            ay += simplex3(nx + 31.7, ny - 47.3, z) * self.turbulence;
+        }
+
+        (ax, ay)
+    }
+}
+
+/// Burst from the origin once the emitter clock reaches `at`
+struct ScheduledBurst {
+    /// Seconds on the emitter clock
+    at: f64,
+    count: usize,
+    /// Seconds until the next repeat; None for a single burst
+    interval: Option<f64>,
+    /// Bursts left including this one; None repeats forever
+    remaining: Option<u32>,
+}
+
+/// Weak so an emitter can be its own sub-emitter without leaking
+struct SubEmitter {
+    state: Weak<RefCell<EmitterState>>,
+    /// Particles emitted per death
+    count: usize,
+    /// Generations of sub-particles allowed; particles at this depth die quietly
+    max_depth: u8,
+}
+
+// Bounce speeds below this (px/s) come to rest instead of jittering
+const REST_SPEED: f64 = 20.0;
+
+pub struct Bounds {
+    rect: Rect,
+    /// Share of speed kept when bouncing off a wall, 0-1
+    restitution: f64,
+    /// Share of speed along a wall lost on each contact, 0-1
+    friction: f64,
+}
+
+impl Bounds {
+    /// A box `width` by `height` from (x, y); restitution and friction are
+    /// clamped to 0-1
+    pub fn new(x: f64, y: f64, width: f64, height: f64, restitution: f64, friction: f64) -> Bounds {
+        Bounds {
+            rect: Rect::new(x, y, width.max(0.0), height.max(0.0)),
+            restitution: restitution.clamp(0.0, 1.0),
+            friction: friction.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Bring a point at (x, y) moving at (vx, vy) px/s back inside, bouncing
+    /// off the walls it passed. Returns whether it met the top or bottom,
+    /// where spin is lost to friction as well.
+    pub fn collide(&self, x: &mut f64, y: &mut f64, vx: &mut f64, vy: &mut f64) -> bool {
+        let Rect {
+            x: left,
+            y: top,
+            width,
+            height,
+        } = self.rect;
+        let (right, bottom) = (left + width, top + height);
+
+        if *x < left || *x > right {
+            *x = x.clamp(left, right);
+            *vx = self.bounce(*vx);
+            *vy *= 1.0 - self.friction;
+        }
+        if *y < top || *y > bottom {
+            *y = y.clamp(top, bottom);
+            *vy = self.bounce(*vy);
+            *vx *= 1.0 - self.friction;
+            return true;
+        }
+        false
+    }
+
+    fn bounce(&self, velocity: f64) -> f64 {
+        let velocity = -velocity * self.restitution;
+        if velocity.abs() < REST_SPEED {
+            0.0
+        } else {
+            velocity
+        }
+    }
+}
+
+/// Evenly spaced values crossed over a particle's life, e.g. [0, 1, 0] to
+/// grow then shrink
+struct LifetimeCurve {
+    stops: Vec<f64>,
+    easing: CubicBezier,
+}
+
+impl LifetimeCurve {
+    /// Value at `age`, 0 at birth and 1 at death
+    fn at(&self, age: f64) -> f64 {
+        let (i, j, t) = stop_span(self.stops.len(), self.easing.solve(age));
+        self.stops[i] + (self.stops[j] - self.stops[i]) * t
+    }
+}
+
+/// Evenly spaced color stops (r, g, b 0-255, alpha 0-1) crossed over a
+/// particle's life
+struct ColorGradient {
+    stops: Vec<(f64, f64, f64, f64)>,
+    easing: CubicBezier,
+}
+
+impl ColorGradient {
+    fn at(&self, age: f64) -> (f64, f64, f64, f64) {
+        let (i, j, t) = stop_span(self.stops.len(), self.easing.solve(age));
+        let (a, b) = (self.stops[i], self.stops[j]);
+        let [r, g, b, a] = batch::lerp4([a.0, a.1, a.2, a.3], [b.0, b.1, b.2, b.3], t);
+        (r, g, b, a)
+    }
+}
+
+/// Stops surrounding `progress` (0-1) across `len` evenly spaced stops, and
+/// the fraction between them
+fn stop_span(len: usize, progress: f64) -> (usize, usize, f64) {
+    let last = len.saturating_sub(1);
+    let position = progress.clamp(0.0, 1.0) * last as f64;
+    let i = (position.floor() as usize).min(last.saturating_sub(1));
+    (i, (i + 1).min(last), position - i as f64)
+}
+
+/// A grid of animation frames in one image, read left to right, top to bottom
+pub struct SpriteSheet {
+    columns: u32,
+    rows: u32,
+    frames: u32,
+    /// Times the animation plays over a particle's life
+    loops: f64,
+}
+
+impl SpriteSheet {
+    /// `frames` of 0 or more than fit means every cell; `loops` of 0 or less
+    /// means once. None without a column or row.
+    pub fn new(columns: u32, rows: u32, frames: u32, loops: f64) -> Option<SpriteSheet> {
+        if columns == 0 || rows == 0 {
+            return None;
+        }
+        let frames = if frames == 0 {
+            columns * rows
+        } else {
+            frames.min(columns * rows)
+        };
+        Some(SpriteSheet {
+            columns,
+            rows,
+            frames,
+            loops: if loops > 0.0 { loops } else { 1.0 },
+        })
+    }
+
+    /// Frame shown at `age`, 0 at birth and 1 at death
+    pub fn frame(&self, age: f64) -> u32 {
+        let frame = (age.clamp(0.0, 1.0) * self.loops * self.frames as f64).floor() as u32;
+        if age >= 1.0 && self.loops.fract() == 0.0 {
+            // Hold the last frame at death rather than wrapping to the first
+            return self.frames - 1;
+        }
+        frame % self.frames
+    }
+
+    /// Column and row of `frame`
+    pub fn cell(&self, frame: u32) -> (u32, u32) {
+        (frame % self.columns, frame / self.columns)
+    }
+
+    /// CSS background-position showing `frame` with background-size set by
+    /// background_size()
+    fn background_position(&self, frame: u32) -> String {
+        let (column, row) = self.cell(frame);
+        let percent = |i: u32, count: u32| {
+            if count > 1 {
+                i as f64 / (count - 1) as f64 * 100.0
+            } else {
+                0.0
+            }
+        };
+        format!(
+            "{}% {}%",
+            percent(column, self.columns),
+            percent(row, self.rows)
+        )
+    }
+
+    fn background_size(&self) -> String {
+        format!("{}% {}%", self.columns * 100, self.rows * 100)
+    }
+}
+
+/// How particles are drawn; both share the same simulation
+enum ParticleRenderer {
+    /// Each particle is an element moved with transforms
+    Dom,
+    /// Particles are drawn into a canvas, without elements
+    Canvas(CanvasTarget),
+    /// Instanced quads in a WebGL2 canvas, for thousands of particles
+    WebGl(GlTarget),
+}
+
+/// What a CanvasTarget draws on
+enum CanvasSurface {
+    Element(HtmlCanvasElement),
+    /// Transferred to a worker, where there's no layout to measure
+    Offscreen(OffscreenCanvas),
+}
+
+impl CanvasSurface {
+    /// Size in canvas pixels
+    fn size(&self) -> (f64, f64) {
+        match self {
+            CanvasSurface::Element(canvas) => (canvas.width() as f64, canvas.height() as f64),
+            CanvasSurface::Offscreen(canvas) => (canvas.width() as f64, canvas.height() as f64),
+        }
+    }
+}
+
+struct CanvasTarget {
+    canvas: CanvasSurface,
+    context: CanvasRenderingContext2d,
+    shape: ParticleShape,
+    colors: Vec<String>,
+    size: f64,
+    sprite: Option<HtmlImageElement>,
+    // In world space: the canvas's page position and canvas px per CSS px
+    page_offset: Option<(f64, f64, f64)>,
+}
+
+impl CanvasTarget {
+    fn draw(
+        &self,
+        particles: &[Particle],
+        trail_width: f64,
+        sheet: Option<&SpriteSheet>,
+    ) -> Result<(), JsValue> {
+        let context = &self.context;
+        let (width, height) = self.canvas.size();
+        context.set_global_alpha(1.0);
+        context.clear_rect(0.0, 0.0, width, height);
+
+        if let Some((x, y, scale)) = self.page_offset {
+            context.set_transform(scale, 0.0, 0.0, scale, -x * scale, -y * scale)?;
+        }
+        if trail_width > 0.0 {
+            self.draw_trails(particles, trail_width);
+        }
+
+        let half = self.size * 0.5;
+        for particle in particles {
+            context.save();
+            context.set_global_alpha(particle.opacity);
+            context.translate(particle.x, particle.y)?;
+            context.rotate(particle.rotation.to_radians())?;
+            context.scale(particle.scale, particle.scale)?;
+
+            if let Some(color) = self.color(particle) {
+                context.set_fill_style_str(&color);
+            }
+            match (self.shape, &self.sprite) {
+                (ParticleShape::Sprite, Some(sprite)) => match sheet {
+                    Some(sheet) => {
+                        let (column, row) = sheet.cell(particle.frame);
+                        let width = sprite.natural_width() as f64 / sheet.columns as f64;
+                        let height = sprite.natural_height() as f64 / sheet.rows as f64;
+                        context
+                            .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                                sprite,
+                                column as f64 * width,
+                                row as f64 * height,
+                                width,
+                                height,
+                                -half,
+                                -half,
+                                self.size,
+                                self.size,
+                            )?;
+                    }
+                    None => {
+                        context.draw_image_with_html_image_element_and_dw_and_dh(
+                            sprite, -half, -half, self.size, self.size,
+                        )?;
+                    }
+                },
+                (ParticleShape::Circle, _) => {
+                    context.begin_path();
+                    context.arc(0.0, 0.0, half, 0.0, std::f64::consts::TAU)?;
+                    context.fill();
+                }
+                _ => context.fill_rect(-half, -half, self.size, self.size),
+            }
+            context.restore();
+        }
+        context.set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    /// Segments through each particle's past positions, thinning and fading
+    /// towards the oldest
+    fn draw_trails(&self, particles: &[Particle], width: f64) {
+        let context = &self.context;
+        context.set_line_cap("round");
+        for particle in particles {
+            let Some(color) = self.color(particle) else {
+                continue;
+            };
+            context.set_stroke_style_str(&color);
+
+            let count = particle.trail.len() as f64;
+            let mut from = (particle.x, particle.y);
+            for (i, &to) in particle.trail.iter().enumerate() {
+                let remaining = 1.0 - i as f64 / count;
+                context.set_global_alpha(particle.opacity * remaining);
+                context.set_line_width(width * particle.scale * remaining);
+                context.begin_path();
+                context.move_to(from.0, from.1);
+                context.line_to(to.0, to.1);
+                context.stroke();
+                from = to;
+            }
+        }
+        context.set_global_alpha(1.0);
+    }
+
+    fn color(&self, particle: &Particle) -> Option<String> {
+        match particle.tint {
+            Some((r, g, b)) => Some(rgb(r, g, b)),
+            None => self
+                .colors
+                .get(particle.color % self.colors.len().max(1))
+                .cloned(),
+        }
+    }
+}
+
+/// Where continuously emitted particles come from
+enum ParticleSource {
+    /// Deep clones of an element, appended next to it
+    Template(Element),
+    /// A callback returning a new element already in the document
+    Factory(Function),
+}
+
+#[derive(Clone)]
+struct Particle {
+    // None when drawn into a canvas
+    element: Option<Rc<Element>>,
+    // Index into the canvas colors
+    color: usize,
+    // Created by the emitter, so removed from the document when it dies
+    owned: bool,
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+    life: f64,
+    max_life: f64,
+    // Size multiplier picked at birth
+    size: f64,
+    scale: f64,
+    opacity: f64,
+    // Color from the emitter's gradient, replacing the renderer's colors
+    tint: Option<(f64, f64, f64)>,
+    rotation: f64,
+    angular_velocity: f64,
+    // Most recent past position first
+    trail: VecDeque<(f64, f64)>,
+    // Sprite sheet frame
+    frame: u32,
+    // Generations of sub-emitter ancestors; 0 for directly emitted particles
+    depth: u8,
+    // Spawn number reported to lifecycle callbacks
+    index: u32,
+}
+
+impl Particle {
+    /// Argument of the spawn and death callbacks: `{ x, y, index, element }`
+    fn event(&self) -> JsValue {
+        let event = js_sys::Object::new();
+        let _ = js_sys::Reflect::set(&event, &"x".into(), &self.x.into());
+        let _ = js_sys::Reflect::set(&event, &"y".into(), &self.y.into());
+        let _ = js_sys::Reflect::set(&event, &"index".into(), &self.index.into());
+        let element = match self.element {
+            Some(ref element) => JsValue::from(Element::clone(element)),
+            None => JsValue::NULL,
+        };
+        let _ = js_sys::Reflect::set(&event, &"element".into(), &element);
+        event.into()
+    }
+}
+
+impl EmitterState {
+    fn drain_pool(&mut self) {
+        for element in self.pool.drain(..) {
+            element.remove();
+        }
+    }
+
+    fn emit(&mut self, element: Option<Element>, x: f64, y: f64, owned: bool, depth: u8) {
+        // Lowered adaptive quality keeps fewer particles alive
+        let cap = (self.max_particles as f64 * performance_monitor::particle_share()).ceil();
+        if self.particles.len() >= cap as usize {
+            if let (Some(element), true) = (element, owned) {
+                recycle(&mut self.pool, self.max_particles, element);
+            }
+            return;
+        }
+        if let (Some(overlay), Some(element)) = (&self.world, &element) {
+            enter_overlay(overlay, element);
+        }
+
+        let (u, v) = match self.distribution {
+            EmitDistribution::Random => (random(), random()),
+            // Additive recurrence with the plastic number's reciprocals: an
+            // even 2D spread however many particles are emitted
+            EmitDistribution::Even => {
+                let n = self.emitted as f64;
+                (
+                    (0.5 + n * 0.754_877_666_246_692_8).fract(),
+                    (0.5 + n * 0.569_840_290_998_053_2).fract(),
+                )
+            }
+        };
+        self.emitted = self.emitted.wrapping_add(1);
+        let (dx, dy) = self.zone.sample(u, v);
+        let (x, y) = (x + dx, y + dy);
+
+        let angle = (self.direction + (random() - 0.5) * self.spread).to_radians();
+        let speed = self.speed.0 + (self.speed.1 - self.speed.0) * random();
+        let (vx, vy) = (angle.sin() * speed, -angle.cos() * speed);
+        let life = self.lifetime + (random() - 0.5) * self.lifetime_variance * 2.0;
+
+        self.particles.push(Particle {
+            element: element.map(Rc::new),
+            color: (random() * 1024.0) as usize,
+            owned,
+            x,
+            y,
+            vx,
+            vy,
+            life: life.max(0.1),
+            max_life: life.max(0.1),
+            size: (1.0 + (random() - 0.5) * self.size_variance * 2.0).max(0.0),
+            scale: 1.0,
+            opacity: 1.0,
+            tint: None,
+            rotation: 0.0,
+            angular_velocity: (random() - 0.5) * 360.0,
+            depth,
+            trail: VecDeque::new(),
+            frame: 0,
+            index: self.spawned,
+        });
+        self.spawned = self.spawned.wrapping_add(1);
+
+        if let (Some(callback), Some(particle)) = (&self.on_spawn, self.particles.last()) {
+            self.pending.push((callback.clone(), particle.event()));
+        }
+    }
+
+    /// Emit one particle from the template or factory, or without an element
+    /// when drawing into a canvas. False when there is nothing to emit from.
+    fn emit_from_source(&mut self, x: f64, y: f64, depth: u8) -> Result<bool, JsValue> {
+        let element = match self.renderer {
+            ParticleRenderer::Dom => match self.spawn()? {
+                Some(element) => Some(element),
+                None => return Ok(false),
+            },
+            _ => None,
+        };
+        self.emit(element, x, y, true, depth);
+        Ok(true)
+    }
+
+    /// Burst the sub-emitter at each (x, y, depth)
+    fn emit_sub_particles(&mut self, deaths: &[(f64, f64, u8)]) -> Result<(), JsValue> {
+        let Some(ref sub) = self.sub_emitter else {
+            return Ok(());
+        };
+        let count = sub.count;
+        let Some(target) = sub.state.upgrade() else {
+            self.sub_emitter = None;
+            return Ok(());
+        };
+
+        let Ok(mut other) = target.try_borrow_mut() else {
+            // Already borrowed, so the sub-emitter is this emitter
+            for &(x, y, depth) in deaths {
+                for _ in 0..count {
+                    if !self.emit_from_source(x, y, depth)? {
+                        return Ok(());
+                    }
+                }
+            }
+            return Ok(());
+        };
+        'deaths: for &(x, y, depth) in deaths {
+            for _ in 0..count {
+                if !other.emit_from_source(x, y, depth)? {
+                    break 'deaths;
+                }
+            }
+        }
+        drop(other);
+
+        // Keep the sub-emitter animating along with this one
+        if self.ticker_id.is_some() {
+            run_loop(&target)?;
+        }
+        Ok(())
+    }
+
+    /// Element for a new particle: a pooled one if available, otherwise
+    /// created from the template or factory
+    fn spawn(&mut self) -> Result<Option<Element>, JsValue> {
+        if self.source.is_none() {
+            return Ok(None);
+        }
+        let element = match self.pool.pop() {
+            Some(element) => {
+                if let Some(html) = element.dyn_ref::<HtmlElement>() {
+                    html.style().remove_property("display")?;
+                }
+                Some(element)
+            }
+            None => self.create()?,
+        };
+
+        let html = element.as_ref().and_then(|e| e.dyn_ref::<HtmlElement>());
+        if let (Some(html), Some(src)) = (html, &self.dom_sprite) {
+            let style = html.style();
+            style.set_property("background-image", &format!("url(\"{}\")", src))?;
+            style.set_property("background-repeat", "no-repeat")?;
+            let size = match self.sheet {
+                Some(ref sheet) => sheet.background_size(),
+                None => "100% 100%".to_string(),
+            };
+            style.set_property("background-size", &size)?;
+        }
+        Ok(element)
+    }
+
+    /// New element from the template or factory
+    fn create(&self) -> Result<Option<Element>, JsValue> {
+        match self.source {
+            Some(ParticleSource::Template(ref template)) => {
+                let element: Element = template.clone_node_with_deep(true)?.dyn_into()?;
+                element.remove_attribute("id")?;
+                element.remove_attribute("hidden")?;
+                match template.parent_node() {
+                    Some(parent) => parent.append_child(&element)?,
+                    None => return Ok(None),
+                };
+                Ok(Some(element))
+            }
+            Some(ParticleSource::Factory(ref factory)) => {
+                Ok(factory.call0(&JsValue::NULL)?.dyn_into::<Element>().ok())
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn update(&mut self, delta_time: f64) -> Result<(), JsValue> {
+        let dt = delta_time.min(0.1); // Cap to prevent huge jumps
+        match frame_clock::fixed_step() {
+            Some(step) => {
+                for _ in 0..self.steps.steps(dt, step) {
+                    self.simulate(step)?;
+                }
+            }
+            None => self.simulate(dt)?,
+        }
+        self.render()
+    }
+
+    /// Advance emission, forces and lifetimes by `dt` seconds
+    fn simulate(&mut self, dt: f64) -> Result<(), JsValue> {
+        // Scheduled bursts
+        self.clock += dt;
+        let mut i = 0;
+        while i < self.schedule.len() {
+            let burst = &mut self.schedule[i];
+            if burst.at > self.clock {
+                i += 1;
+                continue;
+            }
+            let count = burst.count;
+            let done = match (burst.interval, burst.remaining.as_mut()) {
+                (None, _) | (_, Some(1)) => true,
+                (Some(interval), remaining) => {
+                    burst.at += interval;
+                    if let Some(remaining) = remaining {
+                        *remaining -= 1;
+                    }
+                    false
+                }
+            };
+            if done {
+                self.schedule.remove(i);
+            } else {
+                i += 1;
+            }
+
+            let (x, y) = self.origin;
+            for _ in 0..count {
+                if !self.emit_from_source(x, y, 0)? {
+                    break;
+                }
+            }
+        }
+
+        // Continuous emission
+        if self.active && self.emission_rate > 0.0 {
+            self.emission_accumulator += self.emission_rate * dt;
+            while self.emission_accumulator >= 1.0 {
+                self.emission_accumulator -= 1.0;
+                let (x, y) = self.origin;
+                if !self.emit_from_source(x, y, 0)? {
+                    self.emission_accumulator = 0.0;
+                    break;
+                }
+            }
+        }
+
+        // Update particles
+        let (opacity_curve, color_gradient) = (&self.opacity_curve, &self.color_gradient);
+        let (size_curve, fade) = (&self.size_curve, self.fade);
+        self.forces.time += dt;
+        let (trail_length, sheet) = (self.trail_length, &self.sheet);
+        for particle in &mut self.particles {
+            particle.life -= dt;
+            if trail_length > 0 {
+                particle.trail.push_front((particle.x, particle.y));
+                particle.trail.truncate(trail_length);
+            }
+            let (ax, ay) = self.forces.acceleration(particle.x, particle.y);
+            particle.vx += ax * dt;
+            particle.vy += (self.gravity + ay) * dt;
+            particle.x += particle.vx * dt;
+            particle.y += particle.vy * dt;
+            if let Some(ref bounds) = self.bounds {
+                let Particle { x, y, vx, vy, .. } = particle;
+                if bounds.collide(x, y, vx, vy) {
+                    particle.angular_velocity *= 1.0 - bounds.friction;
+                }
+            }
+            particle.rotation += particle.angular_velocity * dt;
+
+            // Fade out
+            let life_fraction = (particle.life / particle.max_life).max(0.0);
+            let age = 1.0 - life_fraction;
+            if let Some(sheet) = sheet {
+                particle.frame = sheet.frame(age);
+            }
+            let size = match size_curve {
+                Some(curve) => curve.at(age).max(0.0),
+                None => life_fraction,
+            };
+            particle.scale = particle.size * size;
+            particle.opacity = match opacity_curve {
+                Some(curve) => curve.at(age).clamp(0.0, 1.0),
+                None if fade => life_fraction,
+                None => 1.0,
+            };
+            if let Some(gradient) = color_gradient {
+                let (r, g, b, a) = gradient.at(age);
+                particle.tint = Some((r, g, b));
+                particle.opacity *= a.clamp(0.0, 1.0);
+            }
+        }
+
+        // Remove dead particles, noting where sub-emitter bursts go
+        let (pool, max) = (&mut self.pool, self.max_particles);
+        let max_depth = self.sub_emitter.as_ref().map_or(0, |sub| sub.max_depth);
+        let mut deaths = Vec::new();
+        let had_particles = !self.particles.is_empty();
+        let (on_death, pending) = (&self.on_death, &mut self.pending);
+        self.particles.retain(|p| {
+            if p.life > 0.0 {
+                return true;
+            }
+            if let Some(callback) = on_death {
+                pending.push((callback.clone(), p.event()));
+            }
+            if p.owned {
+                if let Some(ref element) = p.element {
+                    recycle(pool, max, Element::clone(element));
+                }
+            }
+            if p.depth < max_depth {
+                deaths.push((p.x, p.y, p.depth + 1));
+            }
+            false
+        });
+        if !deaths.is_empty() {
+            self.emit_sub_particles(&deaths)?;
+        }
+        if had_particles && self.particles.is_empty() {
+            if let Some(ref callback) = self.on_all_dead {
+                self.pending.push((callback.clone(), JsValue::UNDEFINED));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draw the particles, or move their elements
+    fn render(&mut self) -> Result<(), JsValue> {
+        match self.renderer {
+            ParticleRenderer::Canvas(ref mut target) => {
+                target.page_offset = match (&self.world, &target.canvas) {
+                    (Some(_), CanvasSurface::Element(canvas)) => {
+                        let rect = measure::page_rect(canvas);
+                        let scale = if rect.width > 0.0 {
+                            canvas.width() as f64 / rect.width
+                        } else {
+                            1.0
+                        };
+                        Some((rect.x, rect.y, scale))
+                    }
+                    _ => None,
+                };
+                return target.draw(&self.particles, self.trail_width, self.sheet.as_ref());
+            }
+            ParticleRenderer::WebGl(ref mut target) => {
+                target.draw(self.particles.iter().map(|p| Instance {
+                    x: p.x,
+                    y: p.y,
+                    scale: p.scale,
+                    rotation: p.rotation,
+                    alpha: p.opacity,
+                    color: p.color,
+                    tint: p.tint,
+                }));
+                return Ok(());
+            }
+            ParticleRenderer::Dom => {}
+        }
+        for particle in &self.particles {
+            let element = particle.element.as_deref();
+            if let Some(html) = element.and_then(|e| e.dyn_ref::<HtmlElement>()) {
+                let style = html.style();
+                let _ = style.set_property(
+                    "transform",
+                    &format!(
+                        "translate({}px, {}px) scale({}) rotate({}deg)",
+                        particle.x, particle.y, particle.scale, particle.rotation
+                    ),
+                );
+                let _ = style.set_property("opacity", &particle.opacity.to_string());
+                if let Some((r, g, b)) = particle.tint {
+                    let _ = style.set_property("background-color", &rgb(r, g, b));
+                }
+                if let Some(ref sheet) = self.sheet {
+                    let position = sheet.background_position(particle.frame);
+                    let _ = style.set_property("background-position", &position);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen]
+impl ParticleEmitter {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ParticleEmitter {
+        ParticleEmitter {
+            state: Rc::new(RefCell::new(EmitterState {
+                particles: Vec::with_capacity(100),
+                direction: 0.0,
+                spread: 50.0,
+                speed: (50.0, 150.0),
+                gravity: 200.0,
+                lifetime: 2.0,
+                lifetime_variance: 0.5,
+                active: false,
+                max_particles: 100,
+                emission_rate: 0.0,
+                emission_accumulator: 0.0,
+                origin: (0.0, 0.0),
+                source: None,
+                ticker_id: None,
+                clock: 0.0,
+                schedule: Vec::new(),
+                pool: Vec::new(),
+                release_when_idle: false,
+                world: None,
+                on_spawn: None,
+                on_death: None,
+                on_all_dead: None,
+                pending: Vec::new(),
+                spawned: 0,
+                zone: EmissionZone::Point,
+                distribution: EmitDistribution::Random,
+                emitted: 0,
+                forces: ForceField::default(),
+                bounds: None,
+                sub_emitter: None,
+                dom_sprite: None,
+                sheet: None,
+                trail_length: 0,
+                trail_width: 0.0,
+                opacity_curve: None,
+                color_gradient: None,
+                size_curve: None,
+                size_variance: 0.0,
+                fade: true,
+                renderer: ParticleRenderer::Dom,
+                steps: FixedStep::default(),
+            })),
+        }
+    }
+
+    /// Launch direction in degrees clockwise from up (90 = right, 180 = down),
+    /// and the width of the cone particles are spread over (360 = all round)
+    #[wasm_bindgen(js_name = setDirection)]
+    pub fn set_direction(&mut self, angle: f64, spread: f64) {
+        let mut state = self.state.borrow_mut();
+        state.direction = angle;
+        state.spread = spread.clamp(0.0, 360.0);
+    }
+
+    /// Launch speed picked between `min` and `max`, in px/s
+    #[wasm_bindgen(js_name = setSpeed)]
+    pub fn set_speed(&mut self, min: f64, max: f64) {
+        let (min, max) = (min.max(0.0), max.max(0.0));
+        self.state.borrow_mut().speed = (min.min(max), min.max(max));
+    }
+
+    /// Shorthand for setDirection/setSpeed: launch every particle with
+    /// (vx, vy) px/s, without spread
+    #[wasm_bindgen(js_name = setVelocity)]
+    pub fn set_velocity(&mut self, vx: f64, vy: f64) {
+        let mut state = self.state.borrow_mut();
+        let speed = vx.hypot(vy);
+        if speed > 0.0 {
+            state.direction = vx.atan2(-vy).to_degrees();
+        }
+        state.spread = 0.0;
+        state.speed = (speed, speed);
+    }
+
+    /// Shorthand widening the current velocity by up to `variance` px/s in
+    /// any direction: the speed range grows by `variance` either side and the
+    /// cone covers the offsets. Prefer setDirection/setSpeed.
+    #[wasm_bindgen(js_name = setVelocityVariance)]
+    pub fn set_velocity_variance(&mut self, variance: f64) {
+        let mut state = self.state.borrow_mut();
+        let variance = variance.max(0.0);
+        let speed = (state.speed.0 + state.speed.1) * 0.5;
+        state.spread = if variance >= speed {
+            360.0
+        } else {
+            2.0 * (variance / speed).asin().to_degrees()
+        };
+        state.speed = ((speed - variance).max(0.0), speed + variance);
+    }
+
+    #[wasm_bindgen(js_name = setGravity)]
+    pub fn set_gravity(&mut self, gravity: f64) {
+        self.state.borrow_mut().gravity = gravity;
+    }
+
+    #[wasm_bindgen(js_name = setLifetime)]
+    pub fn set_lifetime(&mut self, lifetime: f64, variance: f64) {
+        let mut state = self.state.borrow_mut();
+        state.lifetime = lifetime.max(0.1);
+        state.lifetime_variance = variance.max(0.0);
+    }
+
+    /// Particles alive at once; counts in the thousands need useWebGl
+    #[wasm_bindgen(js_name = setMaxParticles)]
+    pub fn set_max_particles(&mut self, max: usize) {
+        self.state.borrow_mut().max_particles = max.clamp(1, MAX_PARTICLES);
+    }
+
+    /// Particles per second emitted while active, from the template or factory
+    #[wasm_bindgen(js_name = setEmissionRate)]
+    pub fn set_emission_rate(&mut self, particles_per_second: f64) {
+        self.state.borrow_mut().emission_rate = particles_per_second.max(0.0);
+    }
+
+    /// Where continuously emitted particles start, in px
+    #[wasm_bindgen(js_name = setOrigin)]
+    pub fn set_origin(&mut self, x: f64, y: f64) {
+        self.state.borrow_mut().origin = (x, y);
+    }
+
+    /// Emit deep clones of `template`, appended to its parent. Clones lose the
+    /// `id` and `hidden` attributes, so the template can stay hidden.
+    #[wasm_bindgen(js_name = setTemplate)]
+    pub fn set_template(&mut self, template: Element) {
+        let mut state = self.state.borrow_mut();
+        state.drain_pool();
+        state.source = Some(ParticleSource::Template(template));
+    }
+
+    /// Emit elements returned by `factory()`, which should add them to the document
+    #[wasm_bindgen(js_name = setFactory)]
+    pub fn set_factory(&mut self, factory: Function) {
+        let mut state = self.state.borrow_mut();
+        state.drain_pool();
+        state.source = Some(ParticleSource::Factory(factory));
+    }
+
+    /// Create `count` hidden elements from the template or factory up front,
+    /// so the first burst doesn't create them mid-frame
+    #[wasm_bindgen]
+    pub fn prewarm(&mut self, count: usize) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        let target = count.min(state.max_particles);
+        while state.pool.len() < target {
+            match state.create()? {
+                Some(element) => recycle(&mut state.pool, target, element),
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // EMISSION ZONES
+    // ========================================================================
+
+    /// Spawn every particle exactly at the emission point (the default)
+    #[wasm_bindgen(js_name = setZonePoint)]
+    pub fn set_zone_point(&mut self) {
+        self.state.borrow_mut().zone = EmissionZone::Point;
+    }
+
+    /// Spawn along the line between two offsets from the emission point
+    #[wasm_bindgen(js_name = setZoneLine)]
+    pub fn set_zone_line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) {
+        self.state.borrow_mut().zone = EmissionZone::Line((x1, y1), (x2, y2));
+    }
+
+    /// Spawn inside a circle around the emission point, or only on its edge
+    #[wasm_bindgen(js_name = setZoneCircle)]
+    pub fn set_zone_circle(&mut self, radius: f64, edge: bool) {
+        self.state.borrow_mut().zone = EmissionZone::Circle {
+            radius: radius.max(0.0),
+            edge,
+        };
+    }
+
+    /// Spawn inside a rectangle centred on the emission point
+    #[wasm_bindgen(js_name = setZoneRect)]
+    pub fn set_zone_rect(&mut self, width: f64, height: f64) {
+        self.state.borrow_mut().zone = EmissionZone::Rect {
+            width: width.max(0.0),
+            height: height.max(0.0),
+        };
+    }
+
+    /// Spawn along an SVG path, its coordinates taken as offsets from the
+    /// emission point
+    #[wasm_bindgen(js_name = setZonePath)]
+    pub fn set_zone_path(&mut self, path: String) -> Result<(), JsValue> {
+        let path = SvgPath::new(path, Some(0.5))?;
+        if path.is_empty() {
+            return Err(JsValue::from_str("Zone path has no segments"));
+        }
+        self.state.borrow_mut().zone = EmissionZone::Path(path);
+        Ok(())
+    }
+
+    /// Placement within the zone
+    #[wasm_bindgen(js_name = setDistribution)]
+    pub fn set_distribution(&mut self, distribution: EmitDistribution) {
+        let mut state = self.state.borrow_mut();
+        state.distribution = distribution;
+        state.emitted = 0;
+    }
+
+    // ========================================================================
+    // FORCES
+    // ========================================================================
+
+    /// Pull particles towards (x, y) with `strength` px/s² at the centre,
+    /// falling off to nothing at `radius`. Returns an id for moveForce and
+    /// removeForce.
+    #[wasm_bindgen(js_name = addAttractor)]
+    pub fn add_attractor(&mut self, x: f64, y: f64, strength: f64, radius: f64) -> u32 {
+        self.add_point_force(x, y, strength.abs(), radius)
+    }
+
+    /// Push particles away from (x, y); otherwise as addAttractor
+    #[wasm_bindgen(js_name = addRepeller)]
+    pub fn add_repeller(&mut self, x: f64, y: f64, strength: f64, radius: f64) -> u32 {
+        self.add_point_force(x, y, -strength.abs(), radius)
+    }
+
+    /// Move an attractor or repeller, e.g. to follow the pointer
+    #[wasm_bindgen(js_name = moveForce)]
+    pub fn move_force(&mut self, id: u32, x: f64, y: f64) {
+        let mut state = self.state.borrow_mut();
+        if let Some(point) = state.forces.points.iter_mut().find(|p| p.id == id) {
+            point.x = x;
+            point.y = y;
+        }
+    }
+
+    #[wasm_bindgen(js_name = removeForce)]
+    pub fn remove_force(&mut self, id: u32) {
+        self.state.borrow_mut().forces.points.retain(|p| p.id != id);
+    }
+
+    /// Constant acceleration in px/s², applied with gravity
+    #[wasm_bindgen(js_name = setWind)]
+    pub fn set_wind(&mut self, x: f64, y: f64) {
+        self.state.borrow_mut().forces.wind = (x, y);
+    }
+
+    /// Swirling simplex-noise acceleration up to `strength` px/s², with swirls
+    /// about `scale` px across. A strength of 0 turns it off.
+    #[wasm_bindgen(js_name = setTurbulence)]
+    pub fn set_turbulence(&mut self, strength: f64, scale: f64) {
+        let mut state = self.state.borrow_mut();
+        state.forces.turbulence = strength.max(0.0);
+        state.forces.turbulence_scale = scale.max(0.0);
+    }
+
+    /// Remove attractors, repellers, wind and turbulence; gravity stays
+    #[wasm_bindgen(js_name = clearForces)]
+    pub fn clear_forces(&mut self) {
+        self.state.borrow_mut().forces = ForceField::default();
+    }
+
+    // ========================================================================
+    // BOUNDS
+    // ========================================================================
+
+    /// Keep particles inside a rectangle, bouncing off its edges. `restitution`
+    /// is the share of speed kept on a bounce (0 = dead stop, 1 = no loss);
+    /// `friction` the share of sliding speed lost on each contact, so
+    /// particles settle on the floor.
+    #[wasm_bindgen(js_name = setBounds)]
+    pub fn set_bounds(
+        &mut self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        restitution: f64,
+        friction: f64,
+    ) {
+        self.state.borrow_mut().bounds =
+            Some(Bounds::new(x, y, width, height, restitution, friction));
+    }
+
+    /// Bounds the size of `container`, from (0, 0): particle coordinates are
+    /// relative to the container (or canvas) they're drawn in. A canvas uses
+    /// its pixel size rather than its CSS size.
+    #[wasm_bindgen(js_name = setBoundsToElement)]
+    pub fn set_bounds_to_element(&mut self, container: Element, restitution: f64, friction: f64) {
+        let (width, height) = match container.dyn_ref::<HtmlCanvasElement>() {
+            Some(canvas) => (canvas.width() as f64, canvas.height() as f64),
+            None => {
+                let rect = measure::element_rect(&container);
+                (rect.width, rect.height)
+            }
+        };
+        self.set_bounds(0.0, 0.0, width, height, restitution, friction);
+    }
+
+    /// Let particles leave freely again
+    #[wasm_bindgen(js_name = clearBounds)]
+    pub fn clear_bounds(&mut self) {
+        self.state.borrow_mut().bounds = None;
+    }
+
+    // ========================================================================
+    // LIFECYCLE EVENTS
+    // ========================================================================
 
-use std::rc::Rc;
-use web_sys::{Element, HtmlElement};
-use wasm_bindgen::prelude::*;
+    /// Called with `{ x, y, index, element }` for each new particle; `index`
+    /// numbers particles in spawn order and `element` is null on canvases
+    #[wasm_bindgen(js_name = onParticleSpawn)]
+    pub fn on_particle_spawn(&mut self, callback: Function) {
+        self.state.borrow_mut().on_spawn = Some(callback);
+    }
 
-#[wasm_bindgen]
-pub struct ParticleEmitter {
-    particles: Vec<Particle>,
-    velocity: (f64, f64),
-    velocity_variance: f64,
-    gravity: f64,
-    lifetime: f64,
-    lifetime_variance: f64,
-    active: bool,
-    max_particles: usize,
-}
+    /// Called with `{ x, y, index, element }` where each particle dies of age.
+    /// Elements passed to emit() stay in the document, so remove them here.
+    #[wasm_bindgen(js_name = onParticleDeath)]
+    pub fn on_particle_death(&mut self, callback: Function) {
+        self.state.borrow_mut().on_death = Some(callback);
+    }
 
-#[derive(Clone)]
-struct Particle {
-    element: Rc<Element>,
-    x: f64,
-    y: f64,
-    vx: f64,
-    vy: f64,
-    life: f64,
-    max_life: f64,
-    scale: f64,
-    rotation: f64,
-    angular_velocity: f64,
-}
+    /// Called when the last living particle dies
+    #[wasm_bindgen(js_name = onAllDead)]
+    pub fn on_all_dead(&mut self, callback: Function) {
+        self.state.borrow_mut().on_all_dead = Some(callback);
+    }
 
-#[wasm_bindgen]
-impl ParticleEmitter {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> ParticleEmitter {
-        ParticleEmitter {
-            particles: Vec::with_capacity(100),
-            velocity: (0.0, -100.0),
-            velocity_variance: 50.0,
-            gravity: 200.0,
-            lifetime: 2.0,
-            lifetime_variance: 0.5,
-            active: false,
-            max_particles: 100,
+    #[wasm_bindgen(js_name = clearCallbacks)]
+    pub fn clear_callbacks(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.on_spawn = None;
+        state.on_death = None;
+        state.on_all_dead = None;
+    }
+
+    // ========================================================================
+    // SCHEDULING
+    // ========================================================================
+
+    /// Burst `count` particles from the origin when the emitter clock reaches
+    /// `time_ms`. The clock is the time passed to update(), so scheduled
+    /// bursts play with run() or a manual update loop.
+    #[wasm_bindgen(js_name = burstAt)]
+    pub fn burst_at(&mut self, time_ms: f64, count: usize) {
+        self.state.borrow_mut().schedule.push(ScheduledBurst {
+            at: time_ms.max(0.0) / 1000.0,
+            count,
+            interval: None,
+            remaining: None,
+        });
+    }
+
+    /// Burst `count` particles every `interval_ms`, starting at `start_ms`
+    /// on the emitter clock, `times` times (0 = until clearSchedule)
+    #[wasm_bindgen(js_name = burstEvery)]
+    pub fn burst_every(&mut self, start_ms: f64, interval_ms: f64, count: usize, times: u32) {
+        self.state.borrow_mut().schedule.push(ScheduledBurst {
+            at: start_ms.max(0.0) / 1000.0,
+            count,
+            // A zero interval would burst every frame forever
+            interval: Some(interval_ms.max(1.0) / 1000.0),
+            remaining: if times == 0 { None } else { Some(times) },
+        });
+    }
+
+    /// Drop bursts that haven't played yet
+    #[wasm_bindgen(js_name = clearSchedule)]
+    pub fn clear_schedule(&mut self) {
+        self.state.borrow_mut().schedule.clear();
+    }
+
+    /// Restart the emitter clock at 0, e.g. before scheduling a new show
+    #[wasm_bindgen(js_name = resetClock)]
+    pub fn reset_clock(&mut self) {
+        self.state.borrow_mut().clock = 0.0;
+    }
+
+    /// Emitter clock in ms
+    #[wasm_bindgen(getter)]
+    pub fn elapsed(&self) -> f64 {
+        self.state.borrow().clock * 1000.0
+    }
+
+    // ========================================================================
+    // SUB-EMITTERS
+    // ========================================================================
+
+    /// Burst `count` particles from `emitter` where each particle dies, e.g.
+    /// sparks from a firework rocket. The emitter may be this one; `maxDepth`
+    /// limits how many generations can spawn (1 = only this emitter's own
+    /// particles burst). The sub-emitter uses its own settings, template and
+    /// renderer, and runs along with this emitter when driven by run().
+    #[wasm_bindgen(js_name = setSubEmitter)]
+    pub fn set_sub_emitter(&mut self, emitter: &ParticleEmitter, count: usize, max_depth: u8) {
+        let sub = SubEmitter {
+            state: Rc::downgrade(&emitter.state),
+            count,
+            max_depth: max_depth.max(1),
+        };
+        self.state.borrow_mut().sub_emitter = Some(sub);
+    }
+
+    #[wasm_bindgen(js_name = clearSubEmitter)]
+    pub fn clear_sub_emitter(&mut self) {
+        self.state.borrow_mut().sub_emitter = None;
+    }
+
+    // ========================================================================
+    // TRAILS
+    // ========================================================================
+
+    /// Draw a fading trail through each particle's last `length` positions,
+    /// `width` px wide at the particle. Canvas rendering only; 0 turns it off.
+    #[wasm_bindgen(js_name = setTrail)]
+    pub fn set_trail(&mut self, length: usize, width: f64) {
+        let mut state = self.state.borrow_mut();
+        state.trail_length = length;
+        state.trail_width = if length > 0 { width.max(0.0) } else { 0.0 };
+        for particle in &mut state.particles {
+            particle.trail.truncate(length);
         }
     }
 
-    /// Configure particle behavior
-    #[wasm_bindgen(js_name = setVelocity)]
-    pub fn set_velocity(&mut self, vx: f64, vy: f64) {
-        self.velocity = (vx, vy);
+    // ========================================================================
+    // LIFETIME CURVES
+    // ========================================================================
+
+    /// Opacity from `start` at birth to `end` at death, along a CSS easing
+    /// (default linear). Replaces the fade that follows remaining life.
+    #[wasm_bindgen(js_name = setOpacityOverLife)]
+    pub fn set_opacity_over_life(
+        &mut self,
+        start: f64,
+        end: f64,
+        easing: Option<String>,
+    ) -> Result<(), JsValue> {
+        self.state.borrow_mut().opacity_curve = Some(LifetimeCurve {
+            stops: vec![start, end],
+            easing: parse_easing(easing)?,
+        });
+        Ok(())
     }
 
-    #[wasm_bindgen(js_name = setVelocityVariance)]
-    pub fn set_velocity_variance(&mut self, variance: f64) {
-        self.velocity_variance = variance.max(0.0);
+    /// Scale particles through `sizes` (evenly spaced) over their life, e.g.
+    /// `[0, 1, 0]` to grow then shrink, along a CSS easing (default linear).
+    /// Replaces the shrink that follows remaining life.
+    #[wasm_bindgen(js_name = setSizeOverLife)]
+    pub fn set_size_over_life(
+        &mut self,
+        sizes: Vec<f64>,
+        easing: Option<String>,
+    ) -> Result<(), JsValue> {
+        if sizes.is_empty() {
+            return Err(JsValue::from_str("setSizeOverLife needs at least one size"));
+        }
+        self.state.borrow_mut().size_curve = Some(LifetimeCurve {
+            stops: sizes,
+            easing: parse_easing(easing)?,
+        });
+        Ok(())
     }
 
-    #[wasm_bindgen(js_name = setGravity)]
-    pub fn set_gravity(&mut self, gravity: f64) {
-        self.gravity = gravity;
+    /// Randomise each particle's size by up to ±`variance` (0.2 = ±20%)
+    #[wasm_bindgen(js_name = setSizeVariance)]
+    pub fn set_size_variance(&mut self, variance: f64) {
+        self.state.borrow_mut().size_variance = variance.clamp(0.0, 1.0);
     }
 
-    #[wasm_bindgen(js_name = setLifetime)]
-    pub fn set_lifetime(&mut self, lifetime: f64, variance: f64) {
-        self.lifetime = lifetime.max(0.1);
-        self.lifetime_variance = variance.max(0.0);
+    /// Whether opacity fades with remaining life when no opacity curve is set.
+    /// Turn off to keep particles opaque while only their size animates.
+    #[wasm_bindgen(js_name = setFade)]
+    pub fn set_fade(&mut self, fade: bool) {
+        self.state.borrow_mut().fade = fade;
     }
 
-    #[wasm_bindgen(js_name = setMaxParticles)]
-    pub fn set_max_particles(&mut self, max: usize) {
-        self.max_particles = max.clamp(1, 1000);
+    /// Color particles through `colors` (CSS colors, evenly spaced) over their
+    /// life, along a CSS easing (default linear). Overrides the renderer's
+    /// colors; in DOM rendering it sets the element's background color.
+    #[wasm_bindgen(js_name = setColorOverLife)]
+    pub fn set_color_over_life(
+        &mut self,
+        colors: Vec<String>,
+        easing: Option<String>,
+    ) -> Result<(), JsValue> {
+        let stops = colors
+            .iter()
+            .map(|color| parse_css_color(color).map_err(|e| JsValue::from_str(&e)))
+            .collect::<Result<Vec<_>, JsValue>>()?;
+        if stops.is_empty() {
+            return Err(JsValue::from_str(
+                "setColorOverLife needs at least one color",
+            ));
+        }
+        self.state.borrow_mut().color_gradient = Some(ColorGradient {
+            stops,
+            easing: parse_easing(easing)?,
+        });
+        Ok(())
+    }
+
+    /// Back to the linear shrink and fade and the renderer's colors
+    #[wasm_bindgen(js_name = clearLifetimeCurves)]
+    pub fn clear_lifetime_curves(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.opacity_curve = None;
+        state.color_gradient = None;
+        state.size_curve = None;
+    }
+
+    // ========================================================================
+    // RENDERING
+    // ========================================================================
+
+    /// Draw particles into `canvas` instead of moving elements, for effects
+    /// with more particles than the DOM can keep up with. Each particle
+    /// picks one of `colors` (CSS colors).
+    #[wasm_bindgen(js_name = useCanvas)]
+    pub fn use_canvas(
+        &mut self,
+        canvas: HtmlCanvasElement,
+        shape: ParticleShape,
+        colors: Vec<String>,
+        size: f64,
+    ) -> Result<(), JsValue> {
+        let context: CanvasRenderingContext2d = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("Canvas 2D context unavailable"))?
+            .dyn_into()?;
+
+        let mut state = self.state.borrow_mut();
+        let sprite = match state.renderer {
+            ParticleRenderer::Canvas(ref mut target) => target.sprite.take(),
+            _ => None,
+        };
+        state.renderer = ParticleRenderer::Canvas(CanvasTarget {
+            canvas: CanvasSurface::Element(canvas),
+            context,
+            shape,
+            colors: canvas_colors(colors),
+            size: size.max(0.0),
+            sprite,
+            page_offset: None,
+        });
+        Ok(())
+    }
+
+    /// As useCanvas, drawing into an OffscreenCanvas, e.g. one transferred
+    /// to a worker with transferControlToOffscreen(). Rect or Circle only, as
+    /// there are no image elements to draw sprites from in a worker. World
+    /// space doesn't offset the drawing, as the worker can't measure the page.
+    #[wasm_bindgen(js_name = useOffscreenCanvas)]
+    pub fn use_offscreen_canvas(
+        &mut self,
+        canvas: OffscreenCanvas,
+        shape: ParticleShape,
+        colors: Vec<String>,
+        size: f64,
+    ) -> Result<(), JsValue> {
+        if shape == ParticleShape::Sprite {
+            return Err(JsValue::from_str(
+                "useOffscreenCanvas doesn't support sprites",
+            ));
+        }
+        // OffscreenCanvasRenderingContext2D has the same drawing methods,
+        // and web-sys calls them by name rather than through the prototype
+        let context: CanvasRenderingContext2d = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("Canvas 2D context unavailable"))?
+            .unchecked_into();
+
+        self.state.borrow_mut().renderer = ParticleRenderer::Canvas(CanvasTarget {
+            canvas: CanvasSurface::Offscreen(canvas),
+            context,
+            shape,
+            colors: canvas_colors(colors),
+            size: size.max(0.0),
+            sprite: None,
+            page_offset: None,
+        });
+        Ok(())
+    }
+
+    /// Image drawn by the Sprite canvas shape, or in DOM rendering the
+    /// background image of emitted elements
+    #[wasm_bindgen(js_name = setSprite)]
+    pub fn set_sprite(&mut self, image: HtmlImageElement) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        match state.renderer {
+            ParticleRenderer::Canvas(CanvasTarget {
+                canvas: CanvasSurface::Offscreen(_),
+                ..
+            }) => {
+                return Err(JsValue::from_str(
+                    "useOffscreenCanvas doesn't support sprites",
+                ))
+            }
+            ParticleRenderer::Canvas(ref mut target) => target.sprite = Some(image),
+            ParticleRenderer::Dom => state.dom_sprite = Some(image.src()),
+            ParticleRenderer::WebGl(_) => {
+                return Err(JsValue::from_str("useWebGl doesn't support sprites"))
+            }
+        }
+        Ok(())
+    }
+
+    /// Treat the sprite as a sheet of `frames` frames (0 = every cell) in a
+    /// `columns` x `rows` grid, played `loops` times over each particle's
+    /// life. In DOM rendering the frame is shown with background-position, so
+    /// elements need the sheet as their background (setSprite does this).
+    #[wasm_bindgen(js_name = setSpriteSheet)]
+    pub fn set_sprite_sheet(
+        &mut self,
+        columns: u32,
+        rows: u32,
+        frames: u32,
+        loops: f64,
+    ) -> Result<(), JsValue> {
+        let sheet = SpriteSheet::new(columns, rows, frames, loops)
+            .ok_or_else(|| JsValue::from_str("Sprite sheet needs at least one column and row"))?;
+        self.state.borrow_mut().sheet = Some(sheet);
+        Ok(())
+    }
+
+    /// Back to a single image
+    #[wasm_bindgen(js_name = clearSpriteSheet)]
+    pub fn clear_sprite_sheet(&mut self) {
+        self.state.borrow_mut().sheet = None;
+    }
+
+    /// Draw particles as instanced quads with WebGL2, uploading all of them in
+    /// one buffer per frame; suited to 10k+ particles. Rect or Circle only,
+    /// sprites aren't supported here.
+    #[wasm_bindgen(js_name = useWebGl)]
+    pub fn use_webgl(
+        &mut self,
+        canvas: HtmlCanvasElement,
+        shape: ParticleShape,
+        colors: Vec<String>,
+        size: f64,
+    ) -> Result<(), JsValue> {
+        if shape == ParticleShape::Sprite {
+            return Err(JsValue::from_str("useWebGl doesn't support sprites"));
+        }
+        let circle = shape == ParticleShape::Circle;
+        let target = GlTarget::new(canvas, circle, &colors, size.max(0.0))?;
+        self.state.borrow_mut().renderer = ParticleRenderer::WebGl(target);
+        Ok(())
+    }
+
+    /// Go back to moving one element per particle
+    #[wasm_bindgen(js_name = useDom)]
+    pub fn use_dom(&mut self) {
+        self.state.borrow_mut().renderer = ParticleRenderer::Dom;
+    }
+
+    /// Simulate in page coordinates, so particles stay put when the elements
+    /// they came from move or scroll. DOM particles move into `overlay` (by
+    /// default a new zero-size layer at the document origin); a canvas is
+    /// drawn offset by its own page position.
+    #[wasm_bindgen(js_name = useWorldSpace)]
+    pub fn use_world_space(&mut self, overlay: Option<Element>) -> Result<(), JsValue> {
+        let overlay = match overlay {
+            Some(overlay) => overlay,
+            None => create_overlay()?,
+        };
+        let mut state = self.state.borrow_mut();
+        // Pooled elements sit in the old space
+        state.drain_pool();
+        state.world = Some(overlay);
+        Ok(())
+    }
+
+    /// Back to positions relative to each element's own place in the layout
+    /// (the default)
+    #[wasm_bindgen(js_name = useLocalSpace)]
+    pub fn use_local_space(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.drain_pool();
+        state.world = None;
+    }
+
+    /// Page coordinates of the centre of `element`, for emitting from it in
+    /// world space
+    #[wasm_bindgen(js_name = setOriginToElement)]
+    pub fn set_origin_to_element(&mut self, element: Element) {
+        let rect = measure::page_rect(&element);
+        self.state.borrow_mut().origin = (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+    }
+
+    #[wasm_bindgen(getter, js_name = isWorldSpace)]
+    pub fn is_world_space(&self) -> bool {
+        self.state.borrow().world.is_some()
     }
 
     // ========================================================================
@@ -78,43 +1674,73 @@ impl ParticleEmitter {
 
     #[wasm_bindgen]
     pub fn start(&mut self) {
-        self.active = true;
+        self.state.borrow_mut().active = true;
     }
 
+    /// Stop emitting; a run() loop ends once the remaining particles have died
     #[wasm_bindgen]
     pub fn stop(&mut self) {
-        self.active = false;
+        self.state.borrow_mut().active = false;
+    }
+
+    /// Start emitting and drive update() from the shared frame loop
+    #[wasm_bindgen]
+    pub fn run(&mut self) -> Result<(), JsValue> {
+        self.state.borrow_mut().active = true;
+        run_loop(&self.state)
     }
 
     #[wasm_bindgen]
     pub fn clear(&mut self) {
-        self.particles.clear();
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        for particle in state.particles.drain(..) {
+            if let (Some(element), true) = (particle.element, particle.owned) {
+                recycle(
+                    &mut state.pool,
+                    state.max_particles,
+                    Element::clone(&element),
+                );
+            }
+        }
+    }
+
+    /// Emit `count` particles at (x, y) from the template or factory, reusing
+    /// pooled elements
+    #[wasm_bindgen]
+    pub fn burst(&mut self, x: f64, y: f64, count: usize) -> Result<(), JsValue> {
+        {
+            let mut state = self.state.borrow_mut();
+            for _ in 0..count {
+                match state.spawn()? {
+                    Some(element) => state.emit(Some(element), x, y, true, 0),
+                    None => break,
+                }
+            }
+        }
+        fire_pending(&self.state);
+        Ok(())
     }
 
     /// Emit a single particle
     #[wasm_bindgen]
     pub fn emit(&mut self, element: Element, x: f64, y: f64) {
-        if self.particles.len() >= self.max_particles {
-            return;
-        }
+        self.state.borrow_mut().emit(Some(element), x, y, false, 0);
+        fire_pending(&self.state);
+    }
 
-        let variance = self.velocity_variance;
-        let vx = self.velocity.0 + (random() - 0.5) * variance * 2.0;
-        let vy = self.velocity.1 + (random() - 0.5) * variance * 2.0;
-        let life = self.lifetime + (random() - 0.5) * self.lifetime_variance * 2.0;
+    /// Emit a particle without an element, for canvas rendering
+    #[wasm_bindgen(js_name = emitAt)]
+    pub fn emit_at(&mut self, x: f64, y: f64) {
+        self.state.borrow_mut().emit(None, x, y, true, 0);
+        fire_pending(&self.state);
+    }
 
-        self.particles.push(Particle {
-            element: Rc::new(element),
-            x,
-            y,
-            vx,
-            vy,
-            life: life.max(0.1),
-            max_life: life.max(0.1),
-            scale: 1.0,
-            rotation: 0.0,
-            angular_velocity: (random() - 0.5) * 360.0,
-        });
+    #[wasm_bindgen(js_name = emitBurstAt)]
+    pub fn emit_burst_at(&mut self, x: f64, y: f64, count: usize) {
+        for _ in 0..count {
+            self.emit_at(x, y);
+        }
     }
 
     /// Emit burst of particles
@@ -131,40 +1757,9 @@ impl ParticleEmitter {
 
     #[wasm_bindgen]
     pub fn update(&mut self, delta_time: f64) -> Result<(), JsValue> {
-        let dt = delta_time.min(0.1); // Cap to prevent huge jumps
-
-        // Update particles
-        for particle in &mut self.particles {
-            particle.life -= dt;
-            particle.vy += self.gravity * dt;
-            particle.x += particle.vx * dt;
-            particle.y += particle.vy * dt;
-            particle.rotation += particle.angular_velocity * dt;
-
-            // Fade out
-            let life_fraction = (particle.life / particle.max_life).max(0.0);
-            particle.scale = life_fraction;
-        }
-
-        // Remove dead particles
-        self.particles.retain(|p| p.life > 0.0);
-
-        // Apply visual updates
-        for particle in &self.particles {
-            if let Some(html) = particle.element.dyn_ref::<HtmlElement>() {
-                let style = html.style();
-                let _ = style.set_property(
-                    "transform",
-                    &format!(
-                        "translate({}px, {}px) scale({}) rotate({}deg)",
-                        particle.x, particle.y, particle.scale, particle.rotation
-                    ),
-                );
-                let _ = style.set_property("opacity", &particle.scale.to_string());
-            }
-        }
-
-        Ok(())
+        let result = self.state.borrow_mut().update(delta_time);
+        fire_pending(&self.state);
+        result
     }
 
     // ========================================================================
@@ -173,26 +1768,193 @@ impl ParticleEmitter {
 
     #[wasm_bindgen(getter, js_name = particleCount)]
     pub fn particle_count(&self) -> usize {
-        self.particles.len()
+        self.state.borrow().particles.len()
     }
 
     #[wasm_bindgen(getter, js_name = isActive)]
     pub fn is_active(&self) -> bool {
-        self.active
+        self.state.borrow().active
+    }
+
+    /// Hidden elements waiting to be reused
+    #[wasm_bindgen(getter, js_name = pooledCount)]
+    pub fn pooled_count(&self) -> usize {
+        self.state.borrow().pool.len()
+    }
+
+    #[wasm_bindgen(getter, js_name = isRunning)]
+    pub fn is_running(&self) -> bool {
+        self.state.borrow().ticker_id.is_some()
     }
 
     #[wasm_bindgen(getter, js_name = maxParticles)]
     pub fn max_particles(&self) -> usize {
-        self.max_particles
+        self.state.borrow().max_particles
+    }
+}
+
+impl ParticleEmitter {
+    /// Particle positions as x, y pairs, for sending out of a worker
+    pub(crate) fn positions(&self) -> Vec<f32> {
+        let state = self.state.borrow();
+        let mut positions = Vec::with_capacity(state.particles.len() * 2);
+        for particle in &state.particles {
+            positions.push(particle.x as f32);
+            positions.push(particle.y as f32);
+        }
+        positions
+    }
+
+    /// Resize the canvas being drawn into, in canvas pixels
+    pub(crate) fn resize_canvas(&mut self, width: u32, height: u32) {
+        if let ParticleRenderer::Canvas(ref target) = self.state.borrow().renderer {
+            match target.canvas {
+                CanvasSurface::Element(ref canvas) => {
+                    canvas.set_width(width);
+                    canvas.set_height(height);
+                }
+                CanvasSurface::Offscreen(ref canvas) => {
+                    canvas.set_width(width);
+                    canvas.set_height(height);
+                }
+            }
+        }
+    }
+
+    fn add_point_force(&mut self, x: f64, y: f64, strength: f64, radius: f64) -> u32 {
+        let mut state = self.state.borrow_mut();
+        let forces = &mut state.forces;
+        let id = forces.next_id;
+        forces.next_id += 1;
+        forces.points.push(PointForce {
+            id,
+            x,
+            y,
+            strength,
+            radius: radius.max(0.0),
+        });
+        id
+    }
+}
+
+impl Drop for ParticleEmitter {
+    fn drop(&mut self) {
+        if let Some(id) = self.state.borrow_mut().ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+fn fire_pending(state: &Rc<RefCell<EmitterState>>) {
+    let pending = std::mem::take(&mut state.borrow_mut().pending);
+    for (callback, arg) in pending {
+        let _ = callback.call1(&JsValue::NULL, &arg);
+    }
+}
+
+/// Zero-size layer at the document origin that world-space particles are
+/// positioned from
+fn create_overlay() -> Result<Element, JsValue> {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| JsValue::from_str("No document"))?;
+    let overlay: HtmlElement = document.create_element("div")?.dyn_into()?;
+    let style = overlay.style();
+    style.set_property("position", "absolute")?;
+    style.set_property("left", "0")?;
+    style.set_property("top", "0")?;
+    style.set_property("width", "0")?;
+    style.set_property("height", "0")?;
+    style.set_property("overflow", "visible")?;
+    style.set_property("pointer-events", "none")?;
+    style.set_property("z-index", "2147483647")?;
+    document
+        .body()
+        .ok_or_else(|| JsValue::from_str("No document body"))?
+        .append_child(&overlay)?;
+    Ok(overlay.into())
+}
+
+/// Move a particle element into the world-space overlay, placed at its origin
+fn enter_overlay(overlay: &Element, element: &Element) {
+    let inside = element
+        .parent_element()
+        .is_some_and(|parent| &parent == overlay);
+    if inside {
+        return;
+    }
+    if let Some(html) = element.dyn_ref::<HtmlElement>() {
+        let style = html.style();
+        let _ = style.set_property("position", "absolute");
+        let _ = style.set_property("left", "0");
+        let _ = style.set_property("top", "0");
+        let _ = style.set_property("margin", "0");
+    }
+    let _ = overlay.append_child(element);
+}
+
+/// Drive `state` from the shared frame loop until it's inactive and empty
+fn run_loop(state: &Rc<RefCell<EmitterState>>) -> Result<(), JsValue> {
+    let mut s = state.borrow_mut();
+    if s.ticker_id.is_some() {
+        return Ok(());
+    }
+
+    let tick_state = Rc::clone(state);
+    s.ticker_id = Some(ticker::subscribe(Box::new(move |_now, dt| {
+        let _ = tick_state.borrow_mut().update(dt);
+        fire_pending(&tick_state);
+        let mut state = tick_state.borrow_mut();
+        let keep = state.active || !state.particles.is_empty() || !state.schedule.is_empty();
+        if !keep {
+            state.ticker_id = None;
+            if state.release_when_idle {
+                state.drain_pool();
+            }
+        }
+        keep
+    }))?);
+    Ok(())
+}
+
+/// Hide a dead particle's element for reuse, or remove it once the pool holds
+/// `max` elements
+fn recycle(pool: &mut Vec<Element>, max: usize, element: Element) {
+    if pool.len() >= max {
+        element.remove();
+        return;
+    }
+    if let Some(html) = element.dyn_ref::<HtmlElement>() {
+        let _ = html.style().set_property("display", "none");
+    }
+    pool.push(element);
+}
+
+fn parse_easing(easing: Option<String>) -> Result<CubicBezier, JsValue> {
+    match easing {
+        Some(easing) => CubicBezier::from_css(&easing).map_err(|e| JsValue::from_str(&e)),
+        None => Ok(CubicBezier::linear()),
+    }
+}
+
+/// Canvas palette, white when none is given
+fn canvas_colors(colors: Vec<String>) -> Vec<String> {
+    if colors.is_empty() {
+        vec!["#ffffff".to_string()]
+    } else {
+        colors
     }
 }
 
+fn rgb(r: f64, g: f64, b: f64) -> String {
+    format!("rgb({}, {}, {})", r.round(), g.round(), b.round())
+}
+
 // Simple random number generator (0.0 - 1.0)
 fn random() -> f64 {
     (js_sys::Math::random() * 1000.0).fract()
 }
 
-
 // ============================================================================
 // PRESET PARTICLE EFFECTS
 // ============================================================================
@@ -202,24 +1964,83 @@ pub struct ParticlePresets;
 
 #[wasm_bindgen]
 impl ParticlePresets {
-    /// Confetti explosion
+    /// Confetti explosion. Without options this only configures the emitter.
+    /// With options it fires a finished burst: it creates its own pieces,
+    /// runs them on the frame loop and removes them once they've fallen.
+    /// Options: `{ colors: [...], shapes: ["square", "circle", "strip"],
+    /// count: 80, direction: 0, spread: 70, size: 10, origin: {x, y} or an
+    /// element (default: viewport centre), container: document.body }`
     #[wasm_bindgen]
-    pub fn confetti() -> ParticleEmitter {
+    pub fn confetti(options: JsValue) -> Result<ParticleEmitter, JsValue> {
         let mut emitter = ParticleEmitter::new();
-        emitter.set_velocity(0.0, -300.0);
-        emitter.set_velocity_variance(200.0);
+        emitter.set_direction(0.0, 70.0);
+        emitter.set_speed(150.0, 500.0);
         emitter.set_gravity(500.0);
         emitter.set_lifetime(3.0, 1.0);
         emitter.set_max_particles(50);
-        emitter
+        if options.is_undefined() || options.is_null() {
+            return Ok(emitter);
+        }
+
+        let options: ConfettiOptions = serde_wasm_bindgen::from_value(options)
+            .map_err(|e| JsValue::from_str(&format!("Invalid confetti options: {:?}", e)))?;
+        let colors = match options.colors {
+            Some(colors) if !colors.is_empty() => colors,
+            _ => CONFETTI_COLORS.iter().map(|c| c.to_string()).collect(),
+        };
+        let shapes = match options.shapes {
+            Some(shapes) if !shapes.is_empty() => shapes
+                .iter()
+                .map(|shape| ConfettiShape::parse(shape))
+                .collect::<Result<Vec<_>, JsValue>>()?,
+            _ => vec![
+                ConfettiShape::Square,
+                ConfettiShape::Circle,
+                ConfettiShape::Strip,
+            ],
+        };
+        let count = options.count.unwrap_or(80).min(MAX_PARTICLES);
+        let size = options.size.unwrap_or(10.0).max(1.0);
+        let (x, y) = confetti_origin(&options.origin)?;
+
+        let document = web_sys::window()
+            .and_then(|w| w.document())
+            .ok_or_else(|| JsValue::from_str("No document"))?;
+        let container: Element = match options.container.dyn_into::<Element>() {
+            Ok(container) => container,
+            Err(_) => document
+                .body()
+                .ok_or_else(|| JsValue::from_str("No document body"))?
+                .into(),
+        };
+
+        emitter.set_direction(
+            options.direction.unwrap_or(0.0),
+            options.spread.unwrap_or(70.0),
+        );
+        emitter.set_max_particles(count);
+        {
+            let mut state = emitter.state.borrow_mut();
+            state.release_when_idle = true;
+            for _ in 0..count {
+                let color = &colors[(random() * colors.len() as f64) as usize % colors.len()];
+                let shape = shapes[(random() * shapes.len() as f64) as usize % shapes.len()];
+                let piece = shape.create(&document, color, size)?;
+                container.append_child(&piece)?;
+                state.emit(Some(piece), x, y, true, 0);
+            }
+        }
+        fire_pending(&emitter.state);
+        run_loop(&emitter.state)?;
+        Ok(emitter)
     }
 
     /// Smoke/dust effect
     #[wasm_bindgen]
     pub fn smoke() -> ParticleEmitter {
         let mut emitter = ParticleEmitter::new();
-        emitter.set_velocity(0.0, -50.0);
-        emitter.set_velocity_variance(30.0);
+        emitter.set_direction(0.0, 60.0);
+        emitter.set_speed(20.0, 80.0);
         emitter.set_gravity(-20.0); // Float upward
         emitter.set_lifetime(2.0, 0.5);
         emitter.set_max_particles(30);
@@ -230,8 +2051,8 @@ impl ParticlePresets {
     #[wasm_bindgen]
     pub fn sparkle() -> ParticleEmitter {
         let mut emitter = ParticleEmitter::new();
-        emitter.set_velocity(0.0, 0.0);
-        emitter.set_velocity_variance(100.0);
+        emitter.set_direction(0.0, 360.0);
+        emitter.set_speed(0.0, 100.0);
         emitter.set_gravity(0.0);
         emitter.set_lifetime(1.0, 0.3);
         emitter.set_max_particles(20);
@@ -242,11 +2063,98 @@ impl ParticlePresets {
     #[wasm_bindgen]
     pub fn explosion() -> ParticleEmitter {
         let mut emitter = ParticleEmitter::new();
-        emitter.set_velocity(0.0, 0.0);
-        emitter.set_velocity_variance(300.0);
+        emitter.set_direction(0.0, 360.0);
+        emitter.set_speed(50.0, 300.0);
         emitter.set_gravity(300.0);
         emitter.set_lifetime(1.5, 0.5);
         emitter.set_max_particles(40);
         emitter
     }
-}
\ No newline at end of file
+}
+
+const CONFETTI_COLORS: [&str; 6] = [
+    "#f94144", "#f8961e", "#f9c74f", "#90be6d", "#43aa8b", "#577590",
+];
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct ConfettiOptions {
+    colors: Option<Vec<String>>,
+    shapes: Option<Vec<String>>,
+    count: Option<usize>,
+    direction: Option<f64>,
+    spread: Option<f64>,
+    size: Option<f64>,
+    /// `{ x, y }` in viewport px, or an element whose centre to burst from
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    origin: JsValue,
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    container: JsValue,
+}
+
+#[derive(Deserialize)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Clone, Copy)]
+enum ConfettiShape {
+    Square,
+    Circle,
+    /// A narrow ribbon
+    Strip,
+}
+
+impl ConfettiShape {
+    fn parse(name: &str) -> Result<ConfettiShape, JsValue> {
+        match name {
+            "square" => Ok(ConfettiShape::Square),
+            "circle" => Ok(ConfettiShape::Circle),
+            "strip" => Ok(ConfettiShape::Strip),
+            _ => Err(JsValue::from_str(&format!(
+                "Invalid confetti shape '{}': square, circle, strip",
+                name
+            ))),
+        }
+    }
+
+    /// A piece fixed to the viewport, centred on its translate()
+    fn create(self, document: &Document, color: &str, size: f64) -> Result<Element, JsValue> {
+        let piece: HtmlElement = document.create_element("div")?.dyn_into()?;
+        let (width, height) = match self {
+            ConfettiShape::Strip => (size * 0.4, size * 1.6),
+            _ => (size, size),
+        };
+        let style = piece.style();
+        style.set_property("position", "fixed")?;
+        style.set_property("left", "0")?;
+        style.set_property("top", "0")?;
+        style.set_property("width", &format!("{}px", width))?;
+        style.set_property("height", &format!("{}px", height))?;
+        style.set_property(
+            "margin",
+            &format!("{}px 0 0 {}px", -height / 2.0, -width / 2.0),
+        )?;
+        style.set_property("background-color", color)?;
+        style.set_property("pointer-events", "none")?;
+        if let ConfettiShape::Circle = self {
+            style.set_property("border-radius", "50%")?;
+        }
+        Ok(piece.into())
+    }
+}
+
+fn confetti_origin(origin: &JsValue) -> Result<(f64, f64), JsValue> {
+    if let Some(element) = origin.dyn_ref::<Element>() {
+        let rect = measure::element_rect(element);
+        return Ok((rect.x + rect.width / 2.0, rect.y + rect.height / 2.0));
+    }
+    if origin.is_undefined() || origin.is_null() {
+        let viewport = measure::viewport_rect()?;
+        return Ok((viewport.width / 2.0, viewport.height / 2.0));
+    }
+    let point: Point = serde_wasm_bindgen::from_value(origin.clone())
+        .map_err(|e| JsValue::from_str(&format!("Invalid confetti origin: {:?}", e)))?;
+    Ok((point.x, point.y))
+}
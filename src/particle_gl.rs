@@ -0,0 +1,253 @@
+use crate::types::parse_css_color;
+use js_sys::Float32Array;
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    HtmlCanvasElement, WebGl2RenderingContext as Gl, WebGlBuffer, WebGlProgram, WebGlShader,
+    WebGlUniformLocation, WebGlVertexArrayObject,
+};
+
+// ============================================================================
+// PARTICLE GL - Instanced WebGL2 quads for large particle counts
+// ============================================================================
+
+const VERTEX_SHADER: &str = r#"#version 300 es
+layout(location = 0) in vec2 a_corner;
+layout(location = 1) in vec4 a_transform; // x, y, scale, rotation (radians)
+layout(location = 2) in vec4 a_color;     // r, g, b, alpha
+uniform vec2 u_resolution;
+uniform float u_size;
+out vec4 v_color;
+out vec2 v_uv;
+
+void main() {
+    float c = cos(a_transform.w);
+    float s = sin(a_transform.w);
+    vec2 p = a_corner * u_size * a_transform.z;
+    p = vec2(p.x * c - p.y * s, p.x * s + p.y * c) + a_transform.xy;
+    vec2 clip = p / u_resolution * 2.0 - 1.0;
+    gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+    v_color = a_color;
+    v_uv = a_corner;
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 300 es
+precision mediump float;
+in vec4 v_color;
+in vec2 v_uv;
+uniform bool u_circle;
+out vec4 out_color;
+
+void main() {
+    if (u_circle && length(v_uv) > 0.5) {
+        discard;
+    }
+    out_color = vec4(v_color.rgb * v_color.a, v_color.a);
+}
+"#;
+
+// x, y, scale, rotation, r, g, b, alpha
+const FLOATS_PER_INSTANCE: i32 = 8;
+
+/// One particle as the GPU sees it
+pub(crate) struct Instance {
+    pub x: f64,
+    pub y: f64,
+    pub scale: f64,
+    /// Degrees
+    pub rotation: f64,
+    pub alpha: f64,
+    /// Index into the target's colors
+    pub color: usize,
+    /// Color (0-255) used instead of the target's colors
+    pub tint: Option<(f64, f64, f64)>,
+}
+
+/// Draws every particle as one instanced draw call; the per-particle data is
+/// uploaded in a single buffer each frame
+pub(crate) struct GlTarget {
+    canvas: HtmlCanvasElement,
+    gl: Gl,
+    program: WebGlProgram,
+    vao: WebGlVertexArrayObject,
+    instances: WebGlBuffer,
+    u_resolution: Option<WebGlUniformLocation>,
+    u_size: Option<WebGlUniformLocation>,
+    u_circle: Option<WebGlUniformLocation>,
+    colors: Vec<[f32; 3]>,
+    size: f64,
+    circle: bool,
+    // Reused between frames to avoid reallocating
+    data: Vec<f32>,
+}
+
+impl GlTarget {
+    pub(crate) fn new(
+        canvas: HtmlCanvasElement,
+        circle: bool,
+        colors: &[String],
+        size: f64,
+    ) -> Result<GlTarget, JsValue> {
+        let gl: Gl = canvas
+            .get_context("webgl2")?
+            .ok_or_else(|| JsValue::from_str("WebGL2 unavailable"))?
+            .dyn_into()?;
+
+        let program = link(
+            &gl,
+            &compile(&gl, Gl::VERTEX_SHADER, VERTEX_SHADER)?,
+            &compile(&gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?,
+        )?;
+
+        let vao = gl
+            .create_vertex_array()
+            .ok_or_else(|| JsValue::from_str("Failed to create vertex array"))?;
+        gl.bind_vertex_array(Some(&vao));
+
+        // Unit quad as a triangle strip, shared by every instance
+        let quad = gl
+            .create_buffer()
+            .ok_or_else(|| JsValue::from_str("Failed to create buffer"))?;
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&quad));
+        let corners: [f32; 8] = [-0.5, -0.5, 0.5, -0.5, -0.5, 0.5, 0.5, 0.5];
+        gl.buffer_data_with_array_buffer_view(
+            Gl::ARRAY_BUFFER,
+            &Float32Array::from(&corners[..]),
+            Gl::STATIC_DRAW,
+        );
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_with_i32(0, 2, Gl::FLOAT, false, 0, 0);
+
+        let instances = gl
+            .create_buffer()
+            .ok_or_else(|| JsValue::from_str("Failed to create buffer"))?;
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&instances));
+        let stride = FLOATS_PER_INSTANCE * 4;
+        for (location, offset) in [(1, 0), (2, 16)] {
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_with_i32(location, 4, Gl::FLOAT, false, stride, offset);
+            gl.vertex_attrib_divisor(location, 1);
+        }
+        gl.bind_vertex_array(None);
+
+        let colors = colors
+            .iter()
+            .map(|c| {
+                let (r, g, b, _) = parse_css_color(c).map_err(|e| JsValue::from_str(&e))?;
+                Ok([r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0])
+            })
+            .collect::<Result<Vec<_>, JsValue>>()?;
+
+        Ok(GlTarget {
+            u_resolution: gl.get_uniform_location(&program, "u_resolution"),
+            u_size: gl.get_uniform_location(&program, "u_size"),
+            u_circle: gl.get_uniform_location(&program, "u_circle"),
+            canvas,
+            gl,
+            program,
+            vao,
+            instances,
+            colors: if colors.is_empty() {
+                vec![[1.0, 1.0, 1.0]]
+            } else {
+                colors
+            },
+            size,
+            circle,
+            data: Vec::new(),
+        })
+    }
+
+    pub(crate) fn draw(&mut self, particles: impl Iterator<Item = Instance>) {
+        self.data.clear();
+        for p in particles {
+            let [r, g, b] = match p.tint {
+                Some((r, g, b)) => [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0],
+                None => self.colors[p.color % self.colors.len()],
+            };
+            self.data.extend_from_slice(&[
+                p.x as f32,
+                p.y as f32,
+                p.scale as f32,
+                p.rotation.to_radians() as f32,
+                r,
+                g,
+                b,
+                p.alpha as f32,
+            ]);
+        }
+
+        let gl = &self.gl;
+        let (width, height) = (self.canvas.width() as i32, self.canvas.height() as i32);
+        gl.viewport(0, 0, width, height);
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(Gl::COLOR_BUFFER_BIT);
+
+        let count = self.data.len() as i32 / FLOATS_PER_INSTANCE;
+        if count == 0 {
+            return;
+        }
+
+        gl.use_program(Some(&self.program));
+        gl.uniform2f(self.u_resolution.as_ref(), width as f32, height as f32);
+        gl.uniform1f(self.u_size.as_ref(), self.size as f32);
+        gl.uniform1i(self.u_circle.as_ref(), self.circle as i32);
+        gl.enable(Gl::BLEND);
+        gl.blend_func(Gl::ONE, Gl::ONE_MINUS_SRC_ALPHA);
+
+        gl.bind_vertex_array(Some(&self.vao));
+        gl.bind_buffer(Gl::ARRAY_BUFFER, Some(&self.instances));
+        gl.buffer_data_with_array_buffer_view(
+            Gl::ARRAY_BUFFER,
+            &Float32Array::from(&self.data[..]),
+            Gl::DYNAMIC_DRAW,
+        );
+        gl.draw_arrays_instanced(Gl::TRIANGLE_STRIP, 0, 4, count);
+        gl.bind_vertex_array(None);
+    }
+}
+
+fn compile(gl: &Gl, kind: u32, source: &str) -> Result<WebGlShader, JsValue> {
+    let shader = gl
+        .create_shader(kind)
+        .ok_or_else(|| JsValue::from_str("Failed to create shader"))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+
+    if gl
+        .get_shader_parameter(&shader, Gl::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        let log = gl.get_shader_info_log(&shader).unwrap_or_default();
+        Err(JsValue::from_str(&format!(
+            "Particle shader failed to compile: {}",
+            log
+        )))
+    }
+}
+
+fn link(gl: &Gl, vertex: &WebGlShader, fragment: &WebGlShader) -> Result<WebGlProgram, JsValue> {
+    let program = gl
+        .create_program()
+        .ok_or_else(|| JsValue::from_str("Failed to create program"))?;
+    gl.attach_shader(&program, vertex);
+    gl.attach_shader(&program, fragment);
+    gl.link_program(&program);
+
+    if gl
+        .get_program_parameter(&program, Gl::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        let log = gl.get_program_info_log(&program).unwrap_or_default();
+        Err(JsValue::from_str(&format!(
+            "Particle shader failed to link: {}",
+            log
+        )))
+    }
+}
@@ -0,0 +1,430 @@
+use crate::particle_effects::{ParticleEmitter, ParticleShape};
+use crate::ticker;
+use js_sys::{Array, Float32Array, Float64Array, Function, Object, Reflect};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    DedicatedWorkerGlobalScope, HtmlCanvasElement, MessageEvent, OffscreenCanvas, Worker,
+};
+
+// ============================================================================
+// PARTICLE WORKER - Simulation and canvas drawing off the main thread
+// ============================================================================
+//
+// The page hands its canvas to a worker as an OffscreenCanvas. Commands
+// (bursts, origin moves, ...) are packed into a Float64Array as
+// [op, args...] and sent once per frame; the worker answers each frame with
+// the particle count and positions in a Float32Array. Both buffers are
+// transferred rather than copied.
+
+const BURST: f64 = 0.0; // x, y, count
+const ORIGIN: f64 = 1.0; // x, y
+const START: f64 = 2.0;
+const STOP: f64 = 3.0;
+const CLEAR: f64 = 4.0;
+const MOVE_FORCE: f64 = 5.0; // id, x, y
+const RESIZE: f64 = 6.0; // width, height
+
+struct WorkerState {
+    worker: Worker,
+    // Encoded commands waiting for the next frame
+    commands: Vec<f64>,
+    ticker_id: Option<u32>,
+    // Latest frame reported by the worker
+    count: u32,
+    positions: Option<Float32Array>,
+    on_frame: Option<Function>,
+}
+
+impl WorkerState {
+    fn flush(&mut self) -> Result<(), JsValue> {
+        if self.commands.is_empty() {
+            return Ok(());
+        }
+        let buffer = Float64Array::from(&self.commands[..]);
+        self.commands.clear();
+        let message = object(&[
+            ("type", "commands".into()),
+            ("buffer", buffer.clone().into()),
+        ])?;
+        self.worker
+            .post_message_with_transfer(&message, &Array::of1(&buffer.buffer()))
+    }
+
+    fn stop(&mut self) {
+        if let Some(id) = self.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+/// Main-thread handle of a particle effect running in a worker. The worker
+/// script creates a ParticleEmitter and hands it to a ParticleWorkerHost:
+///
+/// ```js
+/// const emitter = new ParticleEmitter();
+/// const host = new ParticleWorkerHost(emitter);
+/// self.onmessage = (event) => host.handleMessage(event.data);
+/// ```
+#[wasm_bindgen]
+pub struct ParticleWorker {
+    state: Rc<RefCell<WorkerState>>,
+    _listener: Closure<dyn FnMut(MessageEvent)>,
+}
+
+#[wasm_bindgen]
+impl ParticleWorker {
+    /// Transfer `canvas` to `worker` and have it draw there, as useCanvas
+    /// would, as Rect or Circle particles each one of `colors`. The canvas
+    /// can't be drawn on from the page afterwards.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        worker: Worker,
+        canvas: HtmlCanvasElement,
+        shape: ParticleShape,
+        colors: Vec<String>,
+        size: f64,
+    ) -> Result<ParticleWorker, JsValue> {
+        if shape == ParticleShape::Sprite {
+            return Err(JsValue::from_str("ParticleWorker doesn't support sprites"));
+        }
+        let offscreen = canvas.transfer_control_to_offscreen()?;
+        let colors: Array = colors.into_iter().map(JsValue::from).collect();
+        let message = object(&[
+            ("type", "init".into()),
+            ("canvas", offscreen.clone().into()),
+            ("shape", (shape as u8).into()),
+            ("colors", colors.into()),
+            ("size", size.into()),
+        ])?;
+        worker.post_message_with_transfer(&message, &Array::of1(&offscreen))?;
+
+        let state = Rc::new(RefCell::new(WorkerState {
+            worker,
+            commands: Vec::new(),
+            ticker_id: None,
+            count: 0,
+            positions: None,
+            on_frame: None,
+        }));
+
+        let listener_state = Rc::clone(&state);
+        let listener = Closure::wrap(Box::new(move |event: MessageEvent| {
+            let data = event.data();
+            let kind = Reflect::get(&data, &"type".into()).ok();
+            if kind.and_then(|k| k.as_string()).as_deref() != Some("frame") {
+                return;
+            }
+            let count = Reflect::get(&data, &"count".into())
+                .ok()
+                .and_then(|c| c.as_f64())
+                .unwrap_or(0.0);
+            let positions = Reflect::get(&data, &"particles".into())
+                .ok()
+                .and_then(|p| p.dyn_into::<Float32Array>().ok());
+
+            let mut s = listener_state.borrow_mut();
+            s.count = count as u32;
+            s.positions = positions.clone();
+            let callback = s.on_frame.clone();
+            drop(s);
+            if let (Some(callback), Some(positions)) = (callback, positions) {
+                let _ = callback.call2(&JsValue::NULL, &count.into(), &positions);
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        state
+            .borrow()
+            .worker
+            .set_onmessage(Some(listener.as_ref().unchecked_ref()));
+
+        Ok(ParticleWorker {
+            state,
+            _listener: listener,
+        })
+    }
+
+    /// Emit `count` particles at (x, y)
+    #[wasm_bindgen]
+    pub fn burst(&mut self, x: f64, y: f64, count: usize) -> Result<(), JsValue> {
+        self.send(&[BURST, x, y, count as f64])
+    }
+
+    /// Where continuously emitted particles start
+    #[wasm_bindgen(js_name = setOrigin)]
+    pub fn set_origin(&mut self, x: f64, y: f64) -> Result<(), JsValue> {
+        self.send(&[ORIGIN, x, y])
+    }
+
+    /// Start continuous emission in the worker
+    #[wasm_bindgen]
+    pub fn start(&mut self) -> Result<(), JsValue> {
+        self.send(&[START])
+    }
+
+    #[wasm_bindgen]
+    pub fn stop(&mut self) -> Result<(), JsValue> {
+        self.send(&[STOP])
+    }
+
+    #[wasm_bindgen]
+    pub fn clear(&mut self) -> Result<(), JsValue> {
+        self.send(&[CLEAR])
+    }
+
+    /// Move an attractor or repeller the worker script added, e.g. to follow
+    /// the pointer
+    #[wasm_bindgen(js_name = moveForce)]
+    pub fn move_force(&mut self, id: u32, x: f64, y: f64) -> Result<(), JsValue> {
+        self.send(&[MOVE_FORCE, id as f64, x, y])
+    }
+
+    /// Resize the canvas's drawing buffer, e.g. after the page layout
+    /// changed its CSS size
+    #[wasm_bindgen]
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), JsValue> {
+        self.send(&[RESIZE, width as f64, height as f64])
+    }
+
+    /// Send queued commands now instead of on the next frame
+    #[wasm_bindgen]
+    pub fn flush(&mut self) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        state.stop();
+        state.flush()
+    }
+
+    /// Called with `(count, positions)` for each frame the worker draws;
+    /// positions is a Float32Array of x, y pairs
+    #[wasm_bindgen(js_name = onFrame)]
+    pub fn on_frame(&mut self, callback: Option<Function>) {
+        self.state.borrow_mut().on_frame = callback;
+    }
+
+    /// Particles alive in the worker's latest frame
+    #[wasm_bindgen(getter, js_name = particleCount)]
+    pub fn particle_count(&self) -> u32 {
+        self.state.borrow().count
+    }
+
+    /// x, y pairs from the worker's latest frame
+    #[wasm_bindgen]
+    pub fn positions(&self) -> Float32Array {
+        match self.state.borrow().positions {
+            Some(ref positions) => positions.clone(),
+            None => Float32Array::new_with_length(0),
+        }
+    }
+
+    /// Stop the worker; this handle does nothing afterwards
+    #[wasm_bindgen]
+    pub fn terminate(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.stop();
+        state.commands.clear();
+        state.worker.terminate();
+    }
+}
+
+impl ParticleWorker {
+    /// Queue a command, flushing once per frame
+    fn send(&mut self, command: &[f64]) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        state.commands.extend_from_slice(command);
+        if state.ticker_id.is_some() {
+            return Ok(());
+        }
+
+        let tick_state = Rc::clone(&self.state);
+        state.ticker_id = Some(ticker::subscribe(Box::new(move |_now, _dt| {
+            let mut s = tick_state.borrow_mut();
+            s.ticker_id = None;
+            let _ = s.flush();
+            false
+        }))?);
+        Ok(())
+    }
+}
+
+impl Drop for ParticleWorker {
+    fn drop(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.stop();
+        state.worker.set_onmessage(None);
+    }
+}
+
+// ============================================================================
+// WORKER SIDE
+// ============================================================================
+
+struct HostState {
+    emitter: ParticleEmitter,
+    scope: DedicatedWorkerGlobalScope,
+    ticker_id: Option<u32>,
+    // Particles in the last reported frame, so one empty frame is still sent
+    reported: usize,
+}
+
+impl HostState {
+    fn apply(&mut self, commands: &[f64]) -> Result<(), JsValue> {
+        let mut values = commands.iter().copied();
+        while let Some(op) = values.next() {
+            let mut arg = || values.next().unwrap_or(0.0);
+            match op {
+                BURST => {
+                    let (x, y, count) = (arg(), arg(), arg());
+                    self.emitter.emit_burst_at(x, y, count.max(0.0) as usize);
+                }
+                ORIGIN => {
+                    let (x, y) = (arg(), arg());
+                    self.emitter.set_origin(x, y);
+                }
+                START => self.emitter.start(),
+                STOP => self.emitter.stop(),
+                CLEAR => self.emitter.clear(),
+                MOVE_FORCE => {
+                    let (id, x, y) = (arg(), arg(), arg());
+                    self.emitter.move_force(id as u32, x, y);
+                }
+                RESIZE => {
+                    let (width, height) = (arg(), arg());
+                    self.emitter.resize_canvas(width as u32, height as u32);
+                }
+                _ => {
+                    return Err(JsValue::from_str(&format!(
+                        "Unknown particle worker command {}",
+                        op
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Post the frame's particle count and positions back to the page
+    fn report(&mut self) -> Result<(), JsValue> {
+        let positions = self.emitter.positions();
+        let count = positions.len() / 2;
+        if count == 0 && self.reported == 0 {
+            return Ok(());
+        }
+        self.reported = count;
+
+        let particles = Float32Array::from(&positions[..]);
+        let message = object(&[
+            ("type", "frame".into()),
+            ("count", count.into()),
+            ("particles", particles.clone().into()),
+        ])?;
+        self.scope
+            .post_message_with_transfer(&message, &Array::of1(&particles.buffer()))
+    }
+
+    fn stop(&mut self) {
+        if let Some(id) = self.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+/// Runs a ParticleEmitter inside a dedicated worker for a ParticleWorker on
+/// the page: draws into the transferred canvas, applies the page's
+/// commands and reports each frame back. Configure the emitter (forces,
+/// lifetime curves, emission rate...) before or after creating the host.
+#[wasm_bindgen]
+pub struct ParticleWorkerHost {
+    state: Rc<RefCell<HostState>>,
+}
+
+#[wasm_bindgen]
+impl ParticleWorkerHost {
+    #[wasm_bindgen(constructor)]
+    pub fn new(emitter: ParticleEmitter) -> Result<ParticleWorkerHost, JsValue> {
+        let scope = js_sys::global()
+            .dyn_into::<DedicatedWorkerGlobalScope>()
+            .map_err(|_| JsValue::from_str("ParticleWorkerHost must run in a dedicated worker"))?;
+        Ok(ParticleWorkerHost {
+            state: Rc::new(RefCell::new(HostState {
+                emitter,
+                scope,
+                ticker_id: None,
+                reported: 0,
+            })),
+        })
+    }
+
+    /// Handle a message from the ParticleWorker. Returns false for messages
+    /// that aren't for the host, so the worker can have its own.
+    #[wasm_bindgen(js_name = handleMessage)]
+    pub fn handle_message(&mut self, data: JsValue) -> Result<bool, JsValue> {
+        let kind = Reflect::get(&data, &"type".into())?.as_string();
+        match kind.as_deref() {
+            Some("init") => {
+                let canvas: OffscreenCanvas = Reflect::get(&data, &"canvas".into())?.dyn_into()?;
+                // Enums cross postMessage as their numbers
+                let circle = ParticleShape::Circle as u8 as f64;
+                let shape = match Reflect::get(&data, &"shape".into())?.as_f64() {
+                    Some(shape) if shape == circle => ParticleShape::Circle,
+                    _ => ParticleShape::Rect,
+                };
+                let colors = Array::from(&Reflect::get(&data, &"colors".into())?)
+                    .iter()
+                    .filter_map(|color| color.as_string())
+                    .collect();
+                let size = Reflect::get(&data, &"size".into())?.as_f64().unwrap_or(8.0);
+                self.state
+                    .borrow_mut()
+                    .emitter
+                    .use_offscreen_canvas(canvas, shape, colors, size)?;
+                self.run()?;
+            }
+            Some("commands") => {
+                let buffer: Float64Array = Reflect::get(&data, &"buffer".into())?.dyn_into()?;
+                self.state.borrow_mut().apply(&buffer.to_vec())?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Stop simulating and drawing until the next init message
+    #[wasm_bindgen]
+    pub fn stop(&mut self) {
+        self.state.borrow_mut().stop();
+    }
+}
+
+impl ParticleWorkerHost {
+    fn run(&mut self) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        if state.ticker_id.is_some() {
+            return Ok(());
+        }
+
+        let tick_state = Rc::clone(&self.state);
+        state.ticker_id = Some(ticker::subscribe(Box::new(move |_now, dt| {
+            let mut s = tick_state.borrow_mut();
+            let _ = s.emitter.update(dt);
+            let _ = s.report();
+            true
+        }))?);
+        Ok(())
+    }
+}
+
+impl Drop for ParticleWorkerHost {
+    fn drop(&mut self) {
+        self.state.borrow_mut().stop();
+    }
+}
+
+/// Plain object from (key, value) pairs
+fn object(entries: &[(&str, JsValue)]) -> Result<JsValue, JsValue> {
+    let object = Object::new();
+    for (key, value) in entries {
+        Reflect::set(&object, &(*key).into(), value)?;
+    }
+    Ok(object.into())
+}
@@ -0,0 +1,392 @@
+use crate::frame_clock;
+use crate::ticker;
+use js_sys::{Function, Object, Reflect};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use wasm_bindgen::prelude::*;
+use web_sys::Performance;
+
+// ============================================================================
+// PERFORMANCE MONITOR - Frame timing and adaptive quality
+// ============================================================================
+//
+// The shared ticker reports how long each frame's callbacks took and how far
+// apart frames were; animation loops add their own work to the next frame.
+// With adaptive quality on, frames over budget lower the quality level and a
+// run of comfortable frames raises it again. Particle emitters and
+// animations read the level to cut their cost.
+
+// Frames kept for stats
+const WINDOW: usize = 120;
+// Net slow frames before dropping a quality level, and net comfortable
+// frames (~3s at 60fps) before raising it
+const DEGRADE_AFTER: i32 = 20;
+const RECOVER_AFTER: i32 = -180;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum QualityLevel {
+    /// Everything animates as configured
+    Full = 0,
+    /// Half the particles; shadows and filters jump to their end values
+    Reduced = 1,
+    /// A quarter of the particles; animations jump to their end
+    Minimal = 2,
+}
+
+/// Which savings a lowered quality level makes
+struct Degradation {
+    particles: bool,
+    effects: bool,
+    snap: bool,
+}
+
+struct FrameSample {
+    // ms since the previous frame
+    interval: f64,
+    // ms spent in engine callbacks
+    work: f64,
+}
+
+struct Monitor {
+    enabled: bool,
+    ticker_id: Option<u32>,
+    performance: Option<Performance>,
+    // ms of work allowed per frame, and the expected ms between frames
+    budget: f64,
+    frame_interval: f64,
+    samples: VecDeque<FrameSample>,
+    total_frames: u64,
+    dropped_frames: u64,
+    // Animation loop work since the last ticker frame
+    pending_work: f64,
+    adaptive: bool,
+    quality: QualityLevel,
+    degradation: Degradation,
+    // Slow frames minus comfortable ones since the last level change
+    strain: i32,
+    on_change: Option<Function>,
+}
+
+thread_local! {
+    static MONITOR: RefCell<Monitor> = RefCell::new(Monitor {
+        enabled: false,
+        ticker_id: None,
+        performance: None,
+        budget: 1000.0 / 60.0,
+        frame_interval: 1000.0 / 60.0,
+        samples: VecDeque::with_capacity(WINDOW),
+        total_frames: 0,
+        dropped_frames: 0,
+        pending_work: 0.0,
+        adaptive: false,
+        quality: QualityLevel::Full,
+        degradation: Degradation {
+            particles: true,
+            effects: true,
+            snap: true,
+        },
+        strain: 0,
+        on_change: None,
+    });
+}
+
+impl Monitor {
+    fn record(&mut self, interval: f64, work: f64) -> Option<QualityLevel> {
+        let work = work + std::mem::take(&mut self.pending_work);
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(FrameSample { interval, work });
+        self.total_frames += 1;
+
+        // A frame twice the expected interval means one was dropped; a
+        // frame rate cap spaces frames out on purpose
+        let expected = match frame_clock::frame_interval() {
+            Some(capped) => capped.max(self.frame_interval),
+            None => self.frame_interval,
+        };
+        let missed = (interval / expected).round() - 1.0;
+        if missed > 0.0 {
+            self.dropped_frames += missed as u64;
+        }
+
+        if !self.adaptive {
+            return None;
+        }
+        let slow = work > self.budget || missed > 0.0;
+        let comfortable = work < self.budget * 0.5 && missed <= 0.0;
+        if slow {
+            self.strain += 1;
+        } else if comfortable {
+            self.strain -= 1;
+        }
+
+        let level = match self.quality {
+            QualityLevel::Full if self.strain >= DEGRADE_AFTER => QualityLevel::Reduced,
+            QualityLevel::Reduced if self.strain >= DEGRADE_AFTER => QualityLevel::Minimal,
+            QualityLevel::Reduced if self.strain <= RECOVER_AFTER => QualityLevel::Full,
+            QualityLevel::Minimal if self.strain <= RECOVER_AFTER => QualityLevel::Reduced,
+            _ => return None,
+        };
+        self.set_quality(level)
+    }
+
+    /// Change level, returning the new one to notify about once MONITOR is
+    /// no longer borrowed
+    fn set_quality(&mut self, level: QualityLevel) -> Option<QualityLevel> {
+        self.strain = 0;
+        if level == self.quality {
+            return None;
+        }
+        self.quality = level;
+        Some(level)
+    }
+
+    fn stop(&mut self) {
+        if let Some(id) = self.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct PerformanceMonitor;
+
+#[wasm_bindgen]
+impl PerformanceMonitor {
+    /// Start timing frames of the shared ticker and animation loops, keeping
+    /// the ticker running while enabled
+    #[wasm_bindgen]
+    pub fn enable() -> Result<(), JsValue> {
+        let performance = Reflect::get(&js_sys::global(), &"performance".into())?
+            .dyn_into::<Performance>()
+            .map_err(|_| JsValue::from_str("performance.now() unavailable"))?;
+
+        let subscribe = MONITOR.with(|m| {
+            let mut m = m.borrow_mut();
+            m.enabled = true;
+            m.performance = Some(performance);
+            m.ticker_id.is_none()
+        });
+        if subscribe {
+            // Keeps frames coming so there's always something to measure
+            let id = ticker::subscribe(Box::new(|_now, _dt| enabled()))?;
+            MONITOR.with(|m| m.borrow_mut().ticker_id = Some(id));
+        }
+        Ok(())
+    }
+
+    /// Stop timing; the quality level stays where it is
+    #[wasm_bindgen]
+    pub fn disable() {
+        MONITOR.with(|m| {
+            let mut m = m.borrow_mut();
+            m.enabled = false;
+            m.stop();
+        });
+    }
+
+    #[wasm_bindgen(js_name = isEnabled)]
+    pub fn is_enabled() -> bool {
+        enabled()
+    }
+
+    /// Clear the stats and counters
+    #[wasm_bindgen]
+    pub fn reset() {
+        MONITOR.with(|m| {
+            let mut m = m.borrow_mut();
+            m.samples.clear();
+            m.total_frames = 0;
+            m.dropped_frames = 0;
+            m.pending_work = 0.0;
+            m.strain = 0;
+        });
+    }
+
+    /// ms of engine work allowed per frame before it counts as slow
+    /// (default 16.7)
+    #[wasm_bindgen(js_name = setBudget)]
+    pub fn set_budget(budget_ms: f64) {
+        MONITOR.with(|m| m.borrow_mut().budget = budget_ms.max(0.1));
+    }
+
+    /// Display refresh rate frames are expected at, for counting dropped
+    /// frames (default 60)
+    #[wasm_bindgen(js_name = setTargetFps)]
+    pub fn set_target_fps(fps: f64) {
+        MONITOR.with(|m| m.borrow_mut().frame_interval = 1000.0 / fps.max(1.0));
+    }
+
+    /// `{ fps, frameTime, worstFrameTime, droppedFrames, totalFrames,
+    /// quality, budget }` over the last 120 frames; times in ms
+    #[wasm_bindgen]
+    pub fn stats() -> Result<JsValue, JsValue> {
+        MONITOR.with(|m| {
+            let m = m.borrow();
+            let count = m.samples.len().max(1) as f64;
+            let interval: f64 = m.samples.iter().map(|s| s.interval).sum::<f64>() / count;
+            let work: f64 = m.samples.iter().map(|s| s.work).sum::<f64>() / count;
+            let worst = m.samples.iter().map(|s| s.work).fold(0.0, f64::max);
+            let fps = if interval > 0.0 {
+                1000.0 / interval
+            } else {
+                0.0
+            };
+
+            let stats = Object::new();
+            Reflect::set(&stats, &"fps".into(), &fps.into())?;
+            Reflect::set(&stats, &"frameTime".into(), &work.into())?;
+            Reflect::set(&stats, &"worstFrameTime".into(), &worst.into())?;
+            Reflect::set(
+                &stats,
+                &"droppedFrames".into(),
+                &(m.dropped_frames as f64).into(),
+            )?;
+            Reflect::set(
+                &stats,
+                &"totalFrames".into(),
+                &(m.total_frames as f64).into(),
+            )?;
+            Reflect::set(&stats, &"quality".into(), &(m.quality as u8).into())?;
+            Reflect::set(&stats, &"budget".into(), &m.budget.into())?;
+            Ok(stats.into())
+        })
+    }
+
+    // ========================================================================
+    // ADAPTIVE QUALITY
+    // ========================================================================
+
+    /// Lower the quality level while frames run over budget or drop, and
+    /// raise it again after a few seconds of comfortable frames. Needs
+    /// enable() to see frames.
+    #[wasm_bindgen(js_name = setAdaptive)]
+    pub fn set_adaptive(adaptive: bool) {
+        MONITOR.with(|m| {
+            let mut m = m.borrow_mut();
+            m.adaptive = adaptive;
+            m.strain = 0;
+        });
+    }
+
+    /// Choose which savings lower levels make: fewer particles, shadows and
+    /// filters jumping to their end values, and (Minimal only) animations
+    /// jumping to their end. All are on by default.
+    #[wasm_bindgen(js_name = setDegradation)]
+    pub fn set_degradation(particles: bool, effects: bool, snap: bool) {
+        MONITOR.with(|m| {
+            m.borrow_mut().degradation = Degradation {
+                particles,
+                effects,
+                snap,
+            }
+        });
+    }
+
+    /// Set the level directly. With adaptive quality on, frame timing keeps
+    /// adjusting it from here.
+    #[wasm_bindgen(js_name = setQuality)]
+    pub fn set_quality(level: QualityLevel) {
+        let changed = MONITOR.with(|m| m.borrow_mut().set_quality(level));
+        notify(changed);
+    }
+
+    #[wasm_bindgen]
+    pub fn quality() -> QualityLevel {
+        MONITOR.with(|m| m.borrow().quality)
+    }
+
+    /// Called with the new level (0-2) whenever it changes
+    #[wasm_bindgen(js_name = onQualityChange)]
+    pub fn on_quality_change(callback: Option<Function>) {
+        MONITOR.with(|m| m.borrow_mut().on_change = callback);
+    }
+}
+
+fn notify(changed: Option<QualityLevel>) {
+    let Some(level) = changed else {
+        return;
+    };
+    let callback = MONITOR.with(|m| m.borrow().on_change.clone());
+    if let Some(callback) = callback {
+        let _ = callback.call1(&JsValue::NULL, &(level as u8).into());
+    }
+}
+
+#[inline]
+pub(crate) fn enabled() -> bool {
+    MONITOR.with(|m| m.borrow().enabled)
+}
+
+/// Timestamp to pass to frame_end or add_work, None while disabled
+pub(crate) fn frame_start() -> Option<f64> {
+    MONITOR.with(|m| {
+        let m = m.borrow();
+        m.performance
+            .as_ref()
+            .filter(|_| m.enabled)
+            .map(|p| p.now())
+    })
+}
+
+/// Record a ticker frame `interval_ms` after the previous one, whose
+/// callbacks started at `started`. Frames with no interval to judge, the
+/// first after an idle spell or after the page was hidden, aren't recorded.
+pub(crate) fn frame_end(started: Option<f64>, interval_ms: Option<f64>) {
+    let Some(started) = started else {
+        return;
+    };
+    let changed = MONITOR.with(|m| {
+        let mut m = m.borrow_mut();
+        let work = m.performance.as_ref().map_or(0.0, |p| p.now() - started);
+        match interval_ms {
+            Some(interval) if interval > 0.0 => m.record(interval, work),
+            _ => None,
+        }
+    });
+    notify(changed);
+}
+
+/// Work done outside the ticker (animation frame loops) since `started`,
+/// counted in the next ticker frame
+pub(crate) fn add_work(started: Option<f64>) {
+    let Some(started) = started else {
+        return;
+    };
+    MONITOR.with(|m| {
+        let mut m = m.borrow_mut();
+        let work = m.performance.as_ref().map_or(0.0, |p| p.now() - started);
+        m.pending_work += work;
+    });
+}
+
+/// Share of their maximum particle count emitters may use
+pub(crate) fn particle_share() -> f64 {
+    MONITOR.with(|m| {
+        let m = m.borrow();
+        match m.quality {
+            _ if !m.degradation.particles => 1.0,
+            QualityLevel::Full => 1.0,
+            QualityLevel::Reduced => 0.5,
+            QualityLevel::Minimal => 0.25,
+        }
+    })
+}
+
+/// Whether shadows and filters should jump to their end values
+pub(crate) fn skip_effects() -> bool {
+    MONITOR.with(|m| {
+        let m = m.borrow();
+        m.degradation.effects && m.quality >= QualityLevel::Reduced
+    })
+}
+
+/// Whether animations should jump to their end
+pub(crate) fn snap_to_end() -> bool {
+    MONITOR.with(|m| {
+        let m = m.borrow();
+        m.degradation.snap && m.quality == QualityLevel::Minimal
+    })
+}
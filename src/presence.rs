@@ -0,0 +1,513 @@
+use crate::flip::{self, FlipConfig};
+use crate::measure::{self, Rect};
+use crate::spec::{self, Easing};
+use crate::ticker;
+use crate::types::{AnimateConfig, PropertyType};
+use crate::{migration, Animation, AnimationState};
+use js_sys::{Function, Promise};
+use serde::Deserialize;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{Element, HtmlElement, Node};
+
+// ============================================================================
+// PRESENCE - Enter/exit choreography for a container's children
+// ============================================================================
+
+// Scale children grow from when entering and shrink to when exiting
+const PRESENCE_SCALE: f64 = 0.92;
+
+// Inline styles that hold an exiting child where it was, out of flow
+const PIN_PROPERTIES: [&str; 8] = [
+    "position",
+    "left",
+    "top",
+    "width",
+    "height",
+    "margin",
+    "box-sizing",
+    "pointer-events",
+];
+
+/// Animates the children of a container across a DOM update: new children fade
+/// and scale in, removed ones fade out before they're really gone, and the
+/// rest FLIP into their new places
+#[wasm_bindgen]
+pub struct ListTransition {
+    container: Element,
+    config: FlipConfig,
+    animations: Vec<Rc<RefCell<crate::Animation>>>,
+    // Removed children put back while they fade out; not part of the list
+    exiting: Rc<RefCell<Vec<Element>>>,
+}
+
+#[wasm_bindgen]
+impl ListTransition {
+    /// Config as for Flip.play: `{ duration: 300, easing: "ease-out", spring: false }`
+    #[wasm_bindgen(constructor)]
+    pub fn new(container: Element, config: JsValue) -> Result<ListTransition, JsValue> {
+        Ok(ListTransition {
+            container,
+            config: FlipConfig::parse(config)?,
+            animations: Vec::new(),
+            exiting: Rc::new(RefCell::new(Vec::new())),
+        })
+    }
+
+    /// Call `mutate` to add, remove or reorder children, then animate the
+    /// difference. Removed children are put back out of flow until their exit
+    /// animation finishes. Returns how many children animate.
+    #[wasm_bindgen]
+    pub fn update(&mut self, mutate: Function) -> Result<usize, JsValue> {
+        self.stop()?;
+        let before: Vec<(Element, Rect)> = self
+            .children()
+            .into_iter()
+            .map(|child| {
+                let rect = measure::element_rect(&child);
+                (child, rect)
+            })
+            .collect();
+
+        mutate.call0(&JsValue::NULL)?;
+        // Measure the new layout without transforms left by an earlier update
+        let after = self.children();
+        for child in &after {
+            if let Some(html) = child.dyn_ref::<HtmlElement>() {
+                html.style().remove_property("transform")?;
+            }
+        }
+
+        for child in &after {
+            let animation = match before.iter().find(|(c, _)| c == child) {
+                Some((_, first)) => flip::flip_from(child.clone(), first, &self.config)?,
+                None => Some(self.enter(child)?),
+            };
+            self.animations.extend(animation);
+        }
+        for (child, rect) in before {
+            if !after.contains(&child) {
+                let animation = self.exit(child, &rect)?;
+                self.animations.push(animation);
+            }
+        }
+
+        Ok(self.animations.len())
+    }
+
+    /// Remove `element` from the container with an exit animation
+    #[wasm_bindgen]
+    pub fn remove(&mut self, element: Element) -> Result<usize, JsValue> {
+        let remove = Closure::once_into_js(move || element.remove());
+        self.update(remove.unchecked_into())
+    }
+
+    /// Stop running animations where they are; exiting children are removed,
+    /// unpinned so they can be put back
+    #[wasm_bindgen]
+    pub fn stop(&mut self) -> Result<(), JsValue> {
+        for animation in self.animations.drain(..) {
+            animation.borrow_mut().stop()?;
+        }
+        for child in self.exiting.borrow_mut().drain(..) {
+            unpin(&child)?;
+            child.remove();
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter, js_name = isRunning)]
+    pub fn is_running(&self) -> bool {
+        self.animations
+            .iter()
+            .any(|a| a.borrow().get_state() == AnimationState::Running)
+    }
+}
+
+impl ListTransition {
+    /// Animations started by the last update, for a Choreographer to coordinate
+    pub(crate) fn animations(&self) -> &[Rc<RefCell<crate::Animation>>] {
+        &self.animations
+    }
+
+    /// The container's children, less those still fading out
+    fn children(&self) -> Vec<Element> {
+        let exiting = self.exiting.borrow();
+        children(&self.container)
+            .into_iter()
+            .filter(|child| !exiting.contains(child))
+            .collect()
+    }
+
+    fn enter(&self, child: &Element) -> Result<Rc<RefCell<crate::Animation>>, JsValue> {
+        center_origin(child)?;
+        flip::animate(
+            child.clone(),
+            &[
+                (PropertyType::Opacity, 0.0, 1.0),
+                (PropertyType::Scale, PRESENCE_SCALE, 1.0),
+            ],
+            &self.config,
+        )
+    }
+
+    /// Put a removed child back, pinned where it was, and fade it out
+    fn exit(&self, child: Element, rect: &Rect) -> Result<Rc<RefCell<crate::Animation>>, JsValue> {
+        if let Some(html) = child.dyn_ref::<HtmlElement>() {
+            let style = html.style();
+            style.set_property("position", "fixed")?;
+            style.set_property("left", &format!("{}px", rect.x))?;
+            style.set_property("top", &format!("{}px", rect.y))?;
+            style.set_property("width", &format!("{}px", rect.width))?;
+            style.set_property("height", &format!("{}px", rect.height))?;
+            style.set_property("margin", "0")?;
+            style.set_property("box-sizing", "border-box")?;
+            style.set_property("pointer-events", "none")?;
+            style.remove_property("transform")?;
+        }
+        self.container.append_child(&child)?;
+        self.exiting.borrow_mut().push(child.clone());
+        center_origin(&child)?;
+
+        let animation = flip::animate(
+            child.clone(),
+            &[
+                (PropertyType::Opacity, 1.0, 0.0),
+                (PropertyType::Scale, 1.0, PRESENCE_SCALE),
+            ],
+            &self.config,
+        )?;
+
+        // Really remove the child once it has faded out, unless stop() already has
+        let watched = Rc::clone(&animation);
+        let exiting = Rc::clone(&self.exiting);
+        ticker::subscribe(Box::new(move |_now, _dt| {
+            if watched.borrow().get_state() != AnimationState::Completed {
+                return true;
+            }
+            let mut exiting = exiting.borrow_mut();
+            if let Some(index) = exiting.iter().position(|c| *c == child) {
+                exiting.swap_remove(index);
+                let _ = unpin(&child);
+                child.remove();
+            }
+            false
+        }))?;
+        Ok(animation)
+    }
+}
+
+fn children(container: &Element) -> Vec<Element> {
+    let children = container.children();
+    (0..children.length())
+        .filter_map(|i| children.item(i))
+        .collect()
+}
+
+/// Clear the styles exit() pinned `element` with
+fn unpin(element: &Element) -> Result<(), JsValue> {
+    if let Some(html) = element.dyn_ref::<HtmlElement>() {
+        let style = html.style();
+        for property in PIN_PROPERTIES {
+            style.remove_property(property)?;
+        }
+    }
+    Ok(())
+}
+
+fn center_origin(element: &Element) -> Result<(), JsValue> {
+    if let Some(html) = element.dyn_ref::<HtmlElement>() {
+        html.style().set_property("transform-origin", "50% 50%")?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// EXIT ANIMATIONS - Play out before leaving the DOM
+// ============================================================================
+
+/// What happens to an element once its exit animation has played
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum AfterExit {
+    #[default]
+    Remove,
+    /// `display: none`, leaving it in place
+    Hide,
+}
+
+/// Timing for an enter or exit; the rest of the object is an animate() config
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct MotionConfig {
+    duration: Option<f64>,
+    easing: Option<String>,
+    delay: Option<f64>,
+    then: AfterExit,
+}
+
+impl MotionConfig {
+    fn parse(config: &JsValue, what: &str) -> Result<MotionConfig, JsValue> {
+        let invalid = |e| JsValue::from_str(&format!("Invalid {} config: {:?}", what, e));
+        // The animated properties are checked now rather than on first play
+        let _: AnimateConfig =
+            serde_wasm_bindgen::from_value(migration::migrate(config.clone())?).map_err(invalid)?;
+        serde_wasm_bindgen::from_value(config.clone()).map_err(invalid)
+    }
+
+    /// An animation to `config` from wherever the element is now
+    fn animation(&self, element: &Element, config: &JsValue) -> Result<Animation, JsValue> {
+        let mut animation = Animation::new(element.clone())?;
+        animation.configure(config.clone())?;
+        if let Some(duration) = self.duration {
+            animation.duration = duration.max(0.0);
+        }
+        if let Some(easing) = &self.easing {
+            match spec::parse_easing(easing)? {
+                Easing::Curve(curve) => {
+                    animation.bezier = Some(*curve);
+                    animation.use_spring = false;
+                }
+                Easing::Spring => animation.use_spring = true,
+            }
+        }
+        animation.delay = self.delay.unwrap_or(0.0).max(0.0);
+        Ok(animation)
+    }
+}
+
+fn after_exit(element: &Element, then: AfterExit) {
+    match then {
+        AfterExit::Remove => element.remove(),
+        AfterExit::Hide => {
+            if let Some(html) = element.dyn_ref::<HtmlElement>() {
+                let _ = html.style().set_property("display", "none");
+            }
+        }
+    }
+}
+
+/// A started enter or exit, which the next one interrupts
+struct Playing {
+    animation: Rc<RefCell<Animation>>,
+    interrupted: Rc<Cell<bool>>,
+}
+
+impl Playing {
+    /// Start `animation` and call `done` when it completes, unless it's
+    /// interrupted first. The promise resolves true on completion and false
+    /// on interruption.
+    fn start(
+        animation: Animation,
+        done: impl FnOnce() + 'static,
+    ) -> Result<(Playing, Promise), JsValue> {
+        let animation = Rc::clone(&animation.start()?.animation);
+        let interrupted = Rc::new(Cell::new(false));
+
+        let mut resolver = None;
+        let promise = Promise::new(&mut |resolve, _reject| resolver = Some(resolve));
+        let settle = move |completed: bool| {
+            if let Some(resolve) = &resolver {
+                let _ = resolve.call1(&JsValue::NULL, &JsValue::from_bool(completed));
+            }
+        };
+
+        let watched = Rc::clone(&animation);
+        let stopped = Rc::clone(&interrupted);
+        let mut done = Some(done);
+        ticker::subscribe(Box::new(move |_now, _dt| {
+            if stopped.get() {
+                settle(false);
+                return false;
+            }
+            if watched.borrow().get_state() != AnimationState::Completed {
+                return true;
+            }
+            if let Some(done) = done.take() {
+                done();
+            }
+            settle(true);
+            false
+        }))?;
+
+        Ok((
+            Playing {
+                animation,
+                interrupted,
+            },
+            promise,
+        ))
+    }
+
+    /// Stop where it is, without its completion taking effect
+    fn interrupt(self) -> Result<(), JsValue> {
+        self.interrupted.set(true);
+        self.animation.borrow_mut().stop()
+    }
+
+    fn is_running(&self) -> bool {
+        !self.interrupted.get() && self.animation.borrow().get_state() == AnimationState::Running
+    }
+}
+
+fn play_exit(element: &Element, config: &JsValue) -> Result<(Playing, Promise), JsValue> {
+    let timing = MotionConfig::parse(config, "exit")?;
+    let animation = timing.animation(element, config)?;
+    let leaving = element.clone();
+    let then = timing.then;
+    Playing::start(animation, move || after_exit(&leaving, then))
+}
+
+/// Animate `element` out with an animate() config that may also carry
+/// `duration`, `easing` and `delay`, then remove it from the DOM, or with
+/// `then: "hide"` set `display: none`. Resolves true once it's gone.
+#[wasm_bindgen(js_name = animateOut)]
+pub fn animate_out(element: Element, config: JsValue) -> Result<Promise, JsValue> {
+    Ok(play_exit(&element, &config)?.1)
+}
+
+/// An element's enter and exit animations as a pair: hiding it plays the exit
+/// before it leaves the DOM, and showing it again mid-exit turns it around
+/// from where it got to
+#[wasm_bindgen]
+pub struct Presence {
+    element: Element,
+    enter: JsValue,
+    exit: JsValue,
+    present: bool,
+    // Where the element goes back in once removed
+    place: Option<(Node, Option<Node>)>,
+    // Inline display to restore once hidden
+    display: Option<String>,
+    playing: Option<Playing>,
+}
+
+#[wasm_bindgen]
+impl Presence {
+    /// `enter` and `exit` are configs as for animateOut, `enter` being the
+    /// shown state; `then` on `exit` picks removing or hiding. With
+    /// `present` false the element starts out in its exit state, gone.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        element: Element,
+        enter: JsValue,
+        exit: JsValue,
+        present: Option<bool>,
+    ) -> Result<Presence, JsValue> {
+        MotionConfig::parse(&enter, "enter")?;
+        let timing = MotionConfig::parse(&exit, "exit")?;
+
+        let mut presence = Presence {
+            element,
+            enter,
+            exit,
+            present: true,
+            place: None,
+            display: None,
+            playing: None,
+        };
+        if !present.unwrap_or(true) {
+            presence.leave();
+            timing
+                .animation(&presence.element, &presence.exit)?
+                .set_fraction_complete(1.0)?;
+            after_exit(&presence.element, timing.then);
+        }
+        Ok(presence)
+    }
+
+    /// Put the element back and play the enter animation, turning around an
+    /// exit in progress. Resolves true once entered, or false if hidden
+    /// again first.
+    #[wasm_bindgen]
+    pub fn show(&mut self) -> Result<Promise, JsValue> {
+        self.stop()?;
+        self.present = true;
+
+        if let Some((parent, next)) = self.place.take() {
+            if self.element.parent_node().is_none() {
+                // The sibling it sat before may have gone since
+                let next = next.filter(|n| n.parent_node().as_ref() == Some(&parent));
+                parent.insert_before(&self.element, next.as_ref())?;
+            }
+        }
+        if let Some(display) = self.display.take() {
+            if let Some(html) = self.element.dyn_ref::<HtmlElement>() {
+                if display.is_empty() {
+                    html.style().remove_property("display")?;
+                } else {
+                    html.style().set_property("display", &display)?;
+                }
+            }
+        }
+
+        let timing = MotionConfig::parse(&self.enter, "enter")?;
+        let animation = timing.animation(&self.element, &self.enter)?;
+        let (playing, promise) = Playing::start(animation, || {})?;
+        self.playing = Some(playing);
+        Ok(promise)
+    }
+
+    /// Play the exit animation, then remove or hide the element. Resolves
+    /// true once it's gone, or false if shown again first.
+    #[wasm_bindgen]
+    pub fn hide(&mut self) -> Result<Promise, JsValue> {
+        self.stop()?;
+        self.present = false;
+        self.leave();
+
+        let (playing, promise) = play_exit(&self.element, &self.exit)?;
+        self.playing = Some(playing);
+        Ok(promise)
+    }
+
+    /// Show or hide to match `present`
+    #[wasm_bindgen]
+    pub fn set(&mut self, present: bool) -> Result<Promise, JsValue> {
+        if present {
+            self.show()
+        } else {
+            self.hide()
+        }
+    }
+
+    /// Stop a running enter or exit where it is; a stopped exit doesn't
+    /// remove the element
+    #[wasm_bindgen]
+    pub fn stop(&mut self) -> Result<(), JsValue> {
+        match self.playing.take() {
+            Some(playing) => playing.interrupt(),
+            None => Ok(()),
+        }
+    }
+
+    /// Shown, or entering
+    #[wasm_bindgen(getter, js_name = isPresent)]
+    pub fn is_present(&self) -> bool {
+        self.present
+    }
+
+    #[wasm_bindgen(getter, js_name = isRunning)]
+    pub fn is_running(&self) -> bool {
+        self.playing.as_ref().is_some_and(Playing::is_running)
+    }
+}
+
+impl Presence {
+    /// Remember where the element is and how it's displayed, to come back to
+    fn leave(&mut self) {
+        if let Some(parent) = self.element.parent_node() {
+            self.place = Some((parent, self.element.next_sibling()));
+        }
+        if let Some(html) = self.element.dyn_ref::<HtmlElement>() {
+            let display = html
+                .style()
+                .get_property_value("display")
+                .unwrap_or_default();
+            if display != "none" {
+                self.display = Some(display);
+            }
+        }
+    }
+}
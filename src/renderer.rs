@@ -0,0 +1,240 @@
+use js_sys::Function;
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use web_sys::{CssStyleDeclaration, Element, HtmlElement, SvgElement};
+
+// ============================================================================
+// RENDERER - Where animated values end up (DOM, canvas, WebGL, custom)
+// ============================================================================
+
+/// Receives the CSS-formatted output of an animation frame.
+/// `property` is a kebab-case CSS property or SVG attribute name.
+pub trait Renderer {
+    fn apply(&mut self, property: &str, value: &str) -> Result<(), JsValue>;
+
+    /// Called once after every property of a frame has been applied
+    fn flush(&mut self) -> Result<(), JsValue> {
+        Ok(())
+    }
+}
+
+/// SVG presentation attributes written as attributes rather than inline style
+const SVG_ATTRIBUTES: [&str; 5] = [
+    "stroke-dashoffset",
+    "stroke-width",
+    "fill-opacity",
+    "stroke-opacity",
+    "d",
+];
+
+/// Default renderer: batches a frame's writes and commits them to inline
+/// style (or SVG attributes) on flush, keeping only the last write per property
+pub struct DomRenderer {
+    element: Element,
+    pending: Vec<(String, String)>,
+}
+
+impl DomRenderer {
+    pub fn new(element: Element) -> Self {
+        Self {
+            element,
+            pending: Vec::with_capacity(16),
+        }
+    }
+
+    fn style(&self) -> Option<CssStyleDeclaration> {
+        inline_style(&self.element)
+    }
+}
+
+fn inline_style(element: &Element) -> Option<CssStyleDeclaration> {
+    if let Some(html) = element.dyn_ref::<HtmlElement>() {
+        Some(html.style())
+    } else {
+        element.dyn_ref::<SvgElement>().map(|svg| svg.style())
+    }
+}
+
+fn is_attribute(element: &Element, property: &str) -> bool {
+    element.dyn_ref::<SvgElement>().is_some() && SVG_ATTRIBUTES.contains(&property)
+}
+
+/// Inline values of `properties` on `element` as a DomRenderer would write
+/// them, None where unset
+pub(crate) fn read_inline(
+    element: &Element,
+    properties: &[String],
+) -> Vec<(String, Option<String>)> {
+    let style = inline_style(element);
+    properties
+        .iter()
+        .map(|property| {
+            let value = if is_attribute(element, property) {
+                element.get_attribute(property)
+            } else {
+                style
+                    .as_ref()
+                    .and_then(|style| style.get_property_value(property).ok())
+                    .filter(|value| !value.is_empty())
+            };
+            (property.clone(), value)
+        })
+        .collect()
+}
+
+/// Put back values from read_inline, removing the ones that were unset
+pub(crate) fn restore_inline(
+    element: &Element,
+    values: &[(String, Option<String>)],
+) -> Result<(), JsValue> {
+    let style = inline_style(element);
+    for (property, value) in values {
+        if is_attribute(element, property) {
+            match value {
+                Some(value) => element.set_attribute(property, value)?,
+                None => element.remove_attribute(property)?,
+            }
+        } else if let Some(ref style) = style {
+            match value {
+                Some(value) => style.set_property(property, value)?,
+                None => {
+                    style.remove_property(property)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Renderer for DomRenderer {
+    fn apply(&mut self, property: &str, value: &str) -> Result<(), JsValue> {
+        match self.pending.iter_mut().find(|(p, _)| p == property) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => self.pending.push((property.to_string(), value.to_string())),
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), JsValue> {
+        let is_svg = self.element.dyn_ref::<SvgElement>().is_some();
+        let style = self.style();
+
+        for (property, value) in self.pending.drain(..) {
+            if is_svg && SVG_ATTRIBUTES.contains(&property.as_str()) {
+                self.element.set_attribute(&property, &value).map_err(|_| {
+                    JsValue::from_str(&format!("Failed to set SVG attribute {}", property))
+                })?;
+            } else if let Some(ref style) = style {
+                style
+                    .set_property(&property, &value)
+                    .map_err(|_| JsValue::from_str(&format!("Failed to set {}", property)))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Adapter for a JS object implementing `apply(property, value)` and optionally `flush()`
+pub struct JsRenderer {
+    target: JsValue,
+    apply: Function,
+    flush: Option<Function>,
+}
+
+impl JsRenderer {
+    pub fn from_js(target: JsValue) -> Result<Self, JsValue> {
+        let apply = js_sys::Reflect::get(&target, &"apply".into())?
+            .dyn_into::<Function>()
+            .map_err(|_| {
+                JsValue::from_str("Renderer must have an apply(property, value) method")
+            })?;
+        let flush = js_sys::Reflect::get(&target, &"flush".into())?
+            .dyn_into::<Function>()
+            .ok();
+
+        Ok(Self {
+            target,
+            apply,
+            flush,
+        })
+    }
+}
+
+impl Renderer for JsRenderer {
+    fn apply(&mut self, property: &str, value: &str) -> Result<(), JsValue> {
+        self.apply
+            .call2(&self.target, &property.into(), &value.into())
+            .map(|_| ())
+    }
+
+    fn flush(&mut self) -> Result<(), JsValue> {
+        match self.flush {
+            Some(ref flush) => flush.call0(&self.target).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+// ============================================================================
+// PER-TARGET REGISTRY
+// ============================================================================
+
+thread_local! {
+    static REGISTRY: RefCell<Vec<(Element, JsValue)>> = const { RefCell::new(Vec::new()) };
+}
+
+#[wasm_bindgen]
+pub struct RendererRegistry;
+
+#[wasm_bindgen]
+impl RendererRegistry {
+    /// Use a custom renderer for every Animation created for this element
+    #[wasm_bindgen]
+    pub fn register(element: Element, renderer: JsValue) -> Result<(), JsValue> {
+        // Validate eagerly so mistakes surface here rather than mid-animation
+        JsRenderer::from_js(renderer.clone())?;
+
+        REGISTRY.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            registry.retain(|(e, _)| e != &element);
+            registry.push((element, renderer));
+        });
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn unregister(element: &Element) {
+        REGISTRY.with(|registry| registry.borrow_mut().retain(|(e, _)| e != element));
+    }
+}
+
+/// Renderer for a new animation: the registered one for the element, or the DOM writer
+pub(crate) fn for_element(element: &Element) -> Box<dyn Renderer> {
+    let registered = REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .iter()
+            .find(|(e, _)| e == element)
+            .map(|(_, r)| r.clone())
+    });
+
+    match registered.map(JsRenderer::from_js) {
+        Some(Ok(renderer)) => Box::new(renderer),
+        _ => Box::new(DomRenderer::new(element.clone())),
+    }
+}
+
+/// camelCase style names (e.g. "boxShadow") to the kebab-case setProperty expects
+pub(crate) fn css_name(property: &str) -> String {
+    let mut name = String::with_capacity(property.len() + 4);
+    for ch in property.chars() {
+        if ch.is_ascii_uppercase() {
+            name.push('-');
+            name.push(ch.to_ascii_lowercase());
+        } else {
+            name.push(ch);
+        }
+    }
+    name
+}
@@ -0,0 +1,383 @@
+use crate::gesture::{self, GestureCore, GesturePoint};
+use crate::sequencer::Sequencer;
+use crate::ticker;
+use js_sys::Function;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+// ============================================================================
+// SCRIPT - Timed action runner for demos and showcase loops
+// ============================================================================
+
+#[derive(Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ScriptAction {
+    /// Start (or resume) a registered animation or sequencer
+    Play {
+        target: String,
+    },
+    Pause {
+        target: String,
+    },
+    /// Scrub an animation or sequencer to a fraction
+    Seek {
+        target: String,
+        fraction: f64,
+    },
+    Wait {
+        ms: f64,
+    },
+    /// Replay a pointer path through a registered GestureController and wait for it
+    Gesture {
+        target: String,
+        points: Vec<GesturePoint>,
+    },
+    /// Call a registered JS function
+    Call {
+        target: String,
+    },
+}
+
+/// A function the script calls out to: a Call target or the completion
+/// callback. JS functions in the browser; plain closures in native tests.
+pub trait ScriptCallback: Clone {
+    fn invoke(&self) -> Result<(), JsValue>;
+}
+
+impl ScriptCallback for Function {
+    fn invoke(&self) -> Result<(), JsValue> {
+        self.call0(&JsValue::NULL).map(|_| ())
+    }
+}
+
+pub enum Target<C = Function> {
+    Animation(Rc<RefCell<crate::Animation>>),
+    Sequencer(Sequencer),
+    Gesture(Rc<RefCell<GestureCore>>),
+    Callback(C),
+}
+
+pub struct ScriptState<C = Function> {
+    actions: Vec<ScriptAction>,
+    targets: Vec<(String, Target<C>)>,
+    step: usize,
+    wait_until: f64,
+    repeat_count: i32,
+    current_repeat: i32,
+    gesture_ticker: Option<u32>,
+    running: bool,
+    // Bumped by each start(), so a frame of an earlier run stops driving
+    run_id: u32,
+    completion_callback: Option<C>,
+    // Callbacks due, invoked once the state is no longer borrowed
+    pending: Vec<C>,
+    last_error: Option<JsValue>,
+}
+
+impl<C: ScriptCallback> ScriptState<C> {
+    pub fn new(actions: Vec<ScriptAction>) -> Self {
+        ScriptState {
+            actions,
+            targets: Vec::new(),
+            step: 0,
+            wait_until: 0.0,
+            repeat_count: 1,
+            current_repeat: 0,
+            gesture_ticker: None,
+            running: false,
+            run_id: 0,
+            completion_callback: None,
+            pending: Vec::new(),
+            last_error: None,
+        }
+    }
+
+    pub fn register(&mut self, name: String, target: Target<C>) {
+        self.targets.retain(|(n, _)| n != &name);
+        self.targets.push((name, target));
+    }
+
+    /// Rewind and start running; returns the id tick() is driven with
+    pub fn start(&mut self) -> u32 {
+        self.step = 0;
+        self.wait_until = 0.0;
+        self.current_repeat = 0;
+        self.running = true;
+        self.last_error = None;
+        self.pending.clear();
+        self.run_id = self.run_id.wrapping_add(1);
+        self.run_id
+    }
+
+    pub fn abort(&mut self) {
+        self.running = false;
+        self.pending.clear();
+        self.stop_gesture();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    fn fail(&mut self, error: JsValue) {
+        self.abort();
+        self.last_error = Some(error);
+    }
+
+    fn target(&mut self, name: &str) -> Result<&mut Target<C>, JsValue> {
+        self.targets
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .map(|(_, t)| t)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown script target: {}", name)))
+    }
+
+    /// Run actions until one needs to wait or calls out; calls are queued in
+    /// `pending` so they run before the next action. Returns false when the
+    /// script is done.
+    fn advance(&mut self, now: f64) -> Result<bool, JsValue> {
+        let mut wrapped = false;
+        while self.running && self.pending.is_empty() && now >= self.wait_until {
+            if self.step >= self.actions.len() {
+                // A loop with no waits restarts at most once per frame
+                if wrapped {
+                    break;
+                }
+                wrapped = true;
+                self.current_repeat += 1;
+                if self.repeat_count >= 0 && self.current_repeat >= self.repeat_count {
+                    self.running = false;
+                    self.pending.extend(self.completion_callback.clone());
+                    return Ok(false);
+                }
+                self.step = 0;
+            }
+
+            let action = self.actions[self.step].clone();
+            self.step += 1;
+            self.execute(action, now)?;
+        }
+        Ok(self.running)
+    }
+
+    fn execute(&mut self, action: ScriptAction, now: f64) -> Result<(), JsValue> {
+        match action {
+            ScriptAction::Play { target } => match self.target(&target)? {
+                Target::Animation(anim) => crate::replay(anim),
+                Target::Sequencer(sequencer) => sequencer.play(),
+                _ => Err(JsValue::from_str(&format!("{} can't be played", target))),
+            },
+            ScriptAction::Pause { target } => match self.target(&target)? {
+                Target::Animation(anim) => anim.borrow_mut().pause(),
+                Target::Sequencer(sequencer) => sequencer.pause(),
+                _ => Err(JsValue::from_str(&format!("{} can't be paused", target))),
+            },
+            ScriptAction::Seek { target, fraction } => match self.target(&target)? {
+                Target::Animation(anim) => anim.borrow_mut().set_fraction_complete(fraction),
+                Target::Sequencer(sequencer) => sequencer.seek_to(fraction),
+                _ => Err(JsValue::from_str(&format!("{} can't be seeked", target))),
+            },
+            ScriptAction::Wait { ms } => {
+                self.wait_until = now + ms.max(0.0);
+                Ok(())
+            }
+            ScriptAction::Gesture { target, points } => {
+                let core = match self.target(&target)? {
+                    Target::Gesture(core) => Rc::clone(core),
+                    _ => {
+                        return Err(JsValue::from_str(&format!(
+                            "{} is not a gesture controller",
+                            target
+                        )))
+                    }
+                };
+                let (id, duration) = gesture::play_synthetic(&core, points)?;
+                self.gesture_ticker = Some(id);
+                self.wait_until = now + duration;
+                Ok(())
+            }
+            ScriptAction::Call { target } => match self.target(&target)? {
+                Target::Callback(callback) => {
+                    let callback = callback.clone();
+                    self.pending.push(callback);
+                    Ok(())
+                }
+                _ => Err(JsValue::from_str(&format!("{} is not a function", target))),
+            },
+        }
+    }
+
+    fn stop_gesture(&mut self) {
+        if let Some(id) = self.gesture_ticker.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+/// One frame of run `run_id`: advance, then invoke what came due with the
+/// state released, so callbacks can abort, rerun or query the script.
+/// Returns whether the run goes on.
+pub fn tick<C: ScriptCallback>(state: &Rc<RefCell<ScriptState<C>>>, run_id: u32, now: f64) -> bool {
+    loop {
+        let (running, pending) = {
+            let mut state = state.borrow_mut();
+            if state.run_id != run_id {
+                return false;
+            }
+            let running = match state.advance(now) {
+                Ok(running) => running,
+                Err(e) => {
+                    state.fail(e);
+                    false
+                }
+            };
+            (running, std::mem::take(&mut state.pending))
+        };
+
+        if pending.is_empty() {
+            return running;
+        }
+        for callback in pending {
+            if let Err(e) = callback.invoke() {
+                let mut state = state.borrow_mut();
+                if state.run_id == run_id && state.running {
+                    state.fail(e);
+                }
+            }
+        }
+        if !running {
+            return false;
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct Script {
+    state: Rc<RefCell<ScriptState>>,
+    ticker_id: Option<u32>,
+}
+
+#[wasm_bindgen]
+impl Script {
+    /// `actions` is a JSON list such as
+    /// `[{ type: "play", target: "card" }, { type: "wait", ms: 500 },
+    ///   { type: "gesture", target: "drawer", points: [{ x, y, t }] },
+    ///   { type: "seek", target: "timeline", fraction: 0.5 }, { type: "call", target: "log" }]`
+    #[wasm_bindgen(constructor)]
+    pub fn new(actions: JsValue) -> Result<Script, JsValue> {
+        let actions: Vec<ScriptAction> = serde_wasm_bindgen::from_value(actions)
+            .map_err(|e| JsValue::from_str(&format!("Invalid script: {:?}", e)))?;
+
+        Ok(Script {
+            state: Rc::new(RefCell::new(ScriptState::new(actions))),
+            ticker_id: None,
+        })
+    }
+
+    // ========================================================================
+    // TARGETS
+    // ========================================================================
+
+    #[wasm_bindgen(js_name = addAnimation)]
+    pub fn add_animation(&mut self, name: String, handle: &crate::AnimationHandle) {
+        self.register(name, Target::Animation(Rc::clone(&handle.animation)));
+    }
+
+    /// The script takes ownership of the sequencer
+    #[wasm_bindgen(js_name = addSequencer)]
+    pub fn add_sequencer(&mut self, name: String, sequencer: Sequencer) {
+        self.register(name, Target::Sequencer(sequencer));
+    }
+
+    #[wasm_bindgen(js_name = addGesture)]
+    pub fn add_gesture(&mut self, name: String, controller: &gesture::GestureController) {
+        self.register(name, Target::Gesture(controller.core()));
+    }
+
+    #[wasm_bindgen(js_name = addCallback)]
+    pub fn add_callback(&mut self, name: String, callback: Function) {
+        self.register(name, Target::Callback(callback));
+    }
+
+    // ========================================================================
+    // PLAYBACK
+    // ========================================================================
+
+    /// Times to run the whole script; negative loops until abort()
+    #[wasm_bindgen]
+    pub fn repeat(&mut self, count: i32) {
+        self.state.borrow_mut().repeat_count = count;
+    }
+
+    #[wasm_bindgen(js_name = onComplete)]
+    pub fn on_complete(&mut self, callback: Function) {
+        self.state.borrow_mut().completion_callback = Some(callback);
+    }
+
+    #[wasm_bindgen]
+    pub fn run(&mut self) -> Result<(), JsValue> {
+        self.abort();
+        let run_id = {
+            let mut state = self.state.borrow_mut();
+            if state.actions.is_empty() {
+                return Ok(());
+            }
+            state.start()
+        };
+
+        let state = Rc::clone(&self.state);
+        self.ticker_id = Some(ticker::subscribe(Box::new(move |now, _dt| {
+            tick(&state, run_id, now)
+        }))?);
+        Ok(())
+    }
+
+    /// Stop after the current action, cancelling any gesture playback
+    #[wasm_bindgen]
+    pub fn abort(&mut self) {
+        if let Some(id) = self.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+        self.state.borrow_mut().abort();
+    }
+
+    #[wasm_bindgen(getter, js_name = isRunning)]
+    pub fn is_running(&self) -> bool {
+        self.state.borrow().is_running()
+    }
+
+    /// Index of the next action to run
+    #[wasm_bindgen(getter, js_name = currentStep)]
+    pub fn current_step(&self) -> usize {
+        self.state.borrow().step
+    }
+
+    /// Error that stopped the last run, if any
+    #[wasm_bindgen(getter, js_name = lastError)]
+    pub fn last_error(&self) -> JsValue {
+        self.state
+            .borrow()
+            .last_error
+            .clone()
+            .unwrap_or(JsValue::UNDEFINED)
+    }
+
+    #[wasm_bindgen(getter, js_name = actionCount)]
+    pub fn action_count(&self) -> usize {
+        self.state.borrow().actions.len()
+    }
+}
+
+impl Script {
+    fn register(&mut self, name: String, target: Target) {
+        self.state.borrow_mut().register(name, target);
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
@@ -0,0 +1,645 @@
+use crate::measure;
+use crate::sequencer::Sequencer;
+use crate::spring::Spring;
+use crate::ticker;
+use crate::{Animation, AnimationHandle};
+use js_sys::Function;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, AddEventListenerOptions, Element, Event, HtmlElement};
+
+// ============================================================================
+// SCROLL TRIGGER - Animations driven by an element's scroll through the page
+// ============================================================================
+//
+// Each trigger maps the window scroll range where its element passes through
+// the viewport onto 0..1 and scrubs an animation or sequencer with it. All
+// triggers share one scroll and resize listener; work happens at most once a
+// frame on the shared ticker.
+
+/// A point on an element or the viewport: a fraction of its height plus px
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Edge {
+    pub fraction: f64,
+    pub offset: f64,
+}
+
+impl Edge {
+    /// `top`, `center`, `bottom`, `25%` or `100px`, optionally followed by
+    /// `+=px` or `-=px`
+    pub fn parse(token: &str) -> Option<Edge> {
+        let (base, offset) = match token.find("+=").or_else(|| token.find("-=")) {
+            Some(i) => {
+                let px = parse_px(&token[i + 2..])?;
+                let sign = if token[i..].starts_with('-') {
+                    -1.0
+                } else {
+                    1.0
+                };
+                (&token[..i], sign * px)
+            }
+            None => (token, 0.0),
+        };
+
+        let edge = match base {
+            "top" => Edge::at(0.0),
+            "center" => Edge::at(0.5),
+            "bottom" => Edge::at(1.0),
+            _ => match base.strip_suffix('%') {
+                Some(percent) => Edge::at(percent.trim().parse::<f64>().ok()? / 100.0),
+                None => Edge {
+                    fraction: 0.0,
+                    offset: parse_px(base)?,
+                },
+            },
+        };
+        Some(Edge {
+            offset: edge.offset + offset,
+            ..edge
+        })
+    }
+
+    fn at(fraction: f64) -> Edge {
+        Edge {
+            fraction,
+            offset: 0.0,
+        }
+    }
+
+    /// Distance from the top of something `size` px tall
+    pub fn resolve(&self, size: f64) -> f64 {
+        size * self.fraction + self.offset
+    }
+}
+
+fn parse_px(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let number = value.strip_suffix("px").unwrap_or(value);
+    number.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+/// Where a trigger starts or ends: when `element` on the trigger element
+/// meets `viewport` on the viewport, e.g. "top 80%"
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollPosition {
+    pub element: Edge,
+    pub viewport: Edge,
+}
+
+impl ScrollPosition {
+    /// "<element edge> <viewport edge>"; a single edge is used for both
+    pub fn parse(spec: &str) -> Option<ScrollPosition> {
+        let mut tokens = spec.split_whitespace();
+        let element = Edge::parse(tokens.next()?)?;
+        let viewport = match tokens.next() {
+            Some(token) => Edge::parse(token)?,
+            None => element,
+        };
+        if tokens.next().is_some() {
+            return None;
+        }
+        Some(ScrollPosition { element, viewport })
+    }
+
+    /// Window scroll position at which this is reached, for an element at
+    /// `element_top` (page coordinates) and `element_height` px tall
+    pub fn scroll_offset(
+        &self,
+        element_top: f64,
+        element_height: f64,
+        viewport_height: f64,
+    ) -> f64 {
+        element_top + self.element.resolve(element_height) - self.viewport.resolve(viewport_height)
+    }
+}
+
+/// Fraction of the way from `start` to `end` at scroll position `scroll`
+pub fn progress(scroll: f64, start: f64, end: f64) -> f64 {
+    if end <= start {
+        return if scroll >= start { 1.0 } else { 0.0 };
+    }
+    ((scroll - start) / (end - start)).clamp(0.0, 1.0)
+}
+
+/// Move `current` toward `target` over `dt` seconds, closing about 98% of
+/// the gap in `lag` seconds; 0 lag jumps straight there
+pub fn smooth_toward(current: f64, target: f64, dt: f64, lag: f64) -> f64 {
+    if lag <= 0.0 {
+        return target;
+    }
+    let next = current + (target - current) * (1.0 - (-4.0 * dt / lag).exp());
+    if (target - next).abs() < 1e-4 {
+        target
+    } else {
+        next
+    }
+}
+
+// Longest spring step; longer frames would let the spring overshoot wildly
+const MAX_SPRING_DT: f64 = 0.032;
+
+/// How the applied progress follows the raw scroll progress. Smoothing
+/// hides the steps of discrete wheel events.
+#[derive(Clone)]
+pub enum Smoothing {
+    /// Close about 98% of the gap in this many seconds; 0 follows exactly
+    Lag(f64),
+    /// Pulled along by a spring, which carries momentum between events
+    Spring(Spring),
+}
+
+impl Smoothing {
+    /// Next applied progress, moving from `current` toward `target`
+    pub fn step(&mut self, current: f64, target: f64, dt: f64) -> f64 {
+        match self {
+            Smoothing::Lag(lag) => smooth_toward(current, target, dt, *lag),
+            Smoothing::Spring(spring) => {
+                spring.update(target, dt.min(MAX_SPRING_DT));
+                if (spring.current - target).abs() < 1e-4 && spring.velocity.abs() < 1e-3 {
+                    spring.reset(target);
+                }
+                spring.current
+            }
+        }
+    }
+
+    /// Whether `current` has caught up with `target` and stopped
+    pub fn settled(&self, current: f64, target: f64) -> bool {
+        match self {
+            Smoothing::Lag(_) => current == target,
+            Smoothing::Spring(spring) => current == target && spring.velocity == 0.0,
+        }
+    }
+
+    /// Jump to `value` without smoothing
+    pub fn reset(&mut self, value: f64) {
+        if let Smoothing::Spring(spring) = self {
+            spring.reset(value);
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SpringConfig {
+    stiffness: Option<f64>,
+    damping: Option<f64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct TriggerConfig {
+    start: Option<String>,
+    end: Option<String>,
+    scrub: Option<f64>,
+    spring: Option<SpringConfig>,
+    pin: Option<bool>,
+    pin_spacing: Option<bool>,
+}
+
+impl TriggerConfig {
+    fn smoothing(&self) -> Smoothing {
+        match &self.spring {
+            Some(spring) => {
+                let defaults = Spring::default();
+                Smoothing::Spring(Spring::new(
+                    spring.stiffness.unwrap_or(defaults.stiffness),
+                    spring.damping.unwrap_or(defaults.damping),
+                ))
+            }
+            None => Smoothing::Lag(self.scrub.unwrap_or(0.0).max(0.0)),
+        }
+    }
+}
+
+enum ScrubTarget {
+    None,
+    Animation(Rc<RefCell<Animation>>),
+    Timeline(Rc<dyn Fn(f64) -> Result<(), JsValue>>),
+}
+
+struct Pin {
+    element: HtmlElement,
+    spacing: bool,
+    // Inline values to put back when the trigger is killed
+    saved_translate: String,
+    saved_margin: String,
+}
+
+struct TriggerState {
+    element: Element,
+    start: ScrollPosition,
+    end: ScrollPosition,
+    smoothing: Smoothing,
+    pin: Option<Pin>,
+    // Window scroll positions where progress is 0 and 1
+    start_scroll: f64,
+    end_scroll: f64,
+    target_progress: f64,
+    progress: f64,
+    target: ScrubTarget,
+    on_update: Option<Function>,
+}
+
+impl TriggerState {
+    fn measure(&mut self, viewport_height: f64) {
+        // Measure without the pin's own offset
+        if let Some(pin) = &self.pin {
+            let style = pin.element.style();
+            let _ = style.remove_property("translate");
+            if pin.spacing {
+                let _ = style.remove_property("margin-bottom");
+            }
+        }
+
+        let rect = measure::page_rect(&self.element);
+        self.start_scroll = self
+            .start
+            .scroll_offset(rect.y, rect.height, viewport_height);
+        self.end_scroll = self.end.scroll_offset(rect.y, rect.height, viewport_height);
+
+        // Push later content down by the pinned distance
+        if let Some(pin) = &self.pin {
+            if pin.spacing {
+                let distance = (self.end_scroll - self.start_scroll).max(0.0);
+                let _ = pin
+                    .element
+                    .style()
+                    .set_property("margin-bottom", &format!("{}px", distance));
+            }
+        }
+    }
+
+    /// Take the progress for a scroll position and move the pin with it
+    fn follow(&mut self, scroll: f64) {
+        self.target_progress = progress(scroll, self.start_scroll, self.end_scroll);
+
+        if let Some(pin) = &self.pin {
+            let distance = (self.end_scroll - self.start_scroll).max(0.0);
+            let offset = (scroll - self.start_scroll).clamp(0.0, distance);
+            let _ = pin
+                .element
+                .style()
+                .set_property("translate", &format!("0 {}px", offset));
+        }
+    }
+
+    /// Follow the scroll position; returns whether the scrub is still
+    /// catching up, and the update callback to call once released
+    fn update(&mut self, scroll: f64, dt: f64) -> (bool, Option<Function>) {
+        self.follow(scroll);
+        let next = self.smoothing.step(self.progress, self.target_progress, dt);
+        let changed = next != self.progress;
+        self.progress = next;
+
+        let moving = !self.smoothing.settled(self.progress, self.target_progress);
+        if !changed {
+            return (moving, None);
+        }
+        self.render();
+        (moving, self.on_update.clone())
+    }
+
+    /// Render the scroll position's progress straight away, skipping the
+    /// smoothing
+    fn jump(&mut self, scroll: f64) -> Option<Function> {
+        self.follow(scroll);
+        self.progress = self.target_progress;
+        self.smoothing.reset(self.progress);
+        self.render();
+        self.on_update.clone()
+    }
+
+    fn render(&self) {
+        match &self.target {
+            ScrubTarget::None => {}
+            ScrubTarget::Animation(animation) => {
+                let _ = animation.borrow_mut().set_fraction_complete(self.progress);
+            }
+            ScrubTarget::Timeline(seek) => {
+                let _ = seek(self.progress);
+            }
+        }
+    }
+
+    fn unpin(&mut self) {
+        if let Some(pin) = self.pin.take() {
+            let style = pin.element.style();
+            let _ = restore(&style, "translate", &pin.saved_translate);
+            if pin.spacing {
+                let _ = restore(&style, "margin-bottom", &pin.saved_margin);
+            }
+        }
+    }
+}
+
+fn restore(
+    style: &web_sys::CssStyleDeclaration,
+    property: &str,
+    value: &str,
+) -> Result<(), JsValue> {
+    if value.is_empty() {
+        style.remove_property(property).map(|_| ())
+    } else {
+        style.set_property(property, value)
+    }
+}
+
+// ============================================================================
+// SHARED LISTENER
+// ============================================================================
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+#[derive(Default)]
+struct Registry {
+    next_id: u32,
+    triggers: Vec<(u32, Rc<RefCell<TriggerState>>)>,
+    listeners: Vec<(&'static str, EventCallback)>,
+    ticker_id: Option<u32>,
+    // A scroll or resize arrived since the last frame
+    dirty: bool,
+    resized: bool,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+fn register(state: Rc<RefCell<TriggerState>>) -> Result<u32, JsValue> {
+    let listening = REGISTRY.with(|r| !r.borrow().listeners.is_empty());
+    if !listening {
+        listen()?;
+    }
+    Ok(REGISTRY.with(|r| {
+        let mut r = r.borrow_mut();
+        r.next_id += 1;
+        let id = r.next_id;
+        r.triggers.push((id, state));
+        id
+    }))
+}
+
+fn unregister(id: u32) {
+    let (listeners, ticker_id) = REGISTRY.with(|r| {
+        let mut r = r.borrow_mut();
+        r.triggers.retain(|(tid, _)| *tid != id);
+        if !r.triggers.is_empty() {
+            return (Vec::new(), None);
+        }
+        (std::mem::take(&mut r.listeners), r.ticker_id.take())
+    });
+
+    if let Some(id) = ticker_id {
+        ticker::unsubscribe(id);
+    }
+    if let Some(window) = window() {
+        for (name, callback) in listeners {
+            let _ =
+                window.remove_event_listener_with_callback(name, callback.as_ref().unchecked_ref());
+        }
+    }
+}
+
+fn listen() -> Result<(), JsValue> {
+    let window = window().ok_or_else(|| JsValue::from_str("No window available"))?;
+    let options = AddEventListenerOptions::new();
+    options.set_passive(true);
+
+    let mut listeners = Vec::with_capacity(2);
+    for (name, resize) in [("scroll", false), ("resize", true)] {
+        let callback: EventCallback =
+            Closure::wrap(Box::new(move |_event: Event| schedule(resize)) as Box<dyn FnMut(Event)>);
+        window.add_event_listener_with_callback_and_add_event_listener_options(
+            name,
+            callback.as_ref().unchecked_ref(),
+            &options,
+        )?;
+        listeners.push((name, callback));
+    }
+    REGISTRY.with(|r| r.borrow_mut().listeners = listeners);
+    Ok(())
+}
+
+/// Update the triggers on the next frame, however many events arrive first
+fn schedule(resize: bool) {
+    let start = REGISTRY.with(|r| {
+        let mut r = r.borrow_mut();
+        r.dirty = true;
+        r.resized |= resize;
+        r.ticker_id.is_none()
+    });
+    if start {
+        let id = ticker::subscribe(Box::new(tick)).ok();
+        REGISTRY.with(|r| r.borrow_mut().ticker_id = id);
+    }
+}
+
+fn tick(_now: f64, dt: f64) -> bool {
+    let (triggers, resized) = REGISTRY.with(|r| {
+        let mut r = r.borrow_mut();
+        r.dirty = false;
+        let triggers: Vec<_> = r.triggers.iter().map(|(_, t)| Rc::clone(t)).collect();
+        (triggers, std::mem::take(&mut r.resized))
+    });
+
+    let scroll = scroll_y();
+    let viewport_height = measure::viewport_rect().map_or(0.0, |v| v.height);
+    let mut moving = false;
+    for trigger in triggers {
+        let (still_moving, callback) = {
+            let mut trigger = trigger.borrow_mut();
+            if resized {
+                trigger.measure(viewport_height);
+            }
+            trigger.update(scroll, dt)
+        };
+        moving |= still_moving;
+        notify(&trigger, callback);
+    }
+
+    // Events during the callbacks keep the subscription for another frame
+    REGISTRY.with(|r| {
+        let mut r = r.borrow_mut();
+        let keep = moving || r.dirty;
+        if !keep {
+            r.ticker_id = None;
+        }
+        keep
+    })
+}
+
+/// Call an update callback with the state released, so it can read it
+fn notify(trigger: &Rc<RefCell<TriggerState>>, callback: Option<Function>) {
+    if let Some(callback) = callback {
+        let progress = trigger.borrow().progress;
+        let _ = callback.call1(&JsValue::NULL, &JsValue::from_f64(progress));
+    }
+}
+
+fn scroll_y() -> f64 {
+    window().and_then(|w| w.scroll_y().ok()).unwrap_or(0.0)
+}
+
+// ============================================================================
+// PUBLIC API
+// ============================================================================
+
+/// Config: `{ start: "top bottom", end: "bottom top", scrub: 0, spring,
+/// pin: false, pinSpacing: true }`. `scrub` is the seconds the animation
+/// takes to catch up with the scroll position (0 follows it exactly);
+/// `spring: { stiffness, damping }` follows it with a spring instead.
+#[wasm_bindgen]
+pub struct ScrollTrigger {
+    id: u32,
+    state: Rc<RefCell<TriggerState>>,
+}
+
+#[wasm_bindgen]
+impl ScrollTrigger {
+    #[wasm_bindgen(constructor)]
+    pub fn new(trigger: Element, config: JsValue) -> Result<ScrollTrigger, JsValue> {
+        ScrollTrigger::create(trigger, config, "top bottom", "bottom top")
+    }
+
+    /// Follow the whole page: 0 at the top and 1 scrolled to the bottom,
+    /// unless `start`/`end` say otherwise
+    #[wasm_bindgen(js_name = forPage)]
+    pub fn for_page(config: JsValue) -> Result<ScrollTrigger, JsValue> {
+        let page = window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.document_element())
+            .ok_or_else(|| JsValue::from_str("No document available"))?;
+        ScrollTrigger::create(page, config, "top top", "bottom bottom")
+    }
+
+    /// Drive a started animation's progress from the scroll position; its
+    /// own clock is paused
+    #[wasm_bindgen(js_name = scrubAnimation)]
+    pub fn scrub_animation(&mut self, handle: &AnimationHandle) -> Result<(), JsValue> {
+        handle.animation.borrow_mut().pause()?;
+        self.bind(ScrubTarget::Animation(Rc::clone(&handle.animation)));
+        Ok(())
+    }
+
+    /// Drive a sequencer's timeline from the scroll position; its own clock
+    /// is paused
+    #[wasm_bindgen(js_name = scrubSequencer)]
+    pub fn scrub_sequencer(&mut self, sequencer: &Sequencer) {
+        self.bind(ScrubTarget::Timeline(sequencer.scrubber()));
+    }
+
+    /// Called with the progress (0..1) whenever it changes
+    #[wasm_bindgen(js_name = onUpdate)]
+    pub fn on_update(&mut self, callback: Function) {
+        self.state.borrow_mut().on_update = Some(callback);
+    }
+
+    /// Scrubbed progress, 0 before the start position and 1 past the end
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> f64 {
+        self.state.borrow().progress
+    }
+
+    /// Window scroll positions where progress is 0 and 1, as [start, end]
+    #[wasm_bindgen(getter)]
+    pub fn range(&self) -> Vec<f64> {
+        let state = self.state.borrow();
+        vec![state.start_scroll, state.end_scroll]
+    }
+
+    /// Stop following the scroll and undo pinning
+    #[wasm_bindgen]
+    pub fn kill(&mut self) {
+        unregister(self.id);
+        let mut state = self.state.borrow_mut();
+        state.unpin();
+        state.target = ScrubTarget::None;
+    }
+
+    /// Re-measure every trigger, e.g. after content above them changes size
+    #[wasm_bindgen]
+    pub fn refresh() {
+        schedule(true);
+    }
+}
+
+impl ScrollTrigger {
+    fn create(
+        trigger: Element,
+        config: JsValue,
+        default_start: &str,
+        default_end: &str,
+    ) -> Result<ScrollTrigger, JsValue> {
+        let config: TriggerConfig = if config.is_undefined() || config.is_null() {
+            TriggerConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config).map_err(|e| {
+                JsValue::from_str(&format!("Invalid scroll trigger config: {:?}", e))
+            })?
+        };
+
+        let position = |spec: Option<String>, default: &str| {
+            let spec = spec.unwrap_or_else(|| default.to_string());
+            ScrollPosition::parse(&spec)
+                .ok_or_else(|| JsValue::from_str(&format!("Invalid scroll position: {}", spec)))
+        };
+        let start = position(config.start.clone(), default_start)?;
+        let end = position(config.end.clone(), default_end)?;
+
+        let pin = match config.pin {
+            Some(true) => {
+                let element = trigger
+                    .clone()
+                    .dyn_into::<HtmlElement>()
+                    .map_err(|_| JsValue::from_str("Only HTML elements can be pinned"))?;
+                let style = element.style();
+                Some(Pin {
+                    saved_translate: style.get_property_value("translate")?,
+                    saved_margin: style.get_property_value("margin-bottom")?,
+                    spacing: config.pin_spacing.unwrap_or(true),
+                    element,
+                })
+            }
+            _ => None,
+        };
+
+        let mut state = TriggerState {
+            element: trigger,
+            start,
+            end,
+            smoothing: config.smoothing(),
+            pin,
+            start_scroll: 0.0,
+            end_scroll: 0.0,
+            target_progress: 0.0,
+            progress: 0.0,
+            target: ScrubTarget::None,
+            on_update: None,
+        };
+        state.measure(measure::viewport_rect()?.height);
+        state.jump(scroll_y());
+
+        let state = Rc::new(RefCell::new(state));
+        let id = register(Rc::clone(&state))?;
+        Ok(ScrollTrigger { id, state })
+    }
+
+    fn bind(&mut self, target: ScrubTarget) {
+        let callback = {
+            let mut state = self.state.borrow_mut();
+            state.target = target;
+            state.jump(scroll_y())
+        };
+        notify(&self.state, callback);
+    }
+}
+
+impl Drop for ScrollTrigger {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
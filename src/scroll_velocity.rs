@@ -0,0 +1,266 @@
+use crate::renderer::{self, Renderer};
+use crate::spring::Spring;
+use crate::ticker;
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, AddEventListenerOptions, Element, Event, EventTarget};
+
+// ============================================================================
+// SCROLL VELOCITY - Skew/scale elements by how fast the page is scrolling
+// ============================================================================
+//
+// Each frame the scroll distance over the frame time gives a raw velocity. A
+// spring follows it, so effects swell as scrolling speeds up and wobble back
+// to rest once it stops.
+
+// Longest spring step; longer frames would let the spring overshoot wildly
+const MAX_SPRING_DT: f64 = 0.032;
+// px/s of smoothed velocity treated as standing still
+const REST_VELOCITY: f64 = 1.0;
+
+/// Velocity as a fraction of `max_velocity`, from -1 (up) to 1 (down)
+pub fn intensity(velocity: f64, max_velocity: f64) -> f64 {
+    if max_velocity <= 0.0 {
+        return 0.0;
+    }
+    (velocity / max_velocity).clamp(-1.0, 1.0)
+}
+
+/// Property values at full intensity. Skews, rotation and `y` follow the
+/// scroll direction; scale grows with speed either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VelocityEffect {
+    /// Degrees
+    pub skew_x: f64,
+    /// Degrees
+    pub skew_y: f64,
+    /// Degrees
+    pub rotate: f64,
+    /// Added to a scale of 1
+    pub scale: f64,
+    /// Pixels
+    pub y: f64,
+}
+
+impl VelocityEffect {
+    /// CSS writes for `intensity`; properties left at 0 are skipped so other
+    /// animations keep them
+    pub fn sample(&self, intensity: f64) -> Vec<(&'static str, String)> {
+        let mut writes = Vec::with_capacity(4);
+        // No individual skew property, so skews take the transform
+        let mut skews = Vec::with_capacity(2);
+        if self.skew_x != 0.0 {
+            skews.push(format!("skewX({}deg)", self.skew_x * intensity));
+        }
+        if self.skew_y != 0.0 {
+            skews.push(format!("skewY({}deg)", self.skew_y * intensity));
+        }
+        if !skews.is_empty() {
+            writes.push(("transform", skews.join(" ")));
+        }
+        if self.rotate != 0.0 {
+            writes.push(("rotate", format!("{}deg", self.rotate * intensity)));
+        }
+        if self.scale != 0.0 {
+            let scale = (1.0 + self.scale * intensity.abs()).max(0.0);
+            writes.push(("scale", scale.to_string()));
+        }
+        if self.y != 0.0 {
+            writes.push(("translate", format!("0px {}px", self.y * intensity)));
+        }
+        writes
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SpringConfig {
+    stiffness: Option<f64>,
+    damping: Option<f64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct VelocityConfig {
+    max_velocity: Option<f64>,
+    spring: Option<SpringConfig>,
+}
+
+struct Target {
+    effect: VelocityEffect,
+    renderer: Box<dyn Renderer>,
+}
+
+struct VelocityState {
+    container: Option<Element>,
+    max_velocity: f64,
+    spring: Spring,
+    // Scroll position at the previous frame
+    last: Option<f64>,
+    targets: Vec<Target>,
+    ticker_id: Option<u32>,
+}
+
+impl VelocityState {
+    fn position(&self) -> f64 {
+        match &self.container {
+            Some(container) => container.scroll_top() as f64,
+            None => window().and_then(|w| w.scroll_y().ok()).unwrap_or(0.0),
+        }
+    }
+
+    /// Measure, smooth and write; returns whether still moving
+    fn tick(&mut self, dt: f64) -> bool {
+        let position = self.position();
+        let last = self.last.replace(position);
+        // The ticker's first frame has no duration to measure over
+        if dt <= 0.0 {
+            return true;
+        }
+
+        let raw = last.map_or(0.0, |last| (position - last) / dt);
+        let smoothed = self.spring.update(raw, dt.min(MAX_SPRING_DT));
+        let resting = raw == 0.0
+            && smoothed.abs() < REST_VELOCITY
+            && self.spring.velocity.abs() < REST_VELOCITY;
+        if resting {
+            self.spring.reset(0.0);
+        }
+
+        let intensity = intensity(self.spring.current, self.max_velocity);
+        for target in self.targets.iter_mut() {
+            for (property, value) in target.effect.sample(intensity) {
+                let _ = target.renderer.apply(property, &value);
+            }
+            let _ = target.renderer.flush();
+        }
+        !resting
+    }
+}
+
+/// Run frames until the velocity has settled, however many scroll events
+/// arrive first
+fn schedule(state: &Rc<RefCell<VelocityState>>) {
+    if state.borrow().ticker_id.is_some() {
+        return;
+    }
+    let tick_state = Rc::clone(state);
+    let id = ticker::subscribe(Box::new(move |_now, dt| {
+        let mut s = tick_state.borrow_mut();
+        let moving = s.tick(dt);
+        if !moving {
+            s.ticker_id = None;
+        }
+        moving
+    }))
+    .ok();
+    state.borrow_mut().ticker_id = id;
+}
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+/// Config: `{ maxVelocity: 3000, spring: { stiffness: 300, damping: 30 } }`.
+/// `maxVelocity` is the px/s at which effects reach their full values.
+#[wasm_bindgen]
+pub struct ScrollVelocity {
+    state: Rc<RefCell<VelocityState>>,
+    listener: Option<(EventTarget, EventCallback)>,
+}
+
+#[wasm_bindgen]
+impl ScrollVelocity {
+    /// Measure `container`'s scroll, or the page's without one
+    #[wasm_bindgen(constructor)]
+    pub fn new(container: Option<Element>, config: JsValue) -> Result<ScrollVelocity, JsValue> {
+        let config: VelocityConfig = if config.is_undefined() || config.is_null() {
+            VelocityConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config).map_err(|e| {
+                JsValue::from_str(&format!("Invalid scroll velocity config: {:?}", e))
+            })?
+        };
+        let defaults = Spring::default();
+        let spring = match &config.spring {
+            Some(spring) => Spring::new(
+                spring.stiffness.unwrap_or(defaults.stiffness),
+                spring.damping.unwrap_or(defaults.damping),
+            ),
+            None => defaults,
+        };
+
+        let target: EventTarget = match &container {
+            Some(container) => container.clone().into(),
+            None => window()
+                .ok_or_else(|| JsValue::from_str("No window available"))?
+                .into(),
+        };
+        let state = Rc::new(RefCell::new(VelocityState {
+            container,
+            max_velocity: config.max_velocity.unwrap_or(3000.0),
+            spring,
+            last: None,
+            targets: Vec::new(),
+            ticker_id: None,
+        }));
+        let position = state.borrow().position();
+        state.borrow_mut().last = Some(position);
+
+        let scrolled = Rc::clone(&state);
+        let callback: EventCallback =
+            Closure::wrap(Box::new(move |_: Event| schedule(&scrolled)) as Box<dyn FnMut(Event)>);
+        let options = AddEventListenerOptions::new();
+        options.set_passive(true);
+        target.add_event_listener_with_callback_and_add_event_listener_options(
+            "scroll",
+            callback.as_ref().unchecked_ref(),
+            &options,
+        )?;
+
+        Ok(ScrollVelocity {
+            state,
+            listener: Some((target, callback)),
+        })
+    }
+
+    /// Add an element to distort. Effect: `{ skewX, skewY, rotate, scale, y }`
+    /// at full speed, e.g. `{ skewY: -8, scale: 0.04 }`
+    #[wasm_bindgen(js_name = addTarget)]
+    pub fn add_target(&mut self, element: Element, effect: JsValue) -> Result<(), JsValue> {
+        let effect: VelocityEffect = serde_wasm_bindgen::from_value(effect)
+            .map_err(|e| JsValue::from_str(&format!("Invalid velocity effect: {:?}", e)))?;
+        let renderer = renderer::for_element(&element);
+        self.state
+            .borrow_mut()
+            .targets
+            .push(Target { effect, renderer });
+        Ok(())
+    }
+
+    /// Smoothed scroll velocity in px/s, positive scrolling down
+    #[wasm_bindgen(getter)]
+    pub fn velocity(&self) -> f64 {
+        self.state.borrow().spring.current
+    }
+
+    /// Stop measuring; targets keep their last values
+    #[wasm_bindgen]
+    pub fn unbind(&mut self) {
+        if let Some((target, callback)) = self.listener.take() {
+            let _ = target
+                .remove_event_listener_with_callback("scroll", callback.as_ref().unchecked_ref());
+        }
+        if let Some(id) = self.state.borrow_mut().ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+impl Drop for ScrollVelocity {
+    fn drop(&mut self) {
+        self.unbind();
+    }
+}
@@ -1,13 +1,173 @@
 use wasm_bindgen::prelude::*;
+use js_sys::{Function, JSON};
+use serde::{Deserialize, Serialize};
+use web_sys::Element;
 use std::cell::RefCell;
 use std::rc::Rc;
+use crate::scroll_trigger::ScrollTrigger;
+use crate::spec;
+use crate::ticker;
+use crate::time_warp::TimeWarp;
 
 #[wasm_bindgen]
 pub struct Sequencer {
+    state: Rc<RefCell<SequencerState>>,
+}
+
+/// Timeline shared with the ticker subscription that drives playback
+struct SequencerState {
     steps: Vec<TimelineStep>,
     fraction: f64,
+    elapsed: f64,    // Clock time in ms within the iteration, before the time warp
     running: bool,
+    paused: bool,
+
+    // Repeats: total plays (negative = forever), pause between plays, and
+    // whether odd iterations play backwards
+    repeat_count: i32,
+    repeat_delay: f64,
+    yoyo: bool,
+    iteration: i32,
+    delay_remaining: f64,
+
+    // Clock speed multiplier and direction, applied to the whole timeline
+    time_scale: f64,
+    backwards: bool,
+
     total_duration: f64,
+    time_warp: TimeWarp,
+    labels: Vec<TimelineLabel>,
+
+    // Markers, sorted by time, and the timeline time they were last checked at
+    callbacks: Vec<TimelineCallback>,
+    playhead: Option<f64>,
+    // Crossed markers and lifecycle callbacks with their argument, called
+    // once the state is no longer borrowed
+    pending: Vec<(Function, JsValue)>,
+
+    // Lifecycle callbacks for the timeline as a whole
+    on_step_complete: Option<Function>,
+    on_complete: Option<Function>,
+    on_progress: Option<Function>,
+
+    ticker_id: Option<u32>,
+}
+
+struct TimelineCallback {
+    time: f64,
+    anchor: Anchor,
+    callback: Function,
+    on_scrub: bool,
+}
+
+struct TimelineLabel {
+    name: String,
+    time: f64,
+    anchor: Anchor,
+}
+
+/// Where a label or marker was added, so its time can be re-resolved when
+/// steps are removed or replaced
+struct Anchor {
+    after_step: usize,        // Number of steps that existed when it was added
+    position: Option<String>, // None = end of the timeline at that point
+}
+
+/// How a step was positioned; start times are recomputed from this
+#[derive(Clone)]
+enum Placement {
+    /// After the previous step, overlapping it by a fraction (0-1)
+    After(f64),
+    /// A fixed offset in ms after the previous step's start
+    AfterStart(f64),
+    /// A position expression, see resolve_position
+    At(String),
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq)]
+pub enum StaggerFrom {
+    Start = 0,
+    End = 1,
+    /// Middle items first, spreading outwards
+    Center = 2,
+    Random = 3,
+}
+
+/// When each of `count` items starts, in multiples of the stagger delay
+pub fn stagger_ranks(count: usize, from: StaggerFrom) -> Vec<f64> {
+    let mut ranks: Vec<f64> = match from {
+        StaggerFrom::Start => (0..count).map(|i| i as f64).collect(),
+        StaggerFrom::End => (0..count).map(|i| (count - 1 - i) as f64).collect(),
+        StaggerFrom::Center => {
+            let center = (count as f64 - 1.0) * 0.5;
+            let ranks: Vec<f64> = (0..count).map(|i| (i as f64 - center).abs()).collect();
+            // With an even count the two middle items share the first slot
+            let nearest = ranks.iter().cloned().fold(f64::INFINITY, f64::min);
+            ranks.iter().map(|r| r - nearest).collect()
+        }
+        StaggerFrom::Random => (0..count).map(|i| i as f64).collect(),
+    };
+    if from == StaggerFrom::Random {
+        // Fisher-Yates shuffle
+        for i in (1..count).rev() {
+            let j = (js_sys::Math::random() * (i + 1) as f64) as usize;
+            ranks.swap(i, j.min(i));
+        }
+    }
+    ranks
+}
+
+/// What a position expression is relative to; see parse_position
+#[derive(Debug, PartialEq)]
+pub enum PositionBase<'a> {
+    /// The end of the timeline
+    End,
+    /// An absolute time in ms
+    Time(f64),
+    Label(&'a str),
+}
+
+/// Split a position into its base and offset in ms: "500" (absolute),
+/// "intro" (label), "intro+=200" / "intro-=200" (relative to a label) or
+/// "+=100" / "-=100" (relative to the end of the timeline)
+pub fn parse_position(position: &str) -> Option<(PositionBase<'_>, f64)> {
+    let position = position.trim();
+    let (base, offset) = match position.find("+=").or_else(|| position.find("-=")) {
+        Some(i) => {
+            let amount: f64 = position[i + 2..].trim().parse().ok()?;
+            let sign = if position[i..].starts_with('-') { -1.0 } else { 1.0 };
+            (position[..i].trim(), sign * amount)
+        }
+        None if position.is_empty() => return None,
+        None => (position, 0.0),
+    };
+
+    let base = if base.is_empty() {
+        PositionBase::End
+    } else if let Ok(ms) = base.parse::<f64>() {
+        PositionBase::Time(ms)
+    } else {
+        PositionBase::Label(base)
+    };
+    Some((base, offset))
+}
+
+/// Timeline fraction `elapsed` ms into the zero-based `iteration`; with yoyo
+/// the odd iterations run from the end back to the start
+pub fn iteration_fraction(elapsed: f64, total_duration: f64, iteration: i32, yoyo: bool) -> f64 {
+    let fraction = if total_duration > 0.0 { elapsed / total_duration } else { 1.0 };
+    if yoyo && iteration % 2 == 1 { 1.0 - fraction } else { fraction }
+}
+
+/// Whether the playhead moving from `from` to `to` meets a marker at `time`:
+/// markers it lands on count, the one it leaves from doesn't
+pub fn crosses(time: f64, from: f64, to: f64) -> bool {
+    if to > from {
+        time > from && time <= to
+    } else {
+        time < from && time >= to
+    }
 }
 
 #[derive(Clone)]
@@ -15,7 +175,380 @@ struct TimelineStep {
     animation: Rc<RefCell<crate::Animation>>,
     start: f64,      // Start time in ms
     duration: f64,   // Duration in ms
-    overlap: f64,    // 0.0 = sequential, 1.0 = parallel
+    placement: Placement,
+    rendered: Option<f64>, // Last local fraction written to the animation
+    // Config for steps built with to(), re-applied once when the step first begins
+    deferred: Option<JsValue>,
+    // What to()/fromJson built the step from, kept for toJson
+    spec: Option<StepSpec>,
+}
+
+#[derive(Clone)]
+struct StepSpec {
+    selector: Option<String>,
+    config: JsValue,
+    easing: Option<String>,
+    keyframes: JsValue,
+}
+
+// ============================================================================
+// TIMELINE DOCUMENT - Serialized form used by toJson/fromJson/fromSpec
+// ============================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct TimelineDocument {
+    steps: Vec<StepDocument>,
+    labels: Vec<LabelDocument>,
+    repeat: i32,
+    repeat_delay: f64,
+    yoyo: bool,
+    time_scale: f64,
+}
+
+impl Default for TimelineDocument {
+    fn default() -> Self {
+        TimelineDocument {
+            steps: Vec::new(),
+            labels: Vec::new(),
+            repeat: 1,
+            repeat_delay: 0.0,
+            yoyo: false,
+            time_scale: 1.0,
+        }
+    }
+}
+
+/// One of `position`, `overlap` or `offset` places the step; none means
+/// after the previous step. Specs may write `target` and `properties`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StepDocument {
+    #[serde(alias = "target")]
+    selector: String,
+    #[serde(default, alias = "properties", with = "serde_wasm_bindgen::preserve", skip_serializing_if = "JsValue::is_undefined")]
+    config: JsValue,
+    #[serde(default, with = "serde_wasm_bindgen::preserve", skip_serializing_if = "JsValue::is_undefined")]
+    keyframes: JsValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    easing: Option<String>,
+    #[serde(default = "default_step_duration")]
+    duration: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    overlap: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset: Option<f64>,
+}
+
+fn default_step_duration() -> f64 {
+    400.0
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LabelDocument {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<String>,
+    // Number of steps added before the label
+    #[serde(default)]
+    after_step: usize,
+}
+
+impl SequencerState {
+    /// Write each step's local fraction for the current timeline position. During
+    /// playback steps that haven't reached their start time are left untouched;
+    /// seeking (`fill_before`) resets them to 0.
+    fn render(&mut self, fill_before: bool) -> Result<(), JsValue> {
+        let current_time = self.time_warp.apply(self.fraction) * self.total_duration;
+        self.collect_crossed(current_time, fill_before);
+
+        for (index, step) in self.steps.iter_mut().enumerate() {
+            if current_time >= step.start {
+                if let Some(config) = step.deferred.take() {
+                    let mut animation = step.animation.borrow_mut();
+                    animation.configure(config)?;
+                    // Earlier steps have written their values by now
+                    animation.start_numbers_from_element();
+                }
+            }
+
+            let local = if current_time < step.start {
+                if !fill_before && step.rendered.is_none() { continue; }
+                0.0
+            } else if step.duration <= 0.0 || current_time >= step.start + step.duration {
+                1.0
+            } else {
+                (current_time - step.start) / step.duration
+            };
+
+            if step.rendered == Some(local) { continue; }
+            step.rendered = Some(local);
+            step.animation.borrow_mut().set_fraction_complete(local)?;
+
+            if local == 1.0 && !fill_before {
+                if let Some(ref callback) = self.on_step_complete {
+                    self.pending.push((callback.clone(), JsValue::from(index as u32)));
+                }
+            }
+        }
+
+        if !fill_before {
+            if let Some(ref callback) = self.on_progress {
+                self.pending.push((callback.clone(), JsValue::from_f64(self.fraction)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue markers between the last checked time and `time`, in the order the
+    /// playhead meets them. A fresh playback includes markers at its start time.
+    fn collect_crossed(&mut self, time: f64, scrubbing: bool) {
+        let from = match self.playhead {
+            Some(from) => from,
+            None if scrubbing => time,
+            None => f64::NEG_INFINITY,
+        };
+        self.playhead = Some(time);
+        if from == time { return; }
+
+        let forward = time > from;
+        let crossed = |marker: &&TimelineCallback| {
+            (!scrubbing || marker.on_scrub) && crosses(marker.time, from, time)
+        };
+
+        let marker = |m: &TimelineCallback| (m.callback.clone(), JsValue::UNDEFINED);
+        let markers: Vec<(Function, JsValue)> = if forward {
+            self.callbacks.iter().filter(crossed).map(marker).collect()
+        } else {
+            self.callbacks.iter().rev().filter(crossed).map(marker).collect()
+        };
+        self.pending.extend(markers);
+    }
+
+    /// Advance the clock by `dt` ms; false once the last iteration has finished
+    fn tick(&mut self, dt: f64) -> bool {
+        if !self.running { return false; }
+
+        let mut dt = dt * self.time_scale;
+        if self.delay_remaining > 0.0 {
+            self.delay_remaining -= dt;
+            if self.delay_remaining > 0.0 { return true; }
+            dt = -self.delay_remaining;
+            self.delay_remaining = 0.0;
+        }
+
+        if self.backwards {
+            self.elapsed -= dt;
+            if self.elapsed > 0.0 {
+                self.set_clock(self.elapsed);
+                let _ = self.render(false);
+                return true;
+            }
+
+            // Unwind into the previous iteration, or stop at the very start
+            self.set_clock(0.0);
+            let _ = self.render(false);
+            if self.iteration == 0 {
+                return self.finish();
+            }
+            self.iteration -= 1;
+            self.elapsed = self.total_duration;
+            self.delay_remaining = self.repeat_delay;
+            if !self.yoyo {
+                self.playhead = Some(f64::INFINITY);
+            }
+            return true;
+        }
+
+        self.elapsed += dt;
+        if self.elapsed < self.total_duration {
+            self.set_clock(self.elapsed);
+            let _ = self.render(false);
+            return true;
+        }
+
+        // Land exactly on the end of this iteration before wrapping
+        self.set_clock(self.total_duration);
+        let _ = self.render(false);
+        if self.repeat_count >= 0 && self.iteration + 1 >= self.repeat_count {
+            return self.finish();
+        }
+        self.iteration += 1;
+        self.elapsed = 0.0;
+        self.delay_remaining = self.repeat_delay;
+        if !self.yoyo {
+            // Jumping back to the start isn't a backwards crossing
+            self.playhead = None;
+        }
+        true
+    }
+
+    fn finish(&mut self) -> bool {
+        self.running = false;
+        self.ticker_id = None;
+        if let Some(ref callback) = self.on_complete {
+            self.pending.push((callback.clone(), JsValue::UNDEFINED));
+        }
+        false
+    }
+    /// Yoyo iterations run the playhead from the end back to the start
+    fn yoyo_pass(&self) -> bool {
+        self.yoyo && self.iteration % 2 == 1
+    }
+
+    fn set_clock(&mut self, elapsed: f64) {
+        self.elapsed = elapsed;
+        self.fraction = iteration_fraction(elapsed, self.total_duration, self.iteration, self.yoyo);
+    }
+
+    fn label(&self, name: &str) -> Result<f64, JsValue> {
+        self.labels.iter()
+            .find(|l| l.name == name)
+            .map(|l| l.time)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown label: {}", name)))
+    }
+
+    /// Resolve a position (see parse_position) to ms
+    fn resolve_position(&self, position: &str) -> Result<f64, JsValue> {
+        let (base, offset) = parse_position(position).ok_or_else(|| {
+            JsValue::from_str(&format!("Invalid position: {:?}", position.trim()))
+        })?;
+
+        let time = match base {
+            PositionBase::End => self.total_duration,
+            PositionBase::Time(ms) => ms,
+            PositionBase::Label(name) => self.label(name)?,
+        };
+
+        Ok((time + offset).max(0.0))
+    }
+
+    /// Start time for a step overlapping the previous one by `overlap` (0-1)
+    fn next_start(&self, overlap: f64) -> f64 {
+        match self.steps.last() {
+            Some(prev) => {
+                let prev_end = prev.start + prev.duration;
+                let overlap_offset = prev.duration * overlap.clamp(0.0, 1.0);
+                prev_end - overlap_offset
+            }
+            None => 0.0,
+        }
+    }
+
+    fn place(&self, placement: &Placement) -> Result<f64, JsValue> {
+        match placement {
+            Placement::After(overlap) => Ok(self.next_start(*overlap)),
+            Placement::AfterStart(offset) => Ok(self.steps.last().map(|prev| prev.start).unwrap_or(0.0) + offset),
+            Placement::At(position) => self.resolve_position(position),
+        }
+    }
+
+    fn anchor(&self, position: Option<String>) -> Result<(Anchor, f64), JsValue> {
+        let time = match position {
+            Some(ref position) => self.resolve_position(position)?,
+            None => self.total_duration,
+        };
+        Ok((Anchor { after_step: self.steps.len(), position }, time))
+    }
+
+    fn push_step(&mut self, animation: Rc<RefCell<crate::Animation>>, placement: Placement) -> Result<(), JsValue> {
+        let start = self.place(&placement)?;
+        let duration = animation.borrow().duration;
+        self.steps.push(TimelineStep {
+            animation,
+            start,
+            duration,
+            placement,
+            rendered: None,
+            deferred: None,
+            spec: None,
+        });
+        self.total_duration = self.total_duration.max(start + duration);
+        Ok(())
+    }
+
+    /// Recompute every start time, label and marker in insertion order after
+    /// the step list changed
+    fn relayout(&mut self) {
+        let steps = std::mem::take(&mut self.steps);
+        self.total_duration = 0.0;
+
+        for (index, mut step) in steps.into_iter().enumerate() {
+            self.resolve_anchors(index);
+            step.duration = step.animation.borrow().duration;
+            // A label moved after this step was added may no longer resolve
+            step.start = self.place(&step.placement).unwrap_or_else(|_| self.next_start(0.0));
+            self.total_duration = self.total_duration.max(step.start + step.duration);
+            self.steps.push(step);
+        }
+        self.resolve_anchors(self.steps.len());
+        self.callbacks.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    }
+
+    fn resolve_anchors(&mut self, after_step: usize) {
+        let resolve = |state: &SequencerState, anchor: &Anchor| match anchor.position {
+            Some(ref position) => state.resolve_position(position).unwrap_or(state.total_duration),
+            None => state.total_duration,
+        };
+        for i in 0..self.labels.len() {
+            if self.labels[i].anchor.after_step == after_step {
+                self.labels[i].time = resolve(self, &self.labels[i].anchor);
+            }
+        }
+        for i in 0..self.callbacks.len() {
+            if self.callbacks[i].anchor.after_step == after_step {
+                self.callbacks[i].time = resolve(self, &self.callbacks[i].anchor);
+            }
+        }
+    }
+
+    fn remove_step(&mut self, index: usize) {
+        self.steps.remove(index);
+        let anchors = self.labels.iter_mut().map(|l| &mut l.anchor)
+            .chain(self.callbacks.iter_mut().map(|c| &mut c.anchor));
+        for anchor in anchors {
+            if anchor.after_step > index {
+                anchor.after_step -= 1;
+            }
+        }
+    }
+
+    fn stop_clock(&mut self) {
+        self.running = false;
+        if let Some(id) = self.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+fn seek(state: &Rc<RefCell<SequencerState>>, fraction: f64) -> Result<(), JsValue> {
+    let result = {
+        let mut state = state.borrow_mut();
+        state.fraction = fraction.clamp(0.0, 1.0);
+        let clock = if state.yoyo_pass() { 1.0 - state.fraction } else { state.fraction };
+        state.elapsed = clock * state.total_duration;
+        state.render(true)
+    };
+    fire_pending(state);
+    result
+}
+
+/// Call markers queued by the last render, after releasing the state so they
+/// can control the sequencer
+fn fire_pending(state: &Rc<RefCell<SequencerState>>) {
+    let pending = std::mem::take(&mut state.borrow_mut().pending);
+    for (callback, arg) in pending {
+        let _ = callback.call1(&JsValue::NULL, &arg);
+    }
+}
+
+impl Default for Sequencer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[wasm_bindgen]
@@ -23,10 +556,30 @@ impl Sequencer {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Sequencer {
         Sequencer {
-            steps: Vec::new(),
-            fraction: 0.0,
-            running: false,
-            total_duration: 0.0,
+            state: Rc::new(RefCell::new(SequencerState {
+                steps: Vec::new(),
+                fraction: 0.0,
+                elapsed: 0.0,
+                running: false,
+                paused: false,
+                repeat_count: 1,
+                repeat_delay: 0.0,
+                yoyo: false,
+                iteration: 0,
+                delay_remaining: 0.0,
+                time_scale: 1.0,
+                backwards: false,
+                total_duration: 0.0,
+                time_warp: TimeWarp::default(),
+                labels: Vec::new(),
+                callbacks: Vec::new(),
+                playhead: None,
+                pending: Vec::new(),
+                on_step_complete: None,
+                on_complete: None,
+                on_progress: None,
+                ticker_id: None,
+            })),
         }
     }
 
@@ -37,26 +590,8 @@ impl Sequencer {
     /// Add animation step with overlap control
     #[wasm_bindgen(js_name = addStep)]
     pub fn add_step(&mut self, handle: &crate::AnimationHandle, overlap: f64) {
-        let anim = Rc::clone(&handle.animation);
-        let duration = anim.borrow().duration;
-        
-        let start = if self.steps.is_empty() {
-            0.0
-        } else {
-            let prev = &self.steps[self.steps.len() - 1];
-            let prev_end = prev.start + prev.duration;
-            let overlap_offset = prev.duration * overlap.clamp(0.0, 1.0);
-            prev_end - overlap_offset
-        };
-
-        self.steps.push(TimelineStep {
-            animation: anim,
-            start,
-            duration,
-            overlap: overlap.clamp(0.0, 1.0),
-        });
-
-        self.recalculate_duration();
+        let mut state = self.state.borrow_mut();
+        let _ = state.push_step(Rc::clone(&handle.animation), Placement::After(overlap.clamp(0.0, 1.0)));
     }
 
     /// Sequential step (starts after previous)
@@ -77,63 +612,426 @@ impl Sequencer {
         self.add_step(handle, at);
     }
 
+    /// Add a group after the previous step, each starting `each_delay` ms after
+    /// the last in the order given by `from`
+    #[wasm_bindgen(js_name = addStagger)]
+    pub fn add_stagger(&mut self, handles: Vec<crate::AnimationHandle>, each_delay: f64, from: StaggerFrom) -> Result<(), JsValue> {
+        let ranks = stagger_ranks(handles.len(), from);
+
+        let mut state = self.state.borrow_mut();
+        let each_delay = each_delay.max(0.0);
+        // Push in start order so then() follows the last item to start
+        let mut order: Vec<(f64, &crate::AnimationHandle)> = ranks.into_iter().zip(handles.iter()).collect();
+        order.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let mut previous_rank = None;
+        for (rank, handle) in order {
+            let placement = match previous_rank {
+                Some(previous) => Placement::AfterStart((rank - previous) * each_delay),
+                None => Placement::After(0.0),
+            };
+            previous_rank = Some(rank);
+            state.push_step(Rc::clone(&handle.animation), placement)?;
+        }
+        Ok(())
+    }
+
+    /// Add a step at a position: absolute ms ("500"), a label ("intro"),
+    /// relative to a label ("intro+=200", "intro-=100") or to the end of the
+    /// timeline ("+=100", "-=100")
+    #[wasm_bindgen(js_name = addAt)]
+    pub fn add_at(&mut self, handle: &crate::AnimationHandle, position: &str) -> Result<(), JsValue> {
+        self.state.borrow_mut().push_step(Rc::clone(&handle.animation), Placement::At(position.to_string()))
+    }
+
+    /// Build a step from an animate() config. Start values are read from the
+    /// element when the step first begins, so earlier steps that change the
+    /// same element are taken into account. `position` as in addAt; defaults
+    /// to after the previous step.
+    #[wasm_bindgen]
+    pub fn to(&mut self, element: Element, config: JsValue, duration: f64, position: Option<String>) -> Result<(), JsValue> {
+        let placement = match position {
+            Some(position) => Placement::At(position),
+            None => Placement::After(0.0),
+        };
+        // An id is the only selector we can recover reliably for toJson
+        let id = element.id();
+        let selector = if id.is_empty() { None } else { Some(format!("#{}", id)) };
+        let step_spec = StepSpec { selector, config, easing: None, keyframes: JsValue::UNDEFINED };
+        self.push_config_step(element, step_spec, duration, placement)
+    }
+
+    /// Name a point in the timeline: the current end, or `position` (same
+    /// syntax as addAt). Re-adding a name moves the label.
+    #[wasm_bindgen(js_name = addLabel)]
+    pub fn add_label(&mut self, name: String, position: Option<String>) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        let (anchor, time) = state.anchor(position)?;
+        state.labels.retain(|l| l.name != name);
+        state.labels.push(TimelineLabel { name, time, anchor });
+        Ok(())
+    }
+
+    /// Call `callback` whenever the playhead crosses `position` (same syntax as
+    /// addAt) during playback, and also when scrubbing with seekTo if `on_scrub`
+    #[wasm_bindgen(js_name = addCallback)]
+    pub fn add_callback(&mut self, position: &str, callback: Function, on_scrub: Option<bool>) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        let (anchor, time) = state.anchor(Some(position.to_string()))?;
+        let index = state.callbacks.partition_point(|m| m.time <= time);
+        state.callbacks.insert(index, TimelineCallback {
+            time,
+            anchor,
+            callback,
+            on_scrub: on_scrub.unwrap_or(false),
+        });
+        Ok(())
+    }
+
+    // ========================================================================
+    // EDITING
+    // ========================================================================
+
+    /// Remove a step; later steps, labels and markers move to close the gap
+    #[wasm_bindgen(js_name = removeStep)]
+    pub fn remove_step(&mut self, index: usize) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        if index >= state.steps.len() {
+            return Err(JsValue::from_str("Step index out of range"));
+        }
+        state.remove_step(index);
+        state.relayout();
+        Ok(())
+    }
+
+    /// Remove every step that starts at a label; returns how many were removed
+    #[wasm_bindgen(js_name = removeStepsAt)]
+    pub fn remove_steps_at(&mut self, label: &str) -> Result<usize, JsValue> {
+        let mut state = self.state.borrow_mut();
+        let time = state.label(label)?;
+        let mut removed = 0;
+        while let Some(index) = state.steps.iter().position(|step| (step.start - time).abs() < 1e-6) {
+            state.remove_step(index);
+            removed += 1;
+        }
+        state.relayout();
+        Ok(removed)
+    }
+
+    /// Swap the animation of a step, keeping its placement
+    #[wasm_bindgen(js_name = replaceStep)]
+    pub fn replace_step(&mut self, index: usize, handle: &crate::AnimationHandle) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        let step = state.steps.get_mut(index)
+            .ok_or_else(|| JsValue::from_str("Step index out of range"))?;
+        step.animation = Rc::clone(&handle.animation);
+        step.rendered = None;
+        step.deferred = None;
+        step.spec = None;
+        state.relayout();
+        Ok(())
+    }
+
+    /// Stop playback and remove all steps, labels and markers
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.stop_clock();
+        state.steps.clear();
+        state.labels.clear();
+        state.callbacks.clear();
+        state.total_duration = 0.0;
+        state.paused = false;
+        state.backwards = false;
+        state.fraction = 0.0;
+        state.elapsed = 0.0;
+        state.iteration = 0;
+        state.playhead = None;
+    }
+
+    /// Slow a stretch of the timeline by `factor` (applied to playback and scrubbing)
+    #[wasm_bindgen(js_name = slowZone)]
+    pub fn slow_zone(&mut self, from_fraction: f64, to_fraction: f64, factor: f64) -> Result<(), JsValue> {
+        self.state.borrow_mut().time_warp
+            .add_zone(from_fraction, to_fraction, factor)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
+    // ========================================================================
+    // CALLBACKS
+    // ========================================================================
+
+    /// Called with a step's index each time playback carries it to its end
+    #[wasm_bindgen(js_name = onStepComplete)]
+    pub fn on_step_complete(&mut self, callback: Function) {
+        self.state.borrow_mut().on_step_complete = Some(callback);
+    }
+
+    /// Called when the timeline finishes its last iteration (or reaches the
+    /// start when reversed); not called by stop()
+    #[wasm_bindgen(js_name = onComplete)]
+    pub fn on_complete(&mut self, callback: Function) {
+        self.state.borrow_mut().on_complete = Some(callback);
+    }
+
+    /// Called every frame of playback with the overall fraction
+    #[wasm_bindgen(js_name = onProgress)]
+    pub fn on_progress(&mut self, callback: Function) {
+        self.state.borrow_mut().on_progress = Some(callback);
+    }
+
     // ========================================================================
     // PLAYBACK
     // ========================================================================
 
+    /// Play the timeline forwards on its own clock, starting each step at its
+    /// scheduled time. Continues from the playhead when paused or reversed.
     #[wasm_bindgen]
     pub fn play(&mut self) -> Result<(), JsValue> {
-        self.running = true;
-        self.fraction = 0.0;
-        
-        // Start all animations
-        for step in &self.steps {
-            step.animation.borrow_mut().start_internal()?;
-        }
-        
+        {
+            let mut state = self.state.borrow_mut();
+            let was_reversed = std::mem::replace(&mut state.backwards, false);
+            if state.running { return Ok(()); }
+            if !state.paused && !was_reversed {
+                state.fraction = 0.0;
+                state.elapsed = 0.0;
+                state.iteration = 0;
+                state.playhead = None;
+                state.delay_remaining = 0.0;
+                for step in state.steps.iter_mut() {
+                    // The sequencer owns the clock; halt the animation's own loop
+                    step.animation.borrow_mut().stop()?;
+                    step.rendered = None;
+                }
+            }
+            state.running = true;
+            state.paused = false;
+        }
+        self.start_clock()
+    }
+
+    /// Play backwards from the playhead (through earlier iterations) to the
+    /// start, undoing every step together
+    #[wasm_bindgen]
+    pub fn reverse(&mut self) -> Result<(), JsValue> {
+        {
+            let mut state = self.state.borrow_mut();
+            state.backwards = true;
+            state.paused = false;
+            if state.running { return Ok(()); }
+            state.running = true;
+        }
+        self.start_clock()
+    }
+
+    /// Playback speed of the whole timeline: 2 plays twice as fast
+    #[wasm_bindgen(js_name = setTimeScale)]
+    pub fn set_time_scale(&mut self, rate: f64) -> Result<(), JsValue> {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(JsValue::from_str("Time scale must be greater than 0"));
+        }
+        self.state.borrow_mut().time_scale = rate;
         Ok(())
     }
 
+    fn start_clock(&mut self) -> Result<(), JsValue> {
+        let state = Rc::clone(&self.state);
+        let id = ticker::subscribe(Box::new(move |_now, dt| {
+            let keep = state.borrow_mut().tick(dt * 1000.0);
+            fire_pending(&state);
+            // A marker may have paused or stopped playback
+            keep && state.borrow().running
+        }));
+        match id {
+            Ok(id) => {
+                self.state.borrow_mut().ticker_id = Some(id);
+                Ok(())
+            }
+            Err(e) => {
+                self.state.borrow_mut().running = false;
+                Err(e)
+            }
+        }
+    }
+
+    /// Hold the clock at the current position; play() continues from here
     #[wasm_bindgen]
     pub fn pause(&mut self) -> Result<(), JsValue> {
-        self.running = false;
-        for step in &self.steps {
-            step.animation.borrow_mut().pause()?;
+        let mut state = self.state.borrow_mut();
+        if state.running {
+            state.stop_clock();
+            state.paused = true;
         }
         Ok(())
     }
 
     #[wasm_bindgen]
     pub fn stop(&mut self) -> Result<(), JsValue> {
-        self.running = false;
-        self.fraction = 0.0;
-        for step in &self.steps {
+        let mut state = self.state.borrow_mut();
+        state.stop_clock();
+        state.paused = false;
+        state.backwards = false;
+        state.fraction = 0.0;
+        state.elapsed = 0.0;
+        state.iteration = 0;
+        state.playhead = None;
+        for step in &state.steps {
             step.animation.borrow_mut().stop()?;
         }
         Ok(())
     }
 
-    /// Scrub to specific time fraction (0.0 - 1.0)
+    /// Scrub to specific time fraction (0.0 - 1.0); a running clock continues from there
     #[wasm_bindgen(js_name = seekTo)]
     pub fn seek_to(&mut self, fraction: f64) -> Result<(), JsValue> {
-        self.fraction = fraction.clamp(0.0, 1.0);
-        let current_time = self.fraction * self.total_duration;
-        
-        // Update each animation's fraction based on timeline position
-        for step in &self.steps {
-            let step_end = step.start + step.duration;
-            
-            if current_time < step.start {
-                step.animation.borrow_mut().set_fraction_complete(0.0)?;
-            } else if current_time > step_end {
-                step.animation.borrow_mut().set_fraction_complete(1.0)?;
+        seek(&self.state, fraction)
+    }
+
+    /// Times to play the whole timeline; negative repeats forever
+    #[wasm_bindgen]
+    pub fn repeat(&mut self, count: i32) {
+        self.state.borrow_mut().repeat_count = count;
+    }
+
+    /// Pause in ms between iterations
+    #[wasm_bindgen(setter, js_name = repeatDelay)]
+    pub fn set_repeat_delay(&mut self, value: f64) {
+        self.state.borrow_mut().repeat_delay = value.max(0.0);
+    }
+
+    /// Play every other iteration backwards, reversing all steps together
+    #[wasm_bindgen]
+    pub fn yoyo(&mut self, enabled: bool) {
+        self.state.borrow_mut().yoyo = enabled;
+    }
+
+    /// Scrub to a label added with addLabel()
+    #[wasm_bindgen(js_name = seekToLabel)]
+    pub fn seek_to_label(&mut self, name: &str) -> Result<(), JsValue> {
+        let fraction = {
+            let state = self.state.borrow();
+            let time = state.label(name)?;
+            if state.total_duration > 0.0 {
+                state.time_warp.invert(time / state.total_duration)
             } else {
-                let local_fraction = (current_time - step.start) / step.duration;
-                step.animation.borrow_mut().set_fraction_complete(local_fraction)?;
+                0.0
+            }
+        };
+        self.seek_to(fraction)
+    }
+
+    /// Drive the timeline from scroll position instead of its clock: across
+    /// `trigger`'s pass through the viewport, or the whole page without one.
+    /// `config` is a ScrollTrigger config, e.g. `{ scrub: 0.3 }` to lag
+    /// behind wheel steps or `{ spring: { stiffness: 120, damping: 20 } }`.
+    /// Keep the returned trigger; dropping it unlinks the timeline.
+    #[wasm_bindgen(js_name = linkScroll)]
+    pub fn link_scroll(&self, trigger: Option<Element>, config: JsValue) -> Result<ScrollTrigger, JsValue> {
+        let mut scroll = match trigger {
+            Some(element) => ScrollTrigger::new(element, config)?,
+            None => ScrollTrigger::for_page(config)?,
+        };
+        scroll.scrub_sequencer(self);
+        Ok(scroll)
+    }
+
+    // ========================================================================
+    // SERIALIZATION
+    // ========================================================================
+
+    /// Serialize steps, labels and playback settings to a JSON string.
+    /// Every step must have been built with to() on an element with an id
+    /// (or loaded with fromJson); markers and slow zones aren't included.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsValue> {
+        let state = self.state.borrow();
+        let mut document = TimelineDocument {
+            repeat: state.repeat_count,
+            repeat_delay: state.repeat_delay,
+            yoyo: state.yoyo,
+            time_scale: state.time_scale,
+            ..TimelineDocument::default()
+        };
+
+        for (index, step) in state.steps.iter().enumerate() {
+            let spec = match step.spec {
+                Some(ref spec @ StepSpec { selector: Some(_), .. }) => spec.clone(),
+                _ => return Err(JsValue::from_str(&format!(
+                    "Step {} can't be serialized: build it with to() on an element with an id", index
+                ))),
+            };
+            let mut step_document = StepDocument {
+                selector: spec.selector.unwrap_or_default(),
+                config: spec.config,
+                keyframes: spec.keyframes,
+                easing: spec.easing,
+                duration: step.duration,
+                position: None,
+                overlap: None,
+                offset: None,
+            };
+            match step.placement {
+                Placement::After(overlap) if overlap > 0.0 => step_document.overlap = Some(overlap),
+                Placement::After(_) => {}
+                Placement::AfterStart(offset) => step_document.offset = Some(offset),
+                Placement::At(ref position) => step_document.position = Some(position.clone()),
             }
+            document.steps.push(step_document);
         }
-        
-        Ok(())
+
+        document.labels = state.labels.iter()
+            .map(|label| LabelDocument {
+                name: label.name.clone(),
+                position: label.anchor.position.clone(),
+                after_step: label.anchor.after_step,
+            })
+            .collect();
+
+        let value = serde_wasm_bindgen::to_value(&document)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize timeline: {:?}", e)))?;
+        JSON::stringify(&value)?
+            .as_string()
+            .ok_or_else(|| JsValue::from_str("Failed to serialize timeline"))
+    }
+
+    /// Build a timeline from toJson output (a string or the parsed object),
+    /// resolving each step's selector against the document
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(document: JsValue) -> Result<Sequencer, JsValue> {
+        Sequencer::from_spec(document)
+    }
+
+    /// Build a timeline from a JSON spec (a string or the parsed object):
+    /// toJson's format, where steps may also name `target` and `properties`
+    /// and add `easing` and `keyframes`. See the README for the schema.
+    #[wasm_bindgen(js_name = fromSpec)]
+    pub fn from_spec(document: JsValue) -> Result<Sequencer, JsValue> {
+        let document: TimelineDocument = serde_wasm_bindgen::from_value(spec::parse_document(document)?)
+            .map_err(|e| JsValue::from_str(&format!("Invalid timeline: {:?}", e)))?;
+
+        let mut sequencer = Sequencer::new();
+        let mut labels = document.labels.into_iter().peekable();
+        for (index, step) in document.steps.into_iter().enumerate() {
+            while let Some(label) = labels.next_if(|l| l.after_step <= index) {
+                sequencer.add_label(label.name, label.position)?;
+            }
+            let element = spec::query(&step.selector)?;
+            let placement = match (step.position, step.overlap, step.offset) {
+                (Some(position), _, _) => Placement::At(position),
+                (None, _, Some(offset)) => Placement::AfterStart(offset),
+                (None, overlap, None) => Placement::After(overlap.unwrap_or(0.0).clamp(0.0, 1.0)),
+            };
+            let step_spec = StepSpec { selector: Some(step.selector), config: step.config, easing: step.easing, keyframes: step.keyframes };
+            sequencer.push_config_step(element, step_spec, step.duration, placement)?;
+        }
+        for label in labels {
+            sequencer.add_label(label.name, label.position)?;
+        }
+
+        sequencer.repeat(document.repeat);
+        sequencer.set_repeat_delay(document.repeat_delay);
+        sequencer.yoyo(document.yoyo);
+        sequencer.set_time_scale(document.time_scale)?;
+        Ok(sequencer)
     }
 
     // ========================================================================
@@ -142,27 +1040,83 @@ impl Sequencer {
 
     #[wasm_bindgen(getter, js_name = totalDuration)]
     pub fn total_duration(&self) -> f64 {
-        self.total_duration
+        self.state.borrow().total_duration
     }
 
     #[wasm_bindgen(getter, js_name = stepCount)]
     pub fn step_count(&self) -> usize {
-        self.steps.len()
+        self.state.borrow().steps.len()
     }
 
     #[wasm_bindgen(getter)]
     pub fn fraction(&self) -> f64 {
-        self.fraction
+        self.state.borrow().fraction
+    }
+
+    /// Zero-based index of the iteration being played
+    #[wasm_bindgen(getter)]
+    pub fn iteration(&self) -> i32 {
+        self.state.borrow().iteration
+    }
+
+    #[wasm_bindgen(getter, js_name = timeScale)]
+    pub fn time_scale(&self) -> f64 {
+        self.state.borrow().time_scale
+    }
+
+    #[wasm_bindgen(getter, js_name = isReversed)]
+    pub fn is_reversed(&self) -> bool {
+        self.state.borrow().backwards
     }
 
     #[wasm_bindgen(getter, js_name = isRunning)]
     pub fn is_running(&self) -> bool {
-        self.running
+        self.state.borrow().running
     }
+}
+
+impl Sequencer {
+    /// Seek function sharing this timeline, for something else to drive it
+    /// (e.g. a scroll trigger). Pauses the clock so the two don't fight.
+    pub(crate) fn scrubber(&self) -> Rc<dyn Fn(f64) -> Result<(), JsValue>> {
+        {
+            let mut state = self.state.borrow_mut();
+            if state.running {
+                state.stop_clock();
+                state.paused = true;
+            }
+        }
+        let state = Rc::clone(&self.state);
+        Rc::new(move |fraction| seek(&state, fraction))
+    }
+
+    fn push_config_step(&mut self, element: Element, step_spec: StepSpec, duration: f64, placement: Placement) -> Result<(), JsValue> {
+        let mut animation = crate::Animation::new(element)?;
+        // Validate now rather than when the step begins
+        spec::load_motion(&mut animation, &step_spec.config, &step_spec.keyframes)?;
+        if let Some(ref easing) = step_spec.easing {
+            // Steps are played by fraction, which a spring has no use for
+            match spec::parse_easing(easing)? {
+                spec::Easing::Curve(curve) => animation.bezier = Some(*curve),
+                spec::Easing::Spring => return Err(JsValue::from_str("Timeline steps can't use spring easing")),
+            }
+        }
+        animation.duration = duration.max(0.0);
+
+        let mut state = self.state.borrow_mut();
+        state.push_step(Rc::new(RefCell::new(animation)), placement)?;
+        if let Some(step) = state.steps.last_mut() {
+            if !step_spec.config.is_undefined() && !step_spec.config.is_null() {
+                step.deferred = Some(step_spec.config.clone());
+            }
+            step.spec = Some(step_spec);
+        }
+        Ok(())
+    }
+}
 
-    fn recalculate_duration(&mut self) {
-        self.total_duration = self.steps.iter()
-            .map(|step| step.start + step.duration)
-            .fold(0.0, f64::max);
+impl Drop for Sequencer {
+    fn drop(&mut self) {
+        self.state.borrow_mut().stop_clock();
     }
 }
@@ -1,5 +1,8 @@
 /// Shape Morphing - SVG path interpolation
+use crate::spring::Spring;
+use serde::Deserialize;
 use wasm_bindgen::prelude::*;
+use web_sys::Element;
 
 #[wasm_bindgen]
 pub struct PathMorph {
@@ -8,6 +11,8 @@ pub struct PathMorph {
     progress: f64,
     start_commands: Vec<PathCommand>,
     end_commands: Vec<PathCommand>,
+    precision: Option<u8>,
+    relative: bool,
 }
 
 #[wasm_bindgen]
@@ -33,9 +38,74 @@ impl PathMorph {
             progress: 0.0,
             start_commands,
             end_commands,
+            precision: None,
+            relative: false,
         })
     }
 
+    /// Morph between paths with different commands or command counts.
+    /// Both paths are converted to cubics, then the longest segments of the
+    /// shorter one are split in half until every subpath lines up.
+    /// Subpaths (letter holes, separate shapes) are paired by position and
+    /// size; any left over grow from or shrink to a point at their centre.
+    /// Closed contours of the end path are rotated to start where the start
+    /// path's do, so points travel as little as possible. With `reverse`, a
+    /// contour may also have its winding flipped when that travels less,
+    /// which can change how a nonzero fill treats holes.
+    #[wasm_bindgen]
+    pub fn normalize(
+        start_path: String,
+        end_path: String,
+        reverse: Option<bool>,
+    ) -> Result<PathMorph, JsValue> {
+        if start_path.is_empty() || end_path.is_empty() {
+            return Err(JsValue::from_str("Paths cannot be empty"));
+        }
+
+        let start_subpaths = Subpath::from_commands(&Self::parse_path(&start_path)?);
+        let end_subpaths = Subpath::from_commands(&Self::parse_path(&end_path)?);
+
+        let mut start_commands = Vec::new();
+        let mut end_commands = Vec::new();
+        for (mut start, mut end) in Subpath::pair(start_subpaths, end_subpaths) {
+            let count = start.curves.len().max(end.curves.len());
+            start.subdivide(count);
+            end.subdivide(count);
+            // A subpath closed on one side only keeps its closing segment as a curve
+            let closed = start.closed && end.closed;
+            if closed {
+                end.align_to(&start, reverse.unwrap_or(false));
+            }
+            start_commands.extend(start.into_commands(closed));
+            end_commands.extend(end.into_commands(closed));
+        }
+
+        Ok(PathMorph {
+            start_path,
+            end_path,
+            progress: 0.0,
+            start_commands,
+            end_commands,
+            precision: None,
+            relative: false,
+        })
+    }
+
+    /// Morph between two SVG shape elements: path, circle, ellipse, rect,
+    /// line, polygon or polyline. Their geometry is read from attributes
+    /// and normalized, so any pair can be morphed.
+    #[wasm_bindgen(js_name = fromElements)]
+    pub fn from_elements(start: &Element, end: &Element) -> Result<PathMorph, JsValue> {
+        Self::normalize(element_path(start)?, element_path(end)?, None)
+    }
+
+    /// Morph between shapes given as elements, path strings or descriptors
+    /// such as `{ type: "circle", cx: 50, cy: 50, r: 40 }`
+    #[wasm_bindgen(js_name = fromShapes)]
+    pub fn from_shapes(start: JsValue, end: JsValue) -> Result<PathMorph, JsValue> {
+        Self::normalize(shape_path(start)?, shape_path(end)?, None)
+    }
+
     /// Update morph progress and return interpolated path
     #[wasm_bindgen(js_name = updateProgress)]
     pub fn update_progress(&mut self, progress: f64) -> String {
@@ -67,6 +137,20 @@ impl PathMorph {
         self.interpolate_at(progress.clamp(0.0, 1.0))
     }
 
+    /// Round output coordinates to `digits` decimal places, or keep full
+    /// precision with undefined. 2 is plenty for paths drawn in pixels.
+    #[wasm_bindgen(js_name = setPrecision)]
+    pub fn set_precision(&mut self, digits: Option<u8>) {
+        self.precision = digits.map(|d| d.min(15));
+    }
+
+    /// Write lowercase commands relative to the current point, which keeps
+    /// coordinates small and the path string short
+    #[wasm_bindgen(js_name = setRelative)]
+    pub fn set_relative(&mut self, relative: bool) {
+        self.relative = relative;
+    }
+
     // ========================================================================
     // INTERNAL INTERPOLATION
     // ========================================================================
@@ -76,156 +160,986 @@ impl PathMorph {
     }
 
     fn interpolate_at(&self, t: f64) -> String {
-        let mut result = String::with_capacity(self.start_path.len());
+        let mut writer = PathWriter {
+            out: String::with_capacity(self.start_path.len()),
+            precision: self.precision,
+            relative: self.relative,
+            current: (0.0, 0.0),
+            subpath_start: (0.0, 0.0),
+        };
+        let point = |sx: f64, sy: f64, ex: f64, ey: f64| (lerp(sx, ex, t), lerp(sy, ey, t));
 
         for (start_cmd, end_cmd) in self.start_commands.iter().zip(self.end_commands.iter()) {
-            match (start_cmd, end_cmd) {
+            match (*start_cmd, *end_cmd) {
                 (PathCommand::Move(sx, sy), PathCommand::Move(ex, ey)) => {
-                    result.push_str(&format!("M{} {} ", lerp(*sx, *ex, t), lerp(*sy, *ey, t)));
+                    writer.command('M', &[point(sx, sy, ex, ey)]);
                 }
                 (PathCommand::Line(sx, sy), PathCommand::Line(ex, ey)) => {
-                    result.push_str(&format!("L{} {} ", lerp(*sx, *ex, t), lerp(*sy, *ey, t)));
+                    writer.command('L', &[point(sx, sy, ex, ey)]);
                 }
                 (
                     PathCommand::Cubic(sx1, sy1, sx2, sy2, sx, sy),
                     PathCommand::Cubic(ex1, ey1, ex2, ey2, ex, ey),
                 ) => {
-                    result.push_str(&format!(
-                        "C{} {} {} {} {} {} ",
-                        lerp(*sx1, *ex1, t),
-                        lerp(*sy1, *ey1, t),
-                        lerp(*sx2, *ex2, t),
-                        lerp(*sy2, *ey2, t),
-                        lerp(*sx, *ex, t),
-                        lerp(*sy, *ey, t)
-                    ));
+                    let c1 = point(sx1, sy1, ex1, ey1);
+                    let c2 = point(sx2, sy2, ex2, ey2);
+                    writer.command('C', &[c1, c2, point(sx, sy, ex, ey)]);
                 }
                 (PathCommand::Quad(sx1, sy1, sx, sy), PathCommand::Quad(ex1, ey1, ex, ey)) => {
-                    result.push_str(&format!(
-                        "Q{} {} {} {} ",
-                        lerp(*sx1, *ex1, t),
-                        lerp(*sy1, *ey1, t),
-                        lerp(*sx, *ex, t),
-                        lerp(*sy, *ey, t)
-                    ));
-                }
-                (PathCommand::Close, PathCommand::Close) => {
-                    result.push('Z');
+                    writer.command('Q', &[point(sx1, sy1, ex1, ey1), point(sx, sy, ex, ey)]);
                 }
+                (PathCommand::Close, PathCommand::Close) => writer.close(),
                 _ => {} // Mismatched commands (shouldn't happen after validation)
             }
         }
 
-        result.trim().to_string()
+        writer.out.trim().to_string()
+    }
+
+    /// Swap the start and end paths, for auto-reversing animations
+    pub(crate) fn swap(&mut self) {
+        std::mem::swap(&mut self.start_path, &mut self.end_path);
+        std::mem::swap(&mut self.start_commands, &mut self.end_commands);
     }
 
     // ========================================================================
     // PATH PARSING
     // ========================================================================
 
-    fn parse_path(path: &str) -> Result<Vec<PathCommand>, JsValue> {
+    /// Parse `path` into absolute M, L, C, Q and Z commands: relative
+    /// commands are resolved against the current point, H/V become lines,
+    /// S/T get their reflected control points and arcs become cubics
+    pub(crate) fn parse_path(path: &str) -> Result<Vec<PathCommand>, JsValue> {
+        let mut reader = PathReader::new(path);
         let mut commands = Vec::new();
-        let mut chars = path.trim().chars().peekable();
-
-        while let Some(&ch) = chars.peek() {
-            match ch {
-                'M' | 'm' => {
-                    chars.next();
-                    if let Some(nums) = Self::collect_numbers(&mut chars, 2) {
-                        commands.push(PathCommand::Move(nums[0], nums[1]));
-                    }
+        let mut command: Option<char> = None;
+        let mut current = (0.0, 0.0);
+        let mut subpath_start = (0.0, 0.0);
+        // Second control point of the previous C/S, or control of the previous Q/T
+        let mut last_cubic: Option<(f64, f64)> = None;
+        let mut last_quad: Option<(f64, f64)> = None;
+
+        loop {
+            reader.skip_separators();
+            let Some(next) = reader.peek() else {
+                break;
+            };
+            if next.is_ascii_alphabetic() {
+                reader.advance();
+                command = Some(next);
+            } else if command.is_none() || !reader.at_number() {
+                return Err(JsValue::from_str(&format!(
+                    "Unexpected '{}' in path, expected a command",
+                    next
+                )));
+            }
+            // Numbers without a command letter repeat the previous command
+            let Some(letter) = command else {
+                break;
+            };
+
+            let origin = if letter.is_ascii_lowercase() { current } else { (0.0, 0.0) };
+            let point = |reader: &mut PathReader| -> Result<(f64, f64), JsValue> {
+                let [x, y] = reader.numbers(letter)?;
+                Ok((origin.0 + x, origin.1 + y))
+            };
+            let (mut cubic, mut quad) = (None, None);
+
+            match letter.to_ascii_uppercase() {
+                'M' => {
+                    current = point(&mut reader)?;
+                    subpath_start = current;
+                    commands.push(PathCommand::Move(current.0, current.1));
+                    // Further pairs are lines
+                    command = Some(if letter == 'm' { 'l' } else { 'L' });
                 }
-                'L' | 'l' => {
-                    chars.next();
-                    if let Some(nums) = Self::collect_numbers(&mut chars, 2) {
-                        commands.push(PathCommand::Line(nums[0], nums[1]));
-                    }
+                'L' => {
+                    current = point(&mut reader)?;
+                    commands.push(PathCommand::Line(current.0, current.1));
                 }
-                'C' | 'c' => {
-                    chars.next();
-                    if let Some(nums) = Self::collect_numbers(&mut chars, 6) {
-                        commands.push(PathCommand::Cubic(
-                            nums[0], nums[1], nums[2], nums[3], nums[4], nums[5],
-                        ));
-                    }
+                'H' => {
+                    let [x] = reader.numbers(letter)?;
+                    current.0 = origin.0 + x;
+                    commands.push(PathCommand::Line(current.0, current.1));
                 }
-                'Q' | 'q' => {
-                    chars.next();
-                    if let Some(nums) = Self::collect_numbers(&mut chars, 4) {
-                        commands.push(PathCommand::Quad(nums[0], nums[1], nums[2], nums[3]));
-                    }
+                'V' => {
+                    let [y] = reader.numbers(letter)?;
+                    current.1 = origin.1 + y;
+                    commands.push(PathCommand::Line(current.0, current.1));
                 }
-                'Z' | 'z' => {
-                    chars.next();
+                'C' | 'S' => {
+                    let c1 = if letter.eq_ignore_ascii_case(&'S') {
+                        reflect(last_cubic, current)
+                    } else {
+                        point(&mut reader)?
+                    };
+                    let c2 = point(&mut reader)?;
+                    current = point(&mut reader)?;
+                    commands.push(PathCommand::Cubic(c1.0, c1.1, c2.0, c2.1, current.0, current.1));
+                    cubic = Some(c2);
+                }
+                'Q' | 'T' => {
+                    let control = if letter.eq_ignore_ascii_case(&'T') {
+                        reflect(last_quad, current)
+                    } else {
+                        point(&mut reader)?
+                    };
+                    current = point(&mut reader)?;
+                    commands.push(PathCommand::Quad(control.0, control.1, current.0, current.1));
+                    quad = Some(control);
+                }
+                'A' => {
+                    let [rx, ry, rotation] = reader.numbers(letter)?;
+                    let large_arc = reader.flag(letter)?;
+                    let sweep = reader.flag(letter)?;
+                    let end = point(&mut reader)?;
+                    let arc = Arc {
+                        rx,
+                        ry,
+                        rotation,
+                        large_arc,
+                        sweep,
+                    };
+                    commands.extend(arc.to_cubics(current, end));
+                    current = end;
+                }
+                'Z' => {
                     commands.push(PathCommand::Close);
+                    current = subpath_start;
+                    command = None;
                 }
                 _ => {
-                    chars.next();
+                    return Err(JsValue::from_str(&format!(
+                        "Unsupported path command '{}'",
+                        letter
+                    )))
                 }
             }
+            last_cubic = cubic;
+            last_quad = quad;
         }
 
         Ok(commands)
     }
+}
+
+/// Formats interpolated commands, rounding to `precision` decimals and
+/// optionally writing coordinates relative to the current point
+struct PathWriter {
+    out: String,
+    precision: Option<u8>,
+    relative: bool,
+    /// Current point as written, so rounding errors don't accumulate
+    current: (f64, f64),
+    subpath_start: (f64, f64),
+}
+
+impl PathWriter {
+    fn round(&self, value: f64) -> f64 {
+        let value = match self.precision {
+            Some(digits) => {
+                let scale = 10f64.powi(digits as i32);
+                (value * scale).round() / scale
+            }
+            None => value,
+        };
+        // No "-0"
+        value + 0.0
+    }
+
+    fn command(&mut self, letter: char, points: &[(f64, f64)]) {
+        let origin = if self.relative { self.current } else { (0.0, 0.0) };
+        self.out.push(if self.relative { letter.to_ascii_lowercase() } else { letter });
+
+        for (i, &(x, y)) in points.iter().enumerate() {
+            let (x, y) = (self.round(x - origin.0), self.round(y - origin.1));
+            if i > 0 {
+                self.out.push(' ');
+            }
+            self.out.push_str(&format!("{} {}", x, y));
+            self.current = (origin.0 + x, origin.1 + y);
+        }
+        self.out.push(' ');
+
+        if letter == 'M' {
+            self.subpath_start = self.current;
+        }
+    }
 
-    fn collect_numbers(
-        chars: &mut std::iter::Peekable<std::str::Chars>,
-        count: usize,
-    ) -> Option<Vec<f64>> {
-        let mut numbers = Vec::with_capacity(count);
-        let mut current = String::new();
+    fn close(&mut self) {
+        self.out.push(if self.relative { 'z' } else { 'Z' });
+        self.current = self.subpath_start;
+    }
+}
+
+/// Reflection of the previous control point through the current point, or
+/// the current point itself when the previous command wasn't the same kind
+fn reflect(control: Option<(f64, f64)>, current: (f64, f64)) -> (f64, f64) {
+    match control {
+        Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+        None => current,
+    }
+}
 
-        while numbers.len() < count {
-            match chars.peek() {
-                Some(&ch) if ch.is_numeric() || ch == '.' || ch == '-' => {
-                    current.push(ch);
-                    chars.next();
+/// Reads numbers and flags from path data, allowing the compact forms SVG
+/// editors export ("M10-5.5.5", "a1 1 0 01 10 10")
+struct PathReader {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl PathReader {
+    fn new(path: &str) -> PathReader {
+        PathReader {
+            chars: path.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn advance(&mut self) {
+        self.position += 1;
+    }
+
+    fn skip_separators(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace() || c == ',') {
+            self.advance();
+        }
+    }
+
+    fn at_number(&self) -> bool {
+        self.peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.'))
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_separators();
+        let start = self.position;
+        if matches!(self.peek(), Some('-' | '+')) {
+            self.advance();
+        }
+        let mut seen_dot = false;
+        while let Some(c) = self.peek() {
+            match c {
+                '0'..='9' => {}
+                // A second dot starts the next number
+                '.' if !seen_dot => seen_dot = true,
+                _ => break,
+            }
+            self.advance();
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            let mark = self.position;
+            self.advance();
+            if matches!(self.peek(), Some('-' | '+')) {
+                self.advance();
+            }
+            if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.advance();
                 }
-                Some(&ch) if ch == ',' || ch.is_whitespace() => {
-                    if !current.is_empty() {
-                        if let Ok(num) = current.parse::<f64>() {
-                            numbers.push(num);
-                        }
-                        current.clear();
-                    }
-                    chars.next();
+            } else {
+                // Not an exponent after all
+                self.position = mark;
+            }
+        }
+
+        let text: String = self.chars[start..self.position].iter().collect();
+        let number = text.parse().ok();
+        if number.is_none() {
+            self.position = start;
+        }
+        number
+    }
+
+    fn numbers<const N: usize>(&mut self, command: char) -> Result<[f64; N], JsValue> {
+        let mut numbers = [0.0; N];
+        for number in &mut numbers {
+            *number = self.number().ok_or_else(|| {
+                JsValue::from_str(&format!("Path command '{}' is missing numbers", command))
+            })?;
+        }
+        Ok(numbers)
+    }
+
+    /// Arc flags are single digits and may run into the next number
+    fn flag(&mut self, command: char) -> Result<bool, JsValue> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some('0') => false,
+            Some('1') => true,
+            _ => {
+                return Err(JsValue::from_str(&format!(
+                    "Path command '{}' needs 0 or 1 flags",
+                    command
+                )))
+            }
+        };
+        self.advance();
+        Ok(flag)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PathCommand {
+    Move(f64, f64),
+    Line(f64, f64),
+    Cubic(f64, f64, f64, f64, f64, f64),
+    Quad(f64, f64, f64, f64),
+    Close,
+}
+
+/// Elliptical arc parameters of an SVG A command
+struct Arc {
+    rx: f64,
+    ry: f64,
+    /// x-axis rotation in degrees
+    rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+}
+
+impl Arc {
+    /// Cubic segments (one per quarter turn or less) drawing the arc from
+    /// `from` to `to`, following the SVG spec's endpoint-to-centre conversion
+    fn to_cubics(&self, from: (f64, f64), to: (f64, f64)) -> Vec<PathCommand> {
+        let (x1, y1) = from;
+        let (x2, y2) = to;
+        let (mut rx, mut ry) = (self.rx.abs(), self.ry.abs());
+        if rx == 0.0 || ry == 0.0 {
+            return vec![PathCommand::Line(x2, y2)];
+        }
+        if x1 == x2 && y1 == y2 {
+            return Vec::new();
+        }
+
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        // Start point in the ellipse's frame, relative to the chord midpoint
+        let dx = (x1 - x2) / 2.0;
+        let dy = (y1 - y2) / 2.0;
+        let x1p = cos * dx + sin * dy;
+        let y1p = -sin * dx + cos * dy;
+
+        // Scale up radii too small to reach the end point
+        let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let numerator = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+        let denominator = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+        let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+        if self.large_arc == self.sweep {
+            coefficient = -coefficient;
+        }
+        let cxp = coefficient * rx * y1p / ry;
+        let cyp = -coefficient * ry * x1p / rx;
+        let cx = cos * cxp - sin * cyp + (x1 + x2) / 2.0;
+        let cy = sin * cxp + cos * cyp + (y1 + y2) / 2.0;
+
+        // Signed angle from vector u to vector v
+        let angle = |ux: f64, uy: f64, vx: f64, vy: f64| {
+            (ux * vy - uy * vx).atan2(ux * vx + uy * vy)
+        };
+        let start = angle(1.0, 0.0, (x1p - cxp) / rx, (y1p - cyp) / ry);
+        let mut delta = angle(
+            (x1p - cxp) / rx,
+            (y1p - cyp) / ry,
+            (-x1p - cxp) / rx,
+            (-y1p - cyp) / ry,
+        );
+        if !self.sweep && delta > 0.0 {
+            delta -= std::f64::consts::TAU;
+        } else if self.sweep && delta < 0.0 {
+            delta += std::f64::consts::TAU;
+        }
+
+        // Point and derivative on the ellipse at angle t
+        let point = |t: f64| {
+            let (x, y) = (rx * t.cos(), ry * t.sin());
+            (cos * x - sin * y + cx, sin * x + cos * y + cy)
+        };
+        let tangent = |t: f64| {
+            let (x, y) = (-rx * t.sin(), ry * t.cos());
+            (cos * x - sin * y, sin * x + cos * y)
+        };
+
+        let segments = (delta.abs() / std::f64::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = delta / segments as f64;
+        let k = 4.0 / 3.0 * (step / 4.0).tan();
+        (0..segments)
+            .map(|i| {
+                let t1 = start + step * i as f64;
+                let t2 = t1 + step;
+                let (p1, d1) = (point(t1), tangent(t1));
+                let (p2, d2) = (point(t2), tangent(t2));
+                // Land exactly on the requested end point
+                let p2 = if i + 1 == segments { (x2, y2) } else { p2 };
+                PathCommand::Cubic(
+                    p1.0 + k * d1.0,
+                    p1.1 + k * d1.1,
+                    p2.0 - k * d2.0,
+                    p2.1 - k * d2.1,
+                    p2.0,
+                    p2.1,
+                )
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// ANIMATION - A morph driven by an Animation's curve or spring
+// ============================================================================
+
+/// Path morph attached to an `Animation`, written to the element's `d`
+/// attribute whenever the animation renders
+pub(crate) struct MorphTrack {
+    morph: PathMorph,
+    spring: Spring,
+    progress: f64,
+}
+
+impl MorphTrack {
+    pub(crate) fn new(morph: PathMorph) -> MorphTrack {
+        MorphTrack {
+            morph,
+            spring: Spring::default(),
+            progress: 0.0,
+        }
+    }
+
+    /// Back to the start path, with the spring at rest
+    pub(crate) fn reset(&mut self) {
+        self.spring.reset(0.0);
+        self.progress = 0.0;
+    }
+
+    /// Play towards the other path from where the morph is now
+    pub(crate) fn reverse(&mut self) {
+        self.morph.swap();
+        self.reset();
+    }
+
+    /// Follow an eased progress; values outside 0..1 overshoot the paths
+    pub(crate) fn sample(&mut self, eased: f64) {
+        self.progress = eased;
+    }
+
+    /// Step the spring towards the end path. Returns false once it has settled.
+    pub(crate) fn step(&mut self, delta_time: f64) -> bool {
+        self.progress = self.spring.update(1.0, delta_time);
+        self.spring.velocity.abs() > 0.001 || (self.progress - 1.0).abs() > 0.001
+    }
+
+    pub(crate) fn apply(&self, element: &Element) -> Result<(), JsValue> {
+        element.set_attribute("d", &self.morph.interpolate_at(self.progress))
+    }
+}
+
+// ============================================================================
+// SHAPE PRIMITIVES - Circles, rects and friends as path data
+// ============================================================================
+
+/// An SVG primitive's geometry, from an element's attributes or a JS descriptor
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Shape {
+    Circle {
+        #[serde(default)]
+        cx: f64,
+        #[serde(default)]
+        cy: f64,
+        r: f64,
+    },
+    Ellipse {
+        #[serde(default)]
+        cx: f64,
+        #[serde(default)]
+        cy: f64,
+        rx: f64,
+        ry: f64,
+    },
+    Rect {
+        #[serde(default)]
+        x: f64,
+        #[serde(default)]
+        y: f64,
+        width: f64,
+        height: f64,
+        rx: Option<f64>,
+        ry: Option<f64>,
+    },
+    Line {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    },
+    Polygon {
+        points: Vec<(f64, f64)>,
+    },
+    Polyline {
+        points: Vec<(f64, f64)>,
+    },
+    Path {
+        d: String,
+    },
+}
+
+impl Shape {
+    /// Equivalent path data, starting and winding where the SVG spec says
+    /// the shape does so that morphs between primitives line up
+    fn to_path(&self) -> String {
+        match self {
+            Shape::Circle { cx, cy, r } => ellipse_path(*cx, *cy, *r, *r),
+            Shape::Ellipse { cx, cy, rx, ry } => ellipse_path(*cx, *cy, *rx, *ry),
+            Shape::Rect { x, y, width, height, rx, ry } => {
+                // A missing radius copies the other; both fit inside the rect
+                let (rx, ry) = (rx.or(*ry).unwrap_or(0.0), ry.or(*rx).unwrap_or(0.0));
+                let rx = rx.clamp(0.0, (width / 2.0).max(0.0));
+                let ry = ry.clamp(0.0, (height / 2.0).max(0.0));
+                let (right, bottom) = (x + width, y + height);
+                if rx <= 0.0 || ry <= 0.0 {
+                    return format!("M{} {} H{} V{} H{} Z", x, y, right, bottom, x);
+                }
+                let corner = |x: f64, y: f64| format!("A{} {} 0 0 1 {} {}", rx, ry, x, y);
+                format!(
+                    "M{} {} H{} {} V{} {} H{} {} V{} {} Z",
+                    x + rx, y, right - rx, corner(right, y + ry),
+                    bottom - ry, corner(right - rx, bottom),
+                    x + rx, corner(*x, bottom - ry),
+                    y + ry, corner(x + rx, *y)
+                )
+            }
+            Shape::Line { x1, y1, x2, y2 } => format!("M{} {} L{} {}", x1, y1, x2, y2),
+            Shape::Polygon { points } => points_path(points, true),
+            Shape::Polyline { points } => points_path(points, false),
+            Shape::Path { d } => d.clone(),
+        }
+    }
+}
+
+/// Two half-ellipse arcs from the rightmost point, clockwise
+fn ellipse_path(cx: f64, cy: f64, rx: f64, ry: f64) -> String {
+    format!(
+        "M{} {} A{} {} 0 0 1 {} {} A{} {} 0 0 1 {} {} Z",
+        cx + rx, cy, rx, ry, cx - rx, cy, rx, ry, cx + rx, cy
+    )
+}
+
+fn points_path(points: &[(f64, f64)], closed: bool) -> String {
+    let mut path = String::new();
+    for (i, (x, y)) in points.iter().enumerate() {
+        path.push_str(&format!("{}{} {} ", if i == 0 { 'M' } else { 'L' }, x, y));
+    }
+    if closed && !points.is_empty() {
+        path.push('Z');
+    }
+    path.trim_end().to_string()
+}
+
+/// Path data for an SVG shape element, read from its attributes
+pub(crate) fn element_path(element: &Element) -> Result<String, JsValue> {
+    // Plain user units; "12px" reads as 12, anything else as 0
+    let number = |name: &str| {
+        element
+            .get_attribute(name)
+            .and_then(|value| value.trim().trim_end_matches("px").parse::<f64>().ok())
+    };
+    let value = |name: &str| number(name).unwrap_or(0.0);
+    let points = || {
+        let numbers: Vec<f64> = element
+            .get_attribute("points")
+            .unwrap_or_default()
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter_map(|n| n.parse().ok())
+            .collect();
+        numbers.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect()
+    };
+
+    let tag = element.tag_name().to_ascii_lowercase();
+    let shape = match tag.as_str() {
+        "path" => Shape::Path {
+            d: element.get_attribute("d").unwrap_or_default(),
+        },
+        "circle" => Shape::Circle {
+            cx: value("cx"),
+            cy: value("cy"),
+            r: value("r"),
+        },
+        "ellipse" => Shape::Ellipse {
+            cx: value("cx"),
+            cy: value("cy"),
+            rx: value("rx"),
+            ry: value("ry"),
+        },
+        "rect" => Shape::Rect {
+            x: value("x"),
+            y: value("y"),
+            width: value("width"),
+            height: value("height"),
+            rx: number("rx"),
+            ry: number("ry"),
+        },
+        "line" => Shape::Line {
+            x1: value("x1"),
+            y1: value("y1"),
+            x2: value("x2"),
+            y2: value("y2"),
+        },
+        "polygon" => Shape::Polygon { points: points() },
+        "polyline" => Shape::Polyline { points: points() },
+        _ => {
+            return Err(JsValue::from_str(&format!(
+                "Can't morph <{}>: use path, circle, ellipse, rect, line, polygon or polyline",
+                tag
+            )))
+        }
+    };
+    Ok(shape.to_path())
+}
+
+/// Path data for an element, a path string or a shape descriptor
+fn shape_path(value: JsValue) -> Result<String, JsValue> {
+    if let Some(element) = value.dyn_ref::<Element>() {
+        return element_path(element);
+    }
+    if let Some(path) = value.as_string() {
+        return Ok(path);
+    }
+    let shape: Shape = serde_wasm_bindgen::from_value(value)
+        .map_err(|e| JsValue::from_str(&format!("Invalid shape: {:?}", e)))?;
+    Ok(shape.to_path())
+}
+
+// ============================================================================
+// NORMALIZATION
+// ============================================================================
+
+type Point = (f64, f64);
+
+/// One M...Z run of a path with every segment as a cubic (start, two
+/// control points, end)
+struct Subpath {
+    start: Point,
+    curves: Vec<[Point; 4]>,
+    closed: bool,
+}
+
+impl Subpath {
+    fn new(start: Point) -> Subpath {
+        Subpath {
+            start,
+            curves: Vec::new(),
+            closed: false,
+        }
+    }
+
+    fn end(&self) -> Point {
+        self.curves.last().map_or(self.start, |curve| curve[3])
+    }
+
+    fn from_commands(commands: &[PathCommand]) -> Vec<Subpath> {
+        let mut subpaths: Vec<Subpath> = Vec::new();
+        let mut current = (0.0, 0.0);
+
+        for command in commands {
+            match *command {
+                PathCommand::Move(x, y) => {
+                    current = (x, y);
+                    subpaths.push(Subpath::new(current));
+                    continue;
                 }
-                Some(&ch) if ch.is_alphabetic() => break,
-                None => {
-                    if !current.is_empty() {
-                        if let Ok(num) = current.parse::<f64>() {
-                            numbers.push(num);
+                PathCommand::Close => {
+                    if let Some(subpath) = subpaths.last_mut().filter(|s| !s.closed) {
+                        let (end, start) = (subpath.end(), subpath.start);
+                        if end != start {
+                            subpath.curves.push(line_cubic(end, start));
                         }
+                        subpath.closed = true;
+                        current = start;
                     }
-                    break;
+                    continue;
                 }
-                _ => {
-                    chars.next();
+                _ => {}
+            }
+
+            // Drawing without a move, or after a close, starts a new subpath here
+            if subpaths.last().is_none_or(|s| s.closed) {
+                subpaths.push(Subpath::new(current));
+            }
+            let curve = match *command {
+                PathCommand::Line(x, y) => line_cubic(current, (x, y)),
+                PathCommand::Cubic(x1, y1, x2, y2, x, y) => [current, (x1, y1), (x2, y2), (x, y)],
+                PathCommand::Quad(x1, y1, x, y) => {
+                    // Degree elevation: controls two thirds of the way to the quad control
+                    let (x0, y0) = current;
+                    [
+                        current,
+                        (x0 + 2.0 / 3.0 * (x1 - x0), y0 + 2.0 / 3.0 * (y1 - y0)),
+                        (x + 2.0 / 3.0 * (x1 - x), y + 2.0 / 3.0 * (y1 - y)),
+                        (x, y),
+                    ]
                 }
+                PathCommand::Move(..) | PathCommand::Close => unreachable!(),
+            };
+            current = curve[3];
+            if let Some(subpath) = subpaths.last_mut() {
+                subpath.curves.push(curve);
             }
         }
 
-        if !current.is_empty() {
-            if let Ok(num) = current.parse::<f64>() {
-                numbers.push(num);
+        subpaths
+    }
+
+    /// Pair up subpaths, cheapest match first, where the cost is the distance
+    /// between centroids plus the difference in size. Unmatched subpaths are
+    /// paired with a copy collapsed to their own centroid.
+    fn pair(start: Vec<Subpath>, end: Vec<Subpath>) -> Vec<(Subpath, Subpath)> {
+        let start_shapes: Vec<_> = start.iter().map(Subpath::shape).collect();
+        let end_shapes: Vec<_> = end.iter().map(Subpath::shape).collect();
+
+        let mut costs = Vec::with_capacity(start.len() * end.len());
+        for (i, &(start_area, (sx, sy))) in start_shapes.iter().enumerate() {
+            for (j, &(end_area, (ex, ey))) in end_shapes.iter().enumerate() {
+                let cost = (ex - sx).hypot(ey - sy) + (end_area.sqrt() - start_area.sqrt()).abs();
+                costs.push((cost, i, j));
+            }
+        }
+        costs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut partners: Vec<Option<usize>> = vec![None; start.len()];
+        let mut taken = vec![false; end.len()];
+        for (_, i, j) in costs {
+            if partners[i].is_none() && !taken[j] {
+                partners[i] = Some(j);
+                taken[j] = true;
+            }
+        }
+
+        let mut end: Vec<Option<Subpath>> = end.into_iter().map(Some).collect();
+        let mut pairs = Vec::with_capacity(start.len().max(end.len()));
+        for (i, subpath) in start.into_iter().enumerate() {
+            let partner = partners[i]
+                .and_then(|j| end[j].take())
+                .unwrap_or_else(|| subpath.collapsed(start_shapes[i].1));
+            pairs.push((subpath, partner));
+        }
+        for (j, subpath) in end.into_iter().enumerate() {
+            if let Some(subpath) = subpath {
+                pairs.push((subpath.collapsed(end_shapes[j].1), subpath));
+            }
+        }
+        pairs
+    }
+
+    /// Enclosed area and centroid, from the curves sampled as a polygon.
+    /// Open or flat subpaths use the average of their points as the centroid.
+    fn shape(&self) -> (f64, Point) {
+        let mut points = vec![self.start];
+        for curve in &self.curves {
+            points.extend((1..=8).map(|i| cubic_point(curve, i as f64 / 8.0)));
+        }
+
+        let (mut area, mut cx, mut cy) = (0.0, 0.0, 0.0);
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            let cross = x0 * y1 - x1 * y0;
+            area += cross;
+            cx += (x0 + x1) * cross;
+            cy += (y0 + y1) * cross;
+        }
+        area /= 2.0;
+
+        if area.abs() < 1e-9 {
+            let count = points.len() as f64;
+            let (sx, sy) = points.iter().fold((0.0, 0.0), |(sx, sy), p| (sx + p.0, sy + p.1));
+            return (0.0, (sx / count, sy / count));
+        }
+        (area.abs(), (cx / (6.0 * area), cy / (6.0 * area)))
+    }
+
+    /// A single point at `at`, standing in for a subpath with no partner
+    fn collapsed(&self, at: Point) -> Subpath {
+        Subpath {
+            start: at,
+            curves: vec![[at; 4]],
+            closed: self.closed,
+        }
+    }
+
+    /// Split the longest curves in half until there are `count` of them
+    fn subdivide(&mut self, count: usize) {
+        if self.curves.is_empty() {
+            // A lone move morphs as a point
+            self.curves.push([self.start; 4]);
+        }
+        while self.curves.len() < count {
+            let longest = (0..self.curves.len())
+                .max_by(|&a, &b| {
+                    hull_length(&self.curves[a]).total_cmp(&hull_length(&self.curves[b]))
+                })
+                .unwrap_or(0);
+            let (left, right) = split_cubic(&self.curves[longest]);
+            self.curves[longest] = left;
+            self.curves.insert(longest + 1, right);
+        }
+    }
+
+    /// Rotate this closed loop (and with `reverse`, possibly flip it) so its
+    /// points travel the least total distance to those of `target`, which
+    /// has the same number of curves
+    fn align_to(&mut self, target: &Subpath, reverse: bool) {
+        let count = self.curves.len();
+        let travel = |curves: &[[Point; 4]], offset: usize| -> f64 {
+            target
+                .curves
+                .iter()
+                .enumerate()
+                .map(|(i, curve)| {
+                    let (x, y) = curves[(i + offset) % count][3];
+                    (x - curve[3].0).powi(2) + (y - curve[3].1).powi(2)
+                })
+                .sum()
+        };
+        let best = |curves: &[[Point; 4]]| {
+            (0..count)
+                .map(|offset| (travel(curves, offset), offset))
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .unwrap_or((0.0, 0))
+        };
+
+        let (cost, mut offset) = best(&self.curves);
+        if reverse {
+            let reversed: Vec<_> =
+                self.curves.iter().rev().map(|&[p0, p1, p2, p3]| [p3, p2, p1, p0]).collect();
+            let (reversed_cost, reversed_offset) = best(&reversed);
+            if reversed_cost < cost {
+                self.curves = reversed;
+                offset = reversed_offset;
             }
         }
 
-        if numbers.len() == count {
-            Some(numbers)
-        } else {
-            None
+        self.curves.rotate_left(offset);
+        self.start = self.curves[0][0];
+    }
+
+    fn into_commands(self, closed: bool) -> Vec<PathCommand> {
+        let mut commands = vec![PathCommand::Move(self.start.0, self.start.1)];
+        commands.extend(self.curves.iter().map(|&[_, (x1, y1), (x2, y2), (x, y)]| {
+            PathCommand::Cubic(x1, y1, x2, y2, x, y)
+        }));
+        if closed {
+            commands.push(PathCommand::Close);
         }
+        commands
     }
 }
 
-#[derive(Debug, Clone)]
-enum PathCommand {
-    Move(f64, f64),
-    Line(f64, f64),
-    Cubic(f64, f64, f64, f64, f64, f64),
-    Quad(f64, f64, f64, f64),
-    Close,
+/// Straight line as a cubic with controls at its thirds
+fn line_cubic(from: Point, to: Point) -> [Point; 4] {
+    let third = |t: f64| (lerp(from.0, to.0, t), lerp(from.1, to.1, t));
+    [from, third(1.0 / 3.0), third(2.0 / 3.0), to]
+}
+
+fn cubic_point(curve: &[Point; 4], t: f64) -> Point {
+    let [p0, p1, p2, p3] = *curve;
+    let mt = 1.0 - t;
+    let (a, b) = (mt * mt * mt, 3.0 * mt * mt * t);
+    let (c, d) = (3.0 * mt * t * t, t * t * t);
+    (
+        a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+        a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+    )
+}
+
+/// Length of the control polygon, an upper bound on the curve's length
+fn hull_length(curve: &[Point; 4]) -> f64 {
+    curve
+        .windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0).hypot(pair[1].1 - pair[0].1))
+        .sum()
+}
+
+/// De Casteljau split at the curve's midpoint
+fn split_cubic(curve: &[Point; 4]) -> ([Point; 4], [Point; 4]) {
+    let mid = |a: Point, b: Point| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let [p0, p1, p2, p3] = *curve;
+    let (a, b, c) = (mid(p0, p1), mid(p1, p2), mid(p2, p3));
+    let (d, e) = (mid(a, b), mid(b, c));
+    let f = mid(d, e);
+    ([p0, a, d, f], [f, e, c, p3])
+}
+
+/// Flatten parsed commands into straight segments, splitting each curve
+/// finely enough that it strays no more than `tolerance` from the lines
+pub(crate) fn flatten_path(
+    commands: &[PathCommand],
+    tolerance: f64,
+) -> Vec<((f64, f64), (f64, f64))> {
+    let tolerance = tolerance.max(1e-3);
+    // Lines needed for a curve whose largest second difference is `bend`;
+    // the error of n lines is at most bend * scale / n²
+    let steps = |bend: f64, scale: f64| {
+        ((bend * scale / tolerance).sqrt().ceil() as usize).clamp(1, 1024)
+    };
+    let mut segments = Vec::new();
+    let mut current = (0.0, 0.0);
+    let mut subpath_start = (0.0, 0.0);
+
+    for command in commands {
+        match *command {
+            PathCommand::Move(x, y) => {
+                current = (x, y);
+                subpath_start = current;
+            }
+            PathCommand::Line(x, y) => {
+                segments.push((current, (x, y)));
+                current = (x, y);
+            }
+            PathCommand::Cubic(x1, y1, x2, y2, x, y) => {
+                let curve = [current, (x1, y1), (x2, y2), (x, y)];
+                let (x0, y0) = current;
+                let bend = (x0 - 2.0 * x1 + x2)
+                    .hypot(y0 - 2.0 * y1 + y2)
+                    .max((x1 - 2.0 * x2 + x).hypot(y1 - 2.0 * y2 + y));
+                let steps = steps(bend, 0.75);
+                for i in 1..=steps {
+                    let t = i as f64 / steps as f64;
+                    let point = cubic_point(&curve, t);
+                    segments.push((current, point));
+                    current = point;
+                }
+            }
+            PathCommand::Quad(x1, y1, x, y) => {
+                let (x0, y0) = current;
+                let steps = steps((x0 - 2.0 * x1 + x).hypot(y0 - 2.0 * y1 + y), 0.25);
+                for i in 1..=steps {
+                    let t = i as f64 / steps as f64;
+                    let mt = 1.0 - t;
+                    let point = (
+                        mt * mt * x0 + 2.0 * mt * t * x1 + t * t * x,
+                        mt * mt * y0 + 2.0 * mt * t * y1 + t * t * y,
+                    );
+                    segments.push((current, point));
+                    current = point;
+                }
+            }
+            PathCommand::Close => {
+                if current != subpath_start {
+                    segments.push((current, subpath_start));
+                }
+                current = subpath_start;
+            }
+        }
+    }
+
+    segments
 }
 
 #[inline]
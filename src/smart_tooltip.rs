@@ -0,0 +1,323 @@
+use crate::measure::{self, Rect};
+use crate::spring::Spring;
+use crate::ticker;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{Element, HtmlElement};
+
+// ============================================================================
+// SMART TOOLTIP - Collision-avoiding placement with spring transitions
+// ============================================================================
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TooltipPlacement {
+    Top = 0,
+    Bottom = 1,
+    Left = 2,
+    Right = 3,
+}
+
+impl TooltipPlacement {
+    fn from_u8(value: u8) -> Option<TooltipPlacement> {
+        match value {
+            0 => Some(TooltipPlacement::Top),
+            1 => Some(TooltipPlacement::Bottom),
+            2 => Some(TooltipPlacement::Left),
+            3 => Some(TooltipPlacement::Right),
+            _ => None,
+        }
+    }
+}
+
+struct TooltipState {
+    floating: HtmlElement,
+    anchor: Element,
+    preferred: Vec<TooltipPlacement>,
+    obstacles: Vec<Rect>,
+    gap: f64,
+    margin: f64,
+
+    placement: TooltipPlacement,
+    target: (f64, f64),
+    spring_x: Spring,
+    spring_y: Spring,
+    last_anchor: Option<Rect>,
+    last_viewport: Option<Rect>,
+    placed: bool,
+}
+
+impl TooltipState {
+    /// Re-measure and pick a placement if the anchor or viewport changed
+    fn measure(&mut self, force: bool) -> Result<(), JsValue> {
+        let anchor = measure::element_rect(&self.anchor);
+        let viewport = measure::viewport_rect()?;
+
+        if !force && self.last_anchor == Some(anchor) && self.last_viewport == Some(viewport) {
+            return Ok(());
+        }
+        self.last_anchor = Some(anchor);
+        self.last_viewport = Some(viewport);
+
+        let own = measure::element_rect(&self.floating);
+        let bounds = viewport.inset(self.margin);
+
+        let mut best: Option<(TooltipPlacement, Rect, f64)> = None;
+        for &placement in &self.preferred {
+            let rect = self.candidate(placement, &anchor, own.width, own.height);
+            let cost = rect.overflow_area(&bounds)
+                + self
+                    .obstacles
+                    .iter()
+                    .map(|o| rect.overlap_area(o))
+                    .sum::<f64>();
+
+            if best.as_ref().is_none_or(|b| cost < b.2) {
+                best = Some((placement, rect, cost));
+            }
+            if cost == 0.0 {
+                break;
+            }
+        }
+
+        if let Some((placement, rect, _)) = best {
+            // Slide along the edge to stay on screen when no placement fits fully
+            let x = rect
+                .x
+                .clamp(bounds.x, (bounds.right() - rect.width).max(bounds.x));
+            let y = rect
+                .y
+                .clamp(bounds.y, (bounds.bottom() - rect.height).max(bounds.y));
+
+            self.placement = placement;
+            self.target = (x, y);
+
+            if !self.placed {
+                self.spring_x.current = x;
+                self.spring_y.current = y;
+                self.placed = true;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn candidate(
+        &self,
+        placement: TooltipPlacement,
+        anchor: &Rect,
+        width: f64,
+        height: f64,
+    ) -> Rect {
+        match placement {
+            TooltipPlacement::Top => Rect::new(
+                anchor.center_x() - width * 0.5,
+                anchor.y - self.gap - height,
+                width,
+                height,
+            ),
+            TooltipPlacement::Bottom => Rect::new(
+                anchor.center_x() - width * 0.5,
+                anchor.bottom() + self.gap,
+                width,
+                height,
+            ),
+            TooltipPlacement::Left => Rect::new(
+                anchor.x - self.gap - width,
+                anchor.center_y() - height * 0.5,
+                width,
+                height,
+            ),
+            TooltipPlacement::Right => Rect::new(
+                anchor.right() + self.gap,
+                anchor.center_y() - height * 0.5,
+                width,
+                height,
+            ),
+        }
+    }
+
+    fn step(&mut self, dt: f64) -> Result<(), JsValue> {
+        let dt = dt.min(0.032);
+        let x = self.spring_x.update(self.target.0, dt);
+        let y = self.spring_y.update(self.target.1, dt);
+        self.write(x, y)
+    }
+
+    fn write(&self, x: f64, y: f64) -> Result<(), JsValue> {
+        self.floating
+            .style()
+            .set_property("transform", &format!("translate3d({}px, {}px, 0)", x, y))
+    }
+}
+
+#[wasm_bindgen]
+pub struct SmartTooltip {
+    state: Rc<RefCell<TooltipState>>,
+    ticker_id: Option<u32>,
+}
+
+#[wasm_bindgen]
+impl SmartTooltip {
+    /// `floating` is positioned with `position: fixed` at the viewport origin
+    /// and moved with transforms
+    #[wasm_bindgen(constructor)]
+    pub fn new(floating: HtmlElement, anchor: Element) -> Result<SmartTooltip, JsValue> {
+        let style = floating.style();
+        style.set_property("position", "fixed")?;
+        style.set_property("left", "0")?;
+        style.set_property("top", "0")?;
+
+        Ok(SmartTooltip {
+            state: Rc::new(RefCell::new(TooltipState {
+                floating,
+                anchor,
+                preferred: vec![
+                    TooltipPlacement::Top,
+                    TooltipPlacement::Bottom,
+                    TooltipPlacement::Right,
+                    TooltipPlacement::Left,
+                ],
+                obstacles: Vec::new(),
+                gap: 8.0,
+                margin: 8.0,
+                placement: TooltipPlacement::Top,
+                target: (0.0, 0.0),
+                spring_x: Spring::default(),
+                spring_y: Spring::default(),
+                last_anchor: None,
+                last_viewport: None,
+                placed: false,
+            })),
+            ticker_id: None,
+        })
+    }
+
+    // ========================================================================
+    // CONFIGURATION
+    // ========================================================================
+
+    /// Placements to try in order: 0=Top, 1=Bottom, 2=Left, 3=Right
+    #[wasm_bindgen(js_name = setPreferredPlacements)]
+    pub fn set_preferred_placements(&mut self, placements: Vec<u8>) -> Result<(), JsValue> {
+        let preferred = placements
+            .into_iter()
+            .map(|p| {
+                TooltipPlacement::from_u8(p).ok_or_else(|| {
+                    JsValue::from_str("Invalid placement: 0=Top, 1=Bottom, 2=Left, 3=Right")
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if preferred.is_empty() {
+            return Err(JsValue::from_str("At least one placement is required"));
+        }
+        self.state.borrow_mut().preferred = preferred;
+        Ok(())
+    }
+
+    /// Rects to keep clear of: `[{ x, y, width, height }]` in viewport coordinates
+    #[wasm_bindgen(js_name = setObstacles)]
+    pub fn set_obstacles(&mut self, obstacles: JsValue) -> Result<(), JsValue> {
+        let obstacles: Vec<Rect> = serde_wasm_bindgen::from_value(obstacles)
+            .map_err(|e| JsValue::from_str(&format!("Invalid obstacles: {:?}", e)))?;
+        let mut state = self.state.borrow_mut();
+        state.obstacles = obstacles;
+        state.measure(true)
+    }
+
+    /// Add an element's current rect as an obstacle
+    #[wasm_bindgen(js_name = addObstacleElement)]
+    pub fn add_obstacle_element(&mut self, element: &Element) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        state.obstacles.push(measure::element_rect(element));
+        state.measure(true)
+    }
+
+    #[wasm_bindgen(js_name = clearObstacles)]
+    pub fn clear_obstacles(&mut self) {
+        self.state.borrow_mut().obstacles.clear();
+    }
+
+    /// Distance between anchor and tooltip in px
+    #[wasm_bindgen(setter)]
+    pub fn set_gap(&mut self, gap: f64) {
+        self.state.borrow_mut().gap = gap.max(0.0);
+    }
+
+    /// Minimum distance from the viewport edges in px
+    #[wasm_bindgen(setter)]
+    pub fn set_margin(&mut self, margin: f64) {
+        self.state.borrow_mut().margin = margin.max(0.0);
+    }
+
+    #[wasm_bindgen(js_name = setSpring)]
+    pub fn set_spring(&mut self, stiffness: f64, damping: f64) {
+        let mut state = self.state.borrow_mut();
+        state.spring_x.stiffness = stiffness;
+        state.spring_x.damping = damping;
+        state.spring_y.stiffness = stiffness;
+        state.spring_y.damping = damping;
+    }
+
+    // ========================================================================
+    // TRACKING
+    // ========================================================================
+
+    /// Place the tooltip and follow the anchor until hide()
+    #[wasm_bindgen]
+    pub fn show(&mut self) -> Result<(), JsValue> {
+        {
+            let mut state = self.state.borrow_mut();
+            state.placed = false;
+            state.measure(true)?;
+            let (x, y) = state.target;
+            state.write(x, y)?;
+        }
+
+        if self.ticker_id.is_none() {
+            let state = Rc::clone(&self.state);
+            self.ticker_id = Some(ticker::subscribe(Box::new(move |_now, dt| {
+                let mut state = state.borrow_mut();
+                let _ = state.measure(false);
+                let _ = state.step(dt);
+                true
+            }))?);
+        }
+        Ok(())
+    }
+
+    #[wasm_bindgen]
+    pub fn hide(&mut self) {
+        if let Some(id) = self.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+
+    /// Re-evaluate placement now, e.g. after the tooltip's content changed size
+    #[wasm_bindgen]
+    pub fn reposition(&mut self) -> Result<(), JsValue> {
+        self.state.borrow_mut().measure(true)
+    }
+
+    // ========================================================================
+    // PROPERTIES
+    // ========================================================================
+
+    #[wasm_bindgen(getter)]
+    pub fn placement(&self) -> TooltipPlacement {
+        self.state.borrow().placement
+    }
+
+    #[wasm_bindgen(getter, js_name = isVisible)]
+    pub fn is_visible(&self) -> bool {
+        self.ticker_id.is_some()
+    }
+}
+
+impl Drop for SmartTooltip {
+    fn drop(&mut self) {
+        self.hide();
+    }
+}
@@ -0,0 +1,433 @@
+use crate::cubic::CubicBezier;
+use crate::measure;
+use crate::spring::Spring;
+use crate::ticker;
+use js_sys::{Function, Promise, Reflect};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    window, AddEventListenerOptions, Element, Event, EventTarget, KeyboardEvent, ScrollBehavior,
+    ScrollToOptions,
+};
+
+// ============================================================================
+// SMOOTH SCROLL - Animated scrollTo that gives way to the user
+// ============================================================================
+
+// Longest spring step; longer frames would let the spring overshoot wildly
+const MAX_SPRING_DT: f64 = 0.032;
+// Pixels the scroller may differ from the last written position before it
+// counts as the user scrolling (browsers round to device pixels)
+const DRIFT_TOLERANCE: f64 = 1.5;
+const SCROLL_KEYS: [&str; 7] = [
+    "ArrowUp",
+    "ArrowDown",
+    "PageUp",
+    "PageDown",
+    "Home",
+    "End",
+    " ",
+];
+
+enum Motion {
+    // Boxed: the bezier carries its lookup table
+    Eased {
+        duration: f64,
+        easing: Box<CubicBezier>,
+    },
+    Spring(Spring),
+}
+
+/// Scroll position over time, from `from` to `to`
+pub struct ScrollTween {
+    from: f64,
+    to: f64,
+    elapsed: f64,
+    motion: Motion,
+}
+
+impl ScrollTween {
+    /// Over `duration` ms through `easing`
+    pub fn eased(from: f64, to: f64, duration: f64, easing: CubicBezier) -> Self {
+        ScrollTween {
+            from,
+            to,
+            elapsed: 0.0,
+            motion: Motion::Eased {
+                duration: duration.max(0.0),
+                easing: Box::new(easing),
+            },
+        }
+    }
+
+    /// Pulled by `spring` until it comes to rest
+    pub fn spring(from: f64, to: f64, mut spring: Spring) -> Self {
+        spring.reset(from);
+        ScrollTween {
+            from,
+            to,
+            elapsed: 0.0,
+            motion: Motion::Spring(spring),
+        }
+    }
+
+    /// Advance by `dt` seconds; returns the position and whether it arrived
+    pub fn step(&mut self, dt: f64) -> (f64, bool) {
+        match &mut self.motion {
+            Motion::Eased { duration, easing } => {
+                self.elapsed += dt * 1000.0;
+                if self.elapsed >= *duration {
+                    return (self.to, true);
+                }
+                let eased = easing.solve(self.elapsed / *duration);
+                (self.from + (self.to - self.from) * eased, false)
+            }
+            Motion::Spring(spring) => {
+                let position = spring.update(self.to, dt.min(MAX_SPRING_DT));
+                if (position - self.to).abs() < 0.5 && spring.velocity.abs() < 5.0 {
+                    spring.reset(self.to);
+                    return (self.to, true);
+                }
+                (position, false)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct SpringConfig {
+    stiffness: Option<f64>,
+    damping: Option<f64>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct ScrollToConfig {
+    duration: Option<f64>,
+    easing: Option<String>,
+    spring: Option<SpringConfig>,
+    offset: Option<f64>,
+    interruptible: Option<bool>,
+}
+
+/// The window's scroll, or an element's
+#[derive(Clone, PartialEq)]
+enum Scroller {
+    Window,
+    Element(Element),
+}
+
+impl Scroller {
+    fn position(&self) -> f64 {
+        match self {
+            Scroller::Window => window().and_then(|w| w.scroll_y().ok()).unwrap_or(0.0),
+            Scroller::Element(element) => element.scroll_top() as f64,
+        }
+    }
+
+    fn max(&self) -> f64 {
+        let element = match self {
+            Scroller::Window => window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.document_element()),
+            Scroller::Element(element) => Some(element.clone()),
+        };
+        element
+            .map(|e| (e.scroll_height() - e.client_height()).max(0) as f64)
+            .unwrap_or(0.0)
+    }
+
+    /// Jump straight there, even under CSS `scroll-behavior: smooth`
+    fn set(&self, top: f64) {
+        let options = ScrollToOptions::new();
+        options.set_top(top);
+        options.set_behavior(ScrollBehavior::Instant);
+        match self {
+            Scroller::Window => {
+                if let Some(w) = window() {
+                    w.scroll_to_with_scroll_to_options(&options);
+                }
+            }
+            Scroller::Element(element) => element.scroll_to_with_scroll_to_options(&options),
+        }
+    }
+
+    fn event_target(&self) -> Option<EventTarget> {
+        match self {
+            Scroller::Window => window().map(Into::into),
+            Scroller::Element(element) => Some(element.clone().into()),
+        }
+    }
+
+    /// Scroll position that brings `element`'s top to the scroller's top
+    fn offset_of(&self, element: &Element) -> f64 {
+        let top = measure::element_rect(element).y + self.position();
+        match self {
+            Scroller::Window => top,
+            Scroller::Element(container) => top - measure::element_rect(container).y,
+        }
+    }
+}
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+struct ScrollState {
+    scroller: Scroller,
+    tween: ScrollTween,
+    // Last position written, to notice the scroller being moved by hand
+    written: Option<f64>,
+    // Set by input listeners and acted on at the next frame
+    interrupted: bool,
+    active: bool,
+    completed: bool,
+    ticker_id: Option<u32>,
+    listeners: Vec<(EventTarget, &'static str, EventCallback)>,
+    resolvers: Vec<Function>,
+}
+
+impl ScrollState {
+    /// Stop for good and settle the promise. Runs from the ticker or from
+    /// JS, never from inside one of the listeners being removed.
+    fn finish(&mut self, completed: bool) -> Vec<Function> {
+        if !self.active {
+            return Vec::new();
+        }
+        self.active = false;
+        self.completed = completed;
+        if let Some(id) = self.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+        for (target, name, callback) in self.listeners.drain(..) {
+            let _ =
+                target.remove_event_listener_with_callback(name, callback.as_ref().unchecked_ref());
+        }
+        std::mem::take(&mut self.resolvers)
+    }
+
+    /// Returns whether to keep going
+    fn tick(&mut self, dt: f64) -> bool {
+        if self.interrupted {
+            return false;
+        }
+        if let Some(written) = self.written {
+            if (self.scroller.position() - written).abs() > DRIFT_TOLERANCE {
+                self.interrupted = true;
+                return false;
+            }
+        }
+
+        let (position, arrived) = self.tween.step(dt);
+        let position = position.clamp(0.0, self.scroller.max());
+        self.scroller.set(position);
+        self.written = Some(position);
+        !arrived
+    }
+}
+
+fn settle(resolvers: Vec<Function>, completed: bool) {
+    for resolve in resolvers {
+        let _ = resolve.call1(&JsValue::NULL, &JsValue::from_bool(completed));
+    }
+}
+
+thread_local! {
+    // Running scrolls, so a new one on the same scroller replaces the old
+    static ACTIVE: RefCell<Vec<Weak<RefCell<ScrollState>>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A running scrollTo
+#[wasm_bindgen]
+pub struct ScrollToHandle {
+    state: Rc<RefCell<ScrollState>>,
+}
+
+#[wasm_bindgen]
+impl ScrollToHandle {
+    /// Resolves true on arriving, or false if cancelled or interrupted
+    #[wasm_bindgen(getter)]
+    pub fn finished(&self) -> Promise {
+        let mut state = self.state.borrow_mut();
+        if !state.active {
+            return Promise::resolve(&JsValue::from_bool(state.completed));
+        }
+        let mut resolver = None;
+        let promise = Promise::new(&mut |resolve, _reject| resolver = Some(resolve));
+        state.resolvers.extend(resolver);
+        promise
+    }
+
+    /// Stop where it is
+    #[wasm_bindgen]
+    pub fn cancel(&self) {
+        let resolvers = self.state.borrow_mut().finish(false);
+        settle(resolvers, false);
+    }
+
+    #[wasm_bindgen(getter, js_name = isActive)]
+    pub fn is_active(&self) -> bool {
+        self.state.borrow().active
+    }
+
+    /// Whether the user took over mid-scroll
+    #[wasm_bindgen(getter)]
+    pub fn interrupted(&self) -> bool {
+        self.state.borrow().interrupted
+    }
+}
+
+/// Animate the window's scroll, or a container's, to `target`: a position in
+/// px or an element. Options: `{ container, duration: 600, easing:
+/// "ease-in-out", spring: { stiffness, damping }, offset: 0,
+/// interruptible: true }`. `spring` replaces duration and easing, `offset`
+/// stops that many px short of an element. Wheel, touch, scroll keys or
+/// dragging the scrollbar cancel it unless `interruptible` is false.
+#[wasm_bindgen(js_name = scrollTo)]
+pub fn scroll_to(target: JsValue, options: JsValue) -> Result<ScrollToHandle, JsValue> {
+    let (config, container) = if options.is_undefined() || options.is_null() {
+        (ScrollToConfig::default(), None)
+    } else {
+        let container = Reflect::get(&options, &JsValue::from_str("container"))?
+            .dyn_into::<Element>()
+            .ok();
+        let config: ScrollToConfig = serde_wasm_bindgen::from_value(options)
+            .map_err(|e| JsValue::from_str(&format!("Invalid scrollTo options: {:?}", e)))?;
+        (config, container)
+    };
+    let scroller = container.map_or(Scroller::Window, Scroller::Element);
+
+    let to = if let Some(y) = target.as_f64() {
+        y
+    } else if let Some(element) = target.dyn_ref::<Element>() {
+        scroller.offset_of(element) - config.offset.unwrap_or(0.0)
+    } else {
+        return Err(JsValue::from_str(
+            "scrollTo target must be a number or an element",
+        ));
+    };
+    let from = scroller.position();
+    let to = to.clamp(0.0, scroller.max());
+
+    let tween = match &config.spring {
+        Some(spring) => {
+            let defaults = Spring::default();
+            ScrollTween::spring(
+                from,
+                to,
+                Spring::new(
+                    spring.stiffness.unwrap_or(defaults.stiffness),
+                    spring.damping.unwrap_or(defaults.damping),
+                ),
+            )
+        }
+        None => {
+            let easing = match &config.easing {
+                Some(easing) => CubicBezier::from_css(easing).map_err(|e| JsValue::from_str(&e))?,
+                None => CubicBezier::ease_in_out(),
+            };
+            ScrollTween::eased(from, to, config.duration.unwrap_or(600.0), easing)
+        }
+    };
+
+    // Only one scroll per scroller at a time
+    let replaced: Vec<_> = ACTIVE.with(|active| {
+        let mut active = active.borrow_mut();
+        active.retain(|state| state.strong_count() > 0);
+        active
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|state| state.borrow().scroller == scroller)
+            .collect()
+    });
+    for state in replaced {
+        let resolvers = state.borrow_mut().finish(false);
+        settle(resolvers, false);
+    }
+
+    let state = Rc::new(RefCell::new(ScrollState {
+        scroller,
+        tween,
+        written: None,
+        interrupted: false,
+        active: true,
+        completed: false,
+        ticker_id: None,
+        listeners: Vec::new(),
+        resolvers: Vec::new(),
+    }));
+    let handle = ScrollToHandle {
+        state: Rc::clone(&state),
+    };
+    if (to - from).abs() < 0.5 {
+        state.borrow_mut().finish(true);
+        return Ok(handle);
+    }
+
+    if config.interruptible.unwrap_or(true) {
+        listen_for_input(&state)?;
+    }
+
+    let tick_state = Rc::clone(&state);
+    let id = ticker::subscribe(Box::new(move |_now, dt| {
+        let keep_going = tick_state.borrow_mut().tick(dt);
+        if !keep_going {
+            let mut s = tick_state.borrow_mut();
+            // The ticker drops this callback itself
+            s.ticker_id = None;
+            let completed = !s.interrupted;
+            let resolvers = s.finish(completed);
+            drop(s);
+            settle(resolvers, completed);
+        }
+        keep_going
+    }))?;
+    state.borrow_mut().ticker_id = Some(id);
+
+    ACTIVE.with(|active| active.borrow_mut().push(Rc::downgrade(&state)));
+    Ok(handle)
+}
+
+/// Flag the scroll as interrupted on wheel, touch, pointer or scroll keys.
+/// Scrollbar drags are caught by the position drifting from what was written.
+fn listen_for_input(state: &Rc<RefCell<ScrollState>>) -> Result<(), JsValue> {
+    let target = state
+        .borrow()
+        .scroller
+        .event_target()
+        .ok_or_else(|| JsValue::from_str("No window available"))?;
+    let window: EventTarget = window()
+        .ok_or_else(|| JsValue::from_str("No window available"))?
+        .into();
+
+    let inputs = [
+        (target.clone(), "wheel"),
+        (target.clone(), "touchstart"),
+        (target, "pointerdown"),
+        (window, "keydown"),
+    ];
+    let options = AddEventListenerOptions::new();
+    options.set_passive(true);
+    for (target, name) in inputs {
+        let weak = Rc::downgrade(state);
+        let callback: EventCallback = Closure::wrap(Box::new(move |event: Event| {
+            if let Some(key) = event.dyn_ref::<KeyboardEvent>() {
+                if !SCROLL_KEYS.contains(&key.key().as_str()) {
+                    return;
+                }
+            }
+            if let Some(state) = weak.upgrade() {
+                state.borrow_mut().interrupted = true;
+            }
+        }) as Box<dyn FnMut(Event)>);
+        target.add_event_listener_with_callback_and_add_event_listener_options(
+            name,
+            callback.as_ref().unchecked_ref(),
+            &options,
+        )?;
+        state.borrow_mut().listeners.push((target, name, callback));
+    }
+    Ok(())
+}
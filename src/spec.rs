@@ -0,0 +1,136 @@
+use crate::cubic::CubicBezier;
+use crate::Animation;
+use js_sys::JSON;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
+
+// ============================================================================
+// SPEC - Animations described as JSON rather than builder calls
+// ============================================================================
+//
+// Animation spec:
+//
+//   {
+//     "target": "#card",                  // selector, first match
+//     "properties": { "x": 120, "opacity": 1 },   // as animate()
+//     "keyframes": [{ "time": 0, "x": 0 }, ...],  // as add_keyframes()
+//     "duration": 400,
+//     "easing": "smooth",                 // see parse_easing
+//     "delay": 0,
+//     "repeat": 1,
+//     "autoReverse": false,
+//     "transformOrigin": "50% 50%"
+//   }
+//
+// Timeline specs (Sequencer::from_spec) are toJson documents whose steps may
+// also use `target`/`properties` and carry `easing` and `keyframes`.
+
+pub enum Easing {
+    Curve(Box<CubicBezier>),
+    Spring,
+}
+
+/// An easing name: `smooth`, `snappy`, `bounce`, `spring`, or any CSS easing
+/// (`ease-out`, `cubic-bezier(0.2, 0, 0, 1)`, ...)
+pub fn parse_easing(name: &str) -> Result<Easing, String> {
+    match name.trim().to_lowercase().as_str() {
+        "smooth" => Ok(Easing::Curve(Box::new(CubicBezier::smooth()))),
+        "snappy" => Ok(Easing::Curve(Box::new(CubicBezier::snappy()))),
+        "bounce" => Ok(Easing::Curve(Box::new(CubicBezier::bounce()))),
+        "spring" => Ok(Easing::Spring),
+        css => CubicBezier::from_css(css).map(|curve| Easing::Curve(Box::new(curve))),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnimationSpec {
+    target: String,
+    #[serde(default, with = "serde_wasm_bindgen::preserve")]
+    properties: JsValue,
+    #[serde(default, with = "serde_wasm_bindgen::preserve")]
+    keyframes: JsValue,
+    duration: Option<f64>,
+    easing: Option<String>,
+    #[serde(default)]
+    delay: f64,
+    repeat: Option<i32>,
+    #[serde(default)]
+    auto_reverse: bool,
+    transform_origin: Option<String>,
+}
+
+/// A spec given as a JSON string or the parsed object
+pub(crate) fn parse_document(document: JsValue) -> Result<JsValue, JsValue> {
+    match document.as_string() {
+        Some(text) => JSON::parse(&text),
+        None => Ok(document),
+    }
+}
+
+pub(crate) fn query(selector: &str) -> Result<web_sys::Element, JsValue> {
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| JsValue::from_str("No document"))?;
+    document
+        .query_selector(selector)?
+        .ok_or_else(|| JsValue::from_str(&format!("No element matches {}", selector)))
+}
+
+fn is_absent(value: &JsValue) -> bool {
+    value.is_undefined() || value.is_null()
+}
+
+/// Set up `animation` from a spec's properties and keyframes; one of them
+/// must be given
+pub(crate) fn load_motion(
+    animation: &mut Animation,
+    properties: &JsValue,
+    keyframes: &JsValue,
+) -> Result<(), JsValue> {
+    if is_absent(properties) && is_absent(keyframes) {
+        return Err(JsValue::from_str("Spec needs properties or keyframes"));
+    }
+    if !is_absent(properties) {
+        animation.configure(properties.clone())?;
+    }
+    if !is_absent(keyframes) {
+        animation.load_keyframes(keyframes.clone())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn animation(document: JsValue) -> Result<Animation, JsValue> {
+    let spec: AnimationSpec = serde_wasm_bindgen::from_value(parse_document(document)?)
+        .map_err(|e| JsValue::from_str(&format!("Invalid animation spec: {:?}", e)))?;
+
+    let mut animation = Animation::new(query(&spec.target)?)?;
+    load_motion(&mut animation, &spec.properties, &spec.keyframes)?;
+
+    if let Some(easing) = &spec.easing {
+        match parse_easing(easing)? {
+            Easing::Curve(curve) => {
+                animation.bezier = Some(*curve);
+                animation.use_spring = false;
+            }
+            Easing::Spring => animation.use_spring = true,
+        }
+    }
+    if let Some(duration) = spec.duration {
+        animation.duration = duration.max(0.0);
+    }
+    animation.delay = spec.delay.max(0.0);
+    if let Some(repeat) = spec.repeat {
+        animation.repeat_count = repeat;
+    }
+    animation.auto_reverse = spec.auto_reverse;
+    if let Some(origin) = &spec.transform_origin {
+        let mut parts = origin.split_whitespace().map(str::to_string);
+        animation.transform_origin = (
+            parts.next().unwrap_or_else(|| "50%".to_string()),
+            parts.next().unwrap_or_else(|| "50%".to_string()),
+            parts.next().unwrap_or_else(|| "0".to_string()),
+        );
+    }
+    Ok(animation)
+}
@@ -7,6 +7,12 @@ pub struct Spring {
     pub current: f64,
 }
 
+impl Default for Spring {
+    fn default() -> Self {
+        Self::new(300.0, 30.0)
+    }
+}
+
 impl Spring {
     pub fn new(stiffness: f64, damping: f64) -> Self {
         Self {
@@ -18,10 +24,6 @@ impl Spring {
         }
     }
 
-    pub fn default() -> Self {
-        Self::new(300.0, 30.0)
-    }
-
     pub fn bouncy() -> Self {
         Self::new(250.0, 15.0)
     }
@@ -45,4 +47,33 @@ impl Spring {
         self.current = value;
         self.velocity = 0.0;
     }
-}
\ No newline at end of file
+}
+
+/// Two independent springs driving an (x, y) position
+#[derive(Clone, Default)]
+pub struct Spring2D {
+    pub x: Spring,
+    pub y: Spring,
+}
+
+impl Spring2D {
+    pub fn update(&mut self, target: (f64, f64), delta_time: f64) -> (f64, f64) {
+        (
+            self.x.update(target.0, delta_time),
+            self.y.update(target.1, delta_time),
+        )
+    }
+
+    pub fn reset(&mut self, position: (f64, f64)) {
+        self.x.reset(position.0);
+        self.y.reset(position.1);
+    }
+
+    /// Within `epsilon` of the target and barely moving
+    pub fn at_rest(&self, target: (f64, f64), epsilon: f64) -> bool {
+        (self.x.current - target.0).abs() < epsilon
+            && (self.y.current - target.1).abs() < epsilon
+            && self.x.velocity.abs() < epsilon
+            && self.y.velocity.abs() < epsilon
+    }
+}
@@ -0,0 +1,385 @@
+use crate::cubic::CubicBezier;
+use crate::ticker;
+use crate::types::{AnimatableValue, AnimationProperty, PropertyType};
+use js_sys::Function;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, Element, SvgGeometryElement};
+
+// ============================================================================
+// SVG DRAW-IN - Staggered "draws itself" choreography for inline SVG
+// ============================================================================
+
+const SHAPE_SELECTOR: &str = "path, line, polyline, polygon, circle, ellipse, rect";
+
+#[wasm_bindgen]
+pub struct SvgDrawIn {
+    svg: Element,
+    duration: f64,
+    stagger: f64,
+    fill_duration: f64,
+    easing: String,
+    fill_easing: String,
+    // End of the latest play()'s last animation, in ms
+    total_duration: f64,
+    animations: Vec<Rc<RefCell<crate::Animation>>>,
+}
+
+#[wasm_bindgen]
+impl SvgDrawIn {
+    #[wasm_bindgen(constructor)]
+    pub fn new(svg: Element) -> SvgDrawIn {
+        SvgDrawIn {
+            svg,
+            duration: 1200.0,
+            stagger: 120.0,
+            fill_duration: 400.0,
+            easing: "ease-in-out".to_string(),
+            fill_easing: "ease-out".to_string(),
+            total_duration: 0.0,
+            animations: Vec::new(),
+        }
+    }
+
+    /// Stroke draw duration per shape in ms
+    #[wasm_bindgen(setter)]
+    pub fn set_duration(&mut self, duration: f64) {
+        self.duration = duration.max(0.0);
+    }
+
+    /// Delay between consecutive shapes in ms
+    #[wasm_bindgen(setter)]
+    pub fn set_stagger(&mut self, stagger: f64) {
+        self.stagger = stagger.max(0.0);
+    }
+
+    /// Fill fade after each stroke finishes; 0 disables the fill fade
+    #[wasm_bindgen(setter, js_name = fillDuration)]
+    pub fn set_fill_duration(&mut self, duration: f64) {
+        self.fill_duration = duration.max(0.0);
+    }
+
+    /// CSS easing for the stroke draw ("ease-out", "cubic-bezier(...)")
+    #[wasm_bindgen(js_name = setEasing)]
+    pub fn set_easing(&mut self, easing: String) -> Result<(), JsValue> {
+        crate::cubic::CubicBezier::from_css(&easing)?;
+        self.easing = easing;
+        Ok(())
+    }
+
+    /// CSS easing for the fill fade
+    #[wasm_bindgen(js_name = setFillEasing)]
+    pub fn set_fill_easing(&mut self, easing: String) -> Result<(), JsValue> {
+        crate::cubic::CubicBezier::from_css(&easing)?;
+        self.fill_easing = easing;
+        Ok(())
+    }
+
+    /// Hide every stroked shape behind a full dash offset and play the
+    /// staggered draw-in. Returns the number of shapes drawn.
+    #[wasm_bindgen]
+    pub fn play(&mut self) -> Result<usize, JsValue> {
+        self.stop()?;
+        self.total_duration = 0.0;
+
+        let nodes = self.svg.query_selector_all(SHAPE_SELECTOR)?;
+        let mut index = 0;
+
+        for i in 0..nodes.length() {
+            let shape = match nodes
+                .item(i)
+                .and_then(|n| n.dyn_into::<SvgGeometryElement>().ok())
+            {
+                Some(shape) => shape,
+                None => continue,
+            };
+            let element: Element = shape.clone().into();
+
+            let stroke = computed(&element, "stroke");
+            if stroke.is_empty() || stroke == "none" {
+                continue;
+            }
+
+            let length = shape.get_total_length() as f64;
+            if length <= 0.0 {
+                continue;
+            }
+
+            let delay = index as f64 * self.stagger;
+            element.set_attribute("stroke-dasharray", &length.to_string())?;
+            element.set_attribute("stroke-dashoffset", &length.to_string())?;
+
+            let draw = animation(
+                &element,
+                PropertyType::StrokeDashOffset,
+                length,
+                0.0,
+                self.duration,
+                delay,
+                &self.easing,
+            )?;
+            self.animations.push(draw);
+            let mut end = delay + self.duration;
+
+            let fill = computed(&element, "fill");
+            if self.fill_duration > 0.0 && !fill.is_empty() && fill != "none" {
+                let target = computed(&element, "fill-opacity")
+                    .parse::<f64>()
+                    .unwrap_or(1.0);
+                element.set_attribute("fill-opacity", "0")?;
+
+                let fade = animation(
+                    &element,
+                    PropertyType::FillOpacity,
+                    0.0,
+                    target,
+                    self.fill_duration,
+                    delay + self.duration,
+                    &self.fill_easing,
+                )?;
+                self.animations.push(fade);
+                end += self.fill_duration;
+            }
+
+            self.total_duration = self.total_duration.max(end);
+            index += 1;
+        }
+
+        Ok(index)
+    }
+
+    /// Stop any running draw-in, leaving shapes where they are
+    #[wasm_bindgen]
+    pub fn stop(&mut self) -> Result<(), JsValue> {
+        for anim in self.animations.drain(..) {
+            anim.borrow_mut().stop()?;
+        }
+        Ok(())
+    }
+
+    /// Time until the last shape of the latest play() has drawn and filled, in ms
+    #[wasm_bindgen(getter, js_name = totalDuration)]
+    pub fn total_duration(&self) -> f64 {
+        self.total_duration
+    }
+
+    #[wasm_bindgen(getter, js_name = animationCount)]
+    pub fn animation_count(&self) -> usize {
+        self.animations.len()
+    }
+}
+
+/// Start a tween of one numeric property of `element`
+fn animation(
+    element: &Element,
+    prop_type: PropertyType,
+    from: f64,
+    to: f64,
+    duration: f64,
+    delay: f64,
+    easing: &str,
+) -> Result<Rc<RefCell<crate::Animation>>, JsValue> {
+    let mut animation = crate::Animation::new(element.clone())?
+        .ease(easing.to_string())?
+        .set_delay(delay);
+    animation.duration = duration;
+    animation.properties.push(AnimationProperty {
+        property_type: prop_type,
+        start: AnimatableValue::Number(from),
+        end: AnimatableValue::Number(to),
+        current: AnimatableValue::Number(from),
+    });
+
+    Ok(Rc::clone(&animation.start()?.animation))
+}
+
+// ============================================================================
+// DRAW SVG - Animate the visible span of a single stroke
+// ============================================================================
+
+struct DrawState {
+    element: Element,
+    length: f64,
+    /// Visible part of the stroke as fractions of its length
+    span: (f64, f64),
+    from: (f64, f64),
+    to: (f64, f64),
+    duration: f64,
+    elapsed: f64,
+    easing: CubicBezier,
+    ticker_id: Option<u32>,
+    on_complete: Option<Function>,
+}
+
+impl DrawState {
+    /// One dash covering the span, with a gap long enough that the next
+    /// dash never shows
+    fn write(&self) -> Result<(), JsValue> {
+        let (start, end) = (self.span.0.min(self.span.1), self.span.0.max(self.span.1));
+        let dash = (end - start) * self.length;
+        self.element.set_attribute(
+            "stroke-dasharray",
+            &format!("{} {}", dash, self.length + 1.0),
+        )?;
+        self.element
+            .set_attribute("stroke-dashoffset", &(-start * self.length).to_string())
+    }
+
+    fn stop(&mut self) {
+        if let Some(id) = self.ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
+
+/// Stroke draw-on for one path or shape: measures its length, manages
+/// stroke-dasharray and animates which part of the stroke is visible
+#[wasm_bindgen]
+pub struct DrawSvg {
+    state: Rc<RefCell<DrawState>>,
+}
+
+#[wasm_bindgen]
+impl DrawSvg {
+    /// `element` must be an SVG shape (path, line, circle, rect, ...).
+    /// The whole stroke stays visible until set() or an animation changes it.
+    #[wasm_bindgen(constructor)]
+    pub fn new(element: Element) -> Result<DrawSvg, JsValue> {
+        let length = element
+            .dyn_ref::<SvgGeometryElement>()
+            .ok_or_else(|| JsValue::from_str("DrawSvg needs an SVG shape element"))?
+            .get_total_length() as f64;
+
+        Ok(DrawSvg {
+            state: Rc::new(RefCell::new(DrawState {
+                element,
+                length,
+                span: (0.0, 1.0),
+                from: (0.0, 1.0),
+                to: (0.0, 1.0),
+                duration: 0.0,
+                elapsed: 0.0,
+                easing: CubicBezier::ease_in_out(),
+                ticker_id: None,
+                on_complete: None,
+            })),
+        })
+    }
+
+    /// Stroke length in user units
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> f64 {
+        self.state.borrow().length
+    }
+
+    /// Measure again after the shape's geometry changes
+    #[wasm_bindgen]
+    pub fn remeasure(&mut self) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        if let Some(shape) = state.element.dyn_ref::<SvgGeometryElement>() {
+            state.length = shape.get_total_length() as f64;
+        }
+        state.write()
+    }
+
+    /// Show the stroke between two fractions of its length, e.g. (0.2, 0.8)
+    #[wasm_bindgen]
+    pub fn set(&mut self, start: f64, end: f64) -> Result<(), JsValue> {
+        let mut state = self.state.borrow_mut();
+        state.stop();
+        state.span = (start.clamp(0.0, 1.0), end.clamp(0.0, 1.0));
+        state.write()
+    }
+
+    /// Draw the stroke on from nothing over `duration` ms
+    #[wasm_bindgen]
+    pub fn draw(&mut self, duration: f64, easing: Option<String>) -> Result<(), JsValue> {
+        self.set(0.0, 0.0)?;
+        self.animate(0.0, 1.0, duration, easing)
+    }
+
+    /// Erase the stroke from its start over `duration` ms
+    #[wasm_bindgen]
+    pub fn erase(&mut self, duration: f64, easing: Option<String>) -> Result<(), JsValue> {
+        self.animate(1.0, 1.0, duration, easing)
+    }
+
+    /// Animate the visible span from where it is now to (start, end), with
+    /// a CSS easing ("ease-out", "cubic-bezier(...)", default ease-in-out)
+    #[wasm_bindgen]
+    pub fn animate(
+        &mut self,
+        start: f64,
+        end: f64,
+        duration: f64,
+        easing: Option<String>,
+    ) -> Result<(), JsValue> {
+        let easing = match easing {
+            Some(easing) => CubicBezier::from_css(&easing)?,
+            None => CubicBezier::ease_in_out(),
+        };
+
+        let mut state = self.state.borrow_mut();
+        state.stop();
+        state.from = state.span;
+        state.to = (start.clamp(0.0, 1.0), end.clamp(0.0, 1.0));
+        state.duration = duration.max(0.0);
+        state.elapsed = 0.0;
+        state.easing = easing;
+
+        let tick_state = Rc::clone(&self.state);
+        state.ticker_id = Some(ticker::subscribe(Box::new(move |_now, dt| {
+            let mut s = tick_state.borrow_mut();
+            s.elapsed += dt * 1000.0;
+            let progress = if s.duration > 0.0 {
+                (s.elapsed / s.duration).min(1.0)
+            } else {
+                1.0
+            };
+            let eased = s.easing.solve(progress);
+            s.span = (
+                s.from.0 + (s.to.0 - s.from.0) * eased,
+                s.from.1 + (s.to.1 - s.from.1) * eased,
+            );
+            let _ = s.write();
+            if progress < 1.0 {
+                return true;
+            }
+
+            s.ticker_id = None;
+            let callback = s.on_complete.clone();
+            drop(s);
+            if let Some(callback) = callback {
+                let _ = callback.call0(&JsValue::NULL);
+            }
+            false
+        }))?);
+        Ok(())
+    }
+
+    /// Stop the running animation, leaving the stroke as it is
+    #[wasm_bindgen]
+    pub fn stop(&mut self) {
+        self.state.borrow_mut().stop();
+    }
+
+    /// Called each time an animation reaches its end
+    #[wasm_bindgen(js_name = onComplete)]
+    pub fn on_complete(&mut self, callback: Option<Function>) {
+        self.state.borrow_mut().on_complete = callback;
+    }
+
+    #[wasm_bindgen(getter, js_name = isAnimating)]
+    pub fn is_animating(&self) -> bool {
+        self.state.borrow().ticker_id.is_some()
+    }
+}
+
+fn computed(element: &Element, property: &str) -> String {
+    window()
+        .and_then(|w| w.get_computed_style(element).ok().flatten())
+        .and_then(|style| style.get_property_value(property).ok())
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default()
+}
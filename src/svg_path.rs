@@ -0,0 +1,99 @@
+use crate::shape_morphing::{flatten_path, PathMorph};
+use wasm_bindgen::prelude::*;
+
+// ============================================================================
+// SVG PATH - Length and point lookups on path data, without the DOM
+// ============================================================================
+
+/// Default flattening tolerance in path units
+const DEFAULT_TOLERANCE: f64 = 0.1;
+
+/// A point on a path with the direction of travel there, in degrees
+/// clockwise from the x axis
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathPoint {
+    pub x: f64,
+    pub y: f64,
+    pub angle: f64,
+}
+
+/// Path data flattened into line segments, for measuring and sampling
+#[wasm_bindgen]
+pub struct SvgPath {
+    segments: Vec<((f64, f64), (f64, f64))>,
+    /// Running length at the end of each segment
+    lengths: Vec<f64>,
+}
+
+#[wasm_bindgen]
+impl SvgPath {
+    /// Parse `d`, approximating curves with lines that stray no more than
+    /// `tolerance` (default 0.1) from them
+    #[wasm_bindgen(constructor)]
+    pub fn new(d: String, tolerance: Option<f64>) -> Result<SvgPath, JsValue> {
+        let commands = PathMorph::parse_path(&d)?;
+        let segments = flatten_path(&commands, tolerance.unwrap_or(DEFAULT_TOLERANCE));
+
+        let mut total = 0.0;
+        let lengths = segments
+            .iter()
+            .map(|&((x1, y1), (x2, y2))| {
+                total += (x2 - x1).hypot(y2 - y1);
+                total
+            })
+            .collect();
+
+        Ok(SvgPath { segments, lengths })
+    }
+
+    /// Length of the whole path, like SVGGeometryElement.getTotalLength()
+    #[wasm_bindgen(js_name = getTotalLength)]
+    pub fn get_total_length(&self) -> f64 {
+        self.lengths.last().copied().unwrap_or(0.0)
+    }
+
+    /// Point `length` along the path, clamped to its ends
+    #[wasm_bindgen(js_name = getPointAtLength)]
+    pub fn get_point_at_length(&self, length: f64) -> PathPoint {
+        if self.segments.is_empty() {
+            return PathPoint {
+                x: 0.0,
+                y: 0.0,
+                angle: 0.0,
+            };
+        }
+
+        let length = length.clamp(0.0, self.get_total_length());
+        let i = self
+            .lengths
+            .partition_point(|&l| l < length)
+            .min(self.segments.len() - 1);
+        let ((x1, y1), (x2, y2)) = self.segments[i];
+        let start = if i == 0 { 0.0 } else { self.lengths[i - 1] };
+        let span = self.lengths[i] - start;
+        let t = if span > 0.0 {
+            (length - start) / span
+        } else {
+            0.0
+        };
+
+        PathPoint {
+            x: x1 + (x2 - x1) * t,
+            y: y1 + (y2 - y1) * t,
+            angle: (y2 - y1).atan2(x2 - x1).to_degrees(),
+        }
+    }
+
+    /// Point a `fraction` (0..1) of the way along the path
+    #[wasm_bindgen(js_name = getPointAt)]
+    pub fn get_point_at(&self, fraction: f64) -> PathPoint {
+        self.get_point_at_length(fraction.clamp(0.0, 1.0) * self.get_total_length())
+    }
+}
+
+impl SvgPath {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
@@ -0,0 +1,140 @@
+use crate::frame_clock;
+use crate::performance_monitor;
+use crate::visibility;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{DedicatedWorkerGlobalScope, Window};
+
+// ============================================================================
+// SHARED TICKER - One requestAnimationFrame loop for helper subscribers
+// ============================================================================
+
+/// Called once per frame with (timestamp ms, delta seconds). Return false to unsubscribe.
+pub(crate) type TickFn = Box<dyn FnMut(f64, f64) -> bool>;
+
+type FrameCallback = Closure<dyn FnMut(f64)>;
+
+#[derive(Default)]
+struct TickerState {
+    next_id: u32,
+    subscribers: Vec<(u32, Rc<RefCell<TickFn>>)>,
+    running: bool,
+    last_time: Option<f64>,
+    frame: Option<Rc<FrameCallback>>,
+}
+
+thread_local! {
+    static TICKER: RefCell<TickerState> = RefCell::new(TickerState::default());
+}
+
+/// Register a per-frame callback; the loop starts on the first subscriber
+pub(crate) fn subscribe(tick: TickFn) -> Result<u32, JsValue> {
+    let id = TICKER.with(|t| {
+        let mut t = t.borrow_mut();
+        t.next_id += 1;
+        let id = t.next_id;
+        t.subscribers.push((id, Rc::new(RefCell::new(tick))));
+        id
+    });
+    visibility::watch();
+
+    let start = TICKER.with(|t| !t.borrow().running);
+    if start {
+        request_frame()?;
+    }
+    Ok(id)
+}
+
+pub(crate) fn unsubscribe(id: u32) {
+    TICKER.with(|t| t.borrow_mut().subscribers.retain(|(sid, _)| *sid != id));
+}
+
+fn request_frame() -> Result<(), JsValue> {
+    // Dedicated workers have their own requestAnimationFrame, used when
+    // rendering into an OffscreenCanvas
+    let global = js_sys::global();
+    if !global.is_instance_of::<Window>() && !global.is_instance_of::<DedicatedWorkerGlobalScope>()
+    {
+        return Err(JsValue::from_str("No window or worker available"));
+    }
+
+    let frame = TICKER.with(|t| {
+        let mut t = t.borrow_mut();
+        t.running = true;
+        t.frame
+            .get_or_insert_with(|| {
+                Rc::new(Closure::wrap(Box::new(on_frame) as Box<dyn FnMut(f64)>))
+            })
+            .clone()
+    });
+
+    let callback = frame.as_ref().as_ref().unchecked_ref();
+    match global.dyn_ref::<Window>() {
+        Some(window) => window.request_animation_frame(callback)?,
+        None => global
+            .unchecked_ref::<DedicatedWorkerGlobalScope>()
+            .request_animation_frame(callback)?,
+    };
+    Ok(())
+}
+
+fn on_frame(now: f64) {
+    // Under a frame rate cap, display frames before the next one is due
+    // are skipped and their time goes into the next delta
+    if TICKER.with(|t| frame_clock::frame_due(t.borrow().last_time, now)) {
+        run_subscribers(now);
+    }
+
+    let idle = TICKER.with(|t| {
+        let mut t = t.borrow_mut();
+        if t.subscribers.is_empty() {
+            t.running = false;
+            t.last_time = None;
+        }
+        !t.running
+    });
+
+    if !idle {
+        let _ = request_frame();
+    }
+}
+
+fn run_subscribers(now: f64) {
+    let started = performance_monitor::frame_start();
+    let (subscribers, dt, interval) = TICKER.with(|t| {
+        let mut t = t.borrow_mut();
+        let dt = t
+            .last_time
+            // Time in a background tab doesn't count when pausing there
+            .map(|last| ((now - last - visibility::paused_time(last)) / 1000.0).max(0.0))
+            .unwrap_or(0.0);
+        // Frames stopped while the page was hidden weren't dropped, so the
+        // frame after isn't judged on its spacing
+        let interval = t
+            .last_time
+            .filter(|&last| {
+                visibility::last_gap()
+                    .and_then(|gap| gap.missed(last))
+                    .is_none()
+            })
+            .map(|last| now - last);
+        t.last_time = Some(now);
+        (t.subscribers.clone(), dt, interval)
+    });
+
+    // Callbacks run without the registry borrowed so they can (un)subscribe
+    for (id, tick) in subscribers {
+        let live = TICKER.with(|t| t.borrow().subscribers.iter().any(|(sid, _)| *sid == id));
+        if !live {
+            continue;
+        }
+        let keep = (tick.borrow_mut())(now, dt);
+        if !keep {
+            unsubscribe(id);
+        }
+    }
+
+    performance_monitor::frame_end(started, interval);
+}
@@ -0,0 +1,348 @@
+use crate::measure::{self, Rect};
+use crate::motion;
+use crate::renderer::{self, Renderer};
+use crate::spring::Spring;
+use crate::ticker;
+use crate::types::{AnimatableValue, AnimationProperty, PropertyType};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{window, AddEventListenerOptions, Element, Event, HtmlElement, PointerEvent};
+
+// ============================================================================
+// TILT - 3D rotation toward the pointer, with an optional glare
+// ============================================================================
+//
+// The rotations are ordinary animation properties stepped by the same spring
+// code and written by the same transform writer as a spring Animation; only
+// their targets come from the pointer.
+
+/// Rotations in degrees for a pointer at (`x`, `y`), each 0-1 across the
+/// element. The side under the pointer tips away; `reverse` lifts it.
+pub fn tilt_angles(x: f64, y: f64, max_tilt: f64, reverse: bool) -> (f64, f64) {
+    let sign = if reverse { -1.0 } else { 1.0 };
+    let x = x.clamp(0.0, 1.0) * 2.0 - 1.0;
+    let y = y.clamp(0.0, 1.0) * 2.0 - 1.0;
+    (-y * max_tilt * sign, x * max_tilt * sign)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct TiltConfig {
+    max_tilt: Option<f64>,
+    perspective: Option<f64>,
+    scale: Option<f64>,
+    reverse: bool,
+    glare: bool,
+    max_glare: Option<f64>,
+    stiffness: Option<f64>,
+    damping: Option<f64>,
+}
+
+// Indices into the property list, which is in transform order after the
+// perspective
+const ROTATE_X: usize = 1;
+const ROTATE_Y: usize = 2;
+const SCALE: usize = 3;
+
+struct Glare {
+    element: HtmlElement,
+    renderer: Box<dyn Renderer>,
+    max_opacity: f64,
+}
+
+impl Glare {
+    fn create(parent: &Element, max_opacity: f64) -> Result<Glare, JsValue> {
+        let document = window()
+            .and_then(|w| w.document())
+            .ok_or_else(|| JsValue::from_str("No document available"))?;
+        let element: HtmlElement = document.create_element("div")?.dyn_into()?;
+        let style = element.style();
+        style.set_property("position", "absolute")?;
+        style.set_property("inset", "0")?;
+        style.set_property("pointer-events", "none")?;
+        style.set_property("border-radius", "inherit")?;
+        style.set_property("opacity", "0")?;
+        parent.append_child(&element)?;
+
+        // The overlay is positioned against the element itself
+        if let Some(html) = parent.dyn_ref::<HtmlElement>() {
+            let position = window()
+                .and_then(|w| w.get_computed_style(parent).ok().flatten())
+                .and_then(|s| s.get_property_value("position").ok());
+            if position.as_deref() == Some("static") {
+                html.style().set_property("position", "relative")?;
+            }
+            html.style().set_property("overflow", "hidden")?;
+        }
+
+        let renderer = renderer::for_element(&element);
+        Ok(Glare {
+            element,
+            renderer,
+            max_opacity,
+        })
+    }
+
+    /// Light from the side the pointer is toward, brighter off-center
+    fn write(&mut self, x: f64, y: f64) -> Result<(), JsValue> {
+        let angle = x.atan2(-y).to_degrees();
+        let strength = x.hypot(y).min(1.0);
+        self.renderer.apply(
+            "background",
+            &format!(
+                "linear-gradient({}deg, rgba(255, 255, 255, 1) 0%, rgba(255, 255, 255, 0) 80%)",
+                angle
+            ),
+        )?;
+        self.renderer
+            .apply("opacity", &(strength * self.max_opacity).to_string())?;
+        self.renderer.flush()
+    }
+}
+
+struct TiltState {
+    element: Element,
+    renderer: Box<dyn Renderer>,
+    properties: Vec<AnimationProperty>,
+    springs: Vec<Spring>,
+    transform_origin: (String, String, String),
+    max_tilt: f64,
+    hover_scale: f64,
+    reverse: bool,
+    glare: Option<Glare>,
+    // Measured on entering, so moves don't force layout
+    bounds: Rect,
+    ticker_id: Option<u32>,
+}
+
+impl TiltState {
+    fn aim(&mut self, rotate_x: f64, rotate_y: f64, scale: f64) {
+        self.properties[ROTATE_X].end = AnimatableValue::Number(rotate_x);
+        self.properties[ROTATE_Y].end = AnimatableValue::Number(rotate_y);
+        self.properties[SCALE].end = AnimatableValue::Number(scale);
+    }
+
+    fn point_at(&mut self, x: f64, y: f64) {
+        let bounds = self.bounds;
+        let (rotate_x, rotate_y) = tilt_angles(
+            (x - bounds.x) / bounds.width.max(1.0),
+            (y - bounds.y) / bounds.height.max(1.0),
+            self.max_tilt,
+            self.reverse,
+        );
+        self.aim(rotate_x, rotate_y, self.hover_scale);
+    }
+
+    /// Returns whether still moving
+    fn tick(&mut self, dt: f64) -> bool {
+        let moving = motion::step_springs(&mut self.properties, &mut self.springs, dt.min(0.032));
+        let _ = motion::render(
+            &self.properties,
+            &self.transform_origin,
+            &mut *self.renderer,
+        );
+        let _ = self.renderer.flush();
+
+        if let Some(glare) = &mut self.glare {
+            // Follows the sprung rotation rather than the raw pointer
+            let max = self.max_tilt.abs().max(f64::EPSILON);
+            let sign = if self.reverse { -1.0 } else { 1.0 };
+            let x = motion::number_value(&self.properties, PropertyType::RotateY) / max * sign;
+            let y = -motion::number_value(&self.properties, PropertyType::RotateX) / max * sign;
+            let _ = glare.write(x, y);
+        }
+        moving
+    }
+}
+
+fn schedule(state: &Rc<RefCell<TiltState>>) {
+    if state.borrow().ticker_id.is_some() {
+        return;
+    }
+    let tick_state = Rc::clone(state);
+    let id = ticker::subscribe(Box::new(move |_now, dt| {
+        let mut s = tick_state.borrow_mut();
+        let moving = s.tick(dt);
+        if !moving {
+            s.ticker_id = None;
+        }
+        moving
+    }))
+    .ok();
+    state.borrow_mut().ticker_id = id;
+}
+
+fn number_property(property_type: PropertyType, value: f64) -> AnimationProperty {
+    AnimationProperty {
+        property_type,
+        start: AnimatableValue::Number(value),
+        end: AnimatableValue::Number(value),
+        current: AnimatableValue::Number(value),
+    }
+}
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+/// Tilts an element toward the pointer in 3D and springs it flat on leave.
+/// Config: `{ maxTilt: 12, perspective: 800, scale: 1, reverse: false,
+/// glare: false, maxGlare: 0.35, stiffness: 300, damping: 30 }`. `scale` is
+/// applied while hovered; `glare` adds a light overlay that follows the tilt.
+#[wasm_bindgen]
+pub struct Tilt {
+    state: Rc<RefCell<TiltState>>,
+    listeners: Vec<(&'static str, EventCallback)>,
+}
+
+#[wasm_bindgen]
+impl Tilt {
+    #[wasm_bindgen(constructor)]
+    pub fn new(element: Element, config: JsValue) -> Result<Tilt, JsValue> {
+        let config: TiltConfig = if config.is_undefined() || config.is_null() {
+            TiltConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)
+                .map_err(|e| JsValue::from_str(&format!("Invalid tilt config: {:?}", e)))?
+        };
+        let defaults = Spring::default();
+        let stiffness = config.stiffness.unwrap_or(defaults.stiffness);
+        let damping = config.damping.unwrap_or(defaults.damping);
+
+        let initial = [
+            (
+                PropertyType::Perspective,
+                config.perspective.unwrap_or(800.0),
+            ),
+            (PropertyType::RotateX, 0.0),
+            (PropertyType::RotateY, 0.0),
+            (PropertyType::Scale, 1.0),
+        ];
+        let properties = initial
+            .iter()
+            .map(|&(property_type, value)| number_property(property_type, value))
+            .collect();
+        let springs = initial
+            .iter()
+            .map(|&(_, value)| {
+                let mut spring = Spring::new(stiffness, damping);
+                spring.reset(value);
+                spring
+            })
+            .collect();
+
+        let glare = if config.glare {
+            Some(Glare::create(&element, config.max_glare.unwrap_or(0.35))?)
+        } else {
+            None
+        };
+
+        let renderer = renderer::for_element(&element);
+        let state = Rc::new(RefCell::new(TiltState {
+            element,
+            renderer,
+            properties,
+            springs,
+            transform_origin: ("50%".to_string(), "50%".to_string(), "0".to_string()),
+            max_tilt: config.max_tilt.unwrap_or(12.0),
+            hover_scale: config.scale.unwrap_or(1.0),
+            reverse: config.reverse,
+            glare,
+            bounds: Rect::default(),
+            ticker_id: None,
+        }));
+
+        let mut tilt = Tilt {
+            state,
+            listeners: Vec::with_capacity(3),
+        };
+        tilt.bind()?;
+        Ok(tilt)
+    }
+
+    #[wasm_bindgen(js_name = setMaxTilt)]
+    pub fn set_max_tilt(&mut self, degrees: f64) {
+        self.state.borrow_mut().max_tilt = degrees;
+    }
+
+    /// Spring back to flat
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        self.state.borrow_mut().aim(0.0, 0.0, 1.0);
+        schedule(&self.state);
+    }
+
+    /// Stop reacting to the pointer, spring back to flat and remove the glare
+    #[wasm_bindgen]
+    pub fn unbind(&mut self) {
+        let element = self.state.borrow().element.clone();
+        for (name, callback) in self.listeners.drain(..) {
+            let _ = element
+                .remove_event_listener_with_callback(name, callback.as_ref().unchecked_ref());
+        }
+        if let Some(glare) = self.state.borrow_mut().glare.take() {
+            glare.element.remove();
+        }
+        self.reset();
+    }
+}
+
+impl Tilt {
+    fn bind(&mut self) -> Result<(), JsValue> {
+        let state = Rc::clone(&self.state);
+        self.listen("pointerenter", move |_| {
+            let mut s = state.borrow_mut();
+            s.bounds = measure::element_rect(&s.element);
+        })?;
+
+        let state = Rc::clone(&self.state);
+        self.listen("pointermove", move |event| {
+            if let Some(pointer) = event.dyn_ref::<PointerEvent>() {
+                let (x, y) = (pointer.client_x() as f64, pointer.client_y() as f64);
+                let mut s = state.borrow_mut();
+                if s.bounds.width <= 0.0 {
+                    s.bounds = measure::element_rect(&s.element);
+                }
+                s.point_at(x, y);
+                drop(s);
+                schedule(&state);
+            }
+        })?;
+
+        let state = Rc::clone(&self.state);
+        self.listen("pointerleave", move |_| {
+            state.borrow_mut().aim(0.0, 0.0, 1.0);
+            schedule(&state);
+        })
+    }
+
+    fn listen(
+        &mut self,
+        name: &'static str,
+        handler: impl FnMut(Event) + 'static,
+    ) -> Result<(), JsValue> {
+        let callback: EventCallback = Closure::wrap(Box::new(handler) as Box<dyn FnMut(Event)>);
+        let options = AddEventListenerOptions::new();
+        options.set_passive(true);
+        self.state
+            .borrow()
+            .element
+            .add_event_listener_with_callback_and_add_event_listener_options(
+                name,
+                callback.as_ref().unchecked_ref(),
+                &options,
+            )?;
+        self.listeners.push((name, callback));
+        Ok(())
+    }
+}
+
+impl Drop for Tilt {
+    fn drop(&mut self) {
+        self.unbind();
+        if let Some(id) = self.state.borrow_mut().ticker_id.take() {
+            ticker::unsubscribe(id);
+        }
+    }
+}
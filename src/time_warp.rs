@@ -0,0 +1,110 @@
+// ============================================================================
+// TIME WARP - Local time remapping before easing
+// ============================================================================
+
+#[derive(Clone, Copy)]
+struct SlowZone {
+    from: f64,
+    to: f64,
+    factor: f64,
+}
+
+/// Piecewise-linear remap of wall-clock progress to timeline progress.
+/// A zone with factor 2 plays at half speed; the rest of the timeline speeds
+/// up so the total duration is unchanged.
+#[derive(Clone, Default)]
+pub(crate) struct TimeWarp {
+    zones: Vec<SlowZone>,
+    // (timeline start, timeline end, wall start, wall end), normalised to 0-1
+    segments: Vec<(f64, f64, f64, f64)>,
+}
+
+impl TimeWarp {
+    pub(crate) fn add_zone(&mut self, from: f64, to: f64, factor: f64) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&from) || !(0.0..=1.0).contains(&to) || from >= to {
+            return Err("Slow zone needs 0 <= from < to <= 1".to_string());
+        }
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err("Slow zone factor must be greater than 0".to_string());
+        }
+
+        self.zones.push(SlowZone { from, to, factor });
+        self.rebuild();
+        Ok(())
+    }
+
+    /// Timeline progress at the given wall-clock progress
+    pub(crate) fn apply(&self, progress: f64) -> f64 {
+        let progress = progress.clamp(0.0, 1.0);
+        match self.segment_at(progress) {
+            Some((t0, t1, w0, w1)) => t0 + (progress - w0) / (w1 - w0) * (t1 - t0),
+            None => progress,
+        }
+    }
+
+    /// Wall-clock progress at which the timeline reaches `progress`
+    pub(crate) fn invert(&self, progress: f64) -> f64 {
+        let progress = progress.clamp(0.0, 1.0);
+        match self
+            .segments
+            .iter()
+            .find(|s| progress <= s.1)
+            .or(self.segments.last())
+        {
+            Some(&(t0, t1, w0, w1)) => w0 + (progress - t0) / (t1 - t0) * (w1 - w0),
+            None => progress,
+        }
+    }
+
+    /// d(timeline) / d(wall) at the given wall-clock progress
+    pub(crate) fn rate_at(&self, progress: f64) -> f64 {
+        match self.segment_at(progress.clamp(0.0, 1.0)) {
+            Some((t0, t1, w0, w1)) => (t1 - t0) / (w1 - w0),
+            None => 1.0,
+        }
+    }
+
+    fn segment_at(&self, progress: f64) -> Option<(f64, f64, f64, f64)> {
+        self.segments
+            .iter()
+            .find(|s| progress <= s.3)
+            .or(self.segments.last())
+            .copied()
+    }
+
+    fn rebuild(&mut self) {
+        let mut bounds = vec![0.0, 1.0];
+        for zone in &self.zones {
+            bounds.push(zone.from);
+            bounds.push(zone.to);
+        }
+        bounds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        bounds.dedup();
+
+        // Overlapping zones compound
+        let weighted: Vec<(f64, f64, f64)> = bounds
+            .windows(2)
+            .map(|w| {
+                let mid = (w[0] + w[1]) * 0.5;
+                let factor: f64 = self
+                    .zones
+                    .iter()
+                    .filter(|z| mid >= z.from && mid <= z.to)
+                    .map(|z| z.factor)
+                    .product();
+                (w[0], w[1], (w[1] - w[0]) * factor)
+            })
+            .collect();
+
+        let total: f64 = weighted.iter().map(|s| s.2).sum();
+        let mut wall = 0.0;
+        self.segments = weighted
+            .into_iter()
+            .map(|(t0, t1, length)| {
+                let start = wall;
+                wall += length / total;
+                (t0, t1, start, wall)
+            })
+            .collect();
+    }
+}
@@ -1,18 +1,258 @@
 
 // ============================================================================
-// TRANSACTION SYSTEM 
+// TRANSACTION SYSTEM
 // ============================================================================
-
-use web_sys::{window, Performance};
+//
+// Transactions nest on a global stack. A transaction that doesn't set its
+// duration, timing function or disable-actions flag takes them from the one
+// around it, and its completion waits for every animation started inside,
+// nested transactions included.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use web_sys::{window, Element, Performance};
 use wasm_bindgen::prelude::*;
+use crate::cubic::CubicBezier;
+use crate::types::PropertyType;
+use crate::Animation;
+
+const DEFAULT_DURATION: f64 = 0.25;
+
+// Starts further apart than a 60Hz frame aren't in the same frame and get
+// a fresh timestamp, so a transaction left open doesn't start animations late
+const SAME_FRAME_MS: f64 = 16.0;
+
+// A style change recorded inside begin()/commit(), animated on commit
+struct Mutation {
+    element: Element,
+    property: String,
+    value: JsValue,
+}
+
+/// One open (or committed but still running) transaction
+pub(crate) struct Scope {
+    // None inherits from the enclosing transaction
+    duration: Cell<Option<f64>>,
+    timing_function: Cell<Option<TimingFunction>>,
+    disable_actions: Cell<Option<bool>>,
+    completion: RefCell<Option<js_sys::Function>>,
+    mutations: RefCell<Vec<Mutation>>,
+    parent: Option<Rc<Scope>>,
+    // Animations and nested transactions that haven't finished yet
+    pending: Cell<usize>,
+    committed: Cell<bool>,
+    finished: Cell<bool>,
+    // performance.now() of the first animation started inside
+    start_time: Cell<Option<f64>>,
+}
+
+thread_local! {
+    // Open transactions, innermost last
+    static STACK: RefCell<Vec<Rc<Scope>>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Scope {
+    fn duration(&self) -> f64 {
+        self.explicit_duration().unwrap_or(DEFAULT_DURATION)
+    }
+
+    fn timing_function(&self) -> TimingFunction {
+        self.explicit_timing_function().unwrap_or(TimingFunction::Default)
+    }
+
+    // Set on this transaction or one around it
+    fn explicit_duration(&self) -> Option<f64> {
+        self.duration.get()
+            .or_else(|| self.parent.as_ref().and_then(|p| p.explicit_duration()))
+    }
+
+    fn explicit_timing_function(&self) -> Option<TimingFunction> {
+        self.timing_function.get()
+            .or_else(|| self.parent.as_ref().and_then(|p| p.explicit_timing_function()))
+    }
+
+    fn actions_disabled(&self) -> bool {
+        self.disable_actions.get()
+            .or_else(|| self.parent.as_ref().map(|p| p.actions_disabled()))
+            .unwrap_or(false)
+    }
+
+    /// Start time for an animation starting at `now`, shared by every
+    /// animation started in the same frame within the outermost transaction
+    pub(crate) fn start_time(&self, now: f64) -> f64 {
+        if let Some(parent) = &self.parent {
+            return parent.start_time(now);
+        }
+        match self.start_time.get() {
+            Some(shared) if (now - shared).abs() < SAME_FRAME_MS => shared,
+            _ => {
+                self.start_time.set(Some(now));
+                now
+            }
+        }
+    }
+
+    fn hold(&self) {
+        self.pending.set(self.pending.get() + 1);
+    }
+
+    fn release(&self) {
+        self.pending.set(self.pending.get().saturating_sub(1));
+        self.try_finish();
+    }
+
+    /// Fire the completion once committed with nothing left running, then
+    /// let the enclosing transaction know
+    fn try_finish(&self) {
+        if !self.committed.get() || self.pending.get() > 0 || self.finished.replace(true) {
+            return;
+        }
+        let completion = self.completion.borrow_mut().take();
+        if let Some(callback) = completion {
+            let _ = callback.call0(&JsValue::NULL);
+        }
+        if let Some(parent) = &self.parent {
+            parent.release();
+        }
+    }
+}
+
+/// Open a transaction inside the current one, if any
+fn push() -> Rc<Scope> {
+    let parent = current();
+    if let Some(parent) = &parent {
+        parent.hold();
+    }
+    let scope = Rc::new(Scope {
+        duration: Cell::new(None),
+        timing_function: Cell::new(None),
+        disable_actions: Cell::new(None),
+        completion: RefCell::new(None),
+        mutations: RefCell::new(Vec::new()),
+        parent,
+        pending: Cell::new(0),
+        committed: Cell::new(false),
+        finished: Cell::new(false),
+        start_time: Cell::new(None),
+    });
+    STACK.with(|stack| stack.borrow_mut().push(scope.clone()));
+    scope
+}
+
+fn current() -> Option<Rc<Scope>> {
+    STACK.with(|stack| stack.borrow().last().cloned())
+}
+
+fn current_or_err() -> Result<Rc<Scope>, JsValue> {
+    current().ok_or_else(|| JsValue::from_str("No open transaction"))
+}
+
+/// Close a transaction: animate its recorded changes and finish once they
+/// and anything else started inside are done
+fn commit_scope(scope: Rc<Scope>) -> Result<(), JsValue> {
+    STACK.with(|stack| stack.borrow_mut().retain(|s| !Rc::ptr_eq(s, &scope)));
+
+    let result = start_mutations(&scope);
+    scope.committed.set(true);
+    scope.try_finish();
+    result
+}
+
+fn start_mutations(scope: &Rc<Scope>) -> Result<(), JsValue> {
+    let mutations = std::mem::take(&mut *scope.mutations.borrow_mut());
+    for mut animation in build_animations(scope, mutations)? {
+        if scope.actions_disabled() {
+            // Actions disabled: jump straight to the new values
+            animation.set_fraction_complete(1.0)?;
+        } else {
+            scope.hold();
+            animation.transaction = Some(scope.clone());
+            animation.start()?;
+        }
+    }
+    Ok(())
+}
+
+/// One animation per element, carrying all of its recorded changes
+fn build_animations(scope: &Scope, mutations: Vec<Mutation>) -> Result<Vec<Animation>, JsValue> {
+    let mut groups: Vec<(Element, js_sys::Object)> = Vec::new();
+    for mutation in mutations {
+        let index = match groups.iter().position(|(el, _)| *el == mutation.element) {
+            Some(index) => index,
+            None => {
+                groups.push((mutation.element, js_sys::Object::new()));
+                groups.len() - 1
+            }
+        };
+        let key = JsValue::from_str(&mutation.property);
+        js_sys::Reflect::set(&groups[index].1, &key, &mutation.value)?;
+    }
+
+    let curve = scope.timing_function().curve();
+    let duration = scope.duration() * 1000.0;
+    groups
+        .into_iter()
+        .map(|(element, config)| {
+            Animation::new(element)?
+                .cubic(curve.x1, curve.y1, curve.x2, curve.y2, duration)
+                .animate(config.into())
+        })
+        .collect()
+}
+
+/// Queue a change, replacing an earlier one to the same element and property
+fn record(scope: &Scope, element: Element, property: String, value: JsValue)
+    -> Result<(), JsValue> {
+    property
+        .parse::<PropertyType>()
+        .map_err(|_| JsValue::from_str(&format!("Unknown property: {}", property)))?;
+
+    let mut mutations = scope.mutations.borrow_mut();
+    match mutations.iter_mut().find(|m| m.element == element && m.property == property) {
+        Some(existing) => existing.value = value,
+        None => mutations.push(Mutation { element, property, value }),
+    }
+    Ok(())
+}
+
+/// Count an animation that's starting toward the innermost open transaction.
+/// A duration or timing function set on the transaction replaces the
+/// animation's own; with actions disabled it jumps to its end values.
+pub(crate) fn join(animation: &mut Animation) {
+    let Some(scope) = current() else {
+        return;
+    };
+    scope.hold();
+
+    if scope.actions_disabled() {
+        // Done on the first frame, which also fires its completion
+        animation.use_spring = false;
+        animation.duration = 0.0;
+        animation.delay = 0.0;
+    } else if !animation.use_spring {
+        if let Some(duration) = scope.explicit_duration() {
+            animation.duration = duration * 1000.0;
+        }
+        if let Some(timing) = scope.explicit_timing_function() {
+            animation.bezier = Some(timing.curve());
+        }
+    }
+    animation.transaction = Some(scope);
+}
+
+/// An animation counted by join() has finished or stopped
+pub(crate) fn leave(scope: &Scope) {
+    scope.release();
+}
 
 #[wasm_bindgen]
 pub struct AnimationTransaction {
-    duration: f64,
-    timing_function: TimingFunction,
-    disable_actions: bool,
+    // None inherits from the enclosing transaction
+    duration: Option<f64>,
+    timing_function: Option<TimingFunction>,
+    disable_actions: Option<bool>,
     completion: Option<js_sys::Function>,
-    active: bool,
+    scope: Option<Rc<Scope>>,
     performance: Performance,
     id: String,
     start_time: f64,
@@ -28,6 +268,29 @@ pub enum TimingFunction {
     EaseInOut = 4,
 }
 
+impl TimingFunction {
+    fn from_u8(timing: u8) -> Result<TimingFunction, JsValue> {
+        Ok(match timing {
+            0 => TimingFunction::Default,
+            1 => TimingFunction::Linear,
+            2 => TimingFunction::EaseIn,
+            3 => TimingFunction::EaseOut,
+            4 => TimingFunction::EaseInOut,
+            _ => return Err(JsValue::from_str("Invalid timing function")),
+        })
+    }
+
+    fn curve(self) -> CubicBezier {
+        match self {
+            TimingFunction::Default => CubicBezier::default(),
+            TimingFunction::Linear => CubicBezier::linear(),
+            TimingFunction::EaseIn => CubicBezier::ease_in(),
+            TimingFunction::EaseOut => CubicBezier::ease_out(),
+            TimingFunction::EaseInOut => CubicBezier::ease_in_out(),
+        }
+    }
+}
+
 #[wasm_bindgen]
 impl AnimationTransaction {
     #[wasm_bindgen(constructor)]
@@ -38,11 +301,11 @@ impl AnimationTransaction {
             .ok_or_else(|| JsValue::from_str("No performance API"))?;
 
         Ok(AnimationTransaction {
-            duration: 0.25,
-            timing_function: TimingFunction::Default,
-            disable_actions: false,
+            duration: None,
+            timing_function: None,
+            disable_actions: None,
             completion: None,
-            active: false,
+            scope: None,
             performance,
             id: generate_id(),
             start_time: 0.0,
@@ -55,26 +318,19 @@ impl AnimationTransaction {
 
     #[wasm_bindgen(js_name = setDuration)]
     pub fn set_duration(mut self, duration: f64) -> Self {
-        self.duration = duration.max(0.0);
+        self.duration = Some(duration.max(0.0));
         self
     }
 
     #[wasm_bindgen(js_name = setTimingFunction)]
     pub fn set_timing_function(mut self, timing: u8) -> Result<AnimationTransaction, JsValue> {
-        self.timing_function = match timing {
-            0 => TimingFunction::Default,
-            1 => TimingFunction::Linear,
-            2 => TimingFunction::EaseIn,
-            3 => TimingFunction::EaseOut,
-            4 => TimingFunction::EaseInOut,
-            _ => return Err(JsValue::from_str("Invalid timing function")),
-        };
+        self.timing_function = Some(TimingFunction::from_u8(timing)?);
         Ok(self)
     }
 
     #[wasm_bindgen(js_name = disableActions)]
     pub fn disable_actions(mut self) -> Self {
-        self.disable_actions = true;
+        self.disable_actions = Some(true);
         self
     }
 
@@ -88,49 +344,70 @@ impl AnimationTransaction {
     // TRANSACTION LIFECYCLE
     // ========================================================================
 
+    /// Open on the transaction stack, inside whichever transaction is open
     #[wasm_bindgen]
     pub fn begin(&mut self) {
-        self.active = true;
+        if self.scope.is_none() {
+            let scope = push();
+            scope.duration.set(self.duration);
+            scope.timing_function.set(self.timing_function);
+            scope.disable_actions.set(self.disable_actions);
+            *scope.completion.borrow_mut() = self.completion.clone();
+            self.scope = Some(scope);
+        }
         self.start_time = self.performance.now();
     }
 
+    /// Record a style change, e.g. `txn.set(el, "opacity", 0)`. Nothing is
+    /// written until commit(), which animates every change with the
+    /// transaction's duration and curve.
     #[wasm_bindgen]
-    pub fn commit(&mut self) -> Result<(), JsValue> {
-        if !self.active {
-            return Err(JsValue::from_str("Transaction not active"));
-        }
-
-        self.active = false;
-
-        if let Some(ref callback) = self.completion {
-            let _ = callback.call0(&JsValue::NULL);
-        }
+    pub fn set(&mut self, element: Element, property: String, value: JsValue)
+        -> Result<(), JsValue> {
+        let scope = self.scope.as_ref()
+            .ok_or_else(|| JsValue::from_str("Transaction not active"))?;
+        record(scope, element, property, value)
+    }
 
-        Ok(())
+    #[wasm_bindgen]
+    pub fn commit(&mut self) -> Result<(), JsValue> {
+        let scope = self.scope.take()
+            .ok_or_else(|| JsValue::from_str("Transaction not active"))?;
+        commit_scope(scope)
     }
 
     // ========================================================================
     // QUERIES
     // ========================================================================
 
+    /// Seconds; while open, inherited from the enclosing transaction if unset
     #[wasm_bindgen(getter)]
     pub fn duration(&self) -> f64 {
-        self.duration
+        match &self.scope {
+            Some(scope) => scope.duration(),
+            None => self.duration.unwrap_or(DEFAULT_DURATION),
+        }
     }
 
     #[wasm_bindgen(getter, js_name = timingFunction)]
     pub fn timing_function(&self) -> u8 {
-        self.timing_function as u8
+        match &self.scope {
+            Some(scope) => scope.timing_function() as u8,
+            None => self.timing_function.unwrap_or(TimingFunction::Default) as u8,
+        }
     }
 
     #[wasm_bindgen(getter, js_name = actionsDisabled)]
     pub fn actions_disabled(&self) -> bool {
-        self.disable_actions
+        match &self.scope {
+            Some(scope) => scope.actions_disabled(),
+            None => self.disable_actions.unwrap_or(false),
+        }
     }
 
     #[wasm_bindgen(getter, js_name = isActive)]
     pub fn is_active(&self) -> bool {
-        self.active
+        self.scope.is_some()
     }
 
     #[wasm_bindgen(getter)]
@@ -140,7 +417,7 @@ impl AnimationTransaction {
 
     #[wasm_bindgen(js_name = elapsedTime)]
     pub fn elapsed_time(&self) -> f64 {
-        if self.active {
+        if self.scope.is_some() {
             (self.performance.now() - self.start_time) / 1000.0
         } else {
             0.0
@@ -183,17 +460,76 @@ pub struct Transaction;
 
 #[wasm_bindgen]
 impl Transaction {
+    /// Open a transaction inside the current one, if any
+    #[wasm_bindgen]
+    pub fn begin() {
+        push();
+    }
+
+    /// Close the innermost open transaction
+    #[wasm_bindgen]
+    pub fn commit() -> Result<(), JsValue> {
+        let scope = STACK
+            .with(|stack| stack.borrow_mut().pop())
+            .ok_or_else(|| JsValue::from_str("No open transaction"))?;
+        commit_scope(scope)
+    }
+
+    #[wasm_bindgen(js_name = setDuration)]
+    pub fn set_duration(duration: f64) -> Result<(), JsValue> {
+        current_or_err()?.duration.set(Some(duration.max(0.0)));
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setTimingFunction)]
+    pub fn set_timing_function(timing: u8) -> Result<(), JsValue> {
+        current_or_err()?.timing_function.set(Some(TimingFunction::from_u8(timing)?));
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = setDisableActions)]
+    pub fn set_disable_actions(disabled: bool) -> Result<(), JsValue> {
+        current_or_err()?.disable_actions.set(Some(disabled));
+        Ok(())
+    }
+
+    /// Called once every animation started inside the innermost transaction,
+    /// nested ones included, has finished
+    #[wasm_bindgen(js_name = setCompletion)]
+    pub fn set_completion(callback: js_sys::Function) -> Result<(), JsValue> {
+        *current_or_err()?.completion.borrow_mut() = Some(callback);
+        Ok(())
+    }
+
+    /// Seconds, for the innermost transaction after inheritance
+    #[wasm_bindgen]
+    pub fn duration() -> f64 {
+        current().map_or(DEFAULT_DURATION, |scope| scope.duration())
+    }
+
+    /// Number of open transactions
+    #[wasm_bindgen]
+    pub fn depth() -> usize {
+        STACK.with(|stack| stack.borrow().len())
+    }
+
+    /// Record a style change into the innermost open transaction, so code
+    /// inside a batch() block can animate without holding the transaction
+    #[wasm_bindgen]
+    pub fn set(element: Element, property: String, value: JsValue) -> Result<(), JsValue> {
+        let scope = current_or_err()?;
+        record(&scope, element, property, value)
+    }
+
     /// Execute multiple animations in a single transaction
     #[wasm_bindgen]
     pub fn batch(duration: f64, callback: js_sys::Function) -> Result<(), JsValue> {
-        let mut txn = AnimationTransaction::new()?;
-        txn.duration = duration;
-        txn.begin();
-        
+        let scope = push();
+        scope.duration.set(Some(duration.max(0.0)));
+
         let _ = callback.call0(&JsValue::NULL);
-        
-        txn.commit()?;
-        Ok(())
+
+        commit_scope(scope)
     }
 
     /// Execute with completion callback
@@ -203,14 +539,12 @@ impl Transaction {
         animation_block: js_sys::Function,
         completion_block: js_sys::Function,
     ) -> Result<(), JsValue> {
-        let mut txn = AnimationTransaction::new()?;
-        txn.duration = duration;
-        txn.completion = Some(completion_block);
-        txn.begin();
-        
+        let scope = push();
+        scope.duration.set(Some(duration.max(0.0)));
+        *scope.completion.borrow_mut() = Some(completion_block);
+
         let _ = animation_block.call0(&JsValue::NULL);
-        
-        txn.commit()?;
-        Ok(())
+
+        commit_scope(scope)
     }
 }
@@ -1,4 +1,5 @@
 #![allow(dead_code)]
+use crate::batch;
 use serde::Deserialize;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Copy)]
@@ -77,8 +78,34 @@ pub enum PropertyType {
 }
 
 impl PropertyType {
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
+    /// Shadows and filters, which repaint the element on every change
+    pub fn is_paint_effect(self) -> bool {
+        matches!(
+            self,
+            PropertyType::ShadowOffsetX
+                | PropertyType::ShadowOffsetY
+                | PropertyType::ShadowBlur
+                | PropertyType::ShadowSpread
+                | PropertyType::ShadowColor
+                | PropertyType::Blur
+                | PropertyType::Brightness
+                | PropertyType::Contrast
+                | PropertyType::Saturate
+                | PropertyType::Hue
+                | PropertyType::Grayscale
+                | PropertyType::Invert
+                | PropertyType::Sepia
+                | PropertyType::Dropoff
+                | PropertyType::BackgroundBlur
+        )
+    }
+}
+
+impl std::str::FromStr for PropertyType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, ()> {
+        let prop_type = match s {
             "x" => Some(PropertyType::X),
             "y" => Some(PropertyType::Y),
             "z" => Some(PropertyType::Z),
@@ -117,7 +144,8 @@ impl PropertyType {
             "transformOriginZ" | "transform_origin_z" => Some(PropertyType::TransformOriginZ),
             "perspective" => Some(PropertyType::Perspective),
             _ => None,
-        }
+        };
+        prop_type.ok_or(())
     }
 }
 
@@ -241,6 +269,31 @@ impl LengthUnit {
     }
 }
 
+/// What stop() leaves on the element
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopBehavior {
+    /// Values as they were when stopped
+    Keep,
+    /// The values it would have finished on
+    End,
+    /// The inline styles the element had before the animation first wrote any
+    Restore,
+}
+
+impl StopBehavior {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "keep" => Ok(StopBehavior::Keep),
+            "end" => Ok(StopBehavior::End),
+            "restore" => Ok(StopBehavior::Restore),
+            other => Err(format!(
+                "Unknown stop behavior: {} (use keep, end or restore)",
+                other
+            )),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct AnimationProperty {
     pub property_type: PropertyType,
@@ -365,12 +418,8 @@ pub fn interpolate_value(
             AnimatableValue::Number(s + (e - s) * t)
         }
         (AnimatableValue::Color(r1, g1, b1, a1), AnimatableValue::Color(r2, g2, b2, a2)) => {
-            AnimatableValue::Color(
-                r1 + (r2 - r1) * t,
-                g1 + (g2 - g1) * t,
-                b1 + (b2 - b1) * t,
-                a1 + (a2 - a1) * t,
-            )
+            let [r, g, b, a] = batch::lerp4([*r1, *g1, *b1, *a1], [*r2, *g2, *b2, *a2], t);
+            AnimatableValue::Color(r, g, b, a)
         }
         (AnimatableValue::Length(v1, u), AnimatableValue::Length(v2, _)) => {
             AnimatableValue::Length(v1 + (v2 - v1) * t, u.clone())
@@ -381,12 +430,12 @@ pub fn interpolate_value(
                 offset_y: s1.offset_y + (s2.offset_y - s1.offset_y) * t,
                 blur: s1.blur + (s2.blur - s1.blur) * t,
                 spread: s1.spread + (s2.spread - s1.spread) * t,
-                color: (
-                    s1.color.0 + (s2.color.0 - s1.color.0) * t,
-                    s1.color.1 + (s2.color.1 - s1.color.1) * t,
-                    s1.color.2 + (s2.color.2 - s1.color.2) * t,
-                    s1.color.3 + (s2.color.3 - s1.color.3) * t,
-                ),
+                color: {
+                    let (r1, g1, b1, a1) = s1.color;
+                    let (r2, g2, b2, a2) = s2.color;
+                    let [r, g, b, a] = batch::lerp4([r1, g1, b1, a1], [r2, g2, b2, a2], t);
+                    (r, g, b, a)
+                },
                 inset: s1.inset,
             })
         }
@@ -445,36 +494,36 @@ pub fn format_value(value: &AnimatableValue) -> String {
 pub fn parse_css_length(value: &str) -> Result<(f64, LengthUnit), String> {
     let value = value.trim();
 
-    if value.ends_with("px") {
-        let num = value[..value.len() - 2]
+    if let Some(num) = value.strip_suffix("px") {
+        let num = num
             .parse::<f64>()
             .map_err(|_| "Invalid px value".to_string())?;
         Ok((num, LengthUnit::Px))
-    } else if value.ends_with('%') {
-        let num = value[..value.len() - 1]
+    } else if let Some(num) = value.strip_suffix('%') {
+        let num = num
             .parse::<f64>()
             .map_err(|_| "Invalid % value".to_string())?;
         Ok((num, LengthUnit::Percent))
-    } else if value.ends_with("vw") {
-        let num = value[..value.len() - 2]
+    } else if let Some(num) = value.strip_suffix("vw") {
+        let num = num
             .parse::<f64>()
             .map_err(|_| "Invalid vw value".to_string())?;
         Ok((num, LengthUnit::Vw))
-    } else if value.ends_with("vh") {
-        let num = value[..value.len() - 2]
+    } else if let Some(num) = value.strip_suffix("vh") {
+        let num = num
             .parse::<f64>()
             .map_err(|_| "Invalid vh value".to_string())?;
         Ok((num, LengthUnit::Vh))
-    } else if value.ends_with("em") {
-        let num = value[..value.len() - 2]
-            .parse::<f64>()
-            .map_err(|_| "Invalid em value".to_string())?;
-        Ok((num, LengthUnit::Em))
-    } else if value.ends_with("rem") {
-        let num = value[..value.len() - 3]
+    } else if let Some(num) = value.strip_suffix("rem") {
+        let num = num
             .parse::<f64>()
             .map_err(|_| "Invalid rem value".to_string())?;
         Ok((num, LengthUnit::Rem))
+    } else if let Some(num) = value.strip_suffix("em") {
+        let num = num
+            .parse::<f64>()
+            .map_err(|_| "Invalid em value".to_string())?;
+        Ok((num, LengthUnit::Em))
     } else {
         let num = value
             .parse::<f64>()
@@ -486,8 +535,7 @@ pub fn parse_css_length(value: &str) -> Result<(f64, LengthUnit), String> {
 pub fn parse_css_color(value: &str) -> Result<(f64, f64, f64, f64), String> {
     let value = value.trim().to_lowercase();
     
-    if value.starts_with('#') {
-        let hex = &value[1..];
+    if let Some(hex) = value.strip_prefix('#') {
         
         // Handle #RRGGBBAA (8 characters) ✨
         if hex.len() == 8 {
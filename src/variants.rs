@@ -0,0 +1,335 @@
+use crate::sequencer::{stagger_ranks, StaggerFrom};
+use crate::spec::{self, Easing};
+use crate::types::AnimateConfig;
+use crate::{migration, Animation, AnimationHandle};
+use js_sys::{Object, Reflect};
+use serde::Deserialize;
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use web_sys::Element;
+
+// ============================================================================
+// VARIANTS - Named animation states, propagated down the tree
+// ============================================================================
+//
+// Each element registers its own named states. Moving an element to a state
+// also moves its registered descendants, staggered, so a menu can open and
+// its items follow without each being started by hand.
+
+/// Timing for moving into a variant, from the variant's `transition` key or
+/// the options given to animateToVariant
+#[derive(Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase", default)]
+struct Transition {
+    duration: Option<f64>,
+    easing: Option<String>,
+    delay: Option<f64>,
+    // For the element's registered descendants
+    stagger: Option<f64>,
+    delay_children: Option<f64>,
+    stagger_from: Option<String>,
+}
+
+impl Transition {
+    /// Fields set here, the rest from `fallback`
+    fn or(&self, fallback: &Transition) -> Transition {
+        Transition {
+            duration: self.duration.or(fallback.duration),
+            easing: self.easing.clone().or_else(|| fallback.easing.clone()),
+            delay: self.delay.or(fallback.delay),
+            stagger: self.stagger.or(fallback.stagger),
+            delay_children: self.delay_children.or(fallback.delay_children),
+            stagger_from: self
+                .stagger_from
+                .clone()
+                .or_else(|| fallback.stagger_from.clone()),
+        }
+    }
+
+    fn stagger_from(&self) -> Result<StaggerFrom, JsValue> {
+        match self.stagger_from.as_deref() {
+            None | Some("start") => Ok(StaggerFrom::Start),
+            Some("end") => Ok(StaggerFrom::End),
+            Some("center") => Ok(StaggerFrom::Center),
+            Some("random") => Ok(StaggerFrom::Random),
+            Some(other) => Err(JsValue::from_str(&format!(
+                "Invalid staggerFrom: {} (use start, end, center or random)",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+struct VariantOptions {
+    #[serde(flatten)]
+    transition: Transition,
+    propagate: Option<bool>,
+}
+
+struct Variant {
+    name: String,
+    config: JsValue,
+    transition: Transition,
+}
+
+struct Entry {
+    element: Element,
+    variants: Vec<Variant>,
+    current: Option<String>,
+    running: Option<AnimationHandle>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Vec<Entry>> = const { RefCell::new(Vec::new()) };
+}
+
+fn parse_variants(variants: &JsValue) -> Result<Vec<Variant>, JsValue> {
+    if !variants.is_object() {
+        return Err(JsValue::from_str(
+            "Variants must be an object of named animate() configs",
+        ));
+    }
+    let mut parsed = Vec::new();
+    for entry in Object::entries(variants.unchecked_ref()).iter() {
+        let name = Reflect::get(&entry, &0.into())?
+            .as_string()
+            .unwrap_or_default();
+        let config = Reflect::get(&entry, &1.into())?;
+        // Validate now rather than on the first transition
+        let _: AnimateConfig = serde_wasm_bindgen::from_value(migration::migrate(config.clone())?)
+            .map_err(|e| JsValue::from_str(&format!("Invalid variant {}: {:?}", name, e)))?;
+        let transition = match Reflect::get(&config, &"transition".into())? {
+            value if value.is_undefined() => Transition::default(),
+            value => serde_wasm_bindgen::from_value(value).map_err(|e| {
+                JsValue::from_str(&format!("Invalid transition in variant {}: {:?}", name, e))
+            })?,
+        };
+        parsed.push(Variant {
+            name,
+            config,
+            transition,
+        });
+    }
+    Ok(parsed)
+}
+
+fn with_entry<T>(element: &Element, f: impl FnOnce(&mut Entry) -> T) -> Option<T> {
+    REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .iter_mut()
+            .find(|entry| entry.element == *element)
+            .map(f)
+    })
+}
+
+/// Registered descendants with no registered element between them and
+/// `element`, in document order
+fn registered_children(element: &Element) -> Vec<Element> {
+    let mut children: Vec<Element> = REGISTRY.with(|registry| {
+        let registry = registry.borrow();
+        let inside =
+            |outer: &Element, inner: &Element| outer != inner && outer.contains(Some(inner));
+        registry
+            .iter()
+            .filter(|entry| inside(element, &entry.element))
+            .filter(|entry| {
+                !registry.iter().any(|between| {
+                    inside(element, &between.element) && inside(&between.element, &entry.element)
+                })
+            })
+            .map(|entry| entry.element.clone())
+            .collect()
+    });
+    // DOCUMENT_POSITION_FOLLOWING: b comes after a
+    children.sort_by(|a, b| {
+        if a.compare_document_position(b) & 4 != 0 {
+            std::cmp::Ordering::Less
+        } else {
+            std::cmp::Ordering::Greater
+        }
+    });
+    children
+}
+
+fn animation_for(
+    element: &Element,
+    config: JsValue,
+    transition: &Transition,
+    delay: f64,
+) -> Result<Animation, JsValue> {
+    let mut animation = Animation::new(element.clone())?;
+    animation.configure(config)?;
+    if let Some(duration) = transition.duration {
+        animation.duration = duration.max(0.0);
+    }
+    if let Some(easing) = &transition.easing {
+        match spec::parse_easing(easing)? {
+            Easing::Curve(curve) => animation.bezier = Some(*curve),
+            Easing::Spring => animation.use_spring = true,
+        }
+    }
+    animation.delay = (delay + transition.delay.unwrap_or(0.0)).max(0.0);
+    Ok(animation)
+}
+
+/// Move `element` into `name` if it has such a variant, then its registered
+/// children, each `stagger` ms after the last. Returns how many elements
+/// were animated.
+fn transition_tree(
+    element: &Element,
+    name: &str,
+    overrides: &Transition,
+    parent: &Transition,
+    delay: f64,
+    propagate: bool,
+) -> Result<u32, JsValue> {
+    let found = with_entry(element, |entry| {
+        let variant = entry.variants.iter().find(|v| v.name == name)?;
+        let target = (variant.config.clone(), variant.transition.clone());
+        Some((target, entry.running.take()))
+    })
+    .flatten();
+
+    let mut animated = 0;
+    // Staggering for the children comes from this element's variant, or is
+    // passed down from the nearest ancestor that had one
+    let mut timing = parent.clone();
+    if let Some(((config, transition), running)) = found {
+        if let Some(running) = running {
+            // The new transition starts from wherever this one got to
+            running.stop()?;
+        }
+        let transition = overrides.or(&transition);
+        let handle = animation_for(element, config, &transition, delay)?.start()?;
+        with_entry(element, |entry| {
+            entry.current = Some(name.to_string());
+            entry.running = Some(handle);
+        });
+        timing = transition;
+        animated += 1;
+    }
+    if !propagate {
+        return Ok(animated);
+    }
+
+    let children = registered_children(element);
+    let ranks = stagger_ranks(children.len(), timing.stagger_from()?);
+    let children_delay = delay + timing.delay_children.unwrap_or(0.0);
+    let stagger = timing.stagger.unwrap_or(0.0).max(0.0);
+    // Children set their own timing, apart from the call's overrides
+    let inherited = Transition {
+        duration: overrides.duration,
+        easing: overrides.easing.clone(),
+        ..Transition::default()
+    };
+    for (child, rank) in children.iter().zip(ranks) {
+        animated += transition_tree(
+            child,
+            name,
+            &inherited,
+            &timing,
+            children_delay + rank * stagger,
+            true,
+        )?;
+    }
+    Ok(animated)
+}
+
+/// Animate `element` to its variant `name` and its registered descendants
+/// to theirs (see Variants.define). Options override the variant's
+/// `transition`: `{ duration, easing, delay, stagger, delayChildren,
+/// staggerFrom: "start" | "end" | "center" | "random", propagate: true }`.
+/// Returns how many elements were animated.
+#[wasm_bindgen(js_name = animateToVariant)]
+pub fn animate_to_variant(
+    element: Element,
+    name: String,
+    options: JsValue,
+) -> Result<u32, JsValue> {
+    let options: VariantOptions = if options.is_undefined() || options.is_null() {
+        VariantOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| JsValue::from_str(&format!("Invalid variant options: {:?}", e)))?
+    };
+    transition_tree(
+        &element,
+        &name,
+        &options.transition,
+        &options.transition,
+        0.0,
+        options.propagate.unwrap_or(true),
+    )
+}
+
+/// Registry of named states per element
+#[wasm_bindgen]
+pub struct Variants;
+
+#[wasm_bindgen]
+impl Variants {
+    /// Give `element` named states, each an animate() config that may carry
+    /// a `transition: { duration, easing, delay, stagger, delayChildren,
+    /// staggerFrom }`; the stagger settings apply to registered descendants.
+    /// With `initial`, the element is put in that state straight away.
+    /// Defining again replaces the element's variants.
+    #[wasm_bindgen]
+    pub fn define(
+        element: Element,
+        variants: JsValue,
+        initial: Option<String>,
+    ) -> Result<(), JsValue> {
+        let variants = parse_variants(&variants)?;
+        if let Some(initial) = &initial {
+            let variant = variants
+                .iter()
+                .find(|v| v.name == *initial)
+                .ok_or_else(|| JsValue::from_str(&format!("Unknown variant: {}", initial)))?;
+            animation_for(
+                &element,
+                variant.config.clone(),
+                &Transition::default(),
+                0.0,
+            )?
+            .set_fraction_complete(1.0)?;
+        }
+
+        REGISTRY.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            match registry.iter_mut().find(|entry| entry.element == element) {
+                Some(entry) => {
+                    entry.variants = variants;
+                    if initial.is_some() {
+                        entry.current = initial;
+                    }
+                }
+                None => registry.push(Entry {
+                    element,
+                    variants,
+                    current: initial,
+                    running: None,
+                }),
+            }
+        });
+        Ok(())
+    }
+
+    /// The variant the element was last moved to
+    #[wasm_bindgen]
+    pub fn current(element: &Element) -> Option<String> {
+        with_entry(element, |entry| entry.current.clone()).flatten()
+    }
+
+    /// Forget the element's variants; a running transition finishes
+    #[wasm_bindgen]
+    pub fn remove(element: &Element) {
+        REGISTRY.with(|registry| {
+            registry
+                .borrow_mut()
+                .retain(|entry| entry.element != *element)
+        });
+    }
+}
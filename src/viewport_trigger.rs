@@ -0,0 +1,236 @@
+use crate::{Animation, AnimationState};
+use js_sys::{Array, Function};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{Element, IntersectionObserver, IntersectionObserverEntry, IntersectionObserverInit};
+
+// ============================================================================
+// VIEWPORT TRIGGER - Play animations as elements enter and leave the viewport
+// ============================================================================
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ViewportConfig {
+    pub(crate) threshold: Option<f64>,
+    pub(crate) root_margin: Option<String>,
+    pub(crate) once: Option<bool>,
+    pub(crate) reverse_on_leave: Option<bool>,
+}
+
+struct ViewportState {
+    enter: Option<Rc<RefCell<Animation>>>,
+    exit: Option<Rc<RefCell<Animation>>>,
+    // Fraction of the element that must be visible to count as entered
+    threshold: f64,
+    once: bool,
+    reverse_on_leave: bool,
+    visible: bool,
+    // The enter animation is running backwards after a leave
+    reversed: bool,
+    on_toggle: Option<Function>,
+}
+
+impl ViewportState {
+    /// Returns whether the element is in view and observing can stop
+    fn toggle(&mut self, visible: bool) -> Result<bool, JsValue> {
+        self.visible = visible;
+        if visible {
+            if let Some(exit) = &self.exit {
+                exit.borrow_mut().stop()?;
+            }
+            if let Some(enter) = &self.enter {
+                play_toward(enter, false, &mut self.reversed)?;
+            }
+            return Ok(self.once);
+        }
+
+        if let Some(exit) = &self.exit {
+            crate::replay(exit)?;
+        } else if self.reverse_on_leave {
+            if let Some(enter) = &self.enter {
+                play_toward(enter, true, &mut self.reversed)?;
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Play the enter animation forwards, or backwards to undo it, continuing
+/// from wherever it is so an interrupted animation doesn't jump
+fn play_toward(
+    animation: &Rc<RefCell<Animation>>,
+    backwards: bool,
+    reversed: &mut bool,
+) -> Result<(), JsValue> {
+    let state = animation.borrow().state;
+
+    if *reversed == backwards {
+        // Entering again after finishing plays it from the start
+        return match state {
+            AnimationState::Idle | AnimationState::Completed if !backwards => {
+                crate::replay(animation)
+            }
+            _ => Ok(()),
+        };
+    }
+
+    match state {
+        // Never played, so there's nothing to undo
+        AnimationState::Idle => Ok(()),
+        AnimationState::Running | AnimationState::Paused => {
+            *reversed = backwards;
+            let mut anim = animation.borrow_mut();
+            let done = anim.fraction_complete;
+            anim.reverse()?;
+            // Same point on the timeline, seen from the other end
+            anim.start_time -= (1.0 - done) * anim.duration;
+            Ok(())
+        }
+        AnimationState::Completed => {
+            *reversed = backwards;
+            animation.borrow_mut().reverse()?;
+            crate::spawn_animation_loop(Rc::clone(animation))
+        }
+    }
+}
+
+type ObserverCallback = Closure<dyn FnMut(Array, IntersectionObserver)>;
+
+/// Config: `{ threshold: 0, rootMargin: "0px", once: false,
+/// reverseOnLeave: false }`
+#[wasm_bindgen]
+pub struct ViewportTrigger {
+    element: Element,
+    state: Rc<RefCell<ViewportState>>,
+    observer: IntersectionObserver,
+    _callback: ObserverCallback,
+}
+
+#[wasm_bindgen]
+impl ViewportTrigger {
+    #[wasm_bindgen(constructor)]
+    pub fn new(element: Element, config: JsValue) -> Result<ViewportTrigger, JsValue> {
+        let config: ViewportConfig = if config.is_undefined() || config.is_null() {
+            ViewportConfig::default()
+        } else {
+            serde_wasm_bindgen::from_value(config)
+                .map_err(|e| JsValue::from_str(&format!("Invalid viewport config: {:?}", e)))?
+        };
+        ViewportTrigger::observe(element, config)
+    }
+
+    /// Animation to play on entering. Its start values are applied right
+    /// away so the element waits in its pre-entrance state.
+    #[wasm_bindgen(js_name = setEnter)]
+    pub fn set_enter(&mut self, mut animation: Animation) -> Result<(), JsValue> {
+        animation.set_fraction_complete(0.0)?;
+        let mut state = self.state.borrow_mut();
+        state.enter = Some(Rc::new(RefCell::new(animation)));
+        state.reversed = false;
+        Ok(())
+    }
+
+    /// Animation to play on leaving, instead of reversing the enter one
+    #[wasm_bindgen(js_name = setExit)]
+    pub fn set_exit(&mut self, animation: Animation) {
+        self.state.borrow_mut().exit = Some(Rc::new(RefCell::new(animation)));
+    }
+
+    /// Called with true on entering and false on leaving
+    #[wasm_bindgen(js_name = onToggle)]
+    pub fn on_toggle(&mut self, callback: Function) {
+        self.state.borrow_mut().on_toggle = Some(callback);
+    }
+
+    #[wasm_bindgen(getter, js_name = isVisible)]
+    pub fn is_visible(&self) -> bool {
+        self.state.borrow().visible
+    }
+
+    /// Stop watching the element; running animations finish
+    #[wasm_bindgen]
+    pub fn disconnect(&self) {
+        self.observer.unobserve(&self.element);
+        self.observer.disconnect();
+    }
+}
+
+impl ViewportTrigger {
+    pub(crate) fn observe(
+        element: Element,
+        config: ViewportConfig,
+    ) -> Result<ViewportTrigger, JsValue> {
+        let threshold = config.threshold.unwrap_or(0.0).clamp(0.0, 1.0);
+
+        let state = Rc::new(RefCell::new(ViewportState {
+            enter: None,
+            exit: None,
+            threshold,
+            once: config.once.unwrap_or(false),
+            reverse_on_leave: config.reverse_on_leave.unwrap_or(false),
+            visible: false,
+            reversed: false,
+            on_toggle: None,
+        }));
+
+        let observed = Rc::clone(&state);
+        let callback: ObserverCallback = Closure::wrap(Box::new(
+            move |entries: Array, observer: IntersectionObserver| {
+                for entry in entries.iter() {
+                    let entry: IntersectionObserverEntry = entry.unchecked_into();
+                    on_entry(&observed, &observer, &entry);
+                }
+            },
+        )
+            as Box<dyn FnMut(Array, IntersectionObserver)>);
+
+        let options = IntersectionObserverInit::new();
+        options.set_threshold_f64(threshold);
+        if let Some(margin) = &config.root_margin {
+            options.set_root_margin(margin);
+        }
+        let observer =
+            IntersectionObserver::new_with_options(callback.as_ref().unchecked_ref(), &options)?;
+        observer.observe(&element);
+
+        Ok(ViewportTrigger {
+            element,
+            state,
+            observer,
+            _callback: callback,
+        })
+    }
+}
+
+impl Drop for ViewportTrigger {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+fn on_entry(
+    state: &Rc<RefCell<ViewportState>>,
+    observer: &IntersectionObserver,
+    entry: &IntersectionObserverEntry,
+) {
+    let (done, callback) = {
+        let mut s = state.borrow_mut();
+        let visible = entry.is_intersecting() && entry.intersection_ratio() >= s.threshold;
+        if visible == s.visible {
+            return;
+        }
+        let done = s.toggle(visible).unwrap_or(false);
+        (done, s.on_toggle.clone().map(|c| (c, visible)))
+    };
+
+    if done {
+        observer.disconnect();
+    }
+    // Called with the state released so it can read the trigger
+    if let Some((callback, visible)) = callback {
+        let _ = callback.call1(&JsValue::NULL, &JsValue::from_bool(visible));
+    }
+}
@@ -0,0 +1,154 @@
+use std::cell::RefCell;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+
+// ============================================================================
+// VISIBILITY - What animations do while the tab is in the background
+// ============================================================================
+//
+// Browsers stop animation frames in background tabs. When the tab comes
+// back, an animation either carries on from where it was (pause) or is
+// where it would have been by now (fast-forward), springs included, rather
+// than springs getting one clamped frame for the whole time away.
+
+/// Most simulated time springs are stepped through on coming back, in
+/// seconds; any still moving carry on from there
+pub(crate) const MAX_CATCH_UP_SECS: f64 = 30.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackgroundPolicy {
+    /// Hold animations while hidden and carry on from there
+    Pause,
+    /// Keep to the wall clock: on coming back, jump to where they'd be by now
+    #[default]
+    FastForward,
+}
+
+impl BackgroundPolicy {
+    pub fn parse(name: &str) -> Option<BackgroundPolicy> {
+        match name {
+            "pause" => Some(BackgroundPolicy::Pause),
+            "fastForward" | "fast-forward" => Some(BackgroundPolicy::FastForward),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackgroundPolicy::Pause => "pause",
+            BackgroundPolicy::FastForward => "fastForward",
+        }
+    }
+}
+
+pub(crate) fn parse_or_err(name: &str) -> Result<BackgroundPolicy, JsValue> {
+    BackgroundPolicy::parse(name).ok_or_else(|| {
+        JsValue::from_str(&format!(
+            "Unknown background policy: {} (use pause or fastForward)",
+            name
+        ))
+    })
+}
+
+/// A stretch of time the page spent hidden, in ms
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gap {
+    pub hidden_at: f64,
+    pub shown_at: f64,
+}
+
+impl Gap {
+    /// How much of the gap a frame loop whose last frame ran at
+    /// `last_frame` slept through, if any
+    pub fn missed(&self, last_frame: f64) -> Option<f64> {
+        if self.shown_at <= last_frame {
+            return None;
+        }
+        Some(self.shown_at - self.hidden_at.max(last_frame))
+    }
+}
+
+#[derive(Default)]
+struct Watch {
+    policy: BackgroundPolicy,
+    hidden_at: Option<f64>,
+    // Only the latest gap matters: a loop's next frame comes after it
+    gap: Option<Gap>,
+    listener: Option<Closure<dyn FnMut()>>,
+}
+
+thread_local! {
+    static WATCH: RefCell<Watch> = RefCell::new(Watch::default());
+}
+
+fn document_now() -> Option<(web_sys::Document, f64)> {
+    let window = web_sys::window()?;
+    let now = window.performance()?.now();
+    Some((window.document()?, now))
+}
+
+fn on_visibility_change() {
+    let Some((document, now)) = document_now() else {
+        return;
+    };
+    WATCH.with(|w| {
+        let mut w = w.borrow_mut();
+        if document.hidden() {
+            w.hidden_at.get_or_insert(now);
+        } else if let Some(hidden_at) = w.hidden_at.take() {
+            w.gap = Some(Gap {
+                hidden_at,
+                shown_at: now,
+            });
+        }
+    });
+}
+
+/// Start following the page's visibility, once; called as frame loops start
+pub(crate) fn watch() {
+    if WATCH.with(|w| w.borrow().listener.is_some()) {
+        return;
+    }
+    // Workers have no document, and their frames aren't throttled this way
+    let Some((document, now)) = document_now() else {
+        return;
+    };
+    let listener = Closure::wrap(Box::new(on_visibility_change) as Box<dyn FnMut()>);
+    if document
+        .add_event_listener_with_callback("visibilitychange", listener.as_ref().unchecked_ref())
+        .is_err()
+    {
+        return;
+    }
+    WATCH.with(|w| {
+        let mut w = w.borrow_mut();
+        if document.hidden() {
+            w.hidden_at = Some(now);
+        }
+        w.listener = Some(listener);
+    });
+}
+
+pub(crate) fn last_gap() -> Option<Gap> {
+    WATCH.with(|w| w.borrow().gap)
+}
+
+/// The page-wide policy, for animations that haven't picked their own
+pub(crate) fn policy() -> BackgroundPolicy {
+    WATCH.with(|w| w.borrow().policy)
+}
+
+pub(crate) fn set_policy(policy: BackgroundPolicy) {
+    WATCH.with(|w| w.borrow_mut().policy = policy);
+}
+
+/// ms of a frame's delta since `last_frame` to drop under the page-wide
+/// policy: the hidden time when pausing, none when fast-forwarding
+pub(crate) fn paused_time(last_frame: f64) -> f64 {
+    if policy() != BackgroundPolicy::Pause {
+        return 0.0;
+    }
+    last_gap()
+        .and_then(|gap| gap.missed(last_frame))
+        .unwrap_or(0.0)
+}
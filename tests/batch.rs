@@ -0,0 +1,51 @@
+use animation_engine::testing::batch;
+
+fn scalar(from: &[f64], to: &[f64], t: &[f64]) -> Vec<f64> {
+    from.iter()
+        .zip(to)
+        .zip(t)
+        .map(|((a, b), t)| a + (b - a) * t)
+        .collect()
+}
+
+#[test]
+fn lerp_matches_scalar_including_odd_tail() {
+    let from = [0.0, 10.0, -5.0, 100.0, 3.0];
+    let to = [10.0, 0.0, 5.0, 200.0, 4.0];
+    let t = [0.5, 0.25, 1.0, 0.0, 0.75];
+    let mut out = [0.0; 5];
+    batch::lerp(&from, &to, &t, &mut out);
+    assert_eq!(out.to_vec(), scalar(&from, &to, &t));
+}
+
+#[test]
+fn lerp_uniform_uses_one_progress() {
+    let mut out = [0.0; 3];
+    batch::lerp_uniform(&[0.0, 2.0, 4.0], &[10.0, 4.0, 0.0], 0.5, &mut out);
+    assert_eq!(out, [5.0, 3.0, 2.0]);
+}
+
+#[test]
+fn lerp_cuts_to_shortest_slice() {
+    let mut out = [-1.0; 4];
+    batch::lerp(
+        &[0.0, 0.0, 0.0],
+        &[1.0, 1.0],
+        &[0.5, 0.5, 0.5, 0.5],
+        &mut out,
+    );
+    assert_eq!(out, [0.5, 0.5, -1.0, -1.0]);
+}
+
+#[test]
+fn lerp4_and_colors_interpolate_channels() {
+    let red = [255.0, 0.0, 0.0, 1.0];
+    let blue = [0.0, 0.0, 255.0, 0.0];
+    assert_eq!(batch::lerp4(red, blue, 0.5), [127.5, 0.0, 127.5, 0.5]);
+
+    let from = [red, blue].concat();
+    let to = [blue, red].concat();
+    let mut out = [0.0; 8];
+    batch::lerp_colors(&from, &to, &[0.0, 1.0], &mut out);
+    assert_eq!(out.to_vec(), [red, red].concat());
+}
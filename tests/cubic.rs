@@ -0,0 +1,90 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use animation_engine::CubicBezierCurve as CubicBezier;
+
+// ============================================================================
+// CUBIC BEZIER SOLVER TESTS
+// ============================================================================
+
+fn curves() -> Vec<(&'static str, CubicBezier)> {
+    vec![
+        ("ease", CubicBezier::default()),
+        ("ease-in", CubicBezier::ease_in()),
+        ("ease-out", CubicBezier::ease_out()),
+        ("ease-in-out", CubicBezier::ease_in_out()),
+        ("back", CubicBezier::new(0.68, -0.55, 0.265, 1.55)),
+        ("flat start", CubicBezier::new(0.0, 0.0, 0.2, 1.0)),
+        ("vertical middle", CubicBezier::new(1.0, 0.0, 0.0, 1.0)),
+        ("steep end", CubicBezier::new(0.9, 0.1, 1.0, 0.0)),
+    ]
+}
+
+/// Solve by bisecting x(t) to well below the solver's precision
+fn bisect(curve: &CubicBezier, x: f64) -> f64 {
+    let component = |p1: f64, p2: f64, t: f64| {
+        let u = 1.0 - t;
+        3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t
+    };
+    let (mut start, mut end) = (0.0, 1.0);
+    for _ in 0..60 {
+        let mid = (start + end) / 2.0;
+        if component(curve.x1, curve.x2, mid) < x {
+            start = mid;
+        } else {
+            end = mid;
+        }
+    }
+    component(curve.y1, curve.y2, (start + end) / 2.0)
+}
+
+/// The solver finds t to within 1e-6 in x; where the curve is near vertical
+/// that allows more than 1e-6 in y, so accept anything bisection gives for
+/// an x that close
+fn assert_solves(name: &str, curve: &CubicBezier, x: f64) {
+    let low = bisect(curve, (x - 1e-6).max(0.0));
+    let high = bisect(curve, (x + 1e-6).min(1.0));
+    let actual = curve.solve(x);
+    assert!(
+        actual.is_finite() && actual >= low.min(high) - 1e-9 && actual <= low.max(high) + 1e-9,
+        "{} at {}: expected {} to {}, got {}",
+        name,
+        x,
+        low,
+        high,
+        actual
+    );
+}
+
+#[test]
+fn test_solve_matches_bisection() {
+    for (name, curve) in curves() {
+        for i in 1..1000 {
+            assert_solves(name, &curve, i as f64 / 1000.0);
+        }
+    }
+}
+
+#[test]
+fn test_solve_near_the_ends_stays_on_the_curve() {
+    for (name, curve) in curves() {
+        for x in [1e-9, 1e-6, 1e-3, 1.0 - 1e-3, 1.0 - 1e-6, 1.0 - 1e-9] {
+            assert_solves(name, &curve, x);
+        }
+    }
+}
+
+#[test]
+fn test_solve_endpoints_and_linear() {
+    for (name, curve) in curves() {
+        assert_eq!(curve.solve(0.0), 0.0, "{}", name);
+        assert_eq!(curve.solve(1.0), 1.0, "{}", name);
+        assert_eq!(curve.solve(-0.5), 0.0, "{}", name);
+        assert_eq!(curve.solve(1.5), 1.0, "{}", name);
+    }
+
+    let linear = CubicBezier::linear();
+    for i in 0..=10 {
+        let x = i as f64 / 10.0;
+        assert_eq!(linear.solve(x), x);
+    }
+}
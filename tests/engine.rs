@@ -0,0 +1,19 @@
+use animation_engine::testing::engine::{preset, rest_value};
+
+#[test]
+fn presets_start_offset_and_come_to_rest() {
+    assert_eq!(
+        preset("fade-up"),
+        Some(&[("opacity", 0.0), ("y", 40.0)][..])
+    );
+    assert_eq!(
+        preset("zoom-in"),
+        Some(&[("opacity", 0.0), ("scale", 0.8)][..])
+    );
+    assert_eq!(preset("wobble"), None);
+
+    assert_eq!(rest_value("opacity"), 1.0);
+    assert_eq!(rest_value("scale"), 1.0);
+    assert_eq!(rest_value("y"), 0.0);
+    assert_eq!(rest_value("blur"), 0.0);
+}
@@ -0,0 +1,28 @@
+use animation_engine::testing::follow::translation_to;
+
+#[test]
+fn anchor_lands_on_the_goal() {
+    // A 20x20 cursor resting at the top-left corner, centered on the pointer
+    assert_eq!(
+        translation_to(
+            (300.0, 200.0),
+            (0.0, 0.0),
+            (20.0, 20.0),
+            (0.5, 0.5),
+            (0.0, 0.0)
+        ),
+        (290.0, 190.0)
+    );
+
+    // A tooltip whose top-left corner sits 16px below-right of the pointer
+    assert_eq!(
+        translation_to(
+            (300.0, 200.0),
+            (100.0, 50.0),
+            (120.0, 40.0),
+            (0.0, 0.0),
+            (16.0, 16.0)
+        ),
+        (216.0, 166.0)
+    );
+}
@@ -0,0 +1,40 @@
+use animation_engine::testing::frame_clock::FixedStep;
+
+#[test]
+fn frames_split_into_whole_steps() {
+    let mut clock = FixedStep::default();
+    // 60Hz frames, 120Hz physics
+    for _ in 0..10 {
+        assert_eq!(clock.steps(1.0 / 60.0, 1.0 / 120.0), 2);
+    }
+    assert!(clock.remainder() < 1e-9);
+}
+
+#[test]
+fn display_rates_run_the_same_steps() {
+    let step = 1.0 / 120.0;
+    let total = |hz: f64, frames: usize| {
+        let mut clock = FixedStep::default();
+        (0..frames).map(|_| clock.steps(1.0 / hz, step)).sum::<u32>()
+    };
+    // One second at 60Hz, 120Hz and 144Hz
+    assert_eq!(total(60.0, 60), 120);
+    assert_eq!(total(120.0, 120), 120);
+    assert_eq!(total(144.0, 144), 120);
+}
+
+#[test]
+fn short_frames_carry_over() {
+    let mut clock = FixedStep::default();
+    assert_eq!(clock.steps(0.004, 0.01), 0);
+    assert_eq!(clock.steps(0.004, 0.01), 0);
+    assert_eq!(clock.steps(0.004, 0.01), 1);
+    assert!((clock.remainder() - 0.002).abs() < 1e-9);
+}
+
+#[test]
+fn long_stalls_are_dropped() {
+    let mut clock = FixedStep::default();
+    assert_eq!(clock.steps(5.0, 1.0 / 120.0), 16);
+    assert_eq!(clock.remainder(), 0.0);
+}
@@ -0,0 +1,122 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use animation_engine::{GestureAxis, GestureController, SwipeRecognizer};
+
+// ============================================================================
+// SWIPE RECOGNIZER TESTS
+// ============================================================================
+
+/// Drag from (x0, y0) to (x1, y1) in `steps` moves 16ms apart
+fn drag(swipe: &SwipeRecognizer, from: (f64, f64), to: (f64, f64), steps: usize) -> Option<u8> {
+    swipe.on_pointer_down(from.0, from.1, 0.0);
+    for i in 1..steps {
+        let t = i as f64 / steps as f64;
+        swipe.on_pointer_move(
+            from.0 + (to.0 - from.0) * t,
+            from.1 + (to.1 - from.1) * t,
+            i as f64 * 16.0,
+        );
+    }
+    swipe
+        .on_pointer_up(to.0, to.1, steps as f64 * 16.0)
+        .map(|d| d as u8)
+}
+
+#[test]
+fn test_swipe_directions() {
+    let swipe = SwipeRecognizer::new();
+
+    assert_eq!(
+        drag(&swipe, (200.0, 100.0), (20.0, 110.0), 6),
+        Some(0),
+        "left"
+    );
+    assert_eq!(
+        drag(&swipe, (20.0, 100.0), (200.0, 90.0), 6),
+        Some(1),
+        "right"
+    );
+    assert_eq!(
+        drag(&swipe, (100.0, 300.0), (90.0, 100.0), 6),
+        Some(2),
+        "up"
+    );
+    assert_eq!(
+        drag(&swipe, (100.0, 100.0), (110.0, 300.0), 6),
+        Some(3),
+        "down"
+    );
+}
+
+#[test]
+fn test_swipe_thresholds() {
+    let swipe = SwipeRecognizer::new();
+
+    // Too short
+    assert_eq!(drag(&swipe, (100.0, 100.0), (130.0, 100.0), 2), None);
+
+    // Long but slow
+    assert_eq!(drag(&swipe, (0.0, 100.0), (200.0, 100.0), 60), None);
+
+    // Fast flick that reverses right before release
+    swipe.on_pointer_down(0.0, 0.0, 0.0);
+    swipe.on_pointer_move(150.0, 0.0, 16.0);
+    assert!(swipe.on_pointer_up(100.0, 0.0, 32.0).is_none());
+    assert!(swipe.last_swipe().is_none());
+}
+
+#[test]
+fn test_swipe_released_where_the_last_move_landed() {
+    let swipe = SwipeRecognizer::new();
+
+    // pointerup often repeats the last pointermove's position
+    swipe.on_pointer_down(0.0, 0.0, 0.0);
+    swipe.on_pointer_move(60.0, 0.0, 16.0);
+    swipe.on_pointer_move(120.0, 0.0, 32.0);
+    assert_eq!(swipe.on_pointer_up(120.0, 0.0, 40.0).map(|d| d as u8), Some(1));
+}
+
+// ============================================================================
+// VELOCITY UNITS
+// ============================================================================
+
+#[test]
+fn test_velocities_are_pixels_per_second() {
+    let mut gesture = GestureController::new();
+    gesture.set_friction(1.0);
+    gesture.set_velocity_smoothing(1.0);
+    gesture.set_axis(GestureAxis::Both);
+
+    gesture.on_tap_down(0.0, 0.0, 0.0);
+    for i in 1..=5 {
+        gesture.on_tap_move(i as f64 * 10.0, i as f64 * -5.0, i as f64 * 16.0);
+    }
+
+    // 10px and -5px every 16ms
+    assert!((gesture.velocity_x() - 625.0).abs() < 1e-9);
+    assert!((gesture.velocity_y() + 312.5).abs() < 1e-9);
+    assert!((gesture.velocity() - 625.0).abs() < 1e-9);
+
+    // Swipe thresholds use the same units: 80px per 16ms is 5000px/s
+    let mut swipe = SwipeRecognizer::new();
+    swipe.set_velocity_threshold(6000.0);
+    assert_eq!(drag(&swipe, (0.0, 0.0), (160.0, 0.0), 2), None);
+    swipe.set_velocity_threshold(4000.0);
+    assert_eq!(drag(&swipe, (0.0, 0.0), (160.0, 0.0), 2), Some(1));
+}
+
+#[test]
+fn test_friction_scales_velocity_samples() {
+    let mut gesture = GestureController::new();
+    gesture.set_friction(0.5);
+    gesture.set_velocity_smoothing(1.0);
+
+    gesture.on_tap_down(0.0, 0.0, 0.0);
+    gesture.on_tap_move(0.0, 10.0, 16.0);
+    assert!((gesture.velocity() - 312.5).abs() < 1e-9);
+
+    // Smoothing blends samples without compounding friction
+    gesture.set_velocity_smoothing(0.5);
+    gesture.on_tap_move(0.0, 20.0, 32.0);
+    assert!((gesture.velocity() - 312.5).abs() < 1e-9);
+}
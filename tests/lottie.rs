@@ -0,0 +1,66 @@
+use animation_engine::testing::lottie::{bezier_path, Segment, Track, Transform};
+use animation_engine::CubicBezierCurve;
+
+fn linear_track() -> Track {
+    Track::Animated(vec![
+        Segment {
+            from_frame: 0.0,
+            to_frame: 10.0,
+            from: vec![0.0, 100.0],
+            to: vec![50.0, 0.0],
+            easing: Some(CubicBezierCurve::linear()),
+        },
+        // Hold
+        Segment {
+            from_frame: 10.0,
+            to_frame: 20.0,
+            from: vec![50.0, 0.0],
+            to: vec![80.0, 80.0],
+            easing: None,
+        },
+    ])
+}
+
+#[test]
+fn tracks_ease_between_keys_and_hold_outside_them() {
+    let track = linear_track();
+    assert_eq!(track.sample(-5.0), vec![0.0, 100.0]);
+    let mid = track.sample(5.0);
+    assert!((mid[0] - 25.0).abs() < 1e-3 && (mid[1] - 50.0).abs() < 1e-3);
+    assert_eq!(track.sample(15.0), vec![50.0, 0.0]);
+    assert_eq!(track.sample(20.0), vec![80.0, 80.0]);
+    assert_eq!(track.component(30.0, 1, 0.0), 80.0);
+    assert_eq!(track.component(30.0, 2, 7.0), 7.0);
+}
+
+#[test]
+fn transform_moves_the_anchor_to_the_position() {
+    let transform = Transform {
+        anchor: Track::Static(vec![10.0, 20.0]),
+        position: Track::Static(vec![100.0, 50.0]),
+        scale: Track::Static(vec![200.0, 50.0]),
+        rotation: Track::Static(vec![45.0]),
+        opacity: Track::Static(vec![40.0]),
+    };
+    assert_eq!(
+        transform.svg_at(0.0),
+        "translate(100 50) rotate(45) scale(2 0.5) translate(-10 -20)"
+    );
+    assert_eq!(transform.opacity_at(0.0), 0.4);
+}
+
+#[test]
+fn bezier_paths_use_tangents_relative_to_vertices() {
+    let vertices = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0]];
+    let zero = [[0.0, 0.0]; 3];
+    assert_eq!(
+        bezier_path(&vertices, &zero, &zero, false),
+        "M0 0C0 0 10 0 10 0C10 0 10 10 10 10"
+    );
+
+    let out = [[5.0, 0.0], [0.0, 0.0], [0.0, 0.0]];
+    let closed = bezier_path(&vertices, &zero, &out, true);
+    assert!(closed.starts_with("M0 0C5 0 10 0 10 0"));
+    assert!(closed.ends_with("C10 10 0 0 0 0Z"));
+    assert_eq!(bezier_path(&[], &[], &[], true), "");
+}
@@ -0,0 +1,18 @@
+use animation_engine::testing::magnetic::pull;
+
+#[test]
+fn pulls_toward_the_cursor_within_the_radius() {
+    // 100x40 button centered at (200, 100)
+    let (center, half) = ((200.0, 100.0), (50.0, 20.0));
+
+    // Over the button
+    assert_eq!(pull((220.0, 90.0), center, half, 60.0, 0.5), (10.0, -5.0));
+
+    // 60px right of its edge is still in range, 61px is not
+    assert_eq!(pull((310.0, 100.0), center, half, 60.0, 0.5), (55.0, 0.0));
+    assert_eq!(pull((311.0, 100.0), center, half, 60.0, 0.5), (0.0, 0.0));
+
+    // Past a corner, measured diagonally from the corner
+    assert_eq!(pull((300.0, 170.0), center, half, 60.0, 0.5), (0.0, 0.0));
+    assert_eq!(pull((280.0, 150.0), center, half, 60.0, 0.5), (40.0, 25.0));
+}
@@ -0,0 +1,333 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use animation_engine::testing::motion::{
+    self, AnimatableValue, AnimationProperty, CubicBezier, Keyframe, PropertyType, Spring,
+};
+use animation_engine::Renderer;
+use wasm_bindgen::JsValue;
+
+// ============================================================================
+// MOCK SINK
+// ============================================================================
+
+/// Records every (property, value, frame_time) write instead of touching the DOM
+#[derive(Default)]
+struct MockSink {
+    frame_time: f64,
+    writes: Vec<(String, String, f64)>,
+}
+
+impl Renderer for MockSink {
+    fn apply(&mut self, property: &str, value: &str) -> Result<(), JsValue> {
+        self.writes
+            .push((property.to_string(), value.to_string(), self.frame_time));
+        Ok(())
+    }
+}
+
+impl MockSink {
+    fn begin_frame(&mut self, time: f64) {
+        self.frame_time = time;
+    }
+
+    /// Numeric values written to a property, in frame order
+    fn values(&self, property: &str) -> Vec<f64> {
+        self.writes
+            .iter()
+            .filter(|(p, _, _)| p == property)
+            .filter_map(|(_, v, _)| leading_number(v))
+            .collect()
+    }
+
+    /// Last value written to a property at or before `time`
+    fn value_at(&self, property: &str, time: f64) -> Option<f64> {
+        self.writes
+            .iter()
+            .rev()
+            .find(|(p, _, t)| p == property && *t <= time)
+            .and_then(|(_, v, _)| leading_number(v))
+    }
+
+    fn assert_monotonic(&self, property: &str) {
+        let values = self.values(property);
+        assert!(!values.is_empty(), "no writes to {}", property);
+        let increasing = values.last() >= values.first();
+        for pair in values.windows(2) {
+            let ok = if increasing {
+                pair[1] >= pair[0] - 1e-9
+            } else {
+                pair[1] <= pair[0] + 1e-9
+            };
+            assert!(ok, "{} not monotonic: {} -> {}", property, pair[0], pair[1]);
+        }
+    }
+
+    /// How far the written values went past `target` in the direction of travel
+    fn overshoot(&self, property: &str, target: f64) -> f64 {
+        let values = self.values(property);
+        let start = match values.first() {
+            Some(start) => *start,
+            None => return 0.0,
+        };
+        let direction = if target >= start { 1.0 } else { -1.0 };
+        values
+            .iter()
+            .map(|v| (v - target) * direction)
+            .fold(0.0, f64::max)
+    }
+}
+
+/// First number in a CSS value: "0.5" -> 0.5, "translate3d(12px, 0px, 0px)" -> 12
+fn leading_number(value: &str) -> Option<f64> {
+    let args = value.find('(').map(|i| &value[i + 1..]).unwrap_or(value);
+    let start = args.find(|c: char| c.is_ascii_digit() || c == '-' || c == '.')?;
+    let rest = &args[start..];
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == '-' || c == '.' || c == 'e'))
+        .unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+fn number_property(prop_type: PropertyType, start: f64, end: f64) -> AnimationProperty {
+    AnimationProperty {
+        property_type: prop_type,
+        start: AnimatableValue::Number(start),
+        end: AnimatableValue::Number(end),
+        current: AnimatableValue::Number(start),
+    }
+}
+
+fn origin() -> (String, String, String) {
+    ("50%".to_string(), "50%".to_string(), "0".to_string())
+}
+
+/// Sample a timed curve at 60fps over `duration` ms
+fn run_cubic(
+    properties: &mut [AnimationProperty],
+    bezier: Option<&CubicBezier>,
+    duration: f64,
+) -> MockSink {
+    let mut sink = MockSink::default();
+    let frames = (duration / (1000.0 / 60.0)).ceil() as usize;
+    for frame in 0..=frames {
+        let time = (frame as f64 * 1000.0 / 60.0).min(duration);
+        sink.begin_frame(time);
+        motion::sample_cubic(properties, bezier, time / duration);
+        motion::render(properties, &origin(), &mut sink).unwrap();
+    }
+    sink
+}
+
+// ============================================================================
+// EASING
+// ============================================================================
+
+#[test]
+fn linear_progress_is_proportional() {
+    let mut props = vec![number_property(PropertyType::Opacity, 0.0, 1.0)];
+    let sink = run_cubic(&mut props, None, 400.0);
+
+    assert_eq!(sink.value_at("opacity", 0.0), Some(0.0));
+    let mid = sink.value_at("opacity", 200.0).unwrap();
+    assert!((mid - 0.5).abs() < 0.05, "midpoint was {}", mid);
+    assert_eq!(sink.value_at("opacity", 400.0), Some(1.0));
+    sink.assert_monotonic("opacity");
+}
+
+#[test]
+fn ease_out_is_monotonic_without_overshoot() {
+    let bezier = CubicBezier::from_css("ease-out").unwrap();
+    let mut props = vec![number_property(PropertyType::X, 0.0, 200.0)];
+    let sink = run_cubic(&mut props, Some(&bezier), 300.0);
+
+    sink.assert_monotonic("transform");
+    assert_eq!(sink.overshoot("transform", 200.0), 0.0);
+    assert_eq!(sink.value_at("transform", 300.0), Some(200.0));
+    // Front-loaded: more than half the distance in the first half of the time
+    assert!(sink.value_at("transform", 150.0).unwrap() > 100.0);
+}
+
+#[test]
+fn overshooting_curve_is_detected() {
+    let bezier = CubicBezier::new(0.34, 1.56, 0.64, 1.0);
+    let mut props = vec![number_property(PropertyType::Scale, 1.0, 2.0)];
+    let sink = run_cubic(&mut props, Some(&bezier), 500.0);
+
+    assert!(sink.overshoot("transform", 2.0) > 0.05);
+    assert_eq!(sink.value_at("transform", 500.0), Some(2.0));
+}
+
+#[test]
+fn decreasing_values_track_direction() {
+    let mut props = vec![number_property(PropertyType::Opacity, 1.0, 0.0)];
+    let sink = run_cubic(&mut props, Some(&CubicBezier::default()), 250.0);
+
+    sink.assert_monotonic("opacity");
+    assert_eq!(sink.overshoot("opacity", 0.0), 0.0);
+    assert_eq!(sink.values("opacity").last(), Some(&0.0));
+}
+
+// ============================================================================
+// SPRINGS
+// ============================================================================
+
+fn run_spring(spring: Spring, seconds: f64) -> (MockSink, usize) {
+    let mut props = vec![number_property(PropertyType::Opacity, 0.0, 1.0)];
+    let mut springs = vec![spring];
+    springs[0].reset(0.0);
+
+    let mut sink = MockSink::default();
+    let dt = 1.0 / 60.0;
+    let mut settled_frame = 0;
+    for frame in 0..(seconds / dt) as usize {
+        sink.begin_frame(frame as f64 * dt * 1000.0);
+        let moving = motion::step_springs(&mut props, &mut springs, dt);
+        motion::render(&props, &origin(), &mut sink).unwrap();
+        if !moving {
+            settled_frame = frame;
+            break;
+        }
+    }
+    (sink, settled_frame)
+}
+
+#[test]
+fn bouncy_spring_overshoots_then_settles() {
+    let (sink, settled) = run_spring(Spring::bouncy(), 3.0);
+
+    assert!(sink.overshoot("opacity", 1.0) > 0.05);
+    assert!(settled > 0, "spring never came to rest");
+    let last = *sink.values("opacity").last().unwrap();
+    assert!((last - 1.0).abs() <= 0.01, "settled at {}", last);
+}
+
+#[test]
+fn stiff_spring_settles_faster_than_soft_spring() {
+    let (_, stiff) = run_spring(Spring::new(600.0, 50.0), 5.0);
+    let (_, soft) = run_spring(Spring::new(100.0, 20.0), 5.0);
+
+    assert!(stiff > 0 && soft > 0);
+    assert!(stiff < soft, "stiff {} vs soft {}", stiff, soft);
+}
+
+// ============================================================================
+// KEYFRAMES
+// ============================================================================
+
+#[test]
+fn keyframes_interpolate_between_neighbours() {
+    let keyframes = vec![
+        Keyframe {
+            time: 1.0,
+            properties: vec![(PropertyType::Opacity, AnimatableValue::Number(0.25))],
+        },
+        Keyframe {
+            time: 0.0,
+            properties: vec![(PropertyType::Opacity, AnimatableValue::Number(0.0))],
+        },
+        Keyframe {
+            time: 0.5,
+            properties: vec![(PropertyType::Opacity, AnimatableValue::Number(1.0))],
+        },
+    ];
+    let mut props = vec![number_property(PropertyType::Opacity, 0.0, 0.25)];
+
+    let mut sink = MockSink::default();
+    for (time, progress) in [
+        (0.0, 0.0),
+        (100.0, 0.25),
+        (200.0, 0.5),
+        (300.0, 0.75),
+        (400.0, 1.0),
+    ] {
+        sink.begin_frame(time);
+        motion::sample_keyframes(&mut props, &keyframes, None, progress);
+        motion::render(&props, &origin(), &mut sink).unwrap();
+    }
+
+    assert_eq!(sink.value_at("opacity", 0.0), Some(0.0));
+    assert_eq!(sink.value_at("opacity", 100.0), Some(0.5));
+    assert_eq!(sink.value_at("opacity", 200.0), Some(1.0));
+    assert_eq!(sink.value_at("opacity", 300.0), Some(0.625));
+    assert_eq!(sink.value_at("opacity", 400.0), Some(0.25));
+}
+
+// ============================================================================
+// RENDERING
+// ============================================================================
+
+#[test]
+fn render_groups_transforms_and_names_properties() {
+    let props = vec![
+        number_property(PropertyType::X, 0.0, 10.0),
+        number_property(PropertyType::Rotate, 0.0, 45.0),
+        number_property(PropertyType::BackgroundBlur, 0.0, 4.0),
+        number_property(PropertyType::StrokeDashOffset, 0.0, 12.0),
+    ]
+    .into_iter()
+    .map(|mut p| {
+        p.current = p.end.clone();
+        p
+    })
+    .collect::<Vec<_>>();
+
+    let mut sink = MockSink::default();
+    let writes = motion::render(&props, &origin(), &mut sink).unwrap();
+
+    assert_eq!(writes, sink.writes.len());
+    let written: Vec<(&str, &str)> = sink
+        .writes
+        .iter()
+        .map(|(p, v, _)| (p.as_str(), v.as_str()))
+        .collect();
+    assert!(written.contains(&("transform", "translate3d(10px, 0px, 0px) rotate(45deg)")));
+    assert!(written.contains(&("backdrop-filter", "blur(4px)")));
+    assert!(written.contains(&("stroke-dashoffset", "12")));
+}
+
+#[test]
+fn render_over_keeps_the_base_transform_first() {
+    let mut scale = number_property(PropertyType::Scale, 1.0, 2.0);
+    scale.current = scale.end.clone();
+    let opacity = number_property(PropertyType::Opacity, 0.0, 1.0);
+
+    let mut sink = MockSink::default();
+    motion::render_over(&[scale], &origin(), "translate(-50%, -50%)", &mut sink).unwrap();
+    let transforms: Vec<&str> = sink
+        .writes
+        .iter()
+        .filter(|(p, _, _)| p == "transform")
+        .map(|(_, v, _)| v.as_str())
+        .collect();
+    assert_eq!(transforms, vec!["translate(-50%, -50%) scale(2)"]);
+
+    // Nothing animated in the transform: the element's own is left alone
+    let mut sink = MockSink::default();
+    motion::render_over(&[opacity], &origin(), "translate(-50%, -50%)", &mut sink).unwrap();
+    assert!(sink.writes.iter().all(|(p, _, _)| p != "transform"));
+}
+
+#[test]
+fn written_properties_cover_keyframes_that_leave_and_return() {
+    // x is 0 at both ends, where no transform is written
+    let props = vec![
+        number_property(PropertyType::X, 0.0, 0.0),
+        number_property(PropertyType::Opacity, 0.0, 1.0),
+    ];
+    let keyframes = vec![Keyframe {
+        time: 0.5,
+        properties: vec![(PropertyType::X, AnimatableValue::Number(40.0))],
+    }];
+
+    let without = motion::written_properties(&props, &[], &origin());
+    assert!(without.contains(&"opacity".to_string()));
+    assert!(!without.contains(&"transform".to_string()));
+
+    let written = motion::written_properties(&props, &keyframes, &origin());
+    assert!(written.contains(&"transform".to_string()));
+    assert_eq!(
+        written.iter().filter(|name| *name == "opacity").count(),
+        1,
+        "each property is listed once"
+    );
+}
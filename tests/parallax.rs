@@ -0,0 +1,54 @@
+use animation_engine::testing::parallax::{LayerFactors, LayerStyle};
+
+#[test]
+fn layers_move_against_the_input_by_their_speed() {
+    let background = LayerFactors {
+        speed: 0.5,
+        ..Default::default()
+    };
+    // Container half a viewport below center in an 800px viewport
+    let style = background.sample((0.0, 0.5), (0.0, 800.0));
+    assert_eq!(style.y, -200.0);
+    assert_eq!(style.x, 0.0);
+
+    let foreground = LayerFactors {
+        speed: -0.3,
+        ..Default::default()
+    };
+    assert!((foreground.sample((0.0, 0.5), (0.0, 800.0)).y - 120.0).abs() < 1e-9);
+
+    // Pointer input moves both axes
+    let style = background.sample((1.0, -1.0), (200.0, 100.0));
+    assert_eq!((style.x, style.y), (-100.0, 50.0));
+}
+
+#[test]
+fn scale_and_opacity_follow_distance_from_center() {
+    let layer = LayerFactors {
+        speed: 0.0,
+        scale: 0.2,
+        opacity: -0.6,
+    };
+    let centered = layer.sample((0.0, 0.0), (0.0, 800.0));
+    assert_eq!(
+        centered,
+        LayerStyle {
+            x: 0.0,
+            y: 0.0,
+            scale: 1.0,
+            opacity: 1.0
+        }
+    );
+
+    let edge = layer.sample((0.0, -1.0), (0.0, 800.0));
+    assert!((edge.scale - 1.2).abs() < 1e-9);
+    assert!((edge.opacity - 0.4).abs() < 1e-9);
+
+    // Past a full unit the style holds, and opacity never goes negative
+    let faded = LayerFactors {
+        opacity: -2.0,
+        ..layer
+    };
+    assert_eq!(faded.sample((0.0, 3.0), (0.0, 800.0)).opacity, 0.0);
+    assert!((layer.sample((0.0, 3.0), (0.0, 800.0)).scale - 1.2).abs() < 1e-9);
+}
@@ -0,0 +1,111 @@
+use animation_engine::testing::particle_effects::{Bounds, SpriteSheet};
+
+/// Collide a point with `bounds`, returning its position and velocity after
+/// and whether it met the top or bottom
+fn collide(
+    bounds: &Bounds,
+    position: (f64, f64),
+    velocity: (f64, f64),
+) -> ((f64, f64), (f64, f64), bool) {
+    let ((mut x, mut y), (mut vx, mut vy)) = (position, velocity);
+    let floor = bounds.collide(&mut x, &mut y, &mut vx, &mut vy);
+    ((x, y), (vx, vy), floor)
+}
+
+#[test]
+fn points_inside_the_bounds_are_left_alone() {
+    let bounds = Bounds::new(0.0, 0.0, 100.0, 100.0, 0.5, 0.2);
+    assert_eq!(
+        collide(&bounds, (50.0, 50.0), (300.0, -200.0)),
+        ((50.0, 50.0), (300.0, -200.0), false)
+    );
+    // The edges themselves are inside
+    assert_eq!(
+        collide(&bounds, (100.0, 0.0), (300.0, -200.0)),
+        ((100.0, 0.0), (300.0, -200.0), false)
+    );
+}
+
+#[test]
+fn walls_reflect_with_restitution_and_slow_sliding_with_friction() {
+    let bounds = Bounds::new(0.0, 0.0, 100.0, 100.0, 0.5, 0.2);
+
+    // Through the right wall: pushed back, bounced at half speed, sliding slowed
+    assert_eq!(
+        collide(&bounds, (110.0, 50.0), (400.0, 100.0)),
+        ((100.0, 50.0), (-200.0, 80.0), false)
+    );
+
+    // Through the floor: reported, so spin can be slowed too
+    assert_eq!(
+        collide(&bounds, (50.0, 120.0), (100.0, 400.0)),
+        ((50.0, 100.0), (80.0, -200.0), true)
+    );
+
+    // Into a corner: both walls at once
+    let ((x, y), (vx, vy), floor) = collide(&bounds, (-5.0, -5.0), (-400.0, -400.0));
+    assert_eq!((x, y), (0.0, 0.0));
+    assert!(vx > 0.0 && vy > 0.0);
+    assert!(floor);
+}
+
+#[test]
+fn slow_bounces_come_to_rest() {
+    let bounds = Bounds::new(0.0, 0.0, 100.0, 100.0, 0.5, 0.0);
+    let (_, (_, vy), _) = collide(&bounds, (50.0, 101.0), (0.0, 30.0));
+    assert_eq!(vy, 0.0);
+}
+
+#[test]
+fn restitution_and_friction_are_clamped() {
+    let bounds = Bounds::new(0.0, 0.0, 100.0, 100.0, 3.0, -1.0);
+    assert_eq!(
+        collide(&bounds, (110.0, 50.0), (400.0, 100.0)),
+        ((100.0, 50.0), (-400.0, 100.0), false)
+    );
+}
+
+#[test]
+fn sprite_frames_play_across_the_life() {
+    let sheet = SpriteSheet::new(4, 2, 0, 1.0).unwrap();
+    assert_eq!(sheet.frame(0.0), 0);
+    assert_eq!(sheet.frame(0.124), 0);
+    assert_eq!(sheet.frame(0.125), 1);
+    assert_eq!(sheet.frame(0.5), 4);
+    assert_eq!(sheet.frame(0.99), 7);
+    // Held on the last frame at death rather than wrapping to the first
+    assert_eq!(sheet.frame(1.0), 7);
+    assert_eq!(sheet.frame(1.5), 7);
+    assert_eq!(sheet.frame(-0.5), 0);
+}
+
+#[test]
+fn sprite_loops_wrap_and_partial_loops_end_mid_sheet() {
+    let twice = SpriteSheet::new(2, 2, 0, 2.0).unwrap();
+    assert_eq!(twice.frame(0.25), 2);
+    assert_eq!(twice.frame(0.5), 0);
+    assert_eq!(twice.frame(0.75), 2);
+    assert_eq!(twice.frame(1.0), 3);
+
+    // One and a half plays end halfway through the sheet
+    let partial = SpriteSheet::new(2, 2, 0, 1.5).unwrap();
+    assert_eq!(partial.frame(1.0), 2);
+}
+
+#[test]
+fn sprite_sheets_count_frames_and_find_their_cells() {
+    // Fewer frames than cells leaves the rest of the grid unused
+    let sheet = SpriteSheet::new(3, 3, 7, 1.0).unwrap();
+    assert_eq!(sheet.frame(1.0), 6);
+    assert_eq!(sheet.cell(0), (0, 0));
+    assert_eq!(sheet.cell(4), (1, 1));
+    assert_eq!(sheet.cell(6), (0, 2));
+
+    // More frames than cells, or none, mean every cell; no loops means one
+    let every = SpriteSheet::new(3, 2, 40, 0.0).unwrap();
+    assert_eq!(every.frame(1.0), 5);
+    assert_eq!(every.frame(0.5), 3);
+
+    assert!(SpriteSheet::new(0, 2, 0, 1.0).is_none());
+    assert!(SpriteSheet::new(2, 0, 0, 1.0).is_none());
+}
@@ -0,0 +1,119 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use animation_engine::testing::script::{tick, ScriptAction, ScriptCallback, ScriptState, Target};
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use wasm_bindgen::JsValue;
+
+// ============================================================================
+// SCRIPT RUNNER TESTS
+// ============================================================================
+
+#[derive(Clone)]
+struct Probe(Rc<dyn Fn()>);
+
+impl ScriptCallback for Probe {
+    fn invoke(&self) -> Result<(), JsValue> {
+        (self.0)();
+        Ok(())
+    }
+}
+
+type State = Rc<RefCell<ScriptState<Probe>>>;
+
+fn call(target: &str) -> ScriptAction {
+    ScriptAction::Call {
+        target: target.to_string(),
+    }
+}
+
+/// Register `name` as a callback that logs its name, then runs `then`
+fn register(
+    state: &State,
+    log: &Rc<RefCell<Vec<String>>>,
+    name: &str,
+    then: impl Fn(&State) + 'static,
+) {
+    let weak: Weak<RefCell<ScriptState<Probe>>> = Rc::downgrade(state);
+    let log = Rc::clone(log);
+    let label = name.to_string();
+    let probe = Probe(Rc::new(move || {
+        log.borrow_mut().push(label.clone());
+        if let Some(state) = weak.upgrade() {
+            then(&state);
+        }
+    }));
+    state
+        .borrow_mut()
+        .register(name.to_string(), Target::Callback(probe));
+}
+
+#[test]
+fn test_call_step_can_abort_the_script() {
+    let state: State = Rc::new(RefCell::new(ScriptState::new(vec![
+        call("first"),
+        call("stop"),
+        call("after"),
+    ])));
+    let log = Rc::new(RefCell::new(Vec::new()));
+    register(&state, &log, "first", |state| {
+        assert!(state.borrow().is_running());
+    });
+    register(&state, &log, "stop", |state| state.borrow_mut().abort());
+    register(&state, &log, "after", |_| {});
+
+    let run = state.borrow_mut().start();
+    assert!(!tick(&state, run, 0.0));
+    assert!(!state.borrow().is_running());
+    assert_eq!(*log.borrow(), ["first", "stop"]);
+
+    // Later frames of the aborted run do nothing
+    assert!(!tick(&state, run, 16.0));
+    assert_eq!(log.borrow().len(), 2);
+}
+
+#[test]
+fn test_call_step_can_restart_the_script() {
+    let state: State = Rc::new(RefCell::new(ScriptState::new(vec![
+        call("restart"),
+        ScriptAction::Wait { ms: 100.0 },
+    ])));
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let restarted = Rc::new(Cell::new(None));
+    let next_run = Rc::clone(&restarted);
+    register(&state, &log, "restart", move |state| {
+        if next_run.get().is_none() {
+            state.borrow_mut().abort();
+            next_run.set(Some(state.borrow_mut().start()));
+        }
+    });
+
+    let first = state.borrow_mut().start();
+    // The first run's frame stops driving once it's been replaced
+    assert!(!tick(&state, first, 0.0));
+    assert!(state.borrow().is_running());
+
+    let second = restarted.get().unwrap();
+    assert!(tick(&state, second, 0.0));
+    assert_eq!(log.borrow().len(), 2);
+}
+
+#[test]
+fn test_calls_run_in_order_around_waits() {
+    let state: State = Rc::new(RefCell::new(ScriptState::new(vec![
+        call("a"),
+        ScriptAction::Wait { ms: 100.0 },
+        call("b"),
+    ])));
+    let log = Rc::new(RefCell::new(Vec::new()));
+    register(&state, &log, "a", |_| {});
+    register(&state, &log, "b", |_| {});
+
+    let run = state.borrow_mut().start();
+    assert!(tick(&state, run, 0.0));
+    assert_eq!(*log.borrow(), ["a"]);
+    assert!(tick(&state, run, 50.0));
+    assert!(!tick(&state, run, 100.0));
+    assert_eq!(*log.borrow(), ["a", "b"]);
+    assert!(!state.borrow().is_running());
+}
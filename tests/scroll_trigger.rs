@@ -0,0 +1,99 @@
+use animation_engine::testing::scroll_trigger::{
+    progress, smooth_toward, Edge, ScrollPosition, Smoothing,
+};
+use animation_engine::SpringPhysics;
+
+#[test]
+fn positions_parse_keywords_percentages_and_offsets() {
+    let position = ScrollPosition::parse("top 80%").unwrap();
+    assert_eq!(position.element, Edge::parse("top").unwrap());
+    assert!((position.viewport.fraction - 0.8).abs() < 1e-9);
+
+    let edge = Edge::parse("center+=50").unwrap();
+    assert_eq!(edge.fraction, 0.5);
+    assert_eq!(edge.offset, 50.0);
+    assert_eq!(Edge::parse("bottom-=20px").unwrap().resolve(100.0), 80.0);
+    assert_eq!(Edge::parse("120px").unwrap().resolve(500.0), 120.0);
+
+    // One edge stands for both
+    let center = ScrollPosition::parse("center").unwrap();
+    assert_eq!(center.element, center.viewport);
+
+    assert!(ScrollPosition::parse("").is_none());
+    assert!(ScrollPosition::parse("middle 50%").is_none());
+    assert!(ScrollPosition::parse("top 50% bottom").is_none());
+}
+
+#[test]
+fn scroll_offset_lines_up_element_and_viewport() {
+    // Element 1000px down the page, 200px tall, in an 800px viewport
+    let start = ScrollPosition::parse("top bottom").unwrap();
+    let end = ScrollPosition::parse("bottom top").unwrap();
+    assert_eq!(start.scroll_offset(1000.0, 200.0, 800.0), 200.0);
+    assert_eq!(end.scroll_offset(1000.0, 200.0, 800.0), 1200.0);
+
+    let top_80 = ScrollPosition::parse("top 80%").unwrap();
+    assert_eq!(top_80.scroll_offset(1000.0, 200.0, 800.0), 360.0);
+}
+
+#[test]
+fn progress_is_clamped_between_start_and_end() {
+    assert_eq!(progress(100.0, 200.0, 1200.0), 0.0);
+    assert_eq!(progress(700.0, 200.0, 1200.0), 0.5);
+    assert_eq!(progress(5000.0, 200.0, 1200.0), 1.0);
+    // An empty range switches at its start
+    assert_eq!(progress(199.0, 200.0, 200.0), 0.0);
+    assert_eq!(progress(200.0, 200.0, 200.0), 1.0);
+}
+
+#[test]
+fn smoothing_catches_up_within_the_lag() {
+    assert_eq!(smooth_toward(0.0, 1.0, 1.0 / 60.0, 0.0), 1.0);
+
+    let mut value = 0.0;
+    for _ in 0..30 {
+        value = smooth_toward(value, 1.0, 1.0 / 60.0, 0.5);
+        assert!(value < 1.0);
+    }
+    assert!(value > 0.98);
+    for _ in 0..60 {
+        value = smooth_toward(value, 1.0, 1.0 / 60.0, 0.5);
+    }
+    assert_eq!(value, 1.0);
+}
+
+#[test]
+fn spring_smoothing_carries_momentum_and_settles() {
+    let mut smoothing = Smoothing::Spring(SpringPhysics::new(120.0, 20.0));
+    let mut value = 0.0;
+    let mut passed_halfway = false;
+    for _ in 0..600 {
+        value = smoothing.step(value, 1.0, 1.0 / 60.0);
+        passed_halfway |= value > 0.5;
+        if smoothing.settled(value, 1.0) {
+            break;
+        }
+    }
+    assert!(passed_halfway);
+    assert_eq!(value, 1.0);
+    assert!(smoothing.settled(value, 1.0));
+
+    // A fresh wheel step moves it off the target again
+    value = smoothing.step(value, 0.8, 1.0 / 60.0);
+    assert!(value < 1.0);
+    assert!(!smoothing.settled(value, 0.8));
+}
+
+#[test]
+fn lag_smoothing_settles_on_the_target() {
+    let mut smoothing = Smoothing::Lag(0.2);
+    // The first frame after a scroll can have no elapsed time
+    assert_eq!(smoothing.step(0.0, 1.0, 0.0), 0.0);
+    assert!(!smoothing.settled(0.0, 1.0));
+
+    let mut value = 0.0;
+    for _ in 0..120 {
+        value = smoothing.step(value, 1.0, 1.0 / 60.0);
+    }
+    assert!(smoothing.settled(value, 1.0));
+}
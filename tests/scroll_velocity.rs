@@ -0,0 +1,35 @@
+use animation_engine::testing::scroll_velocity::{intensity, VelocityEffect};
+
+#[test]
+fn intensity_is_clamped_to_the_max_velocity() {
+    assert_eq!(intensity(1500.0, 3000.0), 0.5);
+    assert_eq!(intensity(-9000.0, 3000.0), -1.0);
+    assert_eq!(intensity(500.0, 0.0), 0.0);
+}
+
+#[test]
+fn effects_follow_direction_except_scale() {
+    let effect = VelocityEffect {
+        skew_y: -8.0,
+        scale: 0.1,
+        ..Default::default()
+    };
+    let down = effect.sample(0.5);
+    assert_eq!(
+        down,
+        vec![
+            ("transform", "skewY(-4deg)".to_string()),
+            ("scale", "1.05".to_string()),
+        ]
+    );
+    let up = effect.sample(-0.5);
+    assert_eq!(up[0].1, "skewY(4deg)");
+    assert_eq!(up[1].1, "1.05");
+
+    // Untouched properties are left to other animations
+    let rotate = VelocityEffect {
+        rotate: 3.0,
+        ..Default::default()
+    };
+    assert_eq!(rotate.sample(1.0), vec![("rotate", "3deg".to_string())]);
+}
@@ -0,0 +1,94 @@
+use animation_engine::testing::sequencer::{
+    crosses, iteration_fraction, parse_position, stagger_ranks, PositionBase, StaggerFrom,
+};
+
+#[test]
+fn positions_parse_times_labels_and_offsets() {
+    assert_eq!(
+        parse_position("500"),
+        Some((PositionBase::Time(500.0), 0.0))
+    );
+    assert_eq!(
+        parse_position(" intro "),
+        Some((PositionBase::Label("intro"), 0.0))
+    );
+    assert_eq!(
+        parse_position("intro+=200"),
+        Some((PositionBase::Label("intro"), 200.0))
+    );
+    assert_eq!(
+        parse_position("intro -= 150"),
+        Some((PositionBase::Label("intro"), -150.0))
+    );
+    assert_eq!(parse_position("+=100"), Some((PositionBase::End, 100.0)));
+    assert_eq!(parse_position("-=100"), Some((PositionBase::End, -100.0)));
+    assert_eq!(
+        parse_position("250+=50"),
+        Some((PositionBase::Time(250.0), 50.0))
+    );
+
+    assert_eq!(parse_position(""), None);
+    assert_eq!(parse_position("   "), None);
+    assert_eq!(parse_position("intro+=soon"), None);
+    assert_eq!(parse_position("+="), None);
+}
+
+#[test]
+fn stagger_ranks_order_items_from_each_end_or_the_center() {
+    assert_eq!(
+        stagger_ranks(4, StaggerFrom::Start),
+        vec![0.0, 1.0, 2.0, 3.0]
+    );
+    assert_eq!(stagger_ranks(4, StaggerFrom::End), vec![3.0, 2.0, 1.0, 0.0]);
+
+    // Odd counts start from the middle item
+    assert_eq!(
+        stagger_ranks(5, StaggerFrom::Center),
+        vec![2.0, 1.0, 0.0, 1.0, 2.0]
+    );
+    // Even counts start the two middle items together
+    assert_eq!(
+        stagger_ranks(4, StaggerFrom::Center),
+        vec![1.0, 0.0, 0.0, 1.0]
+    );
+
+    assert!(stagger_ranks(0, StaggerFrom::Center).is_empty());
+    assert_eq!(stagger_ranks(1, StaggerFrom::End), vec![0.0]);
+}
+
+#[test]
+fn repeats_map_time_forward_and_yoyo_passes_backward() {
+    for iteration in 0..4 {
+        assert_eq!(iteration_fraction(250.0, 1000.0, iteration, false), 0.25);
+    }
+
+    assert_eq!(iteration_fraction(250.0, 1000.0, 0, true), 0.25);
+    assert_eq!(iteration_fraction(250.0, 1000.0, 1, true), 0.75);
+    assert_eq!(iteration_fraction(250.0, 1000.0, 2, true), 0.25);
+    assert_eq!(iteration_fraction(1000.0, 1000.0, 1, true), 0.0);
+
+    // An empty timeline is always at its end
+    assert_eq!(iteration_fraction(0.0, 0.0, 0, false), 1.0);
+    assert_eq!(iteration_fraction(0.0, 0.0, 1, true), 0.0);
+}
+
+#[test]
+fn markers_are_crossed_once_in_either_direction() {
+    // Forward: landing on a marker crosses it, leaving one doesn't
+    assert!(crosses(500.0, 400.0, 500.0));
+    assert!(!crosses(400.0, 400.0, 500.0));
+    assert!(!crosses(600.0, 400.0, 500.0));
+
+    // Backward, the same from the other side
+    assert!(crosses(400.0, 500.0, 400.0));
+    assert!(!crosses(500.0, 500.0, 400.0));
+    assert!(!crosses(300.0, 500.0, 400.0));
+
+    // A fresh playback meets a marker at the very start
+    assert!(crosses(0.0, f64::NEG_INFINITY, 16.0));
+    // Unwinding into the previous iteration meets one at the very end
+    assert!(crosses(1000.0, f64::INFINITY, 990.0));
+
+    // Standing still crosses nothing
+    assert!(!crosses(500.0, 500.0, 500.0));
+}
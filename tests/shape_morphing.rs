@@ -0,0 +1,249 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use animation_engine::PathMorph;
+
+// ============================================================================
+// PATH MORPH TESTS
+// ============================================================================
+
+/// Numbers in a path string, ignoring command letters
+fn numbers(path: &str) -> Vec<f64> {
+    path.split(|c: char| c.is_alphabetic() || c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().unwrap())
+        .collect()
+}
+
+fn assert_close(actual: f64, expected: f64, what: &str) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "{}: expected {}, got {}",
+        what,
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_arc_becomes_cubics() {
+    // Half circle of radius 50 from (0, 50) to (100, 50), sweeping through (50, 0)
+    let morph = PathMorph::new(
+        "M0 50 A50 50 0 0 1 100 50".to_string(),
+        "M0 50 A50 50 0 0 1 100 50".to_string(),
+    )
+    .unwrap();
+    let path = morph.get_path();
+
+    assert_eq!(
+        path.matches('C').count(),
+        2,
+        "a half circle is two quarter cubics: {}",
+        path
+    );
+    let n = numbers(&path);
+    // M, then the first cubic ends at the top of the circle
+    assert_close(n[6], 50.0, "midpoint x");
+    assert_close(n[7], 0.0, "midpoint y");
+    assert_close(n[n.len() - 2], 100.0, "end x");
+    assert_close(n[n.len() - 1], 50.0, "end y");
+}
+
+#[test]
+fn test_relative_arc_ends_relative_to_current_point() {
+    let morph = PathMorph::new(
+        "M10 10 a20 20 0 0 0 40 0".to_string(),
+        "M10 10 a20 20 0 0 0 40 0".to_string(),
+    )
+    .unwrap();
+    let n = numbers(&morph.get_path());
+
+    assert_close(n[n.len() - 2], 50.0, "end x");
+    assert_close(n[n.len() - 1], 10.0, "end y");
+}
+
+/// Path produced when morphing `path` onto itself
+fn normalized(path: &str) -> String {
+    PathMorph::new(path.to_string(), path.to_string())
+        .unwrap()
+        .get_path()
+}
+
+#[test]
+fn test_relative_commands_match_absolute() {
+    assert_eq!(
+        normalized("m10 10 l20 0 l0 20 z"),
+        normalized("M10 10 L30 10 L30 30 Z")
+    );
+}
+
+#[test]
+fn test_horizontal_and_vertical_become_lines() {
+    assert_eq!(
+        normalized("M0 0 H50 v25 h-10 V0"),
+        normalized("M0 0 L50 0 L50 25 L40 25 L40 0")
+    );
+}
+
+#[test]
+fn test_smooth_cubic_reflects_previous_control() {
+    assert_eq!(
+        normalized("M0 0 C10 -10 20 -10 30 0 S50 10 60 0"),
+        normalized("M0 0 C10 -10 20 -10 30 0 C40 10 50 10 60 0")
+    );
+}
+
+#[test]
+fn test_smooth_quad_reflects_previous_control() {
+    assert_eq!(
+        normalized("M0 0 Q10 -10 20 0 t20 0"),
+        normalized("M0 0 Q10 -10 20 0 Q30 10 40 0")
+    );
+}
+
+#[test]
+fn test_implicit_repeats_and_compact_numbers() {
+    // Pairs after a move are lines, and "-5.5.5" is two numbers
+    assert_eq!(
+        normalized("M0,0 10-5.5.5 1"),
+        normalized("M0 0 L10 -5.5 L0.5 1")
+    );
+}
+
+#[test]
+fn test_normalize_equalizes_command_counts() {
+    let morph = PathMorph::normalize(
+        "M0 0 L100 0 L50 100 Z".to_string(),
+        "M0 0 L100 0 L100 100 L0 100 Z".to_string(),
+        None,
+    )
+    .unwrap();
+
+    for t in [0.0, 0.5, 1.0] {
+        let path = morph.get_path_at(t);
+        assert_eq!(path.matches('C').count(), 4, "at {}: {}", t, path);
+        assert!(path.ends_with('Z'), "at {}: {}", t, path);
+    }
+}
+
+#[test]
+fn test_normalize_keeps_start_shape() {
+    // The line is split into cubics but every point stays on it
+    let morph = PathMorph::normalize(
+        "M0 0 L100 0".to_string(),
+        "M0 0 C0 50 100 50 100 0 L100 100".to_string(),
+        None,
+    )
+    .unwrap();
+    let n = numbers(&morph.get_path_at(0.0));
+
+    for y in n.iter().skip(1).step_by(2) {
+        assert_close(*y, 0.0, "y on the line");
+    }
+    assert_close(n[n.len() - 2], 100.0, "end x");
+
+    let n = numbers(&morph.get_path_at(1.0));
+    assert_close(n[n.len() - 2], 100.0, "end x");
+    assert_close(n[n.len() - 1], 100.0, "end y");
+}
+
+#[test]
+fn test_normalize_pairs_subpaths_by_position() {
+    // The end path lists its squares in the opposite order
+    let morph = PathMorph::normalize(
+        "M0 0 L10 0 L10 10 L0 10 Z M100 100 L110 100 L110 110 L100 110 Z".to_string(),
+        "M100 100 L120 100 L120 120 L100 120 Z M0 0 L20 0 L20 20 L0 20 Z".to_string(),
+        None,
+    )
+    .unwrap();
+    let n = numbers(&morph.get_path_at(1.0));
+
+    // The first subpath is still the one near the origin
+    assert_close(n[0], 0.0, "first move x");
+    assert_close(n[1], 0.0, "first move y");
+}
+
+#[test]
+fn test_normalize_collapses_unmatched_subpaths() {
+    let morph = PathMorph::normalize(
+        "M0 0 L10 0 L10 10 L0 10 Z M100 100 L110 100 L110 110 L100 110 Z".to_string(),
+        "M0 0 L20 0 L20 20 L0 20 Z".to_string(),
+        None,
+    )
+    .unwrap();
+    let path = morph.get_path_at(1.0);
+    assert_eq!(path.matches('M').count(), 2, "{}", path);
+
+    // The square with no partner shrinks to its centre
+    let second = &path[path.rfind('M').unwrap()..];
+    let n = numbers(second);
+    for pair in n.chunks(2) {
+        assert_close(pair[0], 105.0, "collapsed x");
+        assert_close(pair[1], 105.0, "collapsed y");
+    }
+}
+
+/// Largest distance any number moves between two paths of the same shape
+fn max_travel(a: &str, b: &str) -> f64 {
+    numbers(a)
+        .iter()
+        .zip(numbers(b))
+        .map(|(x, y)| (x - y).abs())
+        .fold(0.0, f64::max)
+}
+
+#[test]
+fn test_normalize_rotates_closed_contours_into_line() {
+    // The same square, drawn from the opposite corner
+    let morph = PathMorph::normalize(
+        "M0 0 L10 0 L10 10 L0 10 Z".to_string(),
+        "M10 10 L0 10 L0 0 L10 0 Z".to_string(),
+        None,
+    )
+    .unwrap();
+
+    let travel = max_travel(&morph.get_path_at(0.0), &morph.get_path_at(1.0));
+    assert_close(travel, 0.0, "travel");
+}
+
+#[test]
+fn test_normalize_reverses_winding_when_allowed() {
+    // The same square, drawn anticlockwise
+    let start = "M0 0 L10 0 L10 10 L0 10 Z";
+    let end = "M0 0 L0 10 L10 10 L10 0 Z";
+
+    let fixed = PathMorph::normalize(start.to_string(), end.to_string(), None).unwrap();
+    assert!(max_travel(&fixed.get_path_at(0.0), &fixed.get_path_at(1.0)) > 1.0);
+
+    let reversed = PathMorph::normalize(start.to_string(), end.to_string(), Some(true)).unwrap();
+    let travel = max_travel(&reversed.get_path_at(0.0), &reversed.get_path_at(1.0));
+    assert_close(travel, 0.0, "travel");
+}
+
+#[test]
+fn test_precision_rounds_output() {
+    let mut morph = PathMorph::normalize(
+        "M0 50 A50 50 0 0 1 100 50".to_string(),
+        "M0 0 L100 0".to_string(),
+        None,
+    )
+    .unwrap();
+    morph.set_precision(Some(2));
+    let path = morph.get_path_at(1.0 / 3.0);
+
+    for n in path.split(|c: char| c.is_alphabetic() || c.is_whitespace()) {
+        let decimals = n.split('.').nth(1).map_or(0, str::len);
+        assert!(decimals <= 2, "{} in {}", n, path);
+    }
+}
+
+#[test]
+fn test_relative_output_draws_the_same_path() {
+    let path = "M10 10 L30 10 C40 20 40 30 30 40 Q20 50 10 40 Z M60 60 L70 70";
+    let mut morph = PathMorph::new(path.to_string(), path.to_string()).unwrap();
+    let absolute = morph.get_path();
+
+    morph.set_relative(true);
+    let relative = morph.get_path();
+    assert!(relative.starts_with('m'), "{}", relative);
+    assert_eq!(normalized(&relative), absolute);
+}
@@ -0,0 +1,37 @@
+use animation_engine::testing::motion::CubicBezier;
+use animation_engine::testing::smooth_scroll::ScrollTween;
+use animation_engine::SpringPhysics;
+
+#[test]
+fn eased_scroll_arrives_after_its_duration() {
+    let mut tween = ScrollTween::eased(100.0, 500.0, 400.0, CubicBezier::linear());
+    assert_eq!(tween.step(0.0), (100.0, false));
+    let (position, arrived) = tween.step(0.1);
+    assert!((position - 200.0).abs() < 1e-6);
+    assert!(!arrived);
+    assert_eq!(tween.step(0.3), (500.0, true));
+
+    // Nothing to animate over
+    let mut instant = ScrollTween::eased(0.0, 300.0, 0.0, CubicBezier::ease_in_out());
+    assert_eq!(instant.step(0.0), (300.0, true));
+}
+
+#[test]
+fn spring_scroll_settles_on_the_target() {
+    let mut tween = ScrollTween::spring(0.0, 1200.0, SpringPhysics::new(300.0, 30.0));
+    let mut frames = 0;
+    loop {
+        let (position, arrived) = tween.step(1.0 / 60.0);
+        frames += 1;
+        if arrived {
+            assert_eq!(position, 1200.0);
+            break;
+        }
+        assert!(frames < 600, "spring never settled");
+    }
+
+    // Long frames are split so the spring doesn't overshoot wildly
+    let mut tween = ScrollTween::spring(0.0, 1000.0, SpringPhysics::new(300.0, 30.0));
+    let (position, _) = tween.step(1.0);
+    assert!(position < 1000.0);
+}
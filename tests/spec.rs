@@ -0,0 +1,25 @@
+use animation_engine::testing::spec::{parse_easing, Easing};
+
+#[test]
+fn easing_names_cover_presets_css_and_spring() {
+    for name in [
+        "smooth",
+        "Snappy",
+        "bounce",
+        "ease-out",
+        "cubic-bezier(0.2, 0, 0, 1)",
+    ] {
+        assert!(
+            matches!(parse_easing(name), Ok(Easing::Curve(_))),
+            "{}",
+            name
+        );
+    }
+    assert!(matches!(parse_easing(" spring "), Ok(Easing::Spring)));
+}
+
+#[test]
+fn unknown_easing_is_an_error() {
+    assert!(parse_easing("wobbly").is_err());
+    assert!(parse_easing("cubic-bezier(2, 0, 0, 1)").is_err());
+}
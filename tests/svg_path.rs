@@ -0,0 +1,50 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use animation_engine::SvgPath;
+
+// ============================================================================
+// SVG PATH TESTS
+// ============================================================================
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64, what: &str) {
+    assert!(
+        (actual - expected).abs() < tolerance,
+        "{}: expected {}, got {}",
+        what,
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_line_length_and_points() {
+    let path = SvgPath::new("M0 0 L30 0 L30 40".to_string(), None).unwrap();
+    assert_close(path.get_total_length(), 70.0, 1e-9, "length");
+
+    let point = path.get_point_at(0.5);
+    assert_close(point.x, 30.0, 1e-9, "x");
+    assert_close(point.y, 5.0, 1e-9, "y");
+    assert_close(point.angle, 90.0, 1e-9, "angle");
+
+    let end = path.get_point_at_length(1000.0);
+    assert_close(end.x, 30.0, 1e-9, "end x");
+    assert_close(end.y, 40.0, 1e-9, "end y");
+}
+
+#[test]
+fn test_curve_length_within_tolerance() {
+    // Half circle of radius 50
+    let path = SvgPath::new("M0 50 A50 50 0 0 1 100 50".to_string(), None).unwrap();
+    let expected = 50.0 * std::f64::consts::PI;
+    assert_close(path.get_total_length(), expected, 0.5, "length");
+
+    let top = path.get_point_at(0.5);
+    assert_close(top.x, 50.0, 0.5, "top x");
+    assert_close(top.y, 0.0, 0.5, "top y");
+}
+
+#[test]
+fn test_close_adds_the_closing_side() {
+    let path = SvgPath::new("M0 0 h10 v10 h-10 z".to_string(), None).unwrap();
+    assert_close(path.get_total_length(), 40.0, 1e-9, "perimeter");
+}
@@ -0,0 +1,15 @@
+use animation_engine::testing::tilt::tilt_angles;
+
+#[test]
+fn side_under_the_pointer_tips_away() {
+    assert_eq!(tilt_angles(0.5, 0.5, 12.0, false), (0.0, 0.0));
+    // Top-right corner: top edge back (rotateX +), right edge back (rotateY +)
+    assert_eq!(tilt_angles(1.0, 0.0, 12.0, false), (12.0, 12.0));
+    assert_eq!(tilt_angles(0.25, 1.0, 12.0, false), (-12.0, -6.0));
+
+    // Reversed lifts it toward the viewer instead
+    assert_eq!(tilt_angles(1.0, 0.0, 12.0, true), (-12.0, -12.0));
+
+    // Positions outside the element are clamped to its edges
+    assert_eq!(tilt_angles(-1.0, 2.0, 10.0, false), (-10.0, -10.0));
+}
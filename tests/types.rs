@@ -0,0 +1,14 @@
+use animation_engine::testing::types::parse_css_length;
+
+#[test]
+fn rem_and_em_lengths_keep_their_own_units() {
+    for (value, num, unit) in [
+        ("1.5rem", 1.5, "rem"),
+        ("2em", 2.0, "em"),
+        ("12px", 12.0, "px"),
+    ] {
+        let (parsed, parsed_unit) = parse_css_length(value).unwrap();
+        assert_eq!(parsed, num, "{}", value);
+        assert_eq!(parsed_unit.as_str(), unit, "{}", value);
+    }
+}
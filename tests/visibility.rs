@@ -0,0 +1,37 @@
+use animation_engine::testing::visibility::{BackgroundPolicy, Gap};
+
+#[test]
+fn policies_parse_by_name() {
+    assert_eq!(
+        BackgroundPolicy::parse("pause"),
+        Some(BackgroundPolicy::Pause)
+    );
+    assert_eq!(
+        BackgroundPolicy::parse("fastForward"),
+        Some(BackgroundPolicy::FastForward)
+    );
+    assert_eq!(
+        BackgroundPolicy::parse("fast-forward"),
+        Some(BackgroundPolicy::FastForward)
+    );
+    assert_eq!(BackgroundPolicy::parse("freeze"), None);
+    assert_eq!(BackgroundPolicy::default(), BackgroundPolicy::FastForward);
+    for policy in [BackgroundPolicy::Pause, BackgroundPolicy::FastForward] {
+        assert_eq!(BackgroundPolicy::parse(policy.name()), Some(policy));
+    }
+}
+
+#[test]
+fn gaps_count_from_the_last_frame() {
+    let gap = Gap {
+        hidden_at: 1_000.0,
+        shown_at: 61_000.0,
+    };
+    // Last frame before hiding: the whole gap was missed
+    assert_eq!(gap.missed(984.0), Some(60_000.0));
+    // Started while hidden: only the time since
+    assert_eq!(gap.missed(31_000.0), Some(30_000.0));
+    // Already framed since coming back
+    assert_eq!(gap.missed(61_016.0), None);
+    assert_eq!(gap.missed(61_000.0), None);
+}
@@ -70,6 +70,30 @@ fn test_cubic_bezier_presets() {
     assert!(fluid.solve(0.5) > 0.0 && fluid.solve(0.5) < 1.0);
 }
 
+#[wasm_bindgen_test]
+fn test_cubic_bezier_from_css() {
+    let parsed = anim::CubicBezier::from_css("cubic-bezier(0.2, 0, 0, 1)").expect("Parse failed");
+    assert_eq!((parsed.x1, parsed.y1, parsed.x2, parsed.y2), (0.2, 0.0, 0.0, 1.0));
+
+    let keyword = anim::CubicBezier::from_css("ease-in-out").expect("Keyword failed");
+    assert_eq!((keyword.x1, keyword.x2), (0.42, 0.58));
+
+    assert!(anim::CubicBezier::from_css("cubic-bezier(1.5, 0, 0, 1)").is_err());
+    assert!(anim::CubicBezier::from_css("steps(4)").is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_cubic_bezier_velocity() {
+    let linear = anim::CubicBezier::linear();
+    assert_eq!(linear.velocity_at(0.3), 1.0, "Linear curve has unit slope");
+
+    // Ease-out starts fast and settles
+    let ease_out = anim::CubicBezier::ease_out();
+    assert!(ease_out.velocity_at(0.05) > 1.0);
+    assert!(ease_out.velocity_at(0.95) < 1.0);
+    assert!(ease_out.velocity_at(1.0) >= 0.0);
+}
+
 // ============================================================================
 // SPRING PHYSICS TESTS
 // ============================================================================
@@ -243,6 +267,44 @@ async fn test_animation_applies_transform() {
     );
 }
 
+#[wasm_bindgen_test]
+fn test_sequencer_to_steps_continue_from_the_previous_step() {
+    let window = window().expect("No window");
+    let document = window.document().expect("No document");
+
+    let element = document
+        .create_element("div")
+        .expect("Failed to create element")
+        .dyn_into::<HtmlElement>()
+        .expect("Failed to cast to HtmlElement");
+    document.body()
+        .expect("No body")
+        .append_child(&element)
+        .expect("Failed to append");
+
+    let config = |json: &str| js_sys::JSON::parse(json).expect("Invalid config");
+    let mut sequencer = anim::Sequencer::new();
+    sequencer.to(element.clone().into(), config(r#"{"x":100}"#), 100.0, None)
+        .expect("First step failed");
+    sequencer.to(element.clone().into(), config(r#"{"x":200}"#), 100.0, None)
+        .expect("Second step failed");
+
+    // The second step begins where the first one ended, not back at 0
+    sequencer.seek_to(0.5).expect("Seek failed");
+    let transform = element.style().get_property_value("transform")
+        .expect("Failed to get transform");
+    assert!(
+        transform.contains("translate3d(100px"),
+        "Second step should start at x = 100, got {}",
+        transform
+    );
+
+    sequencer.seek_to(1.0).expect("Seek failed");
+    let transform = element.style().get_property_value("transform")
+        .expect("Failed to get transform");
+    assert!(transform.contains("translate3d(200px"), "got {}", transform);
+}
+
 // ============================================================================
 // PERFORMANCE TESTS
 // ============================================================================